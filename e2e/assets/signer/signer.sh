@@ -0,0 +1,29 @@
+#!/usr/bin/env bash
+# A signer command that signs with the Ed25519 key in the file given as argument,
+# or in identity.pem next to it.
+set -euo pipefail
+
+KEY="${1:-$(dirname "$0")/identity.pem}"
+REQUEST="$(cat)"
+
+case "$(jq -r .method <<<"$REQUEST")" in
+  get_public_key)
+    PUBLIC_KEY="$(openssl pkey -in "$KEY" -pubout -outform DER | xxd -p | tr -d '\n')"
+    jq -n --arg public_key "$PUBLIC_KEY" '{public_key: $public_key}'
+    ;;
+  sign)
+    if [ -e "$(dirname "$0")/deny" ]; then
+      echo "asked to sign, but denying" >&2
+      jq -n '{error: "approval denied"}'
+      exit 0
+    fi
+    MESSAGE="$(mktemp)"
+    jq -r .content <<<"$REQUEST" | xxd -r -p >"$MESSAGE"
+    SIGNATURE="$(openssl pkeyutl -sign -rawin -inkey "$KEY" -in "$MESSAGE" | xxd -p | tr -d '\n')"
+    rm "$MESSAGE"
+    jq -n --arg signature "$SIGNATURE" '{signature: $signature}'
+    ;;
+  *)
+    jq -n '{error: "unsupported method"}'
+    ;;
+esac
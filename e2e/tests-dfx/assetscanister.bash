@@ -899,6 +899,48 @@ check_permission_failure() {
   diff encoded-compressed-2 src/e2e_project_frontend/assets/notreally.js
 }
 
+@test "can use zstd compression" {
+  install_asset assetscanister
+  for i in $(seq 1 400); do
+    echo "some easily duplicate text $i" >>src/e2e_project_frontend/assets/notreally.js
+  done
+
+  echo '[{
+      "match": "*.js",
+      "encodings": ["identity", "zstd"]
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+
+  dfx_start
+  assert_command dfx deploy
+
+  assert_command dfx canister call --query e2e_project_frontend get '(record{key="/notreally.js";accept_encodings=vec{"zstd"}})'
+  assert_match 'content_encoding = "zstd"'
+}
+
+@test "uploads precompressed files as encodings of their original" {
+  install_asset assetscanister
+  for i in $(seq 1 400); do
+    echo "some easily duplicate text $i" >>src/e2e_project_frontend/assets/notreally.js
+  done
+  gzip -9 --keep src/e2e_project_frontend/assets/notreally.js
+
+  dfx_start
+  assert_command dfx deploy
+
+  assert_command dfx canister call --query e2e_project_frontend list '(record{})'
+  assert_match '"/notreally.js"'
+  assert_not_match '"/notreally.js.gz"'
+
+  ID=$(dfx canister id e2e_project_frontend)
+  PORT=$(get_webserver_port)
+
+  # the precompressed file is served as it is, rather than compressed again
+  assert_command curl -v --output served.gz -H "Accept-Encoding: gzip" http://localhost:"$PORT"/notreally.js?canisterId="$ID"
+  assert_match "content-encoding: gzip"
+  diff served.gz src/e2e_project_frontend/assets/notreally.js.gz
+}
+
 @test "leaves in place files that were already installed" {
   install_asset assetscanister
   dd if=/dev/urandom of=src/e2e_project_frontend/assets/asset1.bin bs=400000 count=1
@@ -1959,6 +2001,30 @@ EOF
   assert_not_contains db07e7e24f6f8ddf53c33a610713259a7c1eb71c270b819ebd311e2d223267f0
 }
 
+@test "upgrades from a version that kept asset content on the heap" {
+  install_asset assetscanister
+  dfx_start
+  echo "kept across upgrades" > src/e2e_project_frontend/assets/kept.txt
+  use_asset_wasm 0.12.1
+  assert_command dfx deploy
+  use_default_asset_wasm
+
+  assert_command dfx deploy
+  assert_command dfx canister call --query e2e_project_frontend get '(record{key="/kept.txt";accept_encodings=vec{"identity"}})'
+  assert_contains 'total_length = 21'
+  assert_contains 'content = blob "kept across upgrades\0a"'
+
+  # the second upgrade reads the new stable memory layout
+  assert_command dfx deploy --upgrade-unchanged
+  assert_command dfx canister call --query e2e_project_frontend get '(record{key="/kept.txt";accept_encodings=vec{"identity"}})'
+  assert_contains 'content = blob "kept across upgrades\0a"'
+
+  ID=$(dfx canister id e2e_project_frontend)
+  PORT=$(get_webserver_port)
+  assert_command curl --fail -vv http://localhost:"$PORT"/kept.txt?canisterId="$ID"
+  assert_match "kept across upgrades"
+}
+
 @test "api version endpoint" {
   install_asset assetscanister
   dfx_start
@@ -2005,6 +2071,90 @@ EOF
 )'
 }
 
+@test "serves redirects and rewrites" {
+  install_asset assetscanister
+  echo '[
+    {
+      "match": "**/*",
+      "security_policy": "standard",
+      "disable_security_policy_warning": true
+    },
+    {
+      "match": "old-file.txt",
+      "redirect": { "location": "/text-with-newlines.txt", "status_code": 308 }
+    },
+    {
+      "match": "app",
+      "rewrite": "/text-with-newlines.txt"
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+  dfx_start
+  assert_command dfx deploy
+  assert_command dfx canister call e2e_project_frontend list_routes '()'
+  assert_contains '"/old-file.txt"'
+  assert_contains '"/app"'
+
+  ID=$(dfx canister id e2e_project_frontend)
+  PORT=$(get_webserver_port)
+
+  assert_command curl --fail -vv http://localhost:"$PORT"/old-file.txt?canisterId="$ID"
+  assert_match "HTTP/[0-9.]* 308"
+  assert_match "location: /text-with-newlines.txt"
+
+  assert_command curl --fail -vv http://localhost:"$PORT"/app?canisterId="$ID"
+  assert_match "HTTP/[0-9.]* 200"
+  assert_match "cherries"
+
+  echo '[
+    {
+      "match": "**/*",
+      "security_policy": "standard",
+      "disable_security_policy_warning": true
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+  assert_command dfx deploy
+  assert_command dfx canister call e2e_project_frontend list_routes '()'
+  assert_eq "(vec {})"
+}
+
+@test "downloads assets with their properties and routes" {
+  install_asset assetscanister
+  echo '[
+    {
+      "match": "text-with-newlines.txt",
+      "cache": { "max_age": 77 },
+      "headers": { "x-extra": "yes" }
+    },
+    {
+      "match": "old-file.txt",
+      "redirect": { "location": "/text-with-newlines.txt", "status_code": 308 }
+    }
+  ]' > src/e2e_project_frontend/assets/.ic-assets.json5
+  dfx_start
+  assert_command dfx deploy
+
+  assert_command dfx canister assets download e2e_project_frontend --dir backup
+  diff backup/text-with-newlines.txt src/e2e_project_frontend/assets/text-with-newlines.txt
+  diff backup/binary/noise.txt src/e2e_project_frontend/assets/binary/noise.txt
+  assert_command jq -c '.[] | select(.match == "text-with-newlines.txt") | [.cache.max_age, .headers["x-extra"]]' backup/.ic-assets.json
+  assert_eq '[77,"yes"]'
+  assert_command jq -c '.[] | select(.match == "/old-file.txt") | .redirect' backup/.ic-assets.json
+  assert_eq '{"location":"/text-with-newlines.txt","status_code":308}'
+
+  assert_command_fail dfx canister assets download e2e_project_frontend --dir backup
+  assert_contains "is not empty"
+
+  # synchronizing the downloaded assets recreates them
+  rm -rf src/e2e_project_frontend/assets
+  mv backup src/e2e_project_frontend/assets
+  assert_command dfx deploy e2e_project_frontend --mode reinstall --yes
+  assert_command dfx canister call --query e2e_project_frontend get_asset_properties '("/text-with-newlines.txt")'
+  assert_contains "77"
+  assert_contains '"x-extra"'
+  assert_command dfx canister call e2e_project_frontend list_routes '()'
+  assert_contains '"/old-file.txt"'
+}
+
 @test "upload limits" {
   # Upload limits are covered in detail in state machine tests.  This verifies the integration.
 
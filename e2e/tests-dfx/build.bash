@@ -371,3 +371,122 @@ teardown() {
   install_asset memory64
   assert_command dfx build --check
 }
+
+@test "build --jobs builds canisters in parallel after their dependencies" {
+  install_asset custom_canister
+  install_asset wasm/identity
+  jq '.canisters.custom2.dependencies=["custom"]' dfx.json | sponge dfx.json
+
+  dfx_start
+  dfx canister create --all
+
+  assert_command dfx build --all --jobs 3
+  assert_contains "Output of building canister 'custom':"
+  assert_contains CUSTOM_CANISTER_BUILD_DONE
+  assert_contains CUSTOM_CANISTER2_BUILD_DONE
+  # custom2 starts after its dependency finished
+  assert_match "Output of building canister 'custom':.*Building canister 'custom2'"
+}
+
+@test "build --jobs stops building after the first failure" {
+  install_asset custom_canister
+  install_asset wasm/identity
+  jq '.canisters.custom3.build="exit 1"' dfx.json | sponge dfx.json
+  jq '.canisters.custom2.dependencies=["custom3"]' dfx.json | sponge dfx.json
+  # custom4 does not depend on the failing canister, and becomes ready while custom is still building
+  jq '.canisters.custom.build="sleep 3"' dfx.json | sponge dfx.json
+  jq '.canisters.custom4={"type":"custom","candid":"main.did","wasm":"main.wasm","dependencies":["custom"]}' dfx.json | sponge dfx.json
+
+  dfx_start
+  dfx canister create --all
+
+  assert_command_fail dfx build --all --jobs 2
+  assert_contains "custom3"
+  assert_contains "Building canister 'custom'."
+  assert_not_contains "Building canister 'custom2'"
+  assert_not_contains "Building canister 'custom4'"
+}
+
+@test "build skips canisters whose inputs did not change" {
+  dfx_start
+  dfx canister create --all
+
+  # builds are only cached on request
+  assert_command dfx build e2e_project_backend
+  assert_contains "Building canister 'e2e_project_backend'."
+  assert_command dfx build e2e_project_backend
+  assert_not_contains "(cached)"
+
+  assert_command dfx build e2e_project_backend --cache
+  assert_not_contains "(cached)"
+  assert_command dfx build e2e_project_backend --cache
+  assert_contains "Building canister 'e2e_project_backend' (cached)."
+
+  echo "// changed" >>src/e2e_project_backend/main.mo
+  assert_command dfx build e2e_project_backend --cache
+  assert_not_contains "(cached)"
+  assert_command dfx build e2e_project_backend --cache
+  assert_contains "Building canister 'e2e_project_backend' (cached)."
+
+  jq '.canisters.e2e_project_backend.optimize="cycles"' dfx.json | sponge dfx.json
+  assert_command dfx build e2e_project_backend --cache
+  assert_not_contains "(cached)"
+}
+
+@test "build cache covers custom build programs and skips downloaded wasms" {
+  install_asset wasm/identity
+  mkdir -p www/wasm
+  cp main.wasm main.did www/wasm/
+  start_webserver --directory www
+  dfx_start
+
+  dfx_new
+  cp ../main.wasm ../main.did .
+  jq '.canisters={}' dfx.json | sponge dfx.json
+  jq '.canisters.local.type="custom"' dfx.json | sponge dfx.json
+  jq '.canisters.local.candid="main.did"' dfx.json | sponge dfx.json
+  jq '.canisters.local.wasm="main.wasm"' dfx.json | sponge dfx.json
+  jq '.canisters.local.build="custom-build-tool"' dfx.json | sponge dfx.json
+  jq '.canisters.remote.type="custom"' dfx.json | sponge dfx.json
+  jq '.canisters.remote.candid="http://localhost:'"$E2E_WEB_SERVER_PORT"'/wasm/main.did"' dfx.json | sponge dfx.json
+  jq '.canisters.remote.wasm="http://localhost:'"$E2E_WEB_SERVER_PORT"'/wasm/main.wasm"' dfx.json | sponge dfx.json
+  dfx canister create --all
+
+  # a build tool outside of the project
+  mkdir -p "$E2E_TEMP_DIR/tools"
+  printf '#!/usr/bin/env bash\necho "built with tool v1"\n' >"$E2E_TEMP_DIR/tools/custom-build-tool"
+  chmod +x "$E2E_TEMP_DIR/tools/custom-build-tool"
+  export PATH="$E2E_TEMP_DIR/tools:$PATH"
+
+  assert_command dfx build local --cache
+  assert_command dfx build local --cache
+  assert_contains "Building canister 'local' (cached)."
+  printf '#!/usr/bin/env bash\necho "built with tool v2"\n' >"$E2E_TEMP_DIR/tools/custom-build-tool"
+  assert_command dfx build local --cache
+  assert_not_contains "(cached)"
+
+  assert_command dfx build remote --cache
+  assert_command dfx build remote --cache
+  assert_contains "Not using the build cache for canister 'remote'"
+  assert_not_contains "(cached)"
+}
+
+@test "build restores canisters from a shared build cache" {
+  dfx_start
+  dfx canister create --all
+  export DFX_BUILD_CACHE_DIR="$E2E_TEMP_DIR/build-cache"
+
+  assert_command dfx build e2e_project_backend
+  assert_not_contains "(cached)"
+  cp .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm ./old.wasm
+
+  rm -rf .dfx/local/canisters/e2e_project_backend
+  assert_command dfx build e2e_project_backend
+  assert_contains "Building canister 'e2e_project_backend' (cached)."
+  assert_command diff .dfx/local/canisters/e2e_project_backend/e2e_project_backend.wasm ./old.wasm
+  assert_file_exists .dfx/local/canisters/e2e_project_backend/service.did
+
+  assert_command dfx canister install e2e_project_backend
+  assert_command dfx canister call e2e_project_backend greet '("cache")'
+  assert_contains "Hello, cache!"
+}
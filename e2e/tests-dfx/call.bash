@@ -249,6 +249,31 @@ teardown() {
   assert_match '("Hello, you!")'
 }
 
+@test "call with a JSON or YAML argument" {
+  install_asset method_signatures
+
+  dfx_start
+  dfx deploy
+
+  assert_command dfx canister call hello_backend returns_opt_string '"world"' --argument-format json
+  assert_eq '(opt "Hello, world!")'
+  assert_command dfx canister call hello_backend returns_opt_string 'null' --argument-format json
+  assert_eq '(null)'
+
+  echo 'fg7gi-vyaaa-aaaal-qadca-cai' > principal.yaml
+  assert_command dfx canister call hello_backend returns_principal --argument-file principal.yaml --argument-format yaml
+  assert_eq '(principal "fg7gi-vyaaa-aaaal-qadca-cai")'
+
+  assert_command dfx canister call hello_backend returns_int '"111_222_333_444_555_666_777_888_999"' --argument-format json
+  assert_eq '(111_222_333_444_555_666_777_888_999 : int)'
+
+  assert_command_fail dfx canister call hello_backend returns_int32 '3000000000' --argument-format json
+  assert_contains "$: '3000000000' is not a valid int32"
+
+  assert_command_fail dfx canister call hello_backend returns_int32 '67' --argument-format json --type raw
+  assert_contains "cannot be used with"
+}
+
 @test "inter-canister calls" {
   dfx_new_rust inter
   install_asset inter
@@ -379,3 +404,40 @@ function impersonate_sender() {
 
     impersonate_sender "${IDENTITY_PRINCIPAL}"
 }
+
+@test "canister batch runs a script of calls and assertions" {
+  install_asset counter
+  dfx_start
+  dfx deploy
+
+  cat > script.txt <<'SCRIPT'
+# set up the counter
+hello_backend.write(5)
+let n = hello_backend.inc_read()
+assert $n == 6
+hello_backend.write(
+  $n
+)
+hello_backend.inc()
+assert hello_backend.read() == (7 : nat)
+hello_backend.read()
+SCRIPT
+  assert_command dfx canister batch script.txt
+  assert_contains "(7 : nat)"
+
+  cat > failing.txt <<'SCRIPT'
+let n = hello_backend.read()
+// the counter is at 7
+assert $n == 8
+hello_backend.inc()
+SCRIPT
+  assert_command_fail dfx canister batch failing.txt
+  assert_contains "Line 3: assertion failed."
+  assert_contains "Expected: 8"
+  assert_command dfx canister call hello_backend read
+  assert_eq "(7 : nat)"
+
+  echo 'assert $missing == 1' >missing.txt
+  assert_command_fail dfx canister batch - <missing.txt
+  assert_contains "Line 1: failed to resolve \$missing."
+}
@@ -45,6 +45,44 @@ teardown() {
     assert_contains 'Canister hello_backend is running and snapshots should not be taken of running canisters'
 }
 
+@test "canister snapshots can be downloaded and uploaded" {
+    dfx_start
+    install_asset counter
+    dfx deploy
+
+    assert_command dfx canister call hello_backend inc_read
+    assert_contains '(1 : nat)'
+
+    dfx canister stop hello_backend
+    assert_command dfx canister snapshot create hello_backend
+    assert_match 'Snapshot ID: ([0-9a-f]+)'
+    snapshot=${BASH_REMATCH[1]}
+
+    assert_command dfx canister snapshot download hello_backend "$snapshot" --dir snapshot_dir
+    assert_file_exists snapshot_dir/metadata.json
+    assert_file_exists snapshot_dir/wasm_module.bin
+    assert_file_exists snapshot_dir/main_memory.bin
+    assert_file_exists snapshot_dir/stable_memory.bin
+
+    assert_command_fail dfx canister snapshot download hello_backend "$snapshot" --dir snapshot_dir
+    assert_contains 'is not empty'
+
+    dfx canister snapshot delete hello_backend "$snapshot"
+    dfx canister start hello_backend
+    assert_command dfx canister call hello_backend inc_read
+    assert_contains '(2 : nat)'
+
+    assert_command dfx canister snapshot upload hello_backend --dir snapshot_dir
+    assert_match 'Snapshot ID: ([0-9a-f]+)'
+    uploaded=${BASH_REMATCH[1]}
+
+    dfx canister stop hello_backend
+    assert_command dfx canister snapshot load hello_backend "$uploaded"
+    dfx canister start hello_backend
+    assert_command dfx canister call hello_backend read
+    assert_contains '(1 : nat)'
+}
+
 @test "can query a website" {
   dfx_start
 
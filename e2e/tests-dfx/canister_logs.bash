@@ -139,3 +139,57 @@ current_time_nanoseconds() {
   assert_command dfx canister logs e2e_project --identity alice
   assert_contains "Hello, Alice!"
 }
+
+@test "canister logs grep" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  dfx canister call e2e_project hello Bob
+  sleep 2
+  assert_command dfx canister logs e2e_project --grep 'Bo+b'
+  assert_not_contains "Alice"
+  assert_contains "Bob"
+}
+
+@test "canister logs json output" {
+  install_asset logs
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install e2e_project
+  dfx canister call e2e_project hello Alice
+  dfx canister call e2e_project hello Bob
+  sleep 2
+  assert_command dfx canister logs e2e_project --output json
+  assert_eq "Hello, Alice!" "$(jq -r '.[0].content' <<<"$stdout")"
+  assert_eq "Hello, Bob!" "$(jq -r '.[1].content' <<<"$stdout")"
+  assert_eq "e2e_project" "$(jq -r '.[1].canister' <<<"$stdout")"
+  assert_eq "string" "$(jq -r '.[1].timestamp_nanos | type' <<<"$stdout")"
+  assert_eq "string" "$(jq -r '.[1].idx | type' <<<"$stdout")"
+
+  assert_command dfx canister logs e2e_project --output ndjson --tail 1
+  assert_eq "Hello, Bob!" "$(jq -r '.content' <<<"$stdout")"
+
+  assert_command_fail dfx canister logs e2e_project --output json --follow
+  assert_contains "Use \`--output ndjson\` instead"
+}
+
+@test "canister logs of several canisters are prefixed" {
+  install_asset logs
+  jq '.canisters.other = .canisters.e2e_project' dfx.json | sponge dfx.json
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install --all
+  dfx canister call e2e_project hello Alice
+  dfx canister call other hello Bob
+  sleep 2
+  assert_command dfx canister logs e2e_project other
+  assert_contains "[e2e_project] ["
+  assert_contains "Alice"
+  assert_contains "[other] ["
+  assert_contains "Bob"
+}
@@ -0,0 +1,47 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+
+  dfx_new hello
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "verifies that the deployed module matches the local build" {
+  dfx_start
+  assert_command dfx deploy hello_backend
+
+  assert_command dfx canister verify hello_backend
+  assert_contains "The deployed module of canister 'hello_backend' matches the local build."
+
+  assert_command dfx canister verify hello_backend --no-build
+  assert_contains "The deployed module of canister 'hello_backend' matches the local build."
+}
+
+@test "reports post-processing differences" {
+  dfx_start
+  assert_command dfx deploy hello_backend
+
+  jq '.canisters.hello_backend.gzip=true' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister verify hello_backend
+  assert_contains "The deployed module matches the local build without gzip compression."
+  assert_contains "The deployed module of canister 'hello_backend' does not match the local build."
+}
+
+@test "reports metadata differences" {
+  dfx_start
+  assert_command dfx deploy hello_backend
+
+  jq '.canisters.hello_backend.metadata=[{"name": "my:section", "visibility": "public", "content": "hello"}]' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister verify hello_backend
+  assert_contains "candid:service: matches"
+  assert_contains "my:section: missing from the deployed module"
+  assert_not_contains "The difference is in the code of the module."
+}
@@ -422,3 +422,39 @@ function textual_decode() {
     exit 1
   fi
 }
+
+@test "create places canisters on named subnets" {
+  define_project_network
+  jq '.networks.local.replica.subnets=[{"name":"app1"},{"name":"app2"},{"name":"sys","subnet_type":"system"}]' dfx.json | sponge dfx.json
+  jq '.canisters.e2e_project_backend.subnet="app2"' dfx.json | sponge dfx.json
+  dfx_start
+  # a subnet principal selects the declared subnet with that id
+  SYS_SUBNET_ID="$(jq -r '.subnets.sys.subnet_id' .dfx/network/local/replica-effective-config.json)"
+  jq ".canisters.e2e_project_frontend.subnet=\"${SYS_SUBNET_ID}\"" dfx.json | sponge dfx.json
+
+  assert_command dfx canister create --all --no-wallet
+  CANISTER_ID_BASE64="$(textual_decode "$(dfx canister id e2e_project_backend)" | xxd -r -p | base64)"
+  FRONTEND_ID_BASE64="$(textual_decode "$(dfx canister id e2e_project_frontend)" | xxd -r -p | base64)"
+  SUBNET_ID="$(jq -r '.subnets.app2.subnet_id' .dfx/network/local/replica-effective-config.json)"
+  TOPOLOGY="$(curl "http://127.0.0.1:$(dfx info pocketic-config-port)/instances/0/read/topology")"
+  echo "${TOPOLOGY}"
+  assert_eq "Application" "$(echo "$TOPOLOGY" | jq -r ".subnet_configs.\"${SUBNET_ID}\".subnet_kind")"
+  assert_eq "System" "$(echo "$TOPOLOGY" | jq -r ".subnet_configs.\"${SYS_SUBNET_ID}\".subnet_kind")"
+  # the first canister created on a subnet gets the beginning of its first canister range
+  EXPECTED_CANISTER_ID_BASE64="$(echo "$TOPOLOGY" | jq -r ".subnet_configs.\"${SUBNET_ID}\".canister_ranges[0].start.canister_id")"
+  assert_eq "${EXPECTED_CANISTER_ID_BASE64}" "${CANISTER_ID_BASE64}"
+  EXPECTED_FRONTEND_ID_BASE64="$(echo "$TOPOLOGY" | jq -r ".subnet_configs.\"${SYS_SUBNET_ID}\".canister_ranges[0].start.canister_id")"
+  assert_eq "${EXPECTED_FRONTEND_ID_BASE64}" "${FRONTEND_ID_BASE64}"
+}
+
+@test "create fails for an undeclared subnet" {
+  jq '.canisters.e2e_project_backend.subnet="nope"' dfx.json | sponge dfx.json
+  dfx_start
+
+  assert_command_fail dfx canister create e2e_project_backend --no-wallet
+  assert_contains "Subnet 'nope' is not declared in the replica configuration of network 'local'"
+
+  jq '.canisters.e2e_project_backend.subnet="aaaaa-aa"' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister create e2e_project_backend --no-wallet
+  assert_contains "Subnet aaaaa-aa is not one of the subnets declared in the replica configuration of network 'local'"
+}
@@ -204,6 +204,47 @@ current_time_nanoseconds() {
   assert_eq "500000 cycles."
 }
 
+@test "history" {
+  start_and_install_nns
+
+  ALICE=$(dfx identity get-principal --identity alice)
+  BOB=$(dfx identity get-principal --identity bob)
+  BOB_SUBACCT1="7C7B7A030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+
+  deploy_cycles_ledger
+
+  assert_command dfx cycles history --identity alice
+  assert_eq "No transactions found."
+
+  assert_command dfx canister call depositor deposit "(record {to = record{owner = principal \"$ALICE\";};cycles = 3_000_000_000_000;})" --identity cycle-giver
+  assert_command dfx cycles transfer "$BOB" 100000 --identity alice
+  assert_command dfx cycles transfer "$BOB" 200000 --identity alice --to-subaccount "$BOB_SUBACCT1"
+
+  assert_command dfx cycles history --identity alice --precise
+  assert_contains "mint"
+  assert_contains "transfer"
+  assert_contains "3000000000000 cycles"
+  assert_contains "100000000 cycles"
+
+  assert_command dfx cycles history --identity alice --output json
+  assert_command jq -r '.[].kind' <(echo "$stdout")
+  assert_eq "transfer
+transfer
+mint"
+  assert_command jq -r '.[0].amount' <(dfx cycles history --identity alice --output json)
+  assert_eq "200000"
+
+  assert_command jq -r '.[].kind' <(dfx cycles history --identity alice --output json --limit 1 --before 2)
+  assert_eq "transfer"
+  assert_command jq -r '.[0].amount' <(dfx cycles history --identity alice --output json --limit 1 --before 2)
+  assert_eq "100000"
+
+  assert_command jq -r 'length' <(dfx cycles history --identity bob --output json)
+  assert_eq "1"
+  assert_command jq -r 'length' <(dfx cycles history --identity bob --subaccount "$BOB_SUBACCT1" --output json)
+  assert_eq "1"
+}
+
 @test "transfer deduplication" {
   start_and_install_nns
 
@@ -467,6 +508,48 @@ current_time_nanoseconds() {
   assert_not_contains "Depositing 10000 cycles onto remote"
 }
 
+@test "top-up project canisters with a cycles policy" {
+  start_and_install_nns
+
+  dfx_new
+  add_cycles_ledger_canisters_to_project
+  install_cycles_ledger_canisters
+  jq '.canisters.e2e_project_backend.cycles={"min_balance":4000000000000,"target_balance":5000000000000}' dfx.json | sponge dfx.json
+
+  deploy_cycles_ledger
+
+  assert_command dfx deploy
+
+  DEFAULT=$(dfx identity get-principal)
+  assert_command dfx canister call depositor deposit "(record {to = record{owner = principal \"$DEFAULT\";};cycles = 3_000_000_000_000;})" --identity cycle-giver
+
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Balance: 3_500_000_000_000 Cycles"
+
+  assert_command dfx cycles top-up --project --dry-run
+  assert_contains "Would top up 'e2e_project_backend' with 1500000000000 cycles from 3500000000000 to 5000000000000 cycles."
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Balance: 3_500_000_000_000 Cycles"
+
+  assert_command dfx cycles top-up --project
+  assert_contains "Topped up 'e2e_project_backend' with 1500000000000 cycles"
+  assert_not_contains "e2e_project_frontend"
+  assert_command dfx canister status e2e_project_backend
+  assert_contains "Balance: 5_000_000_000_000 Cycles"
+  assert_command dfx cycles balance --precise
+  assert_eq "1499900000000 cycles."
+
+  assert_command dfx cycles top-up --project
+  assert_contains "No canister needs a top-up."
+
+  assert_command_fail dfx cycles top-up e2e_project_backend 100000 --project
+  assert_command_fail dfx cycles top-up --dry-run e2e_project_backend 100000
+
+  jq '.canisters.e2e_project_backend.cycles.target_balance=1' dfx.json | sponge dfx.json
+  assert_command_fail dfx cycles top-up --project
+  assert_contains "The target_balance of canister 'e2e_project_backend' is less than its min_balance."
+}
+
 @test "top-up deduplication" {
   start_and_install_nns
 
@@ -291,3 +291,95 @@ teardown() {
   assert_command dfx canister call hyphenated-lib name
   assert_contains lib
 }
+
+@test "deploy --atomic upgrades all canisters" {
+  install_asset atomic_deploy
+  dfx_start
+  assert_command dfx deploy
+
+  assert_command dfx canister stop second
+
+  cp first_v2.mo first.mo
+  cp second_v2.mo second.mo
+  assert_command dfx deploy --atomic --yes
+  assert_command dfx canister call first version
+  assert_eq "(2 : nat)"
+  assert_command dfx canister status second
+  assert_contains "Status: Stopped"
+  assert_command dfx canister start second
+  assert_command dfx canister call second version
+  assert_eq "(2 : nat)"
+  assert_command dfx canister snapshot list first
+  assert_contains "No snapshots found"
+}
+
+@test "deploy --atomic rolls back all canisters if one fails" {
+  install_asset atomic_deploy
+  dfx_start
+  assert_command dfx deploy
+
+  cp first_v2.mo first.mo
+  cp second_bad.mo second.mo
+  assert_command_fail dfx deploy --atomic --yes
+  assert_contains "Rolled back to their snapshots: first, second"
+  assert_contains "Deployment failed and was rolled back."
+
+  assert_command dfx canister call first version
+  assert_eq "(1 : nat)"
+  assert_command dfx canister call second version
+  assert_eq "(1 : nat)"
+  assert_command dfx canister status second
+  assert_contains "Status: Running"
+  assert_command dfx canister snapshot list second
+  assert_contains "No snapshots found"
+}
+
+@test "deploy --estimate prints the cycles cost without deploying" {
+  dfx_start
+
+  assert_command_fail dfx deploy hello_backend --estimate
+  assert_contains "Canister 'hello_backend' has not been built."
+
+  assert_command dfx build hello_backend --check
+  assert_command dfx deploy hello_backend --estimate
+  assert_contains "hello_backend (subnet of 13 nodes):"
+  assert_contains "Canister creation:          0.500 TC"
+  assert_contains "Installation (at most):"
+  assert_contains "Estimated total:"
+  assert_command_fail dfx canister id hello_backend
+
+  assert_command dfx deploy hello_backend
+  assert_command dfx canister install hello_backend --estimate
+  assert_not_contains "Canister creation"
+  assert_contains "Installation (at most):"
+}
+
+@test "deploy --watch redeploys changed canisters and synchronizes changed assets" {
+  mkdir extra
+  jq '.canisters.hello_backend.watch=["extra"]' dfx.json | sponge dfx.json
+  dfx_start
+  dfx deploy --watch >watch.log 2>&1 &
+  WATCH_PID=$!
+  wait_for_watch_log() {
+    timeout 120 sh -c "until grep -q '$1' watch.log; do sleep 1; done" || (cat watch.log && false)
+  }
+  wait_for_watch_log "Watching for changes. Press Ctrl-C to stop."
+
+  sed -i.bak 's/Hello, /Hi, /' src/hello_backend/main.mo
+  wait_for_watch_log "Redeploying: hello_backend hello_frontend"
+  wait_for_watch_log "Deployed changes."
+  assert_command dfx canister call hello_backend greet '("watch")'
+  assert_contains "Hi, watch!"
+
+  echo "watched" >src/hello_frontend/assets/watched.txt
+  wait_for_watch_log "Synchronizing all assets of canister 'hello_frontend'."
+  timeout 60 sh -c "until dfx canister call hello_frontend get '(record{key=\"/watched.txt\";accept_encodings=vec{\"identity\"}})'; do sleep 1; done"
+
+  # paths in the watch field redeploy the canister too
+  echo "extra" >extra/notes.txt
+  timeout 120 sh -c "until [ \"\$(grep -c 'Redeploying: hello_backend' watch.log)\" -ge 2 ]; do sleep 1; done" || (cat watch.log && false)
+
+  kill "$WATCH_PID"
+  assert_command dfx canister call --query hello_frontend get '(record{key="/watched.txt";accept_encodings=vec{"identity"}})'
+  assert_contains 'blob "watched\0a"'
+}
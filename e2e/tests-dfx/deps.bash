@@ -215,6 +215,104 @@ Failed to download from url: http://httpbin.org/status/404."
   assert_contains "Failed to download from url:"
 }
 
+@test "dfx deps pull --locked and dfx deps update use pulled.json as lockfile" {
+  use_test_specific_cache_root # dfx deps pull will download files to cache
+
+  PULLED_DIR="$DFX_CACHE_ROOT/.cache/dfinity/pulled/"
+
+  dfx_start
+
+  setup_onchain
+
+  cd app
+  assert_command_fail dfx deps pull --network local --locked
+  assert_contains "Cannot pull with --locked because deps/pulled.json does not exist."
+
+  assert_command dfx deps pull --network local
+  CANDID_HASH_B="$(sha256sum "deps/candid/$CANISTER_ID_B.did" | cut -d " " -f 1)"
+  assert_command jq -r '.canisters."'"$CANISTER_ID_B"'".candid_hash' deps/pulled.json
+  assert_eq "$CANDID_HASH_B"
+  assert_command jq -r '.canisters."'"$CANISTER_ID_A"'".wasm_url' deps/pulled.json
+  assert_eq "http://localhost:$E2E_WEB_SERVER_PORT/a.wasm"
+
+  assert_command dfx deps pull --network local --locked
+  cp deps/pulled.json pulled.json.bak
+
+  # upstream serves a different wasm for canister a
+  rm -r "${PULLED_DIR:?}/"
+  cp ../onchain/.dfx/local/canisters/c/c.wasm ../www/a.wasm
+  WASM_HASH_DOWNLOAD_A="$(sha256sum ../www/a.wasm | cut -d " " -f 1)"
+
+  assert_command_fail dfx deps pull --network local --locked
+  assert_contains "The pulled canisters are different from deps/pulled.json:"
+  assert_contains "$CANISTER_ID_A: wasm hash changed from"
+  assert_contains "Run \`dfx deps update <CANISTER>\` to accept the changes."
+  assert_command diff pulled.json.bak deps/pulled.json
+  # the changed wasm is not written to the cache
+  assert_directory_not_exists "$PULLED_DIR/$CANISTER_ID_A"
+
+  # only the named dependencies may change
+  assert_command_fail dfx deps update dep_b --network local
+  assert_contains "$CANISTER_ID_A: wasm hash changed from"
+  assert_command diff pulled.json.bak deps/pulled.json
+  assert_directory_not_exists "$PULLED_DIR/$CANISTER_ID_A"
+
+  assert_command dfx deps update "$CANISTER_ID_A" --network local
+  assert_command jq -r '.canisters."'"$CANISTER_ID_A"'".wasm_hash_download' deps/pulled.json
+  assert_eq "$WASM_HASH_DOWNLOAD_A"
+
+  assert_command dfx deps pull --network local --locked
+}
+
+@test "dfx deps tree and dfx deps outdated" {
+  use_test_specific_cache_root # dfx deps pull will download files to cache
+
+  dfx_start
+
+  setup_onchain
+
+  cd app
+  assert_command_fail dfx deps tree
+  assert_contains "Failed to read pulled.json. Please (re)run \`dfx deps pull\`."
+
+  assert_command dfx deps pull --network local
+  HASH_A="$(jq -r '.canisters."'"$CANISTER_ID_A"'".wasm_hash_download' deps/pulled.json)"
+  HASH_B="$(jq -r '.canisters."'"$CANISTER_ID_B"'".wasm_hash_download' deps/pulled.json)"
+  HASH_C="$(jq -r '.canisters."'"$CANISTER_ID_C"'".wasm_hash_download' deps/pulled.json)"
+  URL_A="$(jq -r '.canisters."'"$CANISTER_ID_A"'".wasm_url' deps/pulled.json)"
+  URL_B="$(jq -r '.canisters."'"$CANISTER_ID_B"'".wasm_url' deps/pulled.json)"
+  URL_C="$(jq -r '.canisters."'"$CANISTER_ID_C"'".wasm_url' deps/pulled.json)"
+
+  assert_command dfx deps tree
+  assert_eq "$CANISTER_ID_B (dep_b) $HASH_B from $URL_B
+└── $CANISTER_ID_A $HASH_A from $URL_A
+$CANISTER_ID_C (dep_c) $HASH_C from $URL_C
+└── $CANISTER_ID_A $HASH_A from $URL_A (*)"
+
+  assert_command dfx deps outdated --network local
+  assert_contains "All pulled dependencies are up to date."
+
+  # upgrade canister a with a different wasm
+  cd ../onchain
+  dfx canister install a --mode reinstall --yes --wasm .dfx/local/canisters/c/c.wasm --argument "(opt 3)"
+  MODULE_HASH_A="$(sha256sum .dfx/local/canisters/c/c.wasm | cut -d " " -f 1)"
+  cd ../app
+
+  assert_command dfx deps outdated --network local
+  assert_contains "These pulled dependencies have been upgraded upstream:"
+  assert_contains "$CANISTER_ID_A: module hash changed from"
+  assert_contains "to $MODULE_HASH_A"
+  assert_not_contains "$CANISTER_ID_B (dep_b): module hash changed"
+
+  # pulled.json written by older versions of dfx has no module_hash
+  jq 'del(.canisters."'"$CANISTER_ID_A"'".module_hash)' deps/pulled.json > deps/pulled.json.tmp
+  mv deps/pulled.json.tmp deps/pulled.json
+  assert_command dfx deps outdated --network local
+  assert_contains "The module hash at pull time is unknown for these pulled dependencies, re-pull them to record it:"
+  assert_contains "$CANISTER_ID_A: current module hash is $MODULE_HASH_A"
+  assert_not_contains "$CANISTER_ID_A: module hash changed"
+}
+
 @test "dfx deps pull works when wasm_hash or wasm_hash_url specified" {
   use_test_specific_cache_root # dfx deps pull will download files to cache
 
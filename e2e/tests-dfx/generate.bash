@@ -154,3 +154,48 @@ teardown() {
   assert_command dfx generate
   assert_file_exists "src/hello_backend/hello_backend.did"
 }
+
+@test "dfx generate creates Rust and Python files" {
+  dfx_new hello
+  jq '.canisters.hello_backend.declarations.bindings=["rs", "py"]' dfx.json | sponge dfx.json
+
+  dfx_start
+  dfx canister create --all
+  dfx build
+  dfx canister install --all
+
+  dfx generate hello_backend
+
+  assert_file_exists "src/declarations/hello_backend/hello_backend.rs"
+  assert_file_exists "src/declarations/hello_backend/mod.rs"
+  assert_file_exists "src/declarations/hello_backend/hello_backend_did.py"
+  assert_file_exists "src/declarations/hello_backend/__init__.py"
+  assert_file_not_exists "src/declarations/hello_backend/index.js"
+
+  assert_command cat src/declarations/hello_backend/hello_backend.rs
+  assert_contains "pub async fn greet"
+  assert_command cat src/declarations/hello_backend/mod.rs
+  assert_contains 'std::env::var("CANISTER_ID_HELLO_BACKEND")'
+  assert_command cat src/declarations/hello_backend/hello_backend_did.py
+  assert_contains "def greet(self, arg0: str) -> str: ..."
+  assert_command cat src/declarations/hello_backend/__init__.py
+  assert_contains 'os.environ.get("CANISTER_ID_HELLO_BACKEND")'
+}
+
+@test "dfx generate uses env_override in Rust and Python files" {
+  dfx_new hello
+  jq '.canisters.hello_backend.declarations.bindings=["rs", "py"]' dfx.json | sponge dfx.json
+  jq '.canisters.hello_backend.declarations.env_override="ryjl3-tyaaa-aaaaa-aaaba-cai"' dfx.json | sponge dfx.json
+
+  dfx_start
+  dfx canister create --all
+  dfx build
+
+  dfx generate hello_backend
+
+  assert_command cat src/declarations/hello_backend/mod.rs
+  assert_contains 'candid::Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai")'
+  assert_not_contains "CANISTER_ID_HELLO_BACKEND"
+  assert_command cat src/declarations/hello_backend/__init__.py
+  assert_contains 'canister_id = "ryjl3-tyaaa-aaaaa-aaaba-cai"'
+}
@@ -216,3 +216,41 @@ teardown() {
   assert_command dfx identity get-principal
   assert_eq "2nor3-keehi-duuup-d7jcn-onggn-3atzm-gejtl-5tlzn-k4g6c-nnbf7-7qe"
 }
+
+@test "can call a canister using a signer identity" {
+  install_asset ed25519
+  install_asset signer
+  SIGNER_DIR="$(pwd)"
+  assert_command dfx identity new signer --signer-command "$SIGNER_DIR/signer.sh"
+  assert_command jq -r .signer.command "$DFX_CONFIG_ROOT/.config/dfx/identity/signer/identity.json"
+  assert_eq "$SIGNER_DIR/signer.sh"
+  assert_command dfx identity get-principal --identity signer
+  assert_eq "2nor3-keehi-duuup-d7jcn-onggn-3atzm-gejtl-5tlzn-k4g6c-nnbf7-7qe"
+  assert_command_fail dfx identity export signer
+  assert_contains "signs with an external signer command"
+
+  # arguments are split like in a shell, and a relative program path is made absolute
+  assert_command dfx identity new signer-with-key --signer-command "./signer.sh '$SIGNER_DIR/identity.pem'"
+  assert_command jq -r .signer.command "$DFX_CONFIG_ROOT/.config/dfx/identity/signer-with-key/identity.json"
+  assert_eq "$SIGNER_DIR/signer.sh $SIGNER_DIR/identity.pem"
+  mkdir elsewhere
+  (
+    cd elsewhere
+    assert_command dfx identity get-principal --identity signer-with-key
+    assert_eq "2nor3-keehi-duuup-d7jcn-onggn-3atzm-gejtl-5tlzn-k4g6c-nnbf7-7qe"
+  )
+
+  dfx_new # This installs replica and other binaries
+  dfx identity use signer
+  install_asset whoami
+  dfx_start
+  dfx canister create whoami
+  dfx build
+  dfx canister install whoami
+  assert_command dfx canister call whoami whoami
+  assert_eq '(principal "2nor3-keehi-duuup-d7jcn-onggn-3atzm-gejtl-5tlzn-k4g6c-nnbf7-7qe")'
+
+  touch "$SIGNER_DIR/deny"
+  assert_command_fail dfx canister call whoami whoami
+  assert_contains "approval denied"
+}
@@ -331,6 +331,33 @@ teardown() {
   assert_match "Hello, dfx!"
 }
 
+@test "install converts a JSON or YAML init_arg_file using the candid types" {
+  install_asset deploy_deps
+  dfx_start
+  echo 'dfx' > args.yaml
+  jq '.canisters.dependency.init_arg_file="args.yaml"' dfx.json | sponge dfx.json
+
+  dfx canister create dependency
+  dfx build dependency
+  assert_command dfx canister install dependency
+  assert_command dfx canister call dependency greet
+  assert_match "Hello, dfx!"
+
+  echo '["json"]' > args.json
+  jq '.canisters.dependency.init_arg_file="args.json"' dfx.json | sponge dfx.json
+  assert_command_fail dfx canister install dependency --mode reinstall --yes
+  assert_contains "$: expected text, found an array"
+
+  echo '"json"' > args.json
+  assert_command dfx canister install dependency --mode reinstall --yes
+  assert_command dfx canister call dependency greet
+  assert_match "Hello, json!"
+
+  assert_command dfx canister install dependency --mode reinstall --yes --argument '"cli"' --argument-format json
+  assert_command dfx canister call dependency greet
+  assert_match "Hello, cli!"
+}
+
 @test "install fails if both init_arg and init_arg_file are defined in dfx.json" {
   install_asset deploy_deps
   dfx_start
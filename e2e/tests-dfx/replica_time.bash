@@ -0,0 +1,52 @@
+#!/usr/bin/env bats
+
+load ../utils/_
+
+setup() {
+  standard_setup
+}
+
+teardown() {
+  dfx_stop
+
+  standard_teardown
+}
+
+@test "replica time requires a running PocketIC" {
+  dfx_new
+  assert_command_fail dfx replica time get
+  assert_contains "PocketIC is not running for network 'local'"
+}
+
+@test "replica time can be advanced, set, frozen and resumed" {
+  dfx_new
+  dfx_start
+
+  assert_command dfx replica time get
+  assert_match "^[0-9]{4}-[0-9]{2}-[0-9]{2}T"
+
+  assert_command dfx replica time get --nanos
+  before="$stdout"
+  assert_command dfx replica time advance 1d
+  assert_contains "Advanced time to"
+  assert_command dfx replica time get --nanos
+  (( stdout >= before + 86400000000000 ))
+
+  assert_command dfx replica time freeze
+  assert_contains "Froze time at"
+  assert_command dfx replica time get --nanos
+  frozen="$stdout"
+  sleep 2
+  assert_command dfx replica time get --nanos
+  assert_eq "$frozen"
+
+  assert_command dfx replica time set 2100-01-01T00:00:00Z
+  assert_command dfx replica time get
+  assert_contains "2100-01-01T00:00:0"
+
+  assert_command_fail dfx replica time set 2099-01-01T00:00:00Z
+  assert_contains "Cannot move time backwards"
+
+  assert_command dfx replica time resume
+  assert_contains "Time progresses with wall-clock time again."
+}
@@ -81,3 +81,50 @@ teardown() {
 
   rm "$TMP_NAME_FILE"
 }
+
+@test "canister install --sign-only + send" {
+  install_asset counter
+  dfx_start
+  dfx canister create --all
+  dfx build
+
+  assert_command_fail dfx canister install hello_backend --sign-only
+  assert_contains "please specify --mode install, reinstall or upgrade"
+
+  assert_command dfx canister install hello_backend --sign-only --mode install --message-file install.json
+  assert_contains "Signed 1 message(s) to install code in canister"
+  assert_command jq -r .method_name install.json
+  assert_eq "install_code"
+
+  echo y | assert_command dfx canister send install.json
+  assert_command dfx canister call hello_backend read
+  assert_eq "(0 : nat)"
+
+  assert_command_fail dfx canister install hello_backend --sign-only --mode upgrade --message-file install.json
+  assert_contains "[install.json] already exists"
+}
+
+@test "canister install --sign-only splits large modules into chunks" {
+  dfx_start
+  dfx canister create --all
+  head -c 2500000 /dev/urandom > large.wasm
+
+  assert_command dfx canister install "$(dfx canister id hello_backend)" --wasm large.wasm --sign-only --mode install
+  assert_contains "Signed 5 message(s)"
+  assert_command jq -rc '[.messages[].method_name]' message.json
+  assert_eq '["upload_chunk","upload_chunk","upload_chunk","install_chunked_code","clear_chunk_store"]'
+}
+
+@test "canister update-settings --sign-only + send" {
+  dfx_start
+  dfx deploy hello_backend
+
+  assert_command dfx canister update-settings hello_backend --freezing-threshold 2592000 --sign-only --message-file settings.json
+  assert_contains "dfx canister send settings.json"
+  assert_command jq -r .method_name settings.json
+  assert_eq "update_settings"
+
+  echo y | assert_command dfx canister send settings.json
+  assert_command dfx canister status hello_backend
+  assert_contains "Freezing threshold: 2_592_000"
+}
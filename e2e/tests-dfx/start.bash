@@ -339,6 +339,30 @@ teardown() {
   assert_match "subnet type: System"
 }
 
+@test "dfx starts pocketic with named subnets - local network" {
+  dfx_new hello
+  define_project_network
+  jq '.networks.local.replica.subnets=[{"name":"app1"},{"name":"app2"},{"name":"sys","subnet_type":"system"}]' dfx.json | sponge dfx.json
+
+  assert_command dfx start --background -v
+  assert_match "subnet app1: Application"
+  assert_match "subnet app2: Application"
+  assert_match "subnet sys: System"
+
+  assert_command jq -r '.subnets | keys | join(",")' .dfx/network/local/replica-effective-config.json
+  assert_eq "app1,app2,sys"
+  assert_command curl "http://127.0.0.1:$(dfx info pocketic-config-port)/instances/0/read/topology"
+  assert_eq "2" "$(echo "$stdout" | jq '[.subnet_configs[] | select(.subnet_kind == "Application")] | length')"
+}
+
+@test "dfx start rejects duplicate subnet names" {
+  dfx_new hello
+  define_project_network
+  jq '.networks.local.replica.subnets=[{"name":"app"},{"name":"app"}]' dfx.json | sponge dfx.json
+
+  assert_command_fail dfx start --background
+  assert_contains "Subnet 'app' is declared more than once in the replica configuration."
+}
 
 @test "dfx starts pocketic with subnet type application - shared network" {
   install_shared_asset subnet_type/shared_network_settings/application
@@ -642,3 +642,25 @@ teardown() {
   assert_not_contains "${WALLET_PRINCIPAL}"
 
 }
+
+@test "update settings from per-network configuration in dfx.json" {
+  dfx_start
+  assert_command dfx deploy hello_backend
+
+  jq '.canisters.hello_backend.initialization_values.freezing_threshold="30days"' dfx.json | sponge dfx.json
+  jq '.canisters.hello_backend.initialization_values.networks.local.freezing_threshold="60days"' dfx.json | sponge dfx.json
+
+  assert_command dfx canister update-settings hello_backend --from-config --yes
+  assert_contains "freezing_threshold: 2_592_000 -> 5_184_000"
+  assert_command dfx canister status hello_backend
+  assert_contains "Freezing threshold: 5_184_000"
+
+  assert_command dfx canister update-settings hello_backend --from-config --yes
+  assert_contains "Settings of canister 'hello_backend' already match dfx.json."
+
+  jq '.canisters.hello_backend.initialization_values.networks.local.freezing_threshold="90days"' dfx.json | sponge dfx.json
+  assert_command dfx deploy hello_backend --from-config --yes
+  assert_contains "freezing_threshold: 5_184_000 -> 7_776_000"
+  assert_command dfx canister status hello_backend
+  assert_contains "Freezing threshold: 7_776_000"
+}
@@ -1,5 +1,8 @@
+use crate::canister_api::types::route::Route;
 use crate::error::AssetLoadConfigError;
-use crate::error::AssetLoadConfigError::{LoadRuleFailed, MalformedAssetConfigFile};
+use crate::error::AssetLoadConfigError::{
+    DuplicateRoute, LoadRuleFailed, MalformedAssetConfigFile,
+};
 use crate::error::GetAssetConfigError;
 use crate::error::GetAssetConfigError::AssetConfigNotFound;
 use crate::security_policy::SecurityPolicy;
@@ -18,6 +21,9 @@ use super::content_encoder::ContentEncoder;
 pub(crate) const ASSETS_CONFIG_FILENAME_JSON: &str = ".ic-assets.json";
 pub(crate) const ASSETS_CONFIG_FILENAME_JSON5: &str = ".ic-assets.json5";
 
+const REDIRECT_STATUS_CODES: [u16; 5] = [301, 302, 303, 307, 308];
+const DEFAULT_REDIRECT_STATUS_CODE: u16 = 301;
+
 /// A final piece of metadata assigned to the asset
 #[derive(Derivative, PartialEq, Eq, Serialize, Clone)]
 #[derivative(Default)]
@@ -85,6 +91,13 @@ pub(crate) struct CacheConfig {
     pub(crate) max_age: Option<u64>,
 }
 
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RedirectConfig {
+    pub(crate) location: String,
+    pub(crate) status_code: Option<u16>,
+}
+
 /// A single configuration object, from `.ic-assets.json` config file
 #[derive(Derivative, Clone, Serialize)]
 #[derivative(Debug, PartialEq)]
@@ -149,6 +162,7 @@ type ConfigMap = HashMap<PathBuf, ConfigNode>;
 #[derive(Debug)]
 pub struct AssetSourceDirectoryConfiguration {
     config_map: ConfigMap,
+    routes: Vec<Route>,
 }
 
 /// A directory or subdirectory with assets.
@@ -166,9 +180,15 @@ impl AssetSourceDirectoryConfiguration {
             return Err(AssetLoadConfigError::InvalidRootDir(root_dir.to_path_buf()));
         }
         let mut config_map = HashMap::new();
-        AssetConfigTreeNode::load(None, root_dir, &mut config_map)?;
+        let mut routes = vec![];
+        AssetConfigTreeNode::load(None, root_dir, root_dir, &mut config_map, &mut routes)?;
+
+        Ok(Self { config_map, routes })
+    }
 
-        Ok(Self { config_map })
+    /// Returns the redirects and rewrites from all `.ic-assets.json` files.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
     }
 
     /// Fetches the configuration for the asset.
@@ -223,10 +243,13 @@ impl AssetSourceDirectoryConfiguration {
 
 impl AssetConfigTreeNode {
     /// Constructs config tree for assets directory in a recursive fashion.
+    /// Rules that redirect or rewrite are collected into `routes` instead.
     fn load(
         parent: Option<ConfigNode>,
+        root_dir: &Path,
         dir: &Path,
         configs: &mut ConfigMap,
+        routes: &mut Vec<Route>,
     ) -> Result<(), AssetLoadConfigError> {
         let config_path = match (
             dir.join(ASSETS_CONFIG_FILENAME_JSON).exists(),
@@ -248,6 +271,15 @@ impl AssetConfigTreeNode {
             let interim_rules: Vec<rule_utils::InterimAssetConfigRule> = json5::from_str(&content)
                 .map_err(|e| MalformedAssetConfigFile(config_path.to_path_buf(), e))?;
             for interim_rule in interim_rules {
+                if interim_rule.is_route() {
+                    let route = rule_utils::route_from_interim(interim_rule, root_dir, dir)
+                        .map_err(|e| LoadRuleFailed(config_path.to_path_buf(), e))?;
+                    if routes.iter().any(|r| r.source == route.source) {
+                        return Err(DuplicateRoute(route.source));
+                    }
+                    routes.push(route);
+                    continue;
+                }
                 let rule = AssetConfigRule::from_interim(interim_rule, dir)
                     .map_err(|e| LoadRuleFailed(config_path.to_path_buf(), e))?;
                 rules.push(rule);
@@ -268,7 +300,13 @@ impl AssetConfigTreeNode {
             .filter_map(|x| x.ok())
             .filter(|x| x.file_type().map_or_else(|_e| false, |ft| ft.is_dir()))
         {
-            Self::load(Some(parent_ref.clone()), &f.path(), configs)?;
+            Self::load(
+                Some(parent_ref.clone()),
+                root_dir,
+                &f.path(),
+                configs,
+                routes,
+            )?;
         }
         Ok(())
     }
@@ -332,8 +370,12 @@ impl AssetConfig {
 /// This module contains various utilities needed for serialization/deserialization
 /// and pretty-printing of the `AssetConfigRule` data structure.
 mod rule_utils {
-    use super::{AssetConfig, AssetConfigRule, CacheConfig, HeadersConfig, Maybe, SecurityPolicy};
+    use super::{
+        AssetConfig, AssetConfigRule, CacheConfig, HeadersConfig, Maybe, RedirectConfig,
+        SecurityPolicy, DEFAULT_REDIRECT_STATUS_CODE, REDIRECT_STATUS_CODES,
+    };
     use crate::asset::content_encoder::ContentEncoder;
+    use crate::canister_api::types::route::{Route, RouteTarget};
     use crate::error::LoadRuleError;
     use globset::{Glob, GlobMatcher};
     use itertools::Itertools;
@@ -436,6 +478,100 @@ mod rule_utils {
         encodings: Option<Vec<ContentEncoder>>,
         security_policy: Option<SecurityPolicy>,
         disable_security_policy_warning: Option<bool>,
+        redirect: Option<RedirectConfig>,
+        rewrite: Option<String>,
+    }
+
+    impl InterimAssetConfigRule {
+        pub(super) fn is_route(&self) -> bool {
+            self.redirect.is_some() || self.rewrite.is_some()
+        }
+    }
+
+    /// Converts a rule with `redirect` or `rewrite` into a route.
+    /// Unlike other rules, its `match` is an exact request path rather than a glob pattern.
+    pub(super) fn route_from_interim(
+        InterimAssetConfigRule {
+            r#match,
+            cache,
+            headers,
+            ignore,
+            enable_aliasing,
+            allow_raw_access,
+            encodings,
+            security_policy,
+            disable_security_policy_warning,
+            redirect,
+            rewrite,
+        }: InterimAssetConfigRule,
+        root_dir: &Path,
+        config_file_parent_dir: &Path,
+    ) -> Result<Route, LoadRuleError> {
+        if cache.is_some()
+            || !headers.is_absent()
+            || ignore.is_some()
+            || enable_aliasing.is_some()
+            || allow_raw_access.is_some()
+            || encodings.is_some()
+            || security_policy.is_some()
+            || disable_security_policy_warning.is_some()
+        {
+            return Err(LoadRuleError::RouteWithAssetProperties(r#match));
+        }
+        if r#match.contains(['*', '?', '[', ']', '{', '}']) {
+            return Err(LoadRuleError::RouteWithGlobPattern(r#match));
+        }
+        let target = match (redirect, rewrite) {
+            (
+                Some(RedirectConfig {
+                    location,
+                    status_code,
+                }),
+                None,
+            ) => {
+                let status_code = status_code.unwrap_or(DEFAULT_REDIRECT_STATUS_CODE);
+                if !REDIRECT_STATUS_CODES.contains(&status_code) {
+                    return Err(LoadRuleError::InvalidRedirectStatusCode(
+                        r#match,
+                        status_code,
+                    ));
+                }
+                if location.is_empty() {
+                    return Err(LoadRuleError::EmptyRedirectLocation(r#match));
+                }
+                RouteTarget::Redirect {
+                    location,
+                    status_code,
+                }
+            }
+            (None, Some(key)) => RouteTarget::Rewrite {
+                key: request_path(root_dir, config_file_parent_dir, &key),
+            },
+            (Some(_), Some(_)) => return Err(LoadRuleError::RedirectAndRewrite(r#match)),
+            (None, None) => unreachable!("rule neither redirects nor rewrites"),
+        };
+        Ok(Route {
+            source: request_path(root_dir, config_file_parent_dir, &r#match),
+            target,
+        })
+    }
+
+    /// Paths starting with `/` are relative to the root of the assets directory,
+    /// all others are relative to the directory of the config file.
+    fn request_path(root_dir: &Path, config_file_parent_dir: &Path, path: &str) -> String {
+        if path.starts_with('/') {
+            return path.to_string();
+        }
+        let mut request_path = String::new();
+        if let Ok(relative_dir) = config_file_parent_dir.strip_prefix(root_dir) {
+            for component in relative_dir.components() {
+                request_path.push('/');
+                request_path.push_str(&component.as_os_str().to_string_lossy());
+            }
+        }
+        request_path.push('/');
+        request_path.push_str(path);
+        request_path
     }
 
     impl AssetConfigRule {
@@ -450,6 +586,8 @@ mod rule_utils {
                 encodings,
                 security_policy,
                 disable_security_policy_warning,
+                redirect: _,
+                rewrite: _,
             }: InterimAssetConfigRule,
             config_file_parent_dir: &Path,
         ) -> Result<Self, LoadRuleError> {
@@ -573,6 +711,8 @@ mod rule_utils {
 mod with_tempdir {
 
     use super::*;
+    use crate::canister_api::types::route::RouteTarget;
+    use crate::error::LoadRuleError;
     #[cfg(target_family = "unix")]
     use std::error::Error;
     use std::io::Write;
@@ -1242,4 +1382,129 @@ mod with_tempdir {
         assert_eq!(x.cache.clone().unwrap().max_age, Some(22));
         assert_eq!(y.cache.clone().unwrap().max_age, Some(22));
     }
+
+    #[test]
+    fn routes() {
+        let cfg = Some(HashMap::from([
+            (
+                "".to_string(),
+                r#"[
+                    {"match": "old-pricing", "redirect": {"location": "/pricing.html"}},
+                    {"match": "/temporary", "redirect": {"location": "https://example.com", "status_code": 307}},
+                    {"match": "*", "cache": {"max_age": 20}}
+                ]"#
+                .to_string(),
+            ),
+            (
+                "nested".to_string(),
+                r#"[
+                    {"match": "app", "rewrite": "the-thing.txt"},
+                    {"match": "/absolute", "rewrite": "/index.html"}
+                ]"#
+                .to_string(),
+            ),
+        ]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 7);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let mut assets_config = AssetSourceDirectoryConfiguration::load(&assets_dir).unwrap();
+
+        let mut routes = assets_config.routes().to_vec();
+        routes.sort_by(|a, b| a.source.cmp(&b.source));
+        assert_eq!(
+            routes,
+            vec![
+                Route {
+                    source: "/absolute".to_string(),
+                    target: RouteTarget::Rewrite {
+                        key: "/index.html".to_string()
+                    },
+                },
+                Route {
+                    source: "/nested/app".to_string(),
+                    target: RouteTarget::Rewrite {
+                        key: "/nested/the-thing.txt".to_string()
+                    },
+                },
+                Route {
+                    source: "/old-pricing".to_string(),
+                    target: RouteTarget::Redirect {
+                        location: "/pricing.html".to_string(),
+                        status_code: 301,
+                    },
+                },
+                Route {
+                    source: "/temporary".to_string(),
+                    target: RouteTarget::Redirect {
+                        location: "https://example.com".to_string(),
+                        status_code: 307,
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            assets_config
+                .get_asset_config(assets_dir.join("index.html").as_path())
+                .unwrap(),
+            AssetConfig {
+                cache: Some(CacheConfig { max_age: Some(20) }),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_routes() {
+        for (rule, expected) in [
+            (
+                r#"{"match": "*.html", "redirect": {"location": "/"}}"#,
+                LoadRuleError::RouteWithGlobPattern("*.html".to_string()).to_string(),
+            ),
+            (
+                r#"{"match": "a", "redirect": {"location": "/", "status_code": 200}}"#,
+                LoadRuleError::InvalidRedirectStatusCode("a".to_string(), 200).to_string(),
+            ),
+            (
+                r#"{"match": "a", "redirect": {"location": ""}}"#,
+                LoadRuleError::EmptyRedirectLocation("a".to_string()).to_string(),
+            ),
+            (
+                r#"{"match": "a", "redirect": {"location": "/"}, "rewrite": "/index.html"}"#,
+                LoadRuleError::RedirectAndRewrite("a".to_string()).to_string(),
+            ),
+            (
+                r#"{"match": "a", "rewrite": "/index.html", "cache": {"max_age": 1}}"#,
+                LoadRuleError::RouteWithAssetProperties("a".to_string()).to_string(),
+            ),
+        ] {
+            let cfg = Some(HashMap::from([("".to_string(), format!("[{}]", rule))]));
+            let assets_temp_dir = create_temporary_assets_directory(cfg, 0);
+            let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+            let err = AssetSourceDirectoryConfiguration::load(&assets_dir).unwrap_err();
+            let AssetLoadConfigError::LoadRuleFailed(_, source) = err else {
+                panic!("unexpected error: {}", err);
+            };
+            assert_eq!(source.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn duplicate_routes() {
+        let cfg = Some(HashMap::from([
+            (
+                "".to_string(),
+                r#"[{"match": "nested/a", "redirect": {"location": "/"}}]"#.to_string(),
+            ),
+            (
+                "nested".to_string(),
+                r#"[{"match": "a", "rewrite": "/index.html"}]"#.to_string(),
+            ),
+        ]));
+        let assets_temp_dir = create_temporary_assets_directory(cfg, 0);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let assets_config = AssetSourceDirectoryConfiguration::load(&assets_dir);
+        assert_eq!(
+            assets_config.err().unwrap().to_string(),
+            "More than one redirect or rewrite for '/nested/a'"
+        );
+    }
 }
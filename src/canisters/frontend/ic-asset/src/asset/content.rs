@@ -8,6 +8,9 @@ use sha2::{Digest, Sha256};
 use std::io::Write;
 use std::path::Path;
 
+// The highest level that does not require more memory to decompress.
+const ZSTD_COMPRESSION_LEVEL: i32 = 19;
+
 #[derive(Clone)]
 pub(crate) struct Content {
     pub data: Vec<u8>,
@@ -26,10 +29,20 @@ impl Content {
         Ok(Content { data, media_type })
     }
 
+    /// Loads this content from a file that holds it in another encoding.
+    pub fn load_encoded(&self, path: &Path) -> Result<Content, ReadFileError> {
+        let data = dfx_core::fs::read(path)?;
+        Ok(Content {
+            data,
+            media_type: self.media_type.clone(),
+        })
+    }
+
     pub fn encode(&self, encoder: &ContentEncoder) -> Result<Content, std::io::Error> {
         match encoder {
             ContentEncoder::Gzip => self.to_gzip(),
             ContentEncoder::Brotli => self.to_brotli(),
+            ContentEncoder::Zstd => self.to_zstd(),
             ContentEncoder::Identity => Ok(self.clone()),
         }
     }
@@ -57,6 +70,14 @@ impl Content {
         })
     }
 
+    pub fn to_zstd(&self) -> Result<Content, std::io::Error> {
+        let data = zstd::encode_all(self.data.as_slice(), ZSTD_COMPRESSION_LEVEL)?;
+        Ok(Content {
+            data,
+            media_type: self.media_type.clone(),
+        })
+    }
+
     pub fn sha256(&self) -> Vec<u8> {
         Sha256::digest(&self.data).to_vec()
     }
@@ -1,19 +1,54 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Copy, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum ContentEncoder {
     Gzip,
     #[serde(alias = "br")]
     Brotli,
+    Zstd,
     Identity,
 }
 
+impl ContentEncoder {
+    /// The encoder of files with this extension, such as `.br` files that a bundler compressed next to the original.
+    pub fn from_file_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "gz" => Some(ContentEncoder::Gzip),
+            "br" => Some(ContentEncoder::Brotli),
+            "zst" => Some(ContentEncoder::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The extension of files that hold content in this encoding.
+    pub fn file_extension(&self) -> Option<&'static str> {
+        match self {
+            ContentEncoder::Gzip => Some("gz"),
+            ContentEncoder::Brotli => Some("br"),
+            ContentEncoder::Zstd => Some("zst"),
+            ContentEncoder::Identity => None,
+        }
+    }
+
+    /// The encoder of an encoding named as in the `Content-Encoding` header.
+    pub fn from_content_encoding(content_encoding: &str) -> Option<Self> {
+        match content_encoding {
+            "gzip" => Some(ContentEncoder::Gzip),
+            "br" => Some(ContentEncoder::Brotli),
+            "zstd" => Some(ContentEncoder::Zstd),
+            "identity" => Some(ContentEncoder::Identity),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for ContentEncoder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
             ContentEncoder::Gzip => f.write_str("gzip"),
             ContentEncoder::Brotli => f.write_str("br"),
+            ContentEncoder::Zstd => f.write_str("zstd"),
             ContentEncoder::Identity => f.write_str("identity"),
         }
     }
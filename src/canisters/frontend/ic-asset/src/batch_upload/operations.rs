@@ -7,6 +7,7 @@ use crate::canister_api::types::batch_upload::common::{
     UnsetAssetContentArguments,
 };
 use crate::canister_api::types::batch_upload::v1::{BatchOperationKind, CommitBatchArguments};
+use crate::canister_api::types::route::{DeleteRouteArguments, Route};
 use crate::error::{AssembleCommitBatchArgumentError, SetEncodingError};
 use candid::Nat;
 use std::collections::HashMap;
@@ -15,6 +16,9 @@ use super::plumbing::ChunkUploader;
 
 pub(crate) const BATCH_UPLOAD_API_VERSION: u16 = 1;
 
+/// The first asset canister API version that supports redirects and rewrites.
+pub(crate) const ROUTES_API_VERSION: u16 = 3;
+
 pub(crate) async fn assemble_batch_operations(
     chunk_uploader: Option<&ChunkUploader<'_>>,
     project_assets: &HashMap<String, ProjectAsset>,
@@ -64,6 +68,7 @@ pub(crate) async fn assemble_commit_batch_arguments(
     })
 }
 
+#[derive(Clone, Copy)]
 pub(crate) enum AssetDeletionReason {
     Obsolete,
     Incompatible,
@@ -268,6 +273,115 @@ pub(crate) fn update_properties(
     }
 }
 
+pub(crate) fn update_routes(
+    operations: &mut Vec<BatchOperationKind>,
+    project_routes: &[Route],
+    canister_routes: Vec<Route>,
+    reason: AssetDeletionReason,
+) {
+    let canister_routes: HashMap<String, Route> = canister_routes
+        .into_iter()
+        .map(|route| (route.source.clone(), route))
+        .collect();
+    if matches!(reason, AssetDeletionReason::Obsolete) {
+        for source in canister_routes.keys() {
+            if !project_routes.iter().any(|route| &route.source == source) {
+                operations.push(BatchOperationKind::DeleteRoute(DeleteRouteArguments {
+                    source: source.clone(),
+                }));
+            }
+        }
+    }
+    for route in project_routes {
+        if canister_routes.get(&route.source) != Some(route) {
+            operations.push(BatchOperationKind::SetRoute(route.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_update_routes {
+    use super::{update_routes, AssetDeletionReason};
+    use crate::canister_api::types::batch_upload::v1::BatchOperationKind;
+    use crate::canister_api::types::route::{DeleteRouteArguments, Route, RouteTarget};
+
+    fn redirect(source: &str, location: &str) -> Route {
+        Route {
+            source: source.to_string(),
+            target: RouteTarget::Redirect {
+                location: location.to_string(),
+                status_code: 301,
+            },
+        }
+    }
+
+    fn rewrite(source: &str, key: &str) -> Route {
+        Route {
+            source: source.to_string(),
+            target: RouteTarget::Rewrite {
+                key: key.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn sets_new_and_changed_routes() {
+        let project_routes = vec![
+            redirect("/unchanged", "/a"),
+            redirect("/changed", "/new"),
+            rewrite("/new", "/index.html"),
+        ];
+        let canister_routes = vec![
+            redirect("/unchanged", "/a"),
+            rewrite("/changed", "/index.html"),
+        ];
+        let mut operations = vec![];
+        update_routes(
+            &mut operations,
+            &project_routes,
+            canister_routes,
+            AssetDeletionReason::Obsolete,
+        );
+        operations.sort();
+        assert_eq!(
+            operations,
+            vec![
+                BatchOperationKind::SetRoute(redirect("/changed", "/new")),
+                BatchOperationKind::SetRoute(rewrite("/new", "/index.html")),
+            ]
+        );
+    }
+
+    #[test]
+    fn deletes_obsolete_routes() {
+        let project_routes = vec![redirect("/kept", "/a")];
+        let canister_routes = vec![redirect("/kept", "/a"), redirect("/obsolete", "/b")];
+
+        let mut operations = vec![];
+        update_routes(
+            &mut operations,
+            &project_routes,
+            canister_routes.clone(),
+            AssetDeletionReason::Obsolete,
+        );
+        assert_eq!(
+            operations,
+            vec![BatchOperationKind::DeleteRoute(DeleteRouteArguments {
+                source: "/obsolete".to_string()
+            })]
+        );
+
+        let mut operations = vec![];
+        update_routes(
+            &mut operations,
+            &project_routes,
+            canister_routes,
+            AssetDeletionReason::Incompatible,
+        );
+        assert!(operations.is_empty());
+    }
+}
+
 #[cfg(test)]
 mod test_update_properties {
     use super::update_properties;
@@ -286,6 +400,7 @@ mod test_update_properties {
                 key: key.to_string(),
                 source: PathBuf::from(""),
                 config: asset_props,
+                precompressed: HashMap::new(),
             },
         }
     }
@@ -37,6 +37,8 @@ pub(crate) struct AssetDescriptor {
     pub(crate) source: PathBuf,
     pub(crate) key: String,
     pub(crate) config: AssetConfig,
+    /// Files next to the source that hold its content in other encodings.
+    pub(crate) precompressed: HashMap<ContentEncoder, PathBuf>,
 }
 
 pub(crate) struct ProjectAssetEncoding {
@@ -335,9 +337,14 @@ async fn make_encoding(
             )))
         }
         encoder => {
-            let encoded = content.encode(encoder).map_err(|e| {
-                EncodeContentFailed(asset_descriptor.key.clone(), encoder.to_owned(), e)
-            })?;
+            let encoded = match asset_descriptor.precompressed.get(encoder) {
+                Some(path) => content
+                    .load_encoded(path)
+                    .map_err(CreateEncodingError::LoadPrecompressedContentFailed)?,
+                None => content.encode(encoder).map_err(|e| {
+                    EncodeContentFailed(asset_descriptor.key.clone(), encoder.to_owned(), e)
+                })?,
+            };
             if force_encoding || encoded.data.len() < content.data.len() {
                 let content_encoding = format!("{}", encoder);
                 let project_asset_encoding = make_project_asset_encoding(
@@ -373,7 +380,15 @@ async fn make_encodings(
         .config
         .encodings
         .clone()
-        .unwrap_or_else(|| default_encoders(&content.media_type));
+        .unwrap_or_else(|| {
+            let mut encoders = default_encoders(&content.media_type);
+            for encoder in asset_descriptor.precompressed.keys() {
+                if !encoders.contains(encoder) {
+                    encoders.push(*encoder);
+                }
+            }
+            encoders
+        });
 
     // The identity encoding is always uploaded if it's in the list of chosen encodings.
     // Other encoding are only uploaded if they save bytes compared to identity.
@@ -0,0 +1,46 @@
+use crate::canister_api::methods::method_names::{GET, GET_CHUNK};
+use crate::canister_api::types::asset::{
+    EncodedAsset, GetArguments, GetChunkArguments, GetChunkResponse,
+};
+use candid::Nat;
+use ic_agent::AgentError;
+use ic_utils::call::SyncCall;
+use ic_utils::Canister;
+
+/// Returns the first chunk of an asset encoding, and the length and hash of the entire encoding.
+pub(crate) async fn get(
+    canister: &Canister<'_>,
+    key: &str,
+    content_encoding: &str,
+) -> Result<EncodedAsset, AgentError> {
+    let (encoded_asset,): (EncodedAsset,) = canister
+        .query(GET)
+        .with_arg(GetArguments {
+            key: key.to_string(),
+            accept_encodings: vec![content_encoding.to_string()],
+        })
+        .build()
+        .call()
+        .await?;
+    Ok(encoded_asset)
+}
+
+pub(crate) async fn get_chunk(
+    canister: &Canister<'_>,
+    key: &str,
+    encoded_asset: &EncodedAsset,
+    index: usize,
+) -> Result<Vec<u8>, AgentError> {
+    let (chunk,): (GetChunkResponse,) = canister
+        .query(GET_CHUNK)
+        .with_arg(GetChunkArguments {
+            key: key.to_string(),
+            content_encoding: encoded_asset.content_encoding.clone(),
+            index: Nat::from(index),
+            sha256: encoded_asset.sha256.clone(),
+        })
+        .build()
+        .call()
+        .await?;
+    Ok(chunk.content.into_vec())
+}
@@ -4,6 +4,9 @@ pub(crate) const COMPUTE_EVIDENCE: &str = "compute_evidence";
 pub(crate) const CREATE_BATCH: &str = "create_batch";
 pub(crate) const CREATE_CHUNK: &str = "create_chunk";
 pub(crate) const CREATE_CHUNKS: &str = "create_chunks";
+pub(crate) const GET: &str = "get";
 pub(crate) const GET_ASSET_PROPERTIES: &str = "get_asset_properties";
+pub(crate) const GET_CHUNK: &str = "get_chunk";
 pub(crate) const LIST: &str = "list";
+pub(crate) const LIST_ROUTES: &str = "list_routes";
 pub(crate) const PROPOSE_COMMIT_BATCH: &str = "propose_commit_batch";
@@ -2,5 +2,7 @@ pub(crate) mod api_version;
 pub(crate) mod asset_properties;
 pub(crate) mod batch;
 pub(crate) mod chunk;
+pub(crate) mod get;
 pub(crate) mod list;
 pub(crate) mod method_names;
+pub(crate) mod route;
@@ -0,0 +1,10 @@
+use crate::canister_api::methods::method_names::LIST_ROUTES;
+use crate::canister_api::types::route::Route;
+use ic_agent::AgentError;
+use ic_utils::call::SyncCall;
+use ic_utils::Canister;
+
+pub(crate) async fn list_routes(canister: &Canister<'_>) -> Result<Vec<Route>, AgentError> {
+    let (routes,): (Vec<Route>,) = canister.query(LIST_ROUTES).build().call().await?;
+    Ok(routes)
+}
@@ -1,5 +1,6 @@
-use candid::CandidType;
+use candid::{CandidType, Nat};
 use serde::Deserialize;
+use serde_bytes::ByteBuf;
 use std::collections::HashMap;
 
 /// Information about a content encoding stored for an asset.
@@ -50,3 +51,47 @@ pub struct SetAssetPropertiesArguments {
 /// The arguments to the `get_asset_properties` method.
 #[derive(CandidType, Debug)]
 pub struct GetAssetPropertiesArgument(pub String);
+
+/// The arguments to the `get` method.
+#[derive(CandidType, Debug)]
+pub struct GetArguments {
+    /// The key of the asset.
+    pub key: String,
+    /// The encodings to return, in order of preference.
+    pub accept_encodings: Vec<String>,
+}
+
+/// The first chunk of an asset encoding, returned by the `get` method.
+#[derive(CandidType, Debug, Deserialize)]
+pub struct EncodedAsset {
+    /// The first chunk of the content, or the entire content.
+    pub content: ByteBuf,
+    /// The MIME type of the asset.
+    pub content_type: String,
+    /// The encoding of the content.
+    pub content_encoding: String,
+    /// The sha256 of the entire asset encoding.
+    pub sha256: Option<ByteBuf>,
+    /// The length of the entire asset encoding.
+    pub total_length: Nat,
+}
+
+/// The arguments to the `get_chunk` method.
+#[derive(CandidType, Debug)]
+pub struct GetChunkArguments {
+    /// The key of the asset.
+    pub key: String,
+    /// The encoding to return a chunk of.
+    pub content_encoding: String,
+    /// The index of the chunk.
+    pub index: Nat,
+    /// The sha256 of the entire asset encoding.
+    pub sha256: Option<ByteBuf>,
+}
+
+/// A chunk of an asset encoding, returned by the `get_chunk` method.
+#[derive(CandidType, Debug, Deserialize)]
+pub struct GetChunkResponse {
+    /// The content of the chunk.
+    pub content: ByteBuf,
+}
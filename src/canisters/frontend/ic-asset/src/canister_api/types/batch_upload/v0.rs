@@ -1,6 +1,8 @@
 use super::common::*;
 use crate::error::DowngradeCommitBatchArgumentsV1ToV0Error;
-use crate::error::DowngradeCommitBatchArgumentsV1ToV0Error::V0SetAssetPropertiesNotSupported;
+use crate::error::DowngradeCommitBatchArgumentsV1ToV0Error::{
+    V0RoutesNotSupported, V0SetAssetPropertiesNotSupported,
+};
 use candid::{CandidType, Nat};
 
 /// Batch operations that can be applied atomically.
@@ -57,6 +59,10 @@ impl TryFrom<super::v1::CommitBatchArguments> for CommitBatchArguments {
                 super::v1::BatchOperationKind::SetAssetProperties(_) => {
                     return Err(V0SetAssetPropertiesNotSupported)
                 }
+                super::v1::BatchOperationKind::SetRoute(_)
+                | super::v1::BatchOperationKind::DeleteRoute(_) => {
+                    return Err(V0RoutesNotSupported)
+                }
             };
             operations.push(operation);
         }
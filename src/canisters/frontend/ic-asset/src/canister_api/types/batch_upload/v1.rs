@@ -4,6 +4,7 @@ use crate::canister_api::types::{
         ClearArguments, CreateAssetArguments, DeleteAssetArguments, SetAssetContentArguments,
         UnsetAssetContentArguments,
     },
+    route::{DeleteRouteArguments, SetRouteArguments},
 };
 use candid::{CandidType, Nat};
 use std::collections::HashMap;
@@ -29,6 +30,12 @@ pub enum BatchOperationKind {
 
     /// Set asset properties.
     SetAssetProperties(SetAssetPropertiesArguments),
+
+    /// Add or replace a redirect or rewrite.
+    SetRoute(SetRouteArguments),
+
+    /// Remove a redirect or rewrite.
+    DeleteRoute(DeleteRouteArguments),
 }
 
 /// Apply all of the operations in the batch, and then remove the batch.
@@ -53,6 +60,8 @@ impl CommitBatchArguments {
                     BatchOperationKind::UnsetAssetContent(_) => "UnsetAssetContent",
                     BatchOperationKind::SetAssetContent(_) => "SetAssetContent",
                     BatchOperationKind::SetAssetProperties(_) => "SetAssetProperties",
+                    BatchOperationKind::SetRoute(_) => "SetRoute",
+                    BatchOperationKind::DeleteRoute(_) => "DeleteRoute",
                 };
                 *map.entry(key.to_owned()).or_default() += 1;
                 map
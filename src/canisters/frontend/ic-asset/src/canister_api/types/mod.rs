@@ -1,3 +1,4 @@
 pub(crate) mod asset;
 pub(crate) mod batch_upload;
 pub(crate) mod list;
+pub(crate) mod route;
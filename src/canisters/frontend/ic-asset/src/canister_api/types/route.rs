@@ -0,0 +1,38 @@
+use candid::CandidType;
+use serde::Deserialize;
+
+/// What the asset canister does with requests for the source path of a route.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RouteTarget {
+    /// Respond with a redirect.
+    Redirect {
+        /// The value of the `Location` header.
+        location: String,
+        /// One of 301, 302, 303, 307 or 308.
+        status_code: u16,
+    },
+    /// Serve another asset.
+    Rewrite {
+        /// The key of the asset to serve.
+        key: String,
+    },
+}
+
+/// A redirect or rewrite for an exact request path.
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Route {
+    /// The request path this route applies to.
+    pub source: String,
+    /// What to do with requests for the source path.
+    pub target: RouteTarget,
+}
+
+/// Add or replace the route for a path.
+pub type SetRouteArguments = Route;
+
+/// Remove the route for a path.
+#[derive(CandidType, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DeleteRouteArguments {
+    /// The request path of the route to remove.
+    pub source: String,
+}
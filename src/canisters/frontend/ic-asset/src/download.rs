@@ -0,0 +1,451 @@
+use crate::asset::config::ASSETS_CONFIG_FILENAME_JSON;
+use crate::asset::content_encoder::ContentEncoder;
+use crate::batch_upload::operations::ROUTES_API_VERSION;
+use crate::canister_api::methods::api_version::api_version;
+use crate::canister_api::methods::asset_properties::get_assets_properties;
+use crate::canister_api::methods::get::{get, get_chunk};
+use crate::canister_api::methods::list::list_assets;
+use crate::canister_api::methods::route::list_routes;
+use crate::canister_api::types::asset::{AssetDetails, AssetProperties};
+use crate::canister_api::types::route::{Route, RouteTarget};
+use crate::error::DownloadError;
+use crate::error::DownloadError::{
+    DecodeContentFailed, DirectoryNotEmpty, GetContentFailed, ListAssetsFailed, ListRoutesFailed,
+    SerializeConfigFailed, Sha256Mismatch,
+};
+use candid::Nat;
+use flate2::read::GzDecoder;
+use futures::future::try_join_all;
+use futures_intrusive::sync::SharedSemaphore;
+use ic_agent::AgentError;
+use ic_utils::Canister;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use slog::{debug, info, warn, Logger};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const MAX_CONCURRENT_DOWNLOADS: usize = 20;
+
+/// An asset of the canister, and where its encodings are written.
+struct AssetDownload {
+    details: AssetDetails,
+    /// The path of the asset relative to the download directory, with `/` as separator.
+    relative_path: String,
+    encoders: Vec<ContentEncoder>,
+}
+
+/// Downloads the assets of an asset canister into a directory, along with an `.ic-assets.json`
+/// that reproduces their properties and the routes of the canister when the directory is synchronized.
+///
+/// Encodings other than identity are written next to the asset, such as `app.js.gz` for `/app.js`.
+pub async fn download(
+    canister: &Canister<'_>,
+    dir: &Path,
+    logger: &Logger,
+) -> Result<(), DownloadError> {
+    if dir.exists() && dfx_core::fs::read_dir(dir)?.next().is_some() {
+        return Err(DirectoryNotEmpty(dir.to_path_buf()));
+    }
+    dfx_core::fs::create_dir_all(dir)?;
+
+    let canister_assets = list_assets(canister).await.map_err(ListAssetsFailed)?;
+    info!(
+        logger,
+        "Fetching properties for all assets in the canister."
+    );
+    let asset_properties = get_assets_properties(canister, &canister_assets, None).await?;
+    let routes = if api_version(canister).await >= ROUTES_API_VERSION {
+        list_routes(canister).await.map_err(ListRoutesFailed)?
+    } else {
+        vec![]
+    };
+
+    let downloads = plan_downloads(canister_assets, logger);
+    info!(logger, "Downloading {} assets.", downloads.len());
+    let semaphore = &SharedSemaphore::new(true, MAX_CONCURRENT_DOWNLOADS);
+    try_join_all(downloads.iter().map(|download| async move {
+        let _releaser = semaphore.acquire(1).await;
+        download_asset(canister, download, dir, logger).await
+    }))
+    .await?;
+
+    let config = assets_config(&downloads, &asset_properties, &routes);
+    let config = serde_json::to_string_pretty(&config).map_err(SerializeConfigFailed)?;
+    dfx_core::fs::write(dir.join(ASSETS_CONFIG_FILENAME_JSON), config)?;
+    info!(logger, "Downloaded assets to {}.", dir.display());
+    Ok(())
+}
+
+/// Decides which files to write for each asset.
+/// Assets whose key cannot be written as a file in the download directory are skipped.
+fn plan_downloads(
+    canister_assets: HashMap<String, AssetDetails>,
+    logger: &Logger,
+) -> Vec<AssetDownload> {
+    let mut canister_assets: Vec<_> = canister_assets.into_values().collect();
+    canister_assets.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut files = HashSet::from([ASSETS_CONFIG_FILENAME_JSON.to_string()]);
+    let mut dirs = HashSet::new();
+    let mut downloads = vec![];
+    for details in canister_assets {
+        let Some(relative_path) = relative_path(&details.key) else {
+            warn!(
+                logger,
+                "Skipping asset '{}': its key cannot be used as a file path.", details.key
+            );
+            continue;
+        };
+        let mut encoders = vec![];
+        for encoding in &details.encodings {
+            match ContentEncoder::from_content_encoding(&encoding.content_encoding) {
+                Some(encoder) => encoders.push(encoder),
+                None => warn!(
+                    logger,
+                    "Skipping {} encoding of asset '{}': it cannot be synchronized.",
+                    encoding.content_encoding,
+                    details.key
+                ),
+            }
+        }
+        if encoders.is_empty() {
+            warn!(
+                logger,
+                "Skipping asset '{}': it has no content.", details.key
+            );
+            continue;
+        }
+
+        let mut paths = vec![relative_path.clone()];
+        paths.extend(
+            encoders
+                .iter()
+                .filter_map(ContentEncoder::file_extension)
+                .map(|extension| format!("{relative_path}.{extension}")),
+        );
+        let parents: Vec<&str> = relative_path
+            .match_indices('/')
+            .map(|(index, _)| &relative_path[..index])
+            .collect();
+        if paths
+            .iter()
+            .any(|path| files.contains(path) || dirs.contains(path))
+            || parents.iter().any(|parent| files.contains(*parent))
+        {
+            warn!(
+                logger,
+                "Skipping asset '{}': its files would overwrite those of another asset.",
+                details.key
+            );
+            continue;
+        }
+        dirs.extend(parents.into_iter().map(str::to_string));
+        files.extend(paths);
+        downloads.push(AssetDownload {
+            details,
+            relative_path,
+            encoders,
+        });
+    }
+    downloads
+}
+
+/// Returns the path of the file for an asset key, or None if the key is not a valid relative path.
+fn relative_path(key: &str) -> Option<String> {
+    let relative_path = key.strip_prefix('/')?;
+    relative_path
+        .split('/')
+        .all(|component| {
+            !component.is_empty()
+                && component != "."
+                && component != ".."
+                && !component.contains('\\')
+        })
+        .then(|| relative_path.to_string())
+}
+
+async fn download_asset(
+    canister: &Canister<'_>,
+    download: &AssetDownload,
+    dir: &Path,
+    logger: &Logger,
+) -> Result<(), DownloadError> {
+    let key = &download.details.key;
+    let path = dir.join(&download.relative_path);
+    if let Some(parent) = path.parent() {
+        dfx_core::fs::create_dir_all(parent)?;
+    }
+
+    let has_identity = download.encoders.contains(&ContentEncoder::Identity);
+    let mut identity = None;
+    for encoding in &download.details.encodings {
+        let Some(encoder) = ContentEncoder::from_content_encoding(&encoding.content_encoding)
+        else {
+            continue;
+        };
+        debug!(logger, "  {} ({})", key, encoding.content_encoding);
+        let content = get_content(canister, key, &encoding.content_encoding)
+            .await
+            .map_err(|e| GetContentFailed(key.clone(), encoding.content_encoding.clone(), e))?;
+        if let Some(sha256) = &encoding.sha256 {
+            if Sha256::digest(&content).as_slice() != sha256.as_slice() {
+                return Err(Sha256Mismatch(
+                    key.clone(),
+                    encoding.content_encoding.clone(),
+                ));
+            }
+        }
+        match encoder.file_extension() {
+            Some(extension) => {
+                let encoded_path = precompressed_path(&path, extension);
+                dfx_core::fs::write(&encoded_path, &content)?;
+                if !has_identity && identity.is_none() {
+                    // Synchronizing only finds precompressed files next to their original.
+                    let decoded = decode(&content, encoder)
+                        .map_err(|e| DecodeContentFailed(key.clone(), encoder, e))?;
+                    identity = Some(decoded);
+                }
+            }
+            None => identity = Some(content),
+        }
+    }
+    if let Some(identity) = identity {
+        dfx_core::fs::write(&path, identity)?;
+    }
+    Ok(())
+}
+
+/// Returns the entire content of an asset encoding.
+async fn get_content(
+    canister: &Canister<'_>,
+    key: &str,
+    content_encoding: &str,
+) -> Result<Vec<u8>, AgentError> {
+    let encoded_asset = get(canister, key, content_encoding).await?;
+    let mut content = encoded_asset.content.to_vec();
+    let mut index = 1;
+    while Nat::from(content.len()) < encoded_asset.total_length {
+        let chunk = get_chunk(canister, key, &encoded_asset, index).await?;
+        if chunk.is_empty() {
+            break;
+        }
+        content.extend(chunk);
+        index += 1;
+    }
+    Ok(content)
+}
+
+fn precompressed_path(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(extension);
+    path.with_file_name(file_name)
+}
+
+fn decode(content: &[u8], encoder: ContentEncoder) -> Result<Vec<u8>, std::io::Error> {
+    let mut decoded = vec![];
+    match encoder {
+        ContentEncoder::Gzip => {
+            GzDecoder::new(content).read_to_end(&mut decoded)?;
+        }
+        ContentEncoder::Brotli => {
+            brotli::Decompressor::new(content, 4096).read_to_end(&mut decoded)?;
+        }
+        ContentEncoder::Zstd => decoded = zstd::decode_all(content)?,
+        ContentEncoder::Identity => decoded.extend_from_slice(content),
+    }
+    Ok(decoded)
+}
+
+/// Generates the rules of an `.ic-assets.json` that give the downloaded assets the properties
+/// and encodings they have in the canister, and that recreate the routes of the canister.
+fn assets_config(
+    downloads: &[AssetDownload],
+    asset_properties: &HashMap<String, AssetProperties>,
+    routes: &[Route],
+) -> Vec<Value> {
+    let mut rules = vec![];
+
+    // Files and directories starting with a dot are ignored by default.
+    let hidden_dirs: BTreeSet<&str> = downloads
+        .iter()
+        .flat_map(|download| {
+            let path = download.relative_path.as_str();
+            path.match_indices('/')
+                .map(move |(index, _)| &path[..index])
+                .filter(|dir| {
+                    dir.rsplit('/')
+                        .next()
+                        .is_some_and(|name| name.starts_with('.'))
+                })
+        })
+        .collect();
+    for dir in hidden_dirs {
+        rules.push(json!({ "match": globset::escape(dir), "ignore": false }));
+    }
+
+    for download in downloads {
+        let mut rule = Map::new();
+        rule.insert(
+            "match".to_string(),
+            json!(globset::escape(&download.relative_path)),
+        );
+        let file_name = download
+            .relative_path
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        if file_name.starts_with('.') {
+            rule.insert("ignore".to_string(), json!(false));
+        }
+        if let Some(properties) = asset_properties.get(&download.details.key) {
+            if let Some(max_age) = properties.max_age {
+                rule.insert("cache".to_string(), json!({ "max_age": max_age }));
+            }
+            if let Some(headers) = properties.headers.as_ref().filter(|h| !h.is_empty()) {
+                let headers: BTreeMap<_, _> = headers.iter().collect();
+                rule.insert("headers".to_string(), json!(headers));
+            }
+            if let Some(is_aliased) = properties.is_aliased {
+                rule.insert("enable_aliasing".to_string(), json!(is_aliased));
+            }
+            if let Some(allow_raw_access) = properties.allow_raw_access {
+                rule.insert("allow_raw_access".to_string(), json!(allow_raw_access));
+            }
+        }
+        rule.insert("encodings".to_string(), json!(download.encoders));
+        rules.push(Value::Object(rule));
+    }
+
+    let mut routes = routes.to_vec();
+    routes.sort();
+    for route in routes {
+        rules.push(match route.target {
+            RouteTarget::Redirect {
+                location,
+                status_code,
+            } => json!({
+                "match": route.source,
+                "redirect": { "location": location, "status_code": status_code },
+            }),
+            RouteTarget::Rewrite { key } => json!({ "match": route.source, "rewrite": key }),
+        });
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canister_api::types::asset::AssetEncodingDetails;
+
+    fn asset(key: &str, encodings: &[&str]) -> (String, AssetDetails) {
+        let details = AssetDetails {
+            key: key.to_string(),
+            encodings: encodings
+                .iter()
+                .map(|encoding| AssetEncodingDetails {
+                    content_encoding: encoding.to_string(),
+                    sha256: None,
+                })
+                .collect(),
+            content_type: "text/plain".to_string(),
+        };
+        (key.to_string(), details)
+    }
+
+    fn planned_paths(assets: &[(&str, &[&str])]) -> Vec<String> {
+        let logger = slog::Logger::root(slog::Discard, slog::o!());
+        let canister_assets = assets
+            .iter()
+            .map(|(key, encodings)| asset(key, encodings))
+            .collect();
+        plan_downloads(canister_assets, &logger)
+            .into_iter()
+            .map(|download| download.relative_path)
+            .collect()
+    }
+
+    #[test]
+    fn keys_that_are_not_relative_paths_are_skipped() {
+        assert_eq!(
+            relative_path("/css/app.css").as_deref(),
+            Some("css/app.css")
+        );
+        assert_eq!(
+            relative_path("/.well-known/ic-domains").as_deref(),
+            Some(".well-known/ic-domains")
+        );
+        for key in [
+            "index.html",
+            "/",
+            "/dir/",
+            "//etc/passwd",
+            "/../secret",
+            "/a/./b",
+            "/a\\b",
+        ] {
+            assert_eq!(relative_path(key), None, "{key}");
+        }
+    }
+
+    #[test]
+    fn assets_whose_files_collide_are_skipped() {
+        let paths = planned_paths(&[
+            ("/app.js", &["identity", "gzip"]),
+            ("/app.js.gz", &["identity"]),
+            ("/dir", &["identity"]),
+            ("/dir/file.txt", &["identity"]),
+            ("/other.txt", &["identity", "deflate"]),
+            ("/unknown.txt", &["deflate"]),
+        ]);
+        assert_eq!(paths, ["app.js", "dir", "other.txt"]);
+    }
+
+    #[test]
+    fn config_reproduces_properties_and_routes() {
+        let logger = slog::Logger::root(slog::Discard, slog::o!());
+        let canister_assets = HashMap::from([
+            asset("/index.html", &["identity", "gzip"]),
+            asset("/.well-known/.hidden", &["identity"]),
+            asset("/[draft].txt", &["br"]),
+        ]);
+        let downloads = plan_downloads(canister_assets, &logger);
+        let asset_properties = HashMap::from([(
+            "/index.html".to_string(),
+            AssetProperties {
+                max_age: Some(60),
+                headers: Some(HashMap::from([("x-a".to_string(), "b".to_string())])),
+                allow_raw_access: Some(false),
+                is_aliased: Some(true),
+            },
+        )]);
+        let routes = [Route {
+            source: "/old".to_string(),
+            target: RouteTarget::Redirect {
+                location: "/index.html".to_string(),
+                status_code: 301,
+            },
+        }];
+
+        assert_eq!(
+            Value::Array(assets_config(&downloads, &asset_properties, &routes)),
+            json!([
+                { "match": ".well-known", "ignore": false },
+                { "match": ".well-known/.hidden", "ignore": false, "encodings": ["identity"] },
+                { "match": "[[]draft[]].txt", "encodings": ["brotli"] },
+                {
+                    "match": "index.html",
+                    "cache": { "max_age": 60 },
+                    "headers": { "x-a": "b" },
+                    "enable_aliasing": true,
+                    "allow_raw_access": false,
+                    "encodings": ["identity", "gzip"],
+                },
+                { "match": "/old", "redirect": { "location": "/index.html", "status_code": 301 } },
+            ])
+        );
+    }
+}
@@ -33,4 +33,8 @@ pub enum ComputeEvidenceError {
     /// Failed to list assets in the asset canister.
     #[error("Failed to list assets")]
     ListAssets(#[source] AgentError),
+
+    /// Failed to list routes in the asset canister.
+    #[error("Failed to list routes")]
+    ListRoutes(#[source] AgentError),
 }
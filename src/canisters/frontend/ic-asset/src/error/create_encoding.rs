@@ -1,5 +1,6 @@
 use crate::asset::content_encoder::ContentEncoder;
 use crate::error::create_chunk::CreateChunkError;
+use dfx_core::error::fs::ReadFileError;
 use thiserror::Error;
 
 /// Errors related to creating/uploading an asset content encoding to the asset canister
@@ -12,4 +13,8 @@ pub enum CreateEncodingError {
     /// Failed when encoding asset content.
     #[error("Failed to encode content of '{0}' with {1} encoding")]
     EncodeContentFailed(String, ContentEncoder, #[source] std::io::Error),
+
+    /// Failed to load a precompressed file.
+    #[error("Failed to load precompressed content")]
+    LoadPrecompressedContentFailed(#[source] ReadFileError),
 }
@@ -6,4 +6,8 @@ pub enum DowngradeCommitBatchArgumentsV1ToV0Error {
     /// Asset canister v0 does not support SetAssetProperties.
     #[error("SetAssetProperties is not supported")]
     V0SetAssetPropertiesNotSupported,
+
+    /// Asset canister v0 does not support redirects and rewrites.
+    #[error("Redirects and rewrites are not supported")]
+    V0RoutesNotSupported,
 }
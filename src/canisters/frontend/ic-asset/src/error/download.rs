@@ -0,0 +1,56 @@
+use crate::asset::content_encoder::ContentEncoder;
+use crate::error::get_asset_properties::GetAssetPropertiesError;
+use dfx_core::error::fs::{CreateDirAllError, ReadDirError, WriteFileError};
+use ic_agent::AgentError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors related to downloading the assets of an asset canister.
+#[derive(Error, Debug)]
+pub enum DownloadError {
+    /// Failed to create the directory to download to.
+    #[error(transparent)]
+    CreateDirectoryFailed(#[from] CreateDirAllError),
+
+    /// Failed to decode an encoding of an asset that has no identity encoding.
+    #[error("Failed to decode content of '{0}' with {1} encoding")]
+    DecodeContentFailed(String, ContentEncoder, #[source] std::io::Error),
+
+    /// The directory to download to already holds files.
+    #[error(
+        "Directory {0} is not empty. Choose an empty or new directory to download the assets to"
+    )]
+    DirectoryNotEmpty(PathBuf),
+
+    /// Failed when calling get or get_chunk.
+    #[error("Failed to get {1} encoding of asset '{0}'")]
+    GetContentFailed(String, String, #[source] AgentError),
+
+    /// Failed when getting asset properties.
+    #[error(transparent)]
+    GetAssetPropertiesFailed(#[from] GetAssetPropertiesError),
+
+    /// Failed when calling the list method.
+    #[error("Failed to list assets")]
+    ListAssetsFailed(#[source] AgentError),
+
+    /// Failed when calling the list_routes method.
+    #[error("Failed to list routes")]
+    ListRoutesFailed(#[source] AgentError),
+
+    /// Failed to check whether the directory to download to is empty.
+    #[error(transparent)]
+    ReadDirectoryFailed(#[from] ReadDirError),
+
+    /// Failed to serialize the generated asset configuration.
+    #[error("Failed to serialize asset configuration")]
+    SerializeConfigFailed(#[source] serde_json::Error),
+
+    /// The downloaded content does not match the hash stored in the canister.
+    #[error("The {1} encoding of asset '{0}' does not match its sha256")]
+    Sha256Mismatch(String, String),
+
+    /// Failed to write a downloaded file.
+    #[error(transparent)]
+    WriteFileFailed(#[from] WriteFileError),
+}
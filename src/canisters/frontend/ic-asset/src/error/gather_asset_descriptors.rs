@@ -11,6 +11,10 @@ pub enum GatherAssetDescriptorsError {
     #[error("Asset with key '{0}' defined at {1} and {2}")]
     DuplicateAssetKey(String, Box<PathBuf>, Box<PathBuf>),
 
+    /// A redirect or rewrite for the same path exists in more than one source directory.
+    #[error("More than one redirect or rewrite for '{0}'")]
+    DuplicateRoute(String),
+
     /// Failed to get asset configuration.
     #[error("Failed to get asset configuration")]
     GetAssetConfigFailed(#[from] GetAssetConfigError),
@@ -6,6 +6,10 @@ use thiserror::Error;
 /// Errors related to loading asset configuration.
 #[derive(Error, Debug)]
 pub enum AssetLoadConfigError {
+    /// More than one rule redirects or rewrites the same path.
+    #[error("More than one redirect or rewrite for '{0}'")]
+    DuplicateRoute(String),
+
     /// Failed to canonicalize the root directory.
     #[error("root_dir '{0}' is expected to be a canonical path")]
     InvalidRootDir(PathBuf),
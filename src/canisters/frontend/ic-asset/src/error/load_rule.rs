@@ -11,4 +11,24 @@ pub enum LoadRuleError {
     /// The glob pattern was not valid.
     #[error("{0} is not a valid glob pattern")]
     InvalidGlobPattern(String, #[source] globset::Error),
+
+    /// A redirect had an empty location.
+    #[error("The redirect for '{0}' has an empty location")]
+    EmptyRedirectLocation(String),
+
+    /// A redirect had a status code that is not a redirect status code.
+    #[error("The redirect for '{0}' has status code {1}, but only 301, 302, 303, 307 and 308 are allowed")]
+    InvalidRedirectStatusCode(String, u16),
+
+    /// A rule had both a redirect and a rewrite.
+    #[error("The rule for '{0}' cannot both redirect and rewrite")]
+    RedirectAndRewrite(String),
+
+    /// A redirect or rewrite rule also set asset properties.
+    #[error("The rule for '{0}' redirects or rewrites, so it cannot also configure assets")]
+    RouteWithAssetProperties(String),
+
+    /// A redirect or rewrite rule matched a glob pattern instead of an exact path.
+    #[error("Redirects and rewrites must match an exact path, but '{0}' is a glob pattern")]
+    RouteWithGlobPattern(String),
 }
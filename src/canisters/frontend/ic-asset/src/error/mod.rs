@@ -7,6 +7,7 @@ mod create_chunk;
 mod create_encoding;
 mod create_project_asset;
 mod downgrade_commit_batch_arguments;
+mod download;
 mod gather_asset_descriptors;
 mod get_asset_config;
 mod get_asset_properties;
@@ -26,6 +27,7 @@ pub use create_chunk::CreateChunkError;
 pub use create_encoding::CreateEncodingError;
 pub use create_project_asset::CreateProjectAssetError;
 pub use downgrade_commit_batch_arguments::DowngradeCommitBatchArgumentsV1ToV0Error;
+pub use download::DownloadError;
 pub use gather_asset_descriptors::GatherAssetDescriptorsError;
 pub use get_asset_config::GetAssetConfigError;
 pub use get_asset_properties::GetAssetPropertiesError;
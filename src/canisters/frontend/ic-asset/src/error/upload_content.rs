@@ -32,4 +32,8 @@ pub enum UploadContentError {
     /// Failed when calling the list method.
     #[error("Failed to list assets")]
     ListAssetsFailed(#[source] AgentError),
+
+    /// Failed when calling the list_routes method.
+    #[error("Failed to list routes")]
+    ListRoutesFailed(#[source] AgentError),
 }
@@ -1,20 +1,25 @@
 use crate::asset::content::Content;
-use crate::asset::content_encoder::ContentEncoder::{Brotli, Gzip};
-use crate::batch_upload::operations::assemble_batch_operations;
+use crate::asset::content_encoder::ContentEncoder::{Brotli, Gzip, Zstd};
 use crate::batch_upload::operations::AssetDeletionReason::Obsolete;
+use crate::batch_upload::operations::{
+    assemble_batch_operations, update_routes, ROUTES_API_VERSION,
+};
 use crate::batch_upload::plumbing::{make_project_assets, ProjectAsset};
+use crate::canister_api::methods::api_version::api_version;
 use crate::canister_api::methods::asset_properties::get_assets_properties;
 use crate::canister_api::methods::list::list_assets;
+use crate::canister_api::methods::route::list_routes;
 use crate::canister_api::types::asset::SetAssetPropertiesArguments;
 use crate::canister_api::types::batch_upload::common::{
     ClearArguments, CreateAssetArguments, DeleteAssetArguments, SetAssetContentArguments,
     UnsetAssetContentArguments,
 };
 use crate::canister_api::types::batch_upload::v1::BatchOperationKind;
+use crate::canister_api::types::route::{DeleteRouteArguments, RouteTarget, SetRouteArguments};
 use crate::error::ComputeEvidenceError;
 use crate::error::HashContentError;
 use crate::error::HashContentError::EncodeContentFailed;
-use crate::sync::gather_asset_descriptors;
+use crate::sync::{gather_asset_descriptors, gather_routes};
 use crate::AssetSyncProgressRenderer;
 use ic_utils::Canister;
 use sha2::{Digest, Sha256};
@@ -34,6 +39,11 @@ const TAG_UNSET_ASSET_CONTENT: [u8; 1] = [6];
 const TAG_DELETE_ASSET: [u8; 1] = [7];
 const TAG_CLEAR: [u8; 1] = [8];
 const TAG_SET_ASSET_PROPERTIES: [u8; 1] = [9];
+const TAG_SET_ROUTE: [u8; 1] = [10];
+const TAG_DELETE_ROUTE: [u8; 1] = [11];
+
+const TAG_REDIRECT: [u8; 1] = [12];
+const TAG_REWRITE: [u8; 1] = [13];
 
 /// Compute the hash ("evidence") over the batch operations required to update the assets
 pub async fn compute_evidence(
@@ -43,10 +53,20 @@ pub async fn compute_evidence(
     progress: Option<&dyn AssetSyncProgressRenderer>,
 ) -> Result<String, ComputeEvidenceError> {
     let asset_descriptors = gather_asset_descriptors(dirs, logger)?;
+    let project_routes = gather_routes(dirs)?;
 
     let canister_assets = list_assets(canister)
         .await
         .map_err(ComputeEvidenceError::ListAssets)?;
+    let canister_routes = if api_version(canister).await >= ROUTES_API_VERSION {
+        Some(
+            list_routes(canister)
+                .await
+                .map_err(ComputeEvidenceError::ListRoutes)?,
+        )
+    } else {
+        None
+    };
     info!(
         logger,
         "Fetching properties for all assets in the canister."
@@ -78,6 +98,9 @@ pub async fn compute_evidence(
     )
     .await
     .map_err(ComputeEvidenceError::AssembleCommitBatchArgumentFailed)?;
+    if let Some(canister_routes) = canister_routes {
+        update_routes(&mut operations, &project_routes, canister_routes, Obsolete);
+    }
     operations.sort();
     trace!(logger, "{:#?}", operations);
 
@@ -104,6 +127,8 @@ fn hash_operation(
         BatchOperationKind::DeleteAsset(args) => hash_delete_asset(hasher, args),
         BatchOperationKind::Clear(args) => hash_clear(hasher, args),
         BatchOperationKind::SetAssetProperties(args) => hash_set_asset_properties(hasher, args),
+        BatchOperationKind::SetRoute(args) => hash_set_route(hasher, args),
+        BatchOperationKind::DeleteRoute(args) => hash_delete_route(hasher, args),
     };
     Ok(())
 }
@@ -138,15 +163,21 @@ fn hash_set_asset_content(
 
     let content = {
         let identity = Content::load(&ad.source)?;
-        match args.content_encoding.as_str() {
-            "identity" => identity,
-            "br" | "brotli" => identity
-                .encode(&Brotli)
-                .map_err(|e| EncodeContentFailed(ad.key.clone(), Brotli, e))?,
-            "gzip" => identity
-                .encode(&Gzip)
-                .map_err(|e| EncodeContentFailed(ad.key.clone(), Gzip, e))?,
+        let encoder = match args.content_encoding.as_str() {
+            "identity" => None,
+            "br" | "brotli" => Some(Brotli),
+            "gzip" => Some(Gzip),
+            "zstd" => Some(Zstd),
             _ => unreachable!("unhandled content encoder"),
+        };
+        match encoder {
+            None => identity,
+            Some(encoder) => match ad.precompressed.get(&encoder) {
+                Some(path) => identity.load_encoded(path)?,
+                None => identity
+                    .encode(&encoder)
+                    .map_err(|e| EncodeContentFailed(ad.key.clone(), encoder, e))?,
+            },
         }
     };
     hasher.update(&content.data);
@@ -168,6 +199,30 @@ fn hash_clear(hasher: &mut Sha256, _args: &ClearArguments) {
     hasher.update(TAG_CLEAR);
 }
 
+fn hash_set_route(hasher: &mut Sha256, args: &SetRouteArguments) {
+    hasher.update(TAG_SET_ROUTE);
+    hasher.update(&args.source);
+    match &args.target {
+        RouteTarget::Redirect {
+            location,
+            status_code,
+        } => {
+            hasher.update(TAG_REDIRECT);
+            hasher.update(location);
+            hasher.update(status_code.to_be_bytes());
+        }
+        RouteTarget::Rewrite { key } => {
+            hasher.update(TAG_REWRITE);
+            hasher.update(key);
+        }
+    }
+}
+
+fn hash_delete_route(hasher: &mut Sha256, args: &DeleteRouteArguments) {
+    hasher.update(TAG_DELETE_ROUTE);
+    hasher.update(&args.source);
+}
+
 fn hash_opt_bool(hasher: &mut Sha256, b: Option<bool>) {
     if let Some(b) = b {
         hasher.update(TAG_SOME);
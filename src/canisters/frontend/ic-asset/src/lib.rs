@@ -34,6 +34,7 @@
 mod asset;
 mod batch_upload;
 mod canister_api;
+mod download;
 pub mod error;
 mod evidence;
 mod progress;
@@ -41,6 +42,7 @@ pub mod security_policy;
 mod sync;
 mod upload;
 
+pub use download::download;
 pub use evidence::compute_evidence;
 pub use progress::{AssetSyncProgressRenderer, AssetSyncState};
 pub use sync::prepare_sync_for_proposal;
@@ -1,7 +1,8 @@
 use crate::asset::config::{
     AssetConfig, AssetSourceDirectoryConfiguration, ASSETS_CONFIG_FILENAME_JSON,
 };
-use crate::batch_upload::operations::BATCH_UPLOAD_API_VERSION;
+use crate::asset::content_encoder::ContentEncoder;
+use crate::batch_upload::operations::{BATCH_UPLOAD_API_VERSION, ROUTES_API_VERSION};
 use crate::batch_upload::plumbing::ChunkUploader;
 use crate::batch_upload::plumbing::Mode::{ByProposal, NormalDeploy};
 use crate::batch_upload::{
@@ -15,22 +16,25 @@ use crate::canister_api::methods::{
     asset_properties::get_assets_properties,
     batch::{commit_batch, create_batch},
     list::list_assets,
+    route::list_routes,
 };
 use crate::canister_api::types::batch_upload::v0;
 use crate::canister_api::types::batch_upload::v1::BatchOperationKind;
 use crate::canister_api::types::batch_upload::{
     common::ComputeEvidenceArguments, v1::CommitBatchArguments,
 };
+use crate::canister_api::types::route::Route;
 use crate::error::CompatibilityError::DowngradeV1TOV0Failed;
 use crate::error::GatherAssetDescriptorsError;
 use crate::error::GatherAssetDescriptorsError::{
-    DuplicateAssetKey, InvalidDirectoryEntry, InvalidSourceDirectory, LoadConfigFailed,
+    DuplicateAssetKey, DuplicateRoute, InvalidDirectoryEntry, InvalidSourceDirectory,
+    LoadConfigFailed,
 };
 use crate::error::PrepareSyncForProposalError;
 use crate::error::SyncError;
 use crate::error::SyncError::CommitBatchFailed;
 use crate::error::UploadContentError;
-use crate::error::UploadContentError::{CreateBatchFailed, ListAssetsFailed};
+use crate::error::UploadContentError::{CreateBatchFailed, ListAssetsFailed, ListRoutesFailed};
 use crate::progress::{AssetSyncProgressRenderer, AssetSyncState};
 use candid::Nat;
 use ic_agent::AgentError;
@@ -38,8 +42,8 @@ use ic_utils::Canister;
 use itertools::Itertools;
 use serde_bytes::ByteBuf;
 use slog::{debug, info, trace, warn, Logger};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 const KNOWN_DIRECTORIES: [&str; 1] = [".well-known"];
@@ -59,11 +63,20 @@ pub async fn upload_content_and_assemble_sync_operations(
     }
 
     let asset_descriptors = gather_asset_descriptors(dirs, logger)?;
+    let project_routes = gather_routes(dirs)?;
 
     if let Some(progress) = progress {
         progress.set_state(AssetSyncState::ListAssets);
     }
     let canister_assets = list_assets(canister).await.map_err(ListAssetsFailed)?;
+    let canister_routes = if canister_api_version >= ROUTES_API_VERSION {
+        Some(list_routes(canister).await.map_err(ListRoutesFailed)?)
+    } else {
+        if !project_routes.is_empty() {
+            warn!(logger, "The asset canister is running an old version of the API. It will not be able to serve redirects and rewrites.");
+        }
+        None
+    };
     debug!(
         logger,
         "Fetching properties for all assets in the canister."
@@ -114,19 +127,28 @@ pub async fn upload_content_and_assemble_sync_operations(
         progress.set_state(AssetSyncState::AssembleBatch);
     }
 
-    let commit_batch_args = batch_upload::operations::assemble_commit_batch_arguments(
+    let asset_deletion_reason = match no_delete {
+        true => AssetDeletionReason::Incompatible,
+        false => AssetDeletionReason::Obsolete,
+    };
+    let mut commit_batch_args = batch_upload::operations::assemble_commit_batch_arguments(
         &chunk_uploader,
         project_assets,
         canister_assets,
-        match no_delete {
-            true => AssetDeletionReason::Incompatible,
-            false => AssetDeletionReason::Obsolete,
-        },
+        asset_deletion_reason,
         canister_asset_properties,
         batch_id,
     )
     .await
     .map_err(UploadContentError::AssembleCommitBatchArgumentFailed)?;
+    if let Some(canister_routes) = canister_routes {
+        batch_upload::operations::update_routes(
+            &mut commit_batch_args.operations,
+            &project_routes,
+            canister_routes,
+            asset_deletion_reason,
+        );
+    }
 
     // -v
     debug!(
@@ -314,6 +336,55 @@ fn include_entry(entry: &walkdir::DirEntry, config: &AssetConfig) -> bool {
     }
 }
 
+/// Files such as `app.js.br` that were compressed next to `app.js` become encodings of that asset
+/// rather than assets of their own.
+fn group_precompressed_files(asset_descriptors: Vec<AssetDescriptor>) -> Vec<AssetDescriptor> {
+    let sources: HashSet<PathBuf> = asset_descriptors
+        .iter()
+        .map(|asset_descriptor| asset_descriptor.source.clone())
+        .collect();
+    let (precompressed, mut asset_descriptors): (Vec<_>, Vec<_>) =
+        asset_descriptors.into_iter().partition(|asset_descriptor| {
+            precompressed_encoding(&asset_descriptor.source)
+                .is_some_and(|(_, original)| sources.contains(&original))
+        });
+    for file in precompressed {
+        let (encoder, original) = precompressed_encoding(&file.source).unwrap();
+        match asset_descriptors
+            .iter_mut()
+            .find(|asset_descriptor| asset_descriptor.source == original)
+        {
+            Some(asset_descriptor) => {
+                asset_descriptor.precompressed.insert(encoder, file.source);
+            }
+            // The original is itself a precompressed file, e.g. `app.js.gz` for `app.js.gz.br`.
+            None => asset_descriptors.push(file),
+        }
+    }
+    asset_descriptors
+}
+
+fn precompressed_encoding(path: &Path) -> Option<(ContentEncoder, PathBuf)> {
+    let encoder = ContentEncoder::from_file_extension(path.extension()?.to_str()?)?;
+    Some((encoder, path.with_extension("")))
+}
+
+pub(crate) fn gather_routes(dirs: &[&Path]) -> Result<Vec<Route>, GatherAssetDescriptorsError> {
+    let mut routes: Vec<Route> = vec![];
+    for dir in dirs {
+        let dir = dfx_core::fs::canonicalize(dir).map_err(InvalidSourceDirectory)?;
+        let configuration =
+            AssetSourceDirectoryConfiguration::load(&dir).map_err(LoadConfigFailed)?;
+        for route in configuration.routes() {
+            if routes.iter().any(|r| r.source == route.source) {
+                return Err(DuplicateRoute(route.source.clone()));
+            }
+            routes.push(route.clone());
+        }
+    }
+    Ok(routes)
+}
+
 pub(crate) fn gather_asset_descriptors(
     dirs: &[&Path],
     logger: &Logger,
@@ -352,8 +423,10 @@ pub(crate) fn gather_asset_descriptors(
                 source,
                 key,
                 config,
+                precompressed: HashMap::new(),
             })
         }
+        let asset_descriptors_interim = group_precompressed_files(asset_descriptors_interim);
 
         for asset_descriptor in asset_descriptors_interim {
             if let Some(already_seen) = asset_descriptors.get(&asset_descriptor.key) {
@@ -457,6 +530,7 @@ pub(crate) fn gather_asset_descriptors(
 mod test_gathering_asset_descriptors_with_tempdir {
 
     use crate::asset::config::{CacheConfig, HeadersConfig};
+    use crate::asset::content_encoder::ContentEncoder;
 
     use super::AssetDescriptor;
     use std::{
@@ -481,6 +555,7 @@ mod test_gathering_asset_descriptors_with_tempdir {
                 source: assets_dir.join(&relative_path),
                 key: format!("/{}", relative_path.to_str().unwrap()),
                 config: Default::default(),
+                precompressed: HashMap::new(),
             }
         }
         fn with_headers(mut self, headers: HashMap<&str, &str>) -> Self {
@@ -497,6 +572,11 @@ mod test_gathering_asset_descriptors_with_tempdir {
             self.config.cache = Some(cache);
             self
         }
+        fn with_precompressed(mut self, encoder: ContentEncoder, file: &str) -> Self {
+            let source = self.source.with_file_name(file);
+            self.precompressed.insert(encoder, source);
+            self
+        }
     }
 
     impl PartialEq for AssetDescriptor {
@@ -507,6 +587,7 @@ mod test_gathering_asset_descriptors_with_tempdir {
                 self.config.cache == other.config.cache,
                 self.config.headers == other.config.headers,
                 self.config.ignore.unwrap_or(false) == other.config.ignore.unwrap_or(false),
+                self.precompressed == other.precompressed,
             ]
             .into_iter()
             .all(|v| v)
@@ -961,6 +1042,34 @@ mod test_gathering_asset_descriptors_with_tempdir {
         assert_eq!(asset_descriptors, expected_asset_descriptors);
     }
 
+    #[test]
+    fn precompressed_files_are_encodings_of_their_original() {
+        let files = HashMap::from([
+            (Path::new("app.js").to_path_buf(), "app".to_string()),
+            (Path::new("app.js.br").to_path_buf(), "br".to_string()),
+            (Path::new("app.js.gz").to_path_buf(), "gz".to_string()),
+            (Path::new("app.js.zst").to_path_buf(), "zst".to_string()),
+            (Path::new("archive.tar.gz").to_path_buf(), "gz".to_string()),
+        ]);
+
+        let assets_temp_dir = create_temporary_assets_directory(files);
+        let assets_dir = assets_temp_dir.path().canonicalize().unwrap();
+        let mut asset_descriptors = dbg!(gather_asset_descriptors(&[&assets_dir]));
+
+        let mut expected_asset_descriptors = vec![
+            AssetDescriptor::default_from_path(&assets_dir, "file"),
+            AssetDescriptor::default_from_path(&assets_dir, "app.js")
+                .with_precompressed(ContentEncoder::Brotli, "app.js.br")
+                .with_precompressed(ContentEncoder::Gzip, "app.js.gz")
+                .with_precompressed(ContentEncoder::Zstd, "app.js.zst"),
+            AssetDescriptor::default_from_path(&assets_dir, "archive.tar.gz"),
+        ];
+
+        expected_asset_descriptors.sort_by_key(|v| v.source.clone());
+        asset_descriptors.sort_by_key(|v| v.source.clone());
+        assert_eq!(asset_descriptors, expected_asset_descriptors);
+    }
+
     #[test]
     fn known_directories_can_be_ignored() {
         let files = HashMap::from([
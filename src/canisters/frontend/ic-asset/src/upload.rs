@@ -33,6 +33,7 @@ pub async fn upload(
             source: x.1.clone(),
             key: x.0.clone(),
             config: AssetConfig::default(),
+            precompressed: HashMap::new(),
         })
         .collect();
 
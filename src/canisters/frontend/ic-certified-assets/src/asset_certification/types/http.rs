@@ -14,6 +14,9 @@ pub const FALLBACK_FILE: &str = "/index.html";
 
 const HTTP_REDIRECT_PERMANENT: u16 = 308;
 
+pub const HTTP_PARTIAL_CONTENT: u16 = 206;
+pub const HTTP_RANGE_NOT_SATISFIABLE: u16 = 416;
+
 pub const IC_CERTIFICATE_EXPRESSION_VALUE: &str = r#"default_certification(ValidationArgs{certification: Certification{no_request_certification: Empty{}, response_certification: ResponseCertification{certified_response_headers: ResponseHeaderList{headers: ["content-type"{headers}]}}}})"#;
 
 pub type HeaderField = (String, String);
@@ -81,6 +84,66 @@ impl StreamingCallbackToken {
     }
 }
 
+/// A single byte range requested through the `Range` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=<start>-` or `bytes=<start>-<end>`
+    FromStart { start: usize, end: Option<usize> },
+    /// `bytes=-<length>`
+    Suffix { length: usize },
+}
+
+impl ByteRange {
+    /// Parses the value of a `Range` header.
+    /// Returns `None` for other units, multiple ranges and malformed values, which are all served as if no range was requested.
+    pub fn parse(header_value: &str) -> Option<Self> {
+        let spec = header_value.trim().strip_prefix("bytes=")?.trim();
+        if spec.contains(',') {
+            return None;
+        }
+        let (start, end) = spec.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+        if start.is_empty() {
+            let length = end.parse().ok()?;
+            Some(ByteRange::Suffix { length })
+        } else {
+            let start = start.parse().ok()?;
+            let end = if end.is_empty() {
+                None
+            } else {
+                Some(end.parse().ok()?)
+            };
+            if matches!(end, Some(end) if end < start) {
+                return None;
+            }
+            Some(ByteRange::FromStart { start, end })
+        }
+    }
+
+    /// Returns the first and last (inclusive) byte position of this range in content of `total_length` bytes,
+    /// or `None` if the range cannot be satisfied.
+    pub fn resolve(&self, total_length: usize) -> Option<(usize, usize)> {
+        if total_length == 0 {
+            return None;
+        }
+        match *self {
+            ByteRange::FromStart { start, end } => {
+                if start >= total_length {
+                    return None;
+                }
+                let end = end.map_or(total_length - 1, |end| end.min(total_length - 1));
+                Some((start, end))
+            }
+            ByteRange::Suffix { length } => {
+                if length == 0 {
+                    return None;
+                }
+                Some((total_length.saturating_sub(length), total_length - 1))
+            }
+        }
+    }
+}
+
 impl HttpRequest {
     pub fn get_path(&self) -> &str {
         match self.url.find('?') {
@@ -95,6 +158,11 @@ impl HttpRequest {
             .find_map(|(k, v)| k.eq_ignore_ascii_case(header_key).then_some(v))
     }
 
+    pub fn get_range(&self) -> Option<ByteRange> {
+        self.get_header_value("Range")
+            .and_then(|value| ByteRange::parse(value))
+    }
+
     // Spec:
     // If not set: assume version 1.
     // If available: use requested certificate version.
@@ -191,7 +259,10 @@ impl HttpResponse {
                     format!("\"{}\"", hex::encode(enc.sha256)),
                 );
             }
-            (200, enc.content_chunks[chunk_index].clone())
+            if enc_name == "identity" && enc.chunk_sha256s.is_some() {
+                headers.insert("accept-ranges".to_string(), "bytes".to_string());
+            }
+            (200, enc.content_chunks[chunk_index].load())
         };
 
         HttpResponse {
@@ -288,6 +359,57 @@ impl HttpResponse {
         None
     }
 
+    /// Serves one content chunk of the identity encoding of an asset.
+    ///
+    /// Every chunk is certified with its own `206` response, so a range is served as the whole chunk
+    /// containing its first byte. `content-range` tells the client which bytes it received.
+    pub fn build_206(
+        asset: &Asset,
+        enc: &AssetEncoding,
+        chunk_index: usize,
+        chunk_range: (usize, usize),
+        certificate_header: &HeaderField,
+    ) -> HttpResponse {
+        let mut headers = asset.get_headers_for_partial_content(chunk_range, enc.total_length);
+        headers.insert(certificate_header.0.clone(), certificate_header.1.clone());
+        if !headers
+            .iter()
+            .any(|(header_name, _)| header_name.eq_ignore_ascii_case("etag"))
+        {
+            headers.insert(
+                "etag".to_string(),
+                format!("\"{}\"", hex::encode(enc.sha256)),
+            );
+        }
+
+        HttpResponse {
+            status_code: HTTP_PARTIAL_CONTENT,
+            headers: headers.into_iter().collect::<_>(),
+            body: enc.content_chunks[chunk_index].load(),
+            upgrade: None,
+            streaming_strategy: None,
+        }
+    }
+
+    pub fn build_416(total_length: usize, certificate_header: HeaderField) -> HttpResponse {
+        let mut headers = vec![(
+            "content-range".to_string(),
+            format!("bytes */{}", total_length),
+        )];
+        let certificate_expression = build_ic_certificate_expression_from_headers(&headers);
+        headers.push(build_ic_certificate_expression_header(
+            &certificate_expression,
+        ));
+        headers.push(certificate_header);
+        HttpResponse {
+            status_code: HTTP_RANGE_NOT_SATISFIABLE,
+            headers,
+            body: RcBytes::default(),
+            upgrade: None,
+            streaming_strategy: None,
+        }
+    }
+
     pub fn build_400(err_msg: &str) -> Self {
         HttpResponse {
             status_code: 400,
@@ -324,6 +446,27 @@ impl HttpResponse {
         }
     }
 
+    /// Builds the response for a redirect route. Only the `location` header is certified.
+    pub fn build_route_redirect(
+        status_code: u16,
+        location: &str,
+        certificate_header: HeaderField,
+    ) -> HttpResponse {
+        let mut headers = vec![("location".to_string(), location.to_string())];
+        let certificate_expression = build_ic_certificate_expression_from_headers(&headers);
+        headers.push(build_ic_certificate_expression_header(
+            &certificate_expression,
+        ));
+        headers.push(certificate_header);
+        HttpResponse {
+            status_code,
+            headers,
+            body: RcBytes::from(ByteBuf::default()),
+            upgrade: None,
+            streaming_strategy: None,
+        }
+    }
+
     pub fn build_redirect(status_code: u16, location: String) -> HttpResponse {
         HttpResponse {
             status_code,
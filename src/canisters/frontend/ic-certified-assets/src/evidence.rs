@@ -1,11 +1,13 @@
 use crate::evidence::EvidenceComputation::{Computed, NextChunkIndex, NextOperation};
 use crate::state_machine::Chunk;
 use crate::types::BatchOperation::{
-    Clear, CreateAsset, DeleteAsset, SetAssetContent, SetAssetProperties, UnsetAssetContent,
+    Clear, CreateAsset, DeleteAsset, DeleteRoute, SetAssetContent, SetAssetProperties, SetRoute,
+    UnsetAssetContent,
 };
 use crate::types::{
     ChunkId, ClearArguments, CommitBatchArguments, CreateAssetArguments, DeleteAssetArguments,
-    SetAssetContentArguments, SetAssetPropertiesArguments, UnsetAssetContentArguments,
+    DeleteRouteArguments, RouteTarget, SetAssetContentArguments, SetAssetPropertiesArguments,
+    SetRouteArguments, UnsetAssetContentArguments,
 };
 use itertools::Itertools;
 use serde_bytes::ByteBuf;
@@ -24,6 +26,11 @@ const TAG_UNSET_ASSET_CONTENT: [u8; 1] = [6];
 const TAG_DELETE_ASSET: [u8; 1] = [7];
 const TAG_CLEAR: [u8; 1] = [8];
 const TAG_SET_ASSET_PROPERTIES: [u8; 1] = [9];
+const TAG_SET_ROUTE: [u8; 1] = [10];
+const TAG_DELETE_ROUTE: [u8; 1] = [11];
+
+const TAG_REDIRECT: [u8; 1] = [12];
+const TAG_REWRITE: [u8; 1] = [13];
 
 pub enum EvidenceComputation {
     NextOperation {
@@ -123,6 +130,20 @@ fn next_operation(
                 hasher,
             }
         }
+        Some(SetRoute(args)) => {
+            hash_set_route(&mut hasher, args);
+            NextOperation {
+                operation_index: operation_index + 1,
+                hasher,
+            }
+        }
+        Some(DeleteRoute(args)) => {
+            hash_delete_route(&mut hasher, args);
+            NextOperation {
+                operation_index: operation_index + 1,
+                hasher,
+            }
+        }
     }
 }
 
@@ -155,7 +176,7 @@ fn next_chunk_index(
 
 fn hash_chunk_by_id(hasher: &mut Sha256, chunk_id: &ChunkId, chunks: &HashMap<ChunkId, Chunk>) {
     if let Some(chunk) = chunks.get(chunk_id) {
-        hasher.update(&chunk.content);
+        hasher.update(chunk.content.load());
     }
 }
 
@@ -234,6 +255,30 @@ fn hash_set_asset_properties(hasher: &mut Sha256, args: &SetAssetPropertiesArgum
     }
 }
 
+fn hash_set_route(hasher: &mut Sha256, args: &SetRouteArguments) {
+    hasher.update(TAG_SET_ROUTE);
+    hasher.update(&args.source);
+    match &args.target {
+        RouteTarget::Redirect {
+            location,
+            status_code,
+        } => {
+            hasher.update(TAG_REDIRECT);
+            hasher.update(location);
+            hasher.update(status_code.to_be_bytes());
+        }
+        RouteTarget::Rewrite { key } => {
+            hasher.update(TAG_REWRITE);
+            hasher.update(key);
+        }
+    }
+}
+
+fn hash_delete_route(hasher: &mut Sha256, args: &DeleteRouteArguments) {
+    hasher.update(TAG_DELETE_ROUTE);
+    hasher.update(&args.source);
+}
+
 fn hash_opt_bool(hasher: &mut Sha256, b: Option<bool>) {
     if let Some(b) = b {
         hasher.update(TAG_SOME);
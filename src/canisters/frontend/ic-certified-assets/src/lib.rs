@@ -1,6 +1,7 @@
 //! This module declares canister methods expected by the assets canister client.
 pub mod asset_certification;
 pub mod evidence;
+pub mod stable_memory;
 pub mod state_machine;
 pub mod types;
 mod url_decode;
@@ -8,6 +9,7 @@ mod url_decode;
 #[cfg(test)]
 mod tests;
 
+pub use crate::stable_memory::init_stable_memory;
 pub use crate::state_machine::StableState;
 use crate::{
     asset_certification::types::http::{
@@ -18,7 +20,7 @@ use crate::{
     types::*,
 };
 use asset_certification::types::{certification::AssetKey, rc_bytes::RcBytes};
-use candid::{candid_method, Principal};
+use candid::{candid_method, IDLBuilder, Principal};
 use ic_cdk::api::{call::ManualReply, caller, data_certificate, set_certified_data, time, trap};
 use ic_cdk::{query, update};
 use serde_bytes::ByteBuf;
@@ -35,7 +37,7 @@ thread_local! {
 #[query]
 #[candid_method(query)]
 fn api_version() -> u16 {
-    2
+    3
 }
 
 #[update(guard = "is_manager_or_controller")]
@@ -308,6 +310,12 @@ fn list() -> Vec<AssetDetails> {
     STATE.with(|s| s.borrow().list_assets())
 }
 
+#[query]
+#[candid_method(query)]
+fn list_routes() -> Vec<Route> {
+    STATE.with(|s| s.borrow().list_routes())
+}
+
 #[query]
 #[candid_method(query)]
 fn certified_tree() -> CertifiedTree {
@@ -442,10 +450,30 @@ pub fn init(args: Option<AssetCanisterArgs>) {
     }
 }
 
+/// Takes the state to save before an upgrade.
+///
+/// If [init_stable_memory] was called, pass it to [save_stable_state].
+/// Otherwise it contains all asset content, and has to be saved with `ic_cdk::storage::stable_save`.
 pub fn pre_upgrade() -> StableState {
     STATE.with(|s| s.take().into())
 }
 
+/// Saves the state returned by [pre_upgrade] to the memory passed to [init_stable_memory].
+/// Asset content is already in that memory, so this only saves the rest of the state.
+pub fn save_stable_state(stable_state: StableState) -> Result<(), candid::Error> {
+    let mut ser = IDLBuilder::new();
+    ser.try_reserve_value_serializer_capacity(stable_state.estimate_size())?;
+    ser.arg(&stable_state)?;
+    stable_memory::write_upgrade_bytes(&ser.serialize_to_vec()?);
+    Ok(())
+}
+
+/// Restores the state saved by [save_stable_state], to be passed to [post_upgrade].
+/// [init_stable_memory] must be called first.
+pub fn restore_stable_state() -> Result<StableState, candid::Error> {
+    candid::decode_one(&stable_memory::read_upgrade_bytes())
+}
+
 pub fn post_upgrade(stable_state: StableState, args: Option<AssetCanisterArgs>) {
     let set_permissions = args.and_then(|args| {
         let AssetCanisterArgs::Upgrade(UpgradeArgs { set_permissions }) = args else {ic_cdk::trap("Cannot upgrade the canister with an Init argument. Please provide an Upgrade argument.")};
@@ -0,0 +1,203 @@
+//! This module contains the stable memory layout of the asset canister.
+//!
+//! The canister passes a [Memory] to [init_stable_memory], usually one it got from its own
+//! `MemoryManager`, so that it can keep other data in the remaining stable memory.
+//! Asset and chunk content is then kept in a stable B-tree map inside that memory, so it is
+//! neither limited by the heap size nor copied during upgrades. The rest of the state is small:
+//! it is serialized to a separate region of the same memory in `pre_upgrade` and deserialized
+//! again in `post_upgrade`.
+//!
+//! Canisters that never call [init_stable_memory] keep asset content on the heap,
+//! and must save it with `ic_cdk::storage::stable_save` like before.
+use crate::asset_certification::types::rc_bytes::RcBytes;
+use candid::{CandidType, Deserialize};
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::writer::Writer;
+use ic_stable_structures::{DefaultMemoryImpl, Memory as _, StableBTreeMap};
+use serde_bytes::ByteBuf;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// The stable memory that the asset canister keeps its content in.
+pub type Memory = VirtualMemory<DefaultMemoryImpl>;
+
+type AssetsMemory = VirtualMemory<Memory>;
+
+const UPGRADES: MemoryId = MemoryId::new(0);
+const CONTENT: MemoryId = MemoryId::new(1);
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<Option<MemoryManager<Memory>>> = const { RefCell::new(None) };
+
+    static CONTENT_STORE: RefCell<ContentStore> = RefCell::new(ContentStore::default());
+}
+
+enum Content {
+    Heap(BTreeMap<u64, Vec<u8>>),
+    Stable(StableBTreeMap<u64, Vec<u8>, AssetsMemory>),
+}
+
+struct ContentStore {
+    content: Content,
+    next_id: u64,
+}
+
+impl Default for ContentStore {
+    fn default() -> Self {
+        Self {
+            content: Content::Heap(BTreeMap::new()),
+            next_id: 0,
+        }
+    }
+}
+
+impl ContentStore {
+    fn insert(&mut self, id: u64, bytes: Vec<u8>) {
+        match &mut self.content {
+            Content::Heap(content) => {
+                content.insert(id, bytes);
+            }
+            Content::Stable(content) => {
+                content.insert(id, bytes);
+            }
+        }
+    }
+
+    fn get(&self, id: u64) -> Option<Vec<u8>> {
+        match &self.content {
+            Content::Heap(content) => content.get(&id).cloned(),
+            Content::Stable(content) => content.get(&id),
+        }
+    }
+
+    fn remove(&mut self, id: u64) {
+        match &mut self.content {
+            Content::Heap(content) => {
+                content.remove(&id);
+            }
+            Content::Stable(content) => {
+                content.remove(&id);
+            }
+        }
+    }
+}
+
+/// Makes the asset canister keep asset content in `memory`.
+///
+/// This must be called at the start of both `init` and `post_upgrade`.
+/// Content stored on the heap before the call is moved into `memory`.
+pub fn init_stable_memory(memory: Memory) {
+    let manager = MemoryManager::init(memory);
+    let content = StableBTreeMap::init(manager.get(CONTENT));
+    MEMORY_MANAGER.with(|m| *m.borrow_mut() = Some(manager));
+    CONTENT_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let next_id = content
+            .last_key_value()
+            .map_or(0, |(id, _)| id + 1)
+            .max(store.next_id);
+        let previous = std::mem::replace(&mut store.content, Content::Stable(content));
+        store.next_id = next_id;
+        if let Content::Heap(heap) = previous {
+            for (id, bytes) in heap {
+                store.insert(id, bytes);
+            }
+        }
+    });
+}
+
+/// Returns true if [init_stable_memory] was called.
+pub(crate) fn is_initialized() -> bool {
+    MEMORY_MANAGER.with(|m| m.borrow().is_some())
+}
+
+/// A handle to asset content, which is in stable memory if [init_stable_memory] was called.
+///
+/// The bytes stay stored until [StableBytes::free] consumes the handle.
+/// There is only ever one handle to them, so they cannot be freed twice.
+#[derive(Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StableBytes {
+    id: u64,
+    len: u64,
+}
+
+impl StableBytes {
+    /// Copies `bytes` into the content store.
+    pub fn new(bytes: &[u8]) -> Self {
+        CONTENT_STORE.with(|store| {
+            let mut store = store.borrow_mut();
+            let id = store.next_id;
+            store.next_id += 1;
+            store.insert(id, bytes.to_vec());
+            Self {
+                id,
+                len: bytes.len() as u64,
+            }
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copies the bytes from the content store.
+    pub fn load(&self) -> RcBytes {
+        CONTENT_STORE.with(|store| {
+            let bytes = store
+                .borrow()
+                .get(self.id)
+                .expect("asset content not found");
+            RcBytes::from(ByteBuf::from(bytes))
+        })
+    }
+
+    /// Removes the bytes from the content store.
+    pub fn free(self) {
+        CONTENT_STORE.with(|store| store.borrow_mut().remove(self.id));
+    }
+}
+
+/// The number of byte strings in the content store.
+#[cfg(test)]
+pub(crate) fn stored_count() -> u64 {
+    CONTENT_STORE.with(|store| match &store.borrow().content {
+        Content::Heap(content) => content.len() as u64,
+        Content::Stable(content) => content.len(),
+    })
+}
+
+fn upgrades_memory() -> AssetsMemory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .as_ref()
+            .expect("init_stable_memory was not called")
+            .get(UPGRADES)
+    })
+}
+
+/// Saves the serialized state that is not already in stable memory.
+pub(crate) fn write_upgrade_bytes(bytes: &[u8]) {
+    let mut memory = upgrades_memory();
+    let mut writer = Writer::new(&mut memory, 0);
+    writer
+        .write(&(bytes.len() as u64).to_le_bytes())
+        .and_then(|_| writer.write(bytes))
+        .expect("failed to grow stable memory");
+}
+
+/// Reads the bytes saved by [write_upgrade_bytes].
+pub(crate) fn read_upgrade_bytes() -> Vec<u8> {
+    let memory = upgrades_memory();
+    if memory.size() == 0 {
+        return vec![];
+    }
+    let mut len = [0; 8];
+    memory.read(0, &mut len);
+    let mut bytes = vec![0; u64::from_le_bytes(len) as usize];
+    memory.read(8, &mut bytes);
+    bytes
+}
@@ -3,6 +3,7 @@
 // NB. This module should not depend on ic_cdk, it contains only pure state transition functions.
 // All the environment (time, certificates, etc.) is passed to the state transition functions
 // as formal arguments.  This approach makes it very easy to test the state machine.
+// The only exception is asset content, which lives in the content store (see `stable_memory`).
 use crate::{
     asset_certification::{
         types::{
@@ -11,15 +12,18 @@ use crate::{
                 RequestHash, ResponseHash, WitnessResult,
             },
             http::{
+                build_ic_certificate_expression_from_headers,
                 build_ic_certificate_expression_from_headers_and_encoding,
-                build_ic_certificate_expression_header, response_hash, CallbackFunc, HttpRequest,
-                HttpResponse, StreamingCallbackHttpResponse, StreamingCallbackToken, FALLBACK_FILE,
+                build_ic_certificate_expression_header, response_hash, CallbackFunc, HeaderField,
+                HttpRequest, HttpResponse, StreamingCallbackHttpResponse, StreamingCallbackToken,
+                FALLBACK_FILE, HTTP_PARTIAL_CONTENT, HTTP_RANGE_NOT_SATISFIABLE,
             },
             rc_bytes::RcBytes,
         },
         CertifiedResponses,
     },
     evidence::{EvidenceComputation, EvidenceComputation::Computed},
+    stable_memory::{self, StableBytes},
     types::*,
     url_decode::url_decode,
 };
@@ -39,7 +43,8 @@ use std::convert::TryInto;
 pub const BATCH_EXPIRY_NANOS: u64 = 300_000_000_000;
 
 /// The order in which we pick encodings for certification.
-const ENCODING_CERTIFICATION_ORDER: &[&str] = &["identity", "gzip", "compress", "deflate", "br"];
+const ENCODING_CERTIFICATION_ORDER: &[&str] =
+    &["identity", "gzip", "compress", "deflate", "br", "zstd"];
 // Order of encodings is relevant for v1. Follow ENCODING_CERTIFICATION_ORDER,
 // then follow the order of existing encodings.
 // For v2, it is important to certify all encodings, therefore all encodings are added to the list.
@@ -64,14 +69,16 @@ const DEFAULT_ALIAS_ENABLED: bool = true;
 
 const STATUS_CODES_TO_CERTIFY: [u16; 2] = [200, 304];
 
+const REDIRECT_STATUS_CODES: [u16; 5] = [301, 302, 303, 307, 308];
+
 const DEFAULT_MAX_COMPUTE_EVIDENCE_ITERATIONS: u16 = 20;
 
 type Timestamp = Int;
 
-#[derive(Default, Clone, Debug, CandidType, Deserialize)]
+#[derive(Default, Debug, CandidType, Deserialize)]
 pub struct AssetEncoding {
     pub modified: Timestamp,
-    pub content_chunks: Vec<RcBytes>,
+    pub content_chunks: Vec<StableBytes>,
     pub total_length: usize,
     /// Valid as-is for v2.
     /// For v1, also make sure that encoding name == asset.most_important_encoding_v1()
@@ -79,13 +86,16 @@ pub struct AssetEncoding {
     pub sha256: [u8; 32],
     pub certificate_expression: Option<CertificateExpression>,
     pub response_hashes: Option<HashMap<u16, [u8; 32]>>,
+    /// SHA-256 of each content chunk, which `206` responses serve one at a time.
+    /// Only computed for the identity encoding.
+    pub chunk_sha256s: Option<Vec<[u8; 32]>>,
 }
 
 impl AssetEncoding {
     fn estimate_size(&self) -> usize {
         let mut size = 0;
         size += 8; // modified
-        size += self.total_length + self.content_chunks.len() * 4;
+        size += self.content_chunks.len() * 20;
         size += 5; // total_length
         size += 1; //  certified
         size += self.sha256.len();
@@ -96,8 +106,33 @@ impl AssetEncoding {
         size += 1 + self.response_hashes.as_ref().map_or(0, |hashes| {
             hashes.iter().fold(2, |acc, (_k, v)| acc + 2 + v.len())
         });
+        size += 1 + self
+            .chunk_sha256s
+            .as_ref()
+            .map_or(0, |hashes| 2 + hashes.len() * 32);
         size
     }
+
+    /// The index and the first and last byte of every non-empty content chunk.
+    pub fn chunk_byte_ranges(&self) -> Vec<(usize, (usize, usize))> {
+        let mut ranges = vec![];
+        let mut chunk_start = 0;
+        for (index, chunk) in self.content_chunks.iter().enumerate() {
+            if !chunk.is_empty() {
+                ranges.push((index, (chunk_start, chunk_start + chunk.len() - 1)));
+            }
+            chunk_start += chunk.len();
+        }
+        ranges
+    }
+
+    /// Removes the content from the content store.
+    fn free(self) {
+        for chunk in self.content_chunks {
+            chunk.free();
+        }
+    }
+
     fn asset_hash_path_v2(&self, path: &AssetPath, status_code: u16) -> Option<HashTreePath> {
         self.certificate_expression.as_ref().and_then(|ce| {
             self.response_hashes.as_ref().and_then(|hashes| {
@@ -163,7 +198,7 @@ impl AssetEncoding {
     }
 }
 
-#[derive(Default, Clone, Debug, CandidType, Deserialize)]
+#[derive(Default, Debug, CandidType, Deserialize)]
 pub struct Asset {
     pub content_type: String,
     pub encodings: HashMap<String, AssetEncoding>,
@@ -205,7 +240,7 @@ pub struct CertifiedTree {
 
 pub struct Chunk {
     pub batch_id: BatchId,
-    pub content: RcBytes,
+    pub content: StableBytes,
 }
 
 pub struct Batch {
@@ -258,6 +293,8 @@ pub struct State {
     manage_permissions_principals: BTreeSet<Principal>,
 
     asset_hashes: CertifiedResponses,
+
+    routes: HashMap<String, RouteTarget>,
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
@@ -277,14 +314,132 @@ impl StableStatePermissions {
     }
 }
 
+/// An asset with its content inlined, as saved by canisters that keep asset content on the heap.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+struct HeapAsset {
+    content_type: String,
+    encodings: HashMap<String, HeapAssetEncoding>,
+    max_age: Option<u64>,
+    headers: Option<HashMap<String, String>>,
+    is_aliased: Option<bool>,
+    allow_raw_access: Option<bool>,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
+struct HeapAssetEncoding {
+    modified: Timestamp,
+    content_chunks: Vec<RcBytes>,
+    total_length: usize,
+    certified: bool,
+    sha256: [u8; 32],
+    certificate_expression: Option<CertificateExpression>,
+    response_hashes: Option<HashMap<u16, [u8; 32]>>,
+    chunk_sha256s: Option<Vec<[u8; 32]>>,
+}
+
+impl HeapAsset {
+    fn estimate_size(&self) -> usize {
+        let mut size = 0;
+        size += 1 + self.content_type.len();
+        size += self.encodings.iter().fold(1, |acc, (name, enc)| {
+            acc + 1 + name.len() + 8 + enc.total_length + enc.content_chunks.len() * 4 + 40
+        });
+        size += 1 + self.max_age.as_ref().map_or(0, |_| 8);
+        size += 1 + self.headers.as_ref().map_or(0, |hm| {
+            hm.iter()
+                .fold(2, |acc, (k, v)| acc + 1 + k.len() + 2 + v.len())
+        });
+        size += 4;
+        size
+    }
+}
+
+impl From<Asset> for HeapAsset {
+    fn from(asset: Asset) -> Self {
+        Self {
+            content_type: asset.content_type,
+            encodings: asset
+                .encodings
+                .into_iter()
+                .map(|(name, enc)| (name, enc.into()))
+                .collect(),
+            max_age: asset.max_age,
+            headers: asset.headers,
+            is_aliased: asset.is_aliased,
+            allow_raw_access: asset.allow_raw_access,
+        }
+    }
+}
+
+impl From<AssetEncoding> for HeapAssetEncoding {
+    fn from(enc: AssetEncoding) -> Self {
+        Self {
+            modified: enc.modified,
+            content_chunks: enc
+                .content_chunks
+                .into_iter()
+                .map(|chunk| {
+                    let content = chunk.load();
+                    chunk.free();
+                    content
+                })
+                .collect(),
+            total_length: enc.total_length,
+            certified: enc.certified,
+            sha256: enc.sha256,
+            certificate_expression: enc.certificate_expression,
+            response_hashes: enc.response_hashes,
+            chunk_sha256s: enc.chunk_sha256s,
+        }
+    }
+}
+
+impl From<HeapAsset> for Asset {
+    fn from(asset: HeapAsset) -> Self {
+        Self {
+            content_type: asset.content_type,
+            encodings: asset
+                .encodings
+                .into_iter()
+                .map(|(name, enc)| (name, enc.into()))
+                .collect(),
+            max_age: asset.max_age,
+            headers: asset.headers,
+            is_aliased: asset.is_aliased,
+            allow_raw_access: asset.allow_raw_access,
+        }
+    }
+}
+
+impl From<HeapAssetEncoding> for AssetEncoding {
+    fn from(enc: HeapAssetEncoding) -> Self {
+        Self {
+            modified: enc.modified,
+            content_chunks: enc
+                .content_chunks
+                .iter()
+                .map(|chunk| StableBytes::new(chunk))
+                .collect(),
+            total_length: enc.total_length,
+            certified: enc.certified,
+            sha256: enc.sha256,
+            certificate_expression: enc.certificate_expression,
+            response_hashes: enc.response_hashes,
+            chunk_sha256s: enc.chunk_sha256s,
+        }
+    }
+}
+
+#[derive(Debug, CandidType, Deserialize)]
 pub struct StableState {
     authorized: Vec<Principal>, // ignored if permissions is Some(_)
     permissions: Option<StableStatePermissions>,
-    stable_assets: HashMap<String, Asset>,
+    stable_assets: HashMap<String, HeapAsset>, // used if content is kept on the heap
+    assets: Option<HashMap<String, Asset>>,    // used if content is kept in stable memory
 
     next_batch_id: Option<BatchId>,
     configuration: Option<Configuration>,
+    routes: Option<HashMap<String, RouteTarget>>,
 }
 
 impl StableState {
@@ -295,12 +450,31 @@ impl StableState {
         size += self.stable_assets.iter().fold(2, |acc, (name, asset)| {
             acc + 2 + name.len() + asset.estimate_size()
         });
+        size += 1 + self.assets.as_ref().map_or(0, |assets| {
+            assets.iter().fold(2, |acc, (name, asset)| {
+                acc + 2 + name.len() + asset.estimate_size()
+            })
+        });
         size += 1 + self.next_batch_id.as_ref().map_or(0, |_| 8);
         size += 1 + self.configuration.as_ref().map_or(0, |c| c.estimate_size());
+        size += 1 + self.routes.as_ref().map_or(0, |routes| {
+            routes.iter().fold(2, |acc, (source, target)| {
+                acc + 2 + source.len() + target.estimate_size()
+            })
+        });
         size
     }
 }
 
+impl RouteTarget {
+    fn estimate_size(&self) -> usize {
+        match self {
+            RouteTarget::Redirect { location, .. } => 1 + 2 + location.len() + 2,
+            RouteTarget::Rewrite { key } => 1 + 2 + key.len(),
+        }
+    }
+}
+
 impl Asset {
     fn estimate_size(&self) -> usize {
         let mut size = 0;
@@ -331,8 +505,15 @@ impl Asset {
         self.allow_raw_access.unwrap_or(true)
     }
 
-    fn update_ic_certificate_expressions(&mut self) {
-        // gather all headers
+    /// Removes the content of all encodings from the content store.
+    fn free(self) {
+        for enc in self.encodings.into_values() {
+            enc.free();
+        }
+    }
+
+    // headers certified in addition to content-type (and content-encoding)
+    fn certified_headers(&self) -> Vec<(String, Value)> {
         let mut headers: Vec<(String, Value)> = vec![];
 
         if self.max_age.is_some() {
@@ -343,6 +524,11 @@ impl Asset {
                 headers.push((h.0.into(), Value::String(h.1.into())));
             }
         }
+        headers
+    }
+
+    fn update_ic_certificate_expressions(&mut self) {
+        let headers = self.certified_headers();
 
         // update
         for (enc_name, encoding) in self.encodings.iter_mut() {
@@ -373,6 +559,34 @@ impl Asset {
         )
     }
 
+    // 206 responses also certify content-range
+    fn partial_content_certificate_expression(&self) -> CertificateExpression {
+        let mut headers = self.certified_headers();
+        headers.push(("content-range".to_string(), Value::String("".to_string())));
+        build_ic_certificate_expression_from_headers(&headers)
+    }
+
+    /// Headers of the `206` response serving bytes `first..=last` of the identity encoding.
+    pub fn get_headers_for_partial_content(
+        &self,
+        (first, last): (usize, usize),
+        total_length: usize,
+    ) -> HashMap<String, String> {
+        let ce = self.partial_content_certificate_expression();
+        let mut headers = build_headers(
+            self.headers.as_ref().map(|h| h.iter()),
+            &self.max_age,
+            &self.content_type,
+            "identity",
+            Some(&ce),
+        );
+        headers.insert(
+            "content-range".to_string(),
+            format!("bytes {}-{}/{}", first, last, total_length),
+        );
+        headers
+    }
+
     // certification v1 only certifies the most important encoding
     pub fn most_important_encoding_v1(&self) -> String {
         for enc in encoding_certification_order(self.encodings.keys()).into_iter() {
@@ -405,6 +619,15 @@ impl State {
             .ok_or_else(|| "asset not found".to_string())
     }
 
+    /// Like `get_asset`, but also follows a rewrite route if no asset is found for `path`.
+    fn get_asset_for_path(&self, path: &str) -> Result<&Asset, String> {
+        self.get_asset(&path.to_string())
+            .or_else(|err| match self.routes.get(path) {
+                Some(RouteTarget::Rewrite { key }) => self.assets.get(key).ok_or(err),
+                _ => Err(err),
+            })
+    }
+
     pub fn set_permissions(
         &mut self,
         SetPermissions {
@@ -485,7 +708,7 @@ impl State {
             content_chunks.push(chunk.content);
         }
         if let Some(encoding_content) = arg.last_chunk {
-            content_chunks.push(encoding_content.into());
+            content_chunks.push(StableBytes::new(&encoding_content));
         }
 
         let sha256: [u8; 32] = match arg.sha256 {
@@ -496,11 +719,18 @@ impl State {
             None => {
                 let mut hasher = sha2::Sha256::new();
                 for chunk in content_chunks.iter() {
-                    hasher.update(chunk);
+                    hasher.update(chunk.load());
                 }
                 hasher.finalize().into()
             }
         };
+        let chunk_sha256s: Option<Vec<[u8; 32]>> =
+            (arg.content_encoding == "identity").then(|| {
+                content_chunks
+                    .iter()
+                    .map(|chunk| sha2::Sha256::digest(chunk.load()).into())
+                    .collect()
+            });
 
         let total_length: usize = content_chunks.iter().map(|c| c.len()).sum();
         let enc = AssetEncoding {
@@ -511,8 +741,11 @@ impl State {
             sha256,
             certificate_expression: None, // set by on_asset_change
             response_hashes: None,        // set by on_asset_change
+            chunk_sha256s,
         };
-        asset.encodings.insert(arg.content_encoding, enc);
+        if let Some(replaced) = asset.encodings.insert(arg.content_encoding, enc) {
+            replaced.free();
+        }
 
         on_asset_change(&mut self.asset_hashes, &arg.key, asset, dependent_keys);
 
@@ -526,7 +759,8 @@ impl State {
             .get_mut(&arg.key)
             .ok_or_else(|| "asset not found".to_string())?;
 
-        if asset.encodings.remove(&arg.content_encoding).is_some() {
+        if let Some(enc) = asset.encodings.remove(&arg.content_encoding) {
+            enc.free();
             on_asset_change(&mut self.asset_hashes, &arg.key, asset, dependent_keys);
         }
 
@@ -543,7 +777,9 @@ impl State {
                     self.asset_hashes.remove_fallback_responses_v1();
                 }
             }
-            self.assets.remove(&arg.key);
+            if let Some(asset) = self.assets.remove(&arg.key) {
+                asset.free();
+            }
         }
         for key in aliases_of(&arg.key) {
             // if an existing file can be aliased to the deleted file it has to become a valid alias again
@@ -554,12 +790,27 @@ impl State {
                 }
             }
         }
+        // routes whose source was served by the deleted asset take effect again
+        for source in std::iter::once(arg.key.clone()).chain(aliased_by(&arg.key)) {
+            if self.routes.contains_key(&source) {
+                self.certify_route(&source);
+            }
+        }
     }
 
     pub fn clear(&mut self) {
-        self.assets.clear();
+        let sources: Vec<_> = self.routes.keys().cloned().collect();
+        for source in sources {
+            self.uncertify_route(&source);
+        }
+        self.routes.clear();
+        for (_, asset) in self.assets.drain() {
+            asset.free();
+        }
         self.batches.clear();
-        self.chunks.clear();
+        for (_, chunk) in self.chunks.drain() {
+            chunk.content.free();
+        }
         self.next_batch_id = Nat::from(1_u8);
         self.next_chunk_id = Nat::from(1_u8);
     }
@@ -603,7 +854,7 @@ impl State {
             return Err("Asset too large. Use get() and get_chunk() instead.".to_string());
         }
 
-        Ok(id_enc.content_chunks[0].clone())
+        Ok(id_enc.content_chunks[0].load())
     }
 
     pub fn store(&mut self, arg: StoreArg, time: u64) -> Result<(), String> {
@@ -620,8 +871,11 @@ impl State {
         }
 
         let encoding = asset.encodings.entry(arg.content_encoding).or_default();
+        for chunk in std::mem::take(&mut encoding.content_chunks) {
+            chunk.free();
+        }
         encoding.total_length = arg.content.len();
-        encoding.content_chunks = vec![RcBytes::from(arg.content)];
+        encoding.content_chunks = vec![StableBytes::new(&arg.content)];
         encoding.modified = Int::from(time);
         encoding.sha256 = hash;
 
@@ -633,8 +887,9 @@ impl State {
         self.batches.retain(|_, b| {
             b.expires_at > now || matches!(b.evidence_computation, Some(Computed(_)))
         });
-        self.chunks
-            .retain(|_, c| self.batches.contains_key(&c.batch_id));
+        remove_chunks(&mut self.chunks, |c| {
+            !self.batches.contains_key(&c.batch_id)
+        });
 
         if let Some((batch_id, batch)) = self
             .batches
@@ -716,7 +971,7 @@ impl State {
                 chunk_id.clone(),
                 Chunk {
                     batch_id: batch_id.clone(),
-                    content: RcBytes::from(chunk),
+                    content: StableBytes::new(&chunk),
                 },
             );
             chunk_ids.push(chunk_id);
@@ -783,6 +1038,8 @@ impl State {
                 BatchOperation::DeleteAsset(arg) => self.delete_asset(arg),
                 BatchOperation::Clear(_) => self.clear(),
                 BatchOperation::SetAssetProperties(arg) => self.set_asset_properties(arg)?,
+                BatchOperation::SetRoute(arg) => self.set_route(arg)?,
+                BatchOperation::DeleteRoute(arg) => self.delete_route(arg),
             }
         }
         self.batches.remove(&batch_id);
@@ -889,7 +1146,7 @@ impl State {
         if self.batches.remove(&arg.batch_id).is_none() {
             return Err("batch not found".to_string());
         }
-        self.chunks.retain(|_, c| c.batch_id != arg.batch_id);
+        remove_chunks(&mut self.chunks, |c| c.batch_id == arg.batch_id);
         Ok(())
     }
 
@@ -938,7 +1195,7 @@ impl State {
         for enc in arg.accept_encodings.iter() {
             if let Some(asset_enc) = asset.encodings.get(enc) {
                 return Ok(EncodedAsset {
-                    content: asset_enc.content_chunks[0].clone(),
+                    content: asset_enc.content_chunks[0].load(),
                     content_type: asset.content_type.clone(),
                     content_encoding: enc.clone(),
                     total_length: Nat::from(asset_enc.total_length as u64),
@@ -967,7 +1224,7 @@ impl State {
         }
         let index: usize = arg.index.0.to_usize().unwrap();
 
-        Ok(enc.content_chunks[index].clone())
+        Ok(enc.content_chunks[index].load())
     }
 
     fn build_http_response(
@@ -980,10 +1237,25 @@ impl State {
         etags: Vec<Hash>,
         req: HttpRequest,
     ) -> HttpResponse {
-        if let Ok(asset) = self.get_asset(&path.into()) {
+        if let Ok(asset) = self.get_asset_for_path(path) {
             if !asset.allow_raw_access() && req.is_raw_domain() {
                 return req.redirect_from_raw_to_certified_domain();
             }
+        } else if let Some(RouteTarget::Redirect {
+            location,
+            status_code,
+        }) = self.routes.get(path)
+        {
+            // Certification v1 can only certify response bodies, not redirects.
+            if req.get_certificate_version() != 1 {
+                let (certificate_header, _) =
+                    self.asset_hashes.witness_to_header(path, certificate);
+                return HttpResponse::build_route_redirect(
+                    *status_code,
+                    location,
+                    certificate_header,
+                );
+            }
         } else if let Ok(asset) = self.get_asset(&FALLBACK_FILE.to_string()) {
             if !asset.allow_raw_access() && req.is_raw_domain() {
                 return req.redirect_from_raw_to_certified_domain();
@@ -1012,10 +1284,13 @@ impl State {
                 }
             }
         } else if witness_result == WitnessResult::PathFound {
-            if let Ok(asset) = self.get_asset(&path.into()) {
+            if let Ok(asset) = self.get_asset_for_path(path) {
                 if !asset.allow_raw_access() && req.is_raw_domain() {
                     return req.redirect_from_raw_to_certified_domain();
                 }
+                if let Some(response) = build_range_response(asset, &req, &certificate_header) {
+                    return response;
+                }
                 if let Some(response) = HttpResponse::build_ok_from_requested_encodings(
                     asset,
                     &requested_encodings,
@@ -1082,7 +1357,7 @@ impl State {
         }: StreamingCallbackToken,
     ) -> Result<StreamingCallbackHttpResponse, String> {
         let asset = self
-            .get_asset(&key)
+            .get_asset_for_path(&key)
             .map_err(|_| "Invalid token on streaming: key not found.".to_string())?;
         let enc = asset
             .encodings
@@ -1098,7 +1373,7 @@ impl State {
         let chunk_index = index.0.to_usize().unwrap_or(usize::MAX);
 
         Ok(StreamingCallbackHttpResponse {
-            body: enc.content_chunks[chunk_index].clone(),
+            body: enc.content_chunks[chunk_index].load(),
             token: StreamingCallbackToken::create_token(
                 &content_encoding,
                 enc.content_chunks.len(),
@@ -1151,7 +1426,7 @@ impl State {
 
     // Returns keys that needs to be updated if the supplied key is changed.
     fn dependent_keys(&self, key: &AssetKey) -> Vec<AssetKey> {
-        if self
+        let mut dependent_keys: Vec<AssetKey> = if self
             .assets
             .get(key)
             .and_then(|asset| asset.is_aliased)
@@ -1163,6 +1438,115 @@ impl State {
                 .collect()
         } else {
             Vec::new()
+        };
+        // sources of rewrites to this key, unless an asset is served at the source
+        let rewrite_sources: Vec<AssetKey> = self
+            .routes
+            .iter()
+            .filter(|(source, target)| {
+                matches!(target, RouteTarget::Rewrite { key: target_key } if target_key == key)
+                    && !dependent_keys.contains(source)
+                    && self.get_asset(source).is_err()
+            })
+            .map(|(source, _)| source.clone())
+            .collect();
+        dependent_keys.extend(rewrite_sources);
+        dependent_keys
+    }
+
+    pub fn set_route(&mut self, arg: SetRouteArguments) -> Result<(), String> {
+        if !arg.source.starts_with('/') {
+            return Err(format!("route source '{}' must start with '/'", arg.source));
+        }
+        match &arg.target {
+            RouteTarget::Redirect {
+                location,
+                status_code,
+            } => {
+                if !REDIRECT_STATUS_CODES.contains(status_code) {
+                    return Err(format!("{} is not a redirect status code", status_code));
+                }
+                if location.is_empty() {
+                    return Err("redirect location must not be empty".to_string());
+                }
+            }
+            RouteTarget::Rewrite { key } => {
+                if key == &arg.source {
+                    return Err(format!("route source '{}' rewrites to itself", arg.source));
+                }
+            }
+        }
+
+        self.delete_route(DeleteRouteArguments {
+            source: arg.source.clone(),
+        });
+        self.routes.insert(arg.source.clone(), arg.target);
+        self.certify_route(&arg.source);
+        Ok(())
+    }
+
+    pub fn delete_route(&mut self, arg: DeleteRouteArguments) {
+        if self.routes.contains_key(&arg.source) {
+            self.uncertify_route(&arg.source);
+            self.routes.remove(&arg.source);
+        }
+    }
+
+    pub fn list_routes(&self) -> Vec<Route> {
+        let mut routes: Vec<_> = self
+            .routes
+            .iter()
+            .map(|(source, target)| Route {
+                source: source.clone(),
+                target: target.clone(),
+            })
+            .collect();
+        routes.sort_by(|a, b| a.source.cmp(&b.source));
+        routes
+    }
+
+    // Assets (and their aliases) take precedence over routes, so a route whose source
+    // is served by an asset is not certified.
+    fn certify_route(&mut self, source: &str) {
+        if self.get_asset(&source.to_string()).is_ok() {
+            return;
+        }
+        match self.routes.get(source) {
+            Some(RouteTarget::Redirect {
+                location,
+                status_code,
+            }) => {
+                let hash_path = redirect_hash_path(source, *status_code, location);
+                self.asset_hashes.certify_response_precomputed(&hash_path);
+            }
+            Some(RouteTarget::Rewrite { key }) => {
+                // re-certifying the target also certifies its responses for the source
+                let key = key.clone();
+                let dependent_keys = self.dependent_keys(&key);
+                if let Some(asset) = self.assets.get_mut(&key) {
+                    on_asset_change(&mut self.asset_hashes, &key, asset, dependent_keys);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn uncertify_route(&mut self, source: &str) {
+        match self.routes.get(source) {
+            Some(RouteTarget::Redirect {
+                location,
+                status_code,
+            }) => {
+                let hash_path = redirect_hash_path(source, *status_code, location);
+                self.asset_hashes.remove_response_precomputed(&hash_path);
+            }
+            Some(RouteTarget::Rewrite { .. }) => {
+                if self.get_asset(&source.to_string()).is_err() {
+                    self.asset_hashes.remove_responses_for_path(source);
+                    self.asset_hashes.remove_responses_for_path_v1(source);
+                }
+            }
+            None => {}
         }
     }
 
@@ -1210,6 +1594,20 @@ impl State {
     }
 }
 
+/// Removes the chunks matching `predicate` and frees their content.
+fn remove_chunks(chunks: &mut HashMap<ChunkId, Chunk>, predicate: impl Fn(&Chunk) -> bool) {
+    let chunk_ids: Vec<_> = chunks
+        .iter()
+        .filter(|(_, c)| predicate(c))
+        .map(|(id, _)| id.clone())
+        .collect();
+    for chunk_id in chunk_ids {
+        if let Some(chunk) = chunks.remove(&chunk_id) {
+            chunk.content.free();
+        }
+    }
+}
+
 impl From<State> for StableState {
     fn from(state: State) -> Self {
         let permissions = StableStatePermissions {
@@ -1217,12 +1615,28 @@ impl From<State> for StableState {
             prepare: state.prepare_principals,
             manage_permissions: state.manage_permissions_principals,
         };
+        // batches do not survive upgrades
+        for chunk in state.chunks.into_values() {
+            chunk.content.free();
+        }
+        let (stable_assets, assets) = if stable_memory::is_initialized() {
+            (HashMap::new(), Some(state.assets))
+        } else {
+            let stable_assets = state
+                .assets
+                .into_iter()
+                .map(|(key, asset)| (key, asset.into()))
+                .collect();
+            (stable_assets, None)
+        };
         Self {
             authorized: vec![],
             permissions: Some(permissions),
-            stable_assets: state.assets,
+            stable_assets,
+            assets,
             next_batch_id: Some(state.next_batch_id),
             configuration: Some(state.configuration),
+            routes: Some(state.routes),
         }
     }
 }
@@ -1243,15 +1657,24 @@ impl From<StableState> for State {
                     BTreeSet::new(),
                 )
             };
+        let mut assets = stable_state.assets.unwrap_or_default();
+        // moves inlined content into the content store
+        assets.extend(
+            stable_state
+                .stable_assets
+                .into_iter()
+                .map(|(key, asset)| (key, asset.into())),
+        );
         let mut state = Self {
             commit_principals,
             prepare_principals,
             manage_permissions_principals,
-            assets: stable_state.stable_assets,
+            assets,
             next_batch_id: stable_state
                 .next_batch_id
                 .unwrap_or_else(|| Nat::from(1_u8)),
             configuration: stable_state.configuration.unwrap_or_default(),
+            routes: stable_state.routes.unwrap_or_default(),
             ..Self::default()
         };
 
@@ -1267,6 +1690,16 @@ impl From<StableState> for State {
                 // shouldn't reach this
             }
         }
+        // rewrites were certified along with their target assets
+        let redirect_sources: Vec<_> = state
+            .routes
+            .iter()
+            .filter(|(_, target)| matches!(target, RouteTarget::Redirect { .. }))
+            .map(|(source, _)| source.clone())
+            .collect();
+        for source in redirect_sources {
+            state.certify_route(&source);
+        }
         state
     }
 }
@@ -1298,6 +1731,104 @@ fn build_headers(
     headers
 }
 
+/// Serves a single-range `Range` request from the identity encoding of an asset.
+/// Returns `None` if the full asset should be served instead.
+fn build_range_response(
+    asset: &Asset,
+    req: &HttpRequest,
+    certificate_header: &HeaderField,
+) -> Option<HttpResponse> {
+    // Certification v1 certifies the hash of the whole body, which a partial body can never match.
+    if req.get_certificate_version() == 1 {
+        return None;
+    }
+    let range = req.get_range()?;
+    let enc = asset
+        .encodings
+        .get("identity")
+        .filter(|enc| enc.certified && enc.chunk_sha256s.is_some())?;
+    Some(match range.resolve(enc.total_length) {
+        Some((first, _)) => {
+            // Only whole chunks are certified, so serve the chunk containing the first requested byte.
+            let (chunk_index, chunk_range) = enc
+                .chunk_byte_ranges()
+                .into_iter()
+                .find(|(_, (_, last))| first <= *last)?;
+            HttpResponse::build_206(asset, enc, chunk_index, chunk_range, certificate_header)
+        }
+        None => HttpResponse::build_416(enc.total_length, certificate_header.clone()),
+    })
+}
+
+/// Hash tree paths of the `206` response for every content chunk of the identity encoding,
+/// and of its `416` response.
+fn range_hash_paths(asset: &Asset, path: &AssetPath) -> Vec<HashTreePath> {
+    let Some((enc, chunk_sha256s)) = asset
+        .encodings
+        .get("identity")
+        .and_then(|enc| Some((enc, enc.chunk_sha256s.as_ref()?)))
+    else {
+        return vec![];
+    };
+    let mut hash_paths = vec![];
+    let certificate_expression = asset.partial_content_certificate_expression();
+    for (chunk_index, chunk_range) in enc.chunk_byte_ranges() {
+        let headers = asset.get_headers_for_partial_content(chunk_range, enc.total_length);
+        let certified_headers: Vec<(String, Value)> = headers
+            .into_iter()
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect();
+        let response_hash = response_hash(
+            &certified_headers,
+            HTTP_PARTIAL_CONTENT,
+            &chunk_sha256s[chunk_index],
+        );
+        hash_paths.push(path.hash_tree_path(
+            &certificate_expression,
+            &RequestHash::default(),
+            response_hash,
+        ));
+    }
+
+    let headers = vec![(
+        "content-range".to_string(),
+        Value::String(format!("bytes */{}", enc.total_length)),
+    )];
+    let certificate_expression = build_ic_certificate_expression_from_headers(&headers);
+    let (expr_header_name, expr_header_value) =
+        build_ic_certificate_expression_header(&certificate_expression);
+    let mut certified_headers = headers;
+    certified_headers.push((expr_header_name, Value::String(expr_header_value)));
+    let empty_body_hash: [u8; 32] = sha2::Sha256::digest([]).into();
+    let response_hash = response_hash(
+        &certified_headers,
+        HTTP_RANGE_NOT_SATISFIABLE,
+        &empty_body_hash,
+    );
+    hash_paths.push(path.hash_tree_path(
+        &certificate_expression,
+        &RequestHash::default(),
+        response_hash,
+    ));
+    hash_paths
+}
+
+fn redirect_hash_path(source: &str, status_code: u16, location: &str) -> HashTreePath {
+    let headers = vec![("location".to_string(), Value::String(location.to_string()))];
+    let certificate_expression = build_ic_certificate_expression_from_headers(&headers);
+    let (expr_header_name, expr_header_value) =
+        build_ic_certificate_expression_header(&certificate_expression);
+    let mut certified_headers = headers;
+    certified_headers.push((expr_header_name, Value::String(expr_header_value)));
+    let empty_body_hash: [u8; 32] = sha2::Sha256::digest([]).into();
+    let response_hash = response_hash(&certified_headers, status_code, &empty_body_hash);
+    AssetPath::from(source).hash_tree_path(
+        &certificate_expression,
+        &RequestHash::default(),
+        response_hash,
+    )
+}
+
 fn on_asset_change(
     asset_hashes: &mut CertifiedResponses,
     key: &str,
@@ -1343,6 +1874,13 @@ fn on_asset_change(
             enc.certified = true;
         }
     }
+
+    // Range requests are served from the identity encoding, one content chunk at a time
+    for key in affected_keys.iter() {
+        for hash_path in range_hash_paths(asset, &AssetPath::from(key)) {
+            asset_hashes.certify_response_precomputed(&hash_path);
+        }
+    }
 }
 
 fn delete_preexisting_asset_hashes(
@@ -455,6 +455,47 @@ fn serve_correct_encoding_v1() {
     assert_eq!(no_encoding_response.body.as_ref(), "not found".as_bytes());
 }
 
+#[test]
+fn serve_zstd_encoding() {
+    let mut state = State::default();
+    let time_now = 100_000_000_000;
+
+    const ZSTD_BODY: &[u8] = b"this is 'zstd compressed' content";
+    const CUSTOM_BODY: &[u8] = b"this is 'custom encoded' content";
+
+    create_assets(
+        &mut state,
+        time_now,
+        vec![AssetBuilder::new("/contents.html", "text/html")
+            .with_encoding("x-custom", vec![CUSTOM_BODY])
+            .with_encoding("zstd", vec![ZSTD_BODY])],
+    );
+
+    // zstd is more important than encodings that are not known to the asset canister
+    let unknown_encoding_response = certified_http_request(
+        &state,
+        RequestBuilder::get("/contents.html")
+            .with_header("Accept-Encoding", "unknown")
+            .build(),
+    );
+    assert_eq!(unknown_encoding_response.status_code, 200);
+    assert_eq!(unknown_encoding_response.body.as_ref(), ZSTD_BODY);
+    assert_eq!(
+        lookup_header(&unknown_encoding_response, "Content-Encoding"),
+        Some("zstd")
+    );
+
+    let zstd_response = certified_http_request(
+        &state,
+        RequestBuilder::get("/contents.html")
+            .with_header("Accept-Encoding", "zstd, br")
+            .with_certificate_version(2)
+            .build(),
+    );
+    assert_eq!(zstd_response.status_code, 200);
+    assert_eq!(zstd_response.body.as_ref(), ZSTD_BODY);
+}
+
 #[test]
 fn serve_correct_encoding_v2() {
     let mut state = State::default();
@@ -3920,3 +3961,737 @@ mod enforce_limits {
         );
     }
 }
+
+#[cfg(test)]
+mod range_requests {
+    use super::*;
+    use crate::asset_certification::types::http::ByteRange;
+
+    const CHUNK_1: &[u8] = b"0123456789";
+    const CHUNK_2: &[u8] = b"abcdefghij";
+
+    fn create_media_asset(state: &mut State) {
+        create_assets(
+            state,
+            100_000_000_000,
+            vec![AssetBuilder::new("/video.mp4", "video/mp4")
+                .with_encoding("identity", vec![CHUNK_1, CHUNK_2])],
+        );
+    }
+
+    fn range_request(range: &str) -> HttpRequest {
+        RequestBuilder::get("/video.mp4")
+            .with_header("Accept-Encoding", "identity")
+            .with_header("Range", range)
+            .with_certificate_version(2)
+            .build()
+    }
+
+    #[test]
+    fn parses_range_header() {
+        assert_eq!(
+            ByteRange::parse("bytes=0-"),
+            Some(ByteRange::FromStart {
+                start: 0,
+                end: None
+            })
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=5-9"),
+            Some(ByteRange::FromStart {
+                start: 5,
+                end: Some(9)
+            })
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=-4"),
+            Some(ByteRange::Suffix { length: 4 })
+        );
+        assert_eq!(ByteRange::parse("bytes=0-1,4-5"), None);
+        assert_eq!(ByteRange::parse("bytes=9-5"), None);
+        assert_eq!(ByteRange::parse("items=0-5"), None);
+        assert_eq!(ByteRange::parse("bytes=abc"), None);
+    }
+
+    #[test]
+    fn resolves_range_against_length() {
+        let range = |start, end| ByteRange::FromStart { start, end };
+        assert_eq!(range(0, None).resolve(20), Some((0, 19)));
+        assert_eq!(range(5, Some(100)).resolve(20), Some((5, 19)));
+        assert_eq!(range(20, None).resolve(20), None);
+        assert_eq!(ByteRange::Suffix { length: 4 }.resolve(20), Some((16, 19)));
+        assert_eq!(ByteRange::Suffix { length: 40 }.resolve(20), Some((0, 19)));
+        assert_eq!(ByteRange::Suffix { length: 0 }.resolve(20), None);
+    }
+
+    #[test]
+    fn serves_partial_content() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+
+        let response = state.http_request(range_request("bytes=2-5"), &[], unused_callback());
+        assert_eq!(response.status_code, 206);
+        assert_eq!(response.body.as_ref(), CHUNK_1);
+        assert_eq!(
+            lookup_header(&response, "content-range"),
+            Some("bytes 0-9/20")
+        );
+        assert!(lookup_header(&response, "ic-certificate").is_some());
+        assert!(response.streaming_strategy.is_none());
+
+        let response = state.http_request(range_request("bytes=-3"), &[], unused_callback());
+        assert_eq!(response.status_code, 206);
+        assert_eq!(response.body.as_ref(), CHUNK_2);
+        assert_eq!(
+            lookup_header(&response, "content-range"),
+            Some("bytes 10-19/20")
+        );
+    }
+
+    #[test]
+    fn partial_content_is_the_chunk_containing_the_first_byte() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+
+        let response = state.http_request(range_request("bytes=8-"), &[], unused_callback());
+        assert_eq!(response.status_code, 206);
+        assert_eq!(response.body.as_ref(), CHUNK_1);
+        assert_eq!(
+            lookup_header(&response, "content-range"),
+            Some("bytes 0-9/20")
+        );
+
+        let response = state.http_request(range_request("bytes=10-"), &[], unused_callback());
+        assert_eq!(response.status_code, 206);
+        assert_eq!(response.body.as_ref(), CHUNK_2);
+        assert_eq!(
+            lookup_header(&response, "content-range"),
+            Some("bytes 10-19/20")
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_range() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+
+        let response = state.http_request(range_request("bytes=20-"), &[], unused_callback());
+        assert_eq!(response.status_code, 416);
+        assert_eq!(
+            lookup_header(&response, "content-range"),
+            Some("bytes */20")
+        );
+    }
+
+    #[test]
+    fn ignores_range_when_not_applicable() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+        create_assets(
+            &mut state,
+            100_000_000_000,
+            vec![AssetBuilder::new("/style.css", "text/css").with_encoding("gzip", vec![CHUNK_1])],
+        );
+
+        // certification v1 cannot certify partial bodies
+        let response = state.http_request(
+            RequestBuilder::get("/video.mp4")
+                .with_header("Range", "bytes=2-5")
+                .with_certificate_version(1)
+                .build(),
+            &[],
+            unused_callback(),
+        );
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), CHUNK_1);
+
+        // multiple ranges are not supported
+        let response = state.http_request(range_request("bytes=0-1,4-5"), &[], unused_callback());
+        assert_eq!(response.status_code, 200);
+        assert_eq!(lookup_header(&response, "accept-ranges"), Some("bytes"));
+
+        // only the identity encoding is served partially
+        let response = state.http_request(
+            RequestBuilder::get("/style.css")
+                .with_header("Accept-Encoding", "gzip")
+                .with_header("Range", "bytes=2-5")
+                .with_certificate_version(2)
+                .build(),
+            &[],
+            unused_callback(),
+        );
+        assert_eq!(response.status_code, 200);
+        assert_eq!(lookup_header(&response, "accept-ranges"), None);
+    }
+
+    #[test]
+    fn range_responses_verify() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+
+        for (range, status_code) in [
+            ("bytes=2-5", 206),
+            ("bytes=10-", 206),
+            ("bytes=-3", 206),
+            ("bytes=20-", 416),
+        ] {
+            let response = certified_http_request(&state, range_request(range));
+            assert_eq!(response.status_code, status_code);
+        }
+    }
+
+    #[test]
+    fn range_responses_verify_after_changing_properties() {
+        let mut state = State::default();
+        create_media_asset(&mut state);
+
+        state
+            .set_asset_properties(SetAssetPropertiesArguments {
+                key: "/video.mp4".into(),
+                max_age: Some(Some(604800)),
+                headers: Some(Some(HashMap::from([(
+                    "X-Content-Type-Options".into(),
+                    "nosniff".into(),
+                )]))),
+                allow_raw_access: None,
+                is_aliased: None,
+            })
+            .unwrap();
+
+        let response = certified_http_request(&state, range_request("bytes=12-"));
+        assert_eq!(response.status_code, 206);
+        assert_eq!(
+            lookup_header(&response, "cache-control"),
+            Some("max-age=604800")
+        );
+        let response = certified_http_request(&state, range_request("bytes=30-"));
+        assert_eq!(response.status_code, 416);
+    }
+}
+
+mod routes {
+    use super::*;
+    use crate::types::{ClearArguments, DeleteRouteArguments, Route, RouteTarget};
+
+    const INDEX_BODY: &[u8] = b"<!DOCTYPE html><html>Index</html>";
+    const PRICING_BODY: &[u8] = b"<!DOCTYPE html><html>Pricing</html>";
+
+    fn redirect(source: &str, location: &str, status_code: u16) -> BatchOperation {
+        BatchOperation::SetRoute(Route {
+            source: source.to_string(),
+            target: RouteTarget::Redirect {
+                location: location.to_string(),
+                status_code,
+            },
+        })
+    }
+
+    fn rewrite(source: &str, key: &str) -> BatchOperation {
+        BatchOperation::SetRoute(Route {
+            source: source.to_string(),
+            target: RouteTarget::Rewrite {
+                key: key.to_string(),
+            },
+        })
+    }
+
+    fn commit_operations(state: &mut State, operations: Vec<BatchOperation>) -> Result<(), String> {
+        let time_now = 100_000_000_000;
+        let batch_id = state.create_batch(time_now).unwrap();
+        state.commit_batch(
+            CommitBatchArguments {
+                batch_id,
+                operations,
+            },
+            time_now,
+        )
+    }
+
+    fn create_site(state: &mut State) {
+        create_assets(
+            state,
+            100_000_000_000,
+            vec![
+                AssetBuilder::new("/index.html", "text/html")
+                    .with_encoding("identity", vec![INDEX_BODY]),
+                AssetBuilder::new("/pricing.html", "text/html")
+                    .with_encoding("identity", vec![PRICING_BODY]),
+            ],
+        );
+    }
+
+    fn get(path: &str) -> HttpRequest {
+        RequestBuilder::get(path)
+            .with_header("Accept-Encoding", "identity")
+            .with_certificate_version(2)
+            .build()
+    }
+
+    #[test]
+    fn serves_certified_redirect() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(&mut state, vec![redirect("/old-pricing", "/pricing", 301)]).unwrap();
+
+        let response = certified_http_request(&state, get("/old-pricing"));
+        assert_eq!(response.status_code, 301);
+        assert_eq!(lookup_header(&response, "location"), Some("/pricing"));
+        assert!(response.body.as_ref().is_empty());
+
+        // certification v1 cannot certify redirects, so the fallback is served instead
+        let response = certified_http_request(
+            &state,
+            RequestBuilder::get("/old-pricing")
+                .with_certificate_version(1)
+                .build(),
+        );
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+    }
+
+    #[test]
+    fn serves_certified_rewrite() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(&mut state, vec![rewrite("/plans", "/pricing.html")]).unwrap();
+
+        let response = certified_http_request(&state, get("/plans"));
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), PRICING_BODY);
+
+        // the rewrite follows changes to the target asset
+        const NEW_PRICING_BODY: &[u8] = b"<!DOCTYPE html><html>New pricing</html>";
+        create_assets(
+            &mut state,
+            100_000_000_000,
+            vec![AssetBuilder::new("/pricing.html", "text/html")
+                .with_encoding("identity", vec![NEW_PRICING_BODY])],
+        );
+        let response = certified_http_request(&state, get("/plans"));
+        assert_eq!(response.body.as_ref(), NEW_PRICING_BODY);
+
+        // without a target asset, the fallback is served
+        commit_operations(
+            &mut state,
+            vec![BatchOperation::DeleteAsset(DeleteAssetArguments {
+                key: "/pricing.html".to_string(),
+            })],
+        )
+        .unwrap();
+        let response = certified_http_request(&state, get("/plans"));
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+    }
+
+    #[test]
+    fn assets_take_precedence_over_routes() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(
+            &mut state,
+            vec![
+                redirect("/pricing", "https://example.com/pricing", 302),
+                rewrite("/index.html", "/pricing.html"),
+            ],
+        )
+        .unwrap();
+
+        // "/pricing" is an alias of "/pricing.html"
+        let response = certified_http_request(&state, get("/pricing"));
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), PRICING_BODY);
+        let response = certified_http_request(&state, get("/index.html"));
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+
+        commit_operations(
+            &mut state,
+            vec![BatchOperation::DeleteAsset(DeleteAssetArguments {
+                key: "/pricing.html".to_string(),
+            })],
+        )
+        .unwrap();
+        let response = certified_http_request(&state, get("/pricing"));
+        assert_eq!(response.status_code, 302);
+        assert_eq!(
+            lookup_header(&response, "location"),
+            Some("https://example.com/pricing")
+        );
+    }
+
+    #[test]
+    fn routes_can_be_replaced_and_deleted() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(&mut state, vec![redirect("/old", "/a", 301)]).unwrap();
+        commit_operations(&mut state, vec![redirect("/old", "/b", 308)]).unwrap();
+
+        let response = certified_http_request(&state, get("/old"));
+        assert_eq!(response.status_code, 308);
+        assert_eq!(lookup_header(&response, "location"), Some("/b"));
+        assert_eq!(
+            state.list_routes(),
+            vec![Route {
+                source: "/old".to_string(),
+                target: RouteTarget::Redirect {
+                    location: "/b".to_string(),
+                    status_code: 308
+                },
+            }]
+        );
+
+        commit_operations(
+            &mut state,
+            vec![BatchOperation::DeleteRoute(DeleteRouteArguments {
+                source: "/old".to_string(),
+            })],
+        )
+        .unwrap();
+        let response = certified_http_request(&state, get("/old"));
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+        assert!(state.list_routes().is_empty());
+    }
+
+    #[test]
+    fn clear_removes_routes() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(
+            &mut state,
+            vec![
+                redirect("/old", "/pricing", 301),
+                BatchOperation::Clear(ClearArguments {}),
+            ],
+        )
+        .unwrap();
+        assert!(state.list_routes().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_routes() {
+        let mut state = State::default();
+        assert!(commit_operations(&mut state, vec![redirect("/old", "/new", 200)]).is_err());
+        assert!(commit_operations(&mut state, vec![redirect("/old", "", 301)]).is_err());
+        assert!(commit_operations(&mut state, vec![redirect("old", "/new", 301)]).is_err());
+        assert!(commit_operations(&mut state, vec![rewrite("/old", "/old")]).is_err());
+        assert!(state.list_routes().is_empty());
+    }
+
+    #[test]
+    fn routes_survive_stable_roundtrip() {
+        let mut state = State::default();
+        create_site(&mut state);
+        commit_operations(
+            &mut state,
+            vec![
+                redirect("/old-pricing", "/pricing", 301),
+                rewrite("/plans", "/pricing.html"),
+            ],
+        )
+        .unwrap();
+
+        let stable_state: StableState = state.into();
+        let state: State = stable_state.into();
+
+        let response = certified_http_request(&state, get("/old-pricing"));
+        assert_eq!(response.status_code, 301);
+        let response = certified_http_request(&state, get("/plans"));
+        assert_eq!(response.body.as_ref(), PRICING_BODY);
+    }
+
+    #[test]
+    fn set_route_affects_evidence() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+
+        let mut evidences = vec![];
+        for operation in [
+            redirect("/old", "/new", 301),
+            redirect("/old", "/new", 308),
+            redirect("/old", "/other", 301),
+            redirect("/older", "/new", 301),
+            rewrite("/old", "/new"),
+            BatchOperation::DeleteRoute(DeleteRouteArguments {
+                source: "/old".to_string(),
+            }),
+        ] {
+            let batch_id = state.create_batch(time_now).unwrap();
+            state
+                .propose_commit_batch(CommitBatchArguments {
+                    batch_id: batch_id.clone(),
+                    operations: vec![operation],
+                })
+                .unwrap();
+            let evidence = state
+                .compute_evidence(ComputeEvidenceArguments {
+                    batch_id: batch_id.clone(),
+                    max_iterations: Some(3),
+                })
+                .unwrap()
+                .unwrap();
+            delete_batch(&mut state, batch_id);
+            assert!(!evidences.contains(&evidence));
+            evidences.push(evidence);
+        }
+    }
+}
+
+mod stable_memory {
+    use super::*;
+    use crate::stable_memory::stored_count;
+    use crate::types::{Permission, UnsetAssetContentArguments};
+    use candid::{CandidType, Int};
+    use ic_stable_structures::memory_manager::{MemoryId, MemoryManager};
+    use ic_stable_structures::DefaultMemoryImpl;
+    use sha2::Digest as _;
+
+    const INDEX_BODY: &[u8] = b"<!DOCTYPE html><html>Index</html>";
+
+    // The shape of `StableState` in versions that kept asset content on the heap.
+    #[derive(CandidType)]
+    struct HeapStableState {
+        authorized: Vec<Principal>,
+        stable_assets: HashMap<String, HeapAsset>,
+    }
+
+    #[derive(CandidType)]
+    struct HeapAsset {
+        content_type: String,
+        encodings: HashMap<String, HeapAssetEncoding>,
+    }
+
+    #[derive(CandidType)]
+    struct HeapAssetEncoding {
+        modified: Int,
+        content_chunks: Vec<ByteBuf>,
+        total_length: usize,
+        certified: bool,
+        sha256: [u8; 32],
+    }
+
+    fn init_stable_memory() {
+        let manager = MemoryManager::init(DefaultMemoryImpl::default());
+        crate::init_stable_memory(manager.get(MemoryId::new(0)));
+    }
+
+    fn get_index(state: &State) -> HttpResponse {
+        certified_http_request(
+            state,
+            RequestBuilder::get("/index.html")
+                .with_header("Accept-Encoding", "identity")
+                .build(),
+        )
+    }
+
+    #[test]
+    fn upgrades_from_heap_storage() {
+        init_stable_memory();
+        let heap_stable_state = HeapStableState {
+            authorized: vec![some_principal()],
+            stable_assets: HashMap::from([(
+                "/index.html".to_string(),
+                HeapAsset {
+                    content_type: "text/html".to_string(),
+                    encodings: HashMap::from([(
+                        "identity".to_string(),
+                        HeapAssetEncoding {
+                            modified: Int::from(100_000_000_000_u64),
+                            content_chunks: vec![ByteBuf::from(INDEX_BODY.to_vec())],
+                            total_length: INDEX_BODY.len(),
+                            certified: true,
+                            sha256: sha2::Sha256::digest(INDEX_BODY).into(),
+                        },
+                    )]),
+                },
+            )]),
+        };
+        let bytes = candid::encode_one(heap_stable_state).unwrap();
+        let stable_state: StableState = candid::decode_one(&bytes).unwrap();
+
+        let stored_before = stored_count();
+        let state = State::from(stable_state);
+        assert_eq!(stored_count(), stored_before + 1);
+
+        let response = get_index(&state);
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+        assert!(state.has_permission(&some_principal(), &Permission::Commit));
+    }
+
+    #[test]
+    fn upgrades_do_not_copy_content() {
+        init_stable_memory();
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+
+        create_assets(
+            &mut state,
+            time_now,
+            vec![AssetBuilder::new("/index.html", "text/html")
+                .with_encoding("identity", vec![INDEX_BODY])],
+        );
+        let stored_before = stored_count();
+
+        let bytes = candid::encode_one(StableState::from(state)).unwrap();
+        let stable_state: StableState = candid::decode_one(&bytes).unwrap();
+        let state = State::from(stable_state);
+
+        assert_eq!(stored_count(), stored_before);
+        let response = get_index(&state);
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+    }
+
+    #[test]
+    fn inlines_content_without_stable_memory() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+
+        create_assets(
+            &mut state,
+            time_now,
+            vec![AssetBuilder::new("/index.html", "text/html")
+                .with_encoding("identity", vec![INDEX_BODY])],
+        );
+        let stored_before = stored_count();
+
+        let stable_state = StableState::from(state);
+        assert_eq!(stored_count(), stored_before - 1);
+        let bytes = candid::encode_one(stable_state).unwrap();
+        assert!(bytes
+            .windows(INDEX_BODY.len())
+            .any(|window| window == INDEX_BODY));
+
+        let stable_state: StableState = candid::decode_one(&bytes).unwrap();
+        let state = State::from(stable_state);
+        assert_eq!(stored_count(), stored_before);
+        let response = get_index(&state);
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+    }
+
+    #[test]
+    fn moves_heap_content_into_stable_memory() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+
+        create_assets(
+            &mut state,
+            time_now,
+            vec![AssetBuilder::new("/index.html", "text/html")
+                .with_encoding("identity", vec![INDEX_BODY])],
+        );
+        let stored_before = stored_count();
+        init_stable_memory();
+
+        assert_eq!(stored_count(), stored_before);
+        let response = get_index(&state);
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body.as_ref(), INDEX_BODY);
+    }
+
+    #[test]
+    fn frees_content_of_deleted_and_replaced_assets() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+        let stored_before = stored_count();
+
+        create_assets(
+            &mut state,
+            time_now,
+            vec![AssetBuilder::new("/index.html", "text/html")
+                .with_encoding("identity", vec![INDEX_BODY, INDEX_BODY])
+                .with_encoding("gzip", vec![INDEX_BODY])],
+        );
+        assert_eq!(stored_count(), stored_before + 3);
+
+        let batch_id = state.create_batch(time_now).unwrap();
+        state
+            .commit_batch(
+                CommitBatchArguments {
+                    batch_id,
+                    operations: vec![
+                        BatchOperation::SetAssetContent(SetAssetContentArguments {
+                            key: "/index.html".to_string(),
+                            content_encoding: "identity".to_string(),
+                            chunk_ids: vec![],
+                            last_chunk: Some(ByteBuf::from(INDEX_BODY.to_vec())),
+                            sha256: None,
+                        }),
+                        BatchOperation::UnsetAssetContent(UnsetAssetContentArguments {
+                            key: "/index.html".to_string(),
+                            content_encoding: "gzip".to_string(),
+                        }),
+                    ],
+                },
+                time_now,
+            )
+            .unwrap();
+        assert_eq!(stored_count(), stored_before + 1);
+
+        state.delete_asset(DeleteAssetArguments {
+            key: "/index.html".to_string(),
+        });
+        assert_eq!(stored_count(), stored_before);
+    }
+
+    #[test]
+    fn frees_chunks_of_deleted_batches() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+        let stored_before = stored_count();
+
+        let batch_id = state.create_batch(time_now).unwrap();
+        state
+            .create_chunk(
+                CreateChunkArg {
+                    batch_id: batch_id.clone(),
+                    content: ByteBuf::from(INDEX_BODY.to_vec()),
+                },
+                time_now,
+            )
+            .unwrap();
+        assert_eq!(stored_count(), stored_before + 1);
+
+        delete_batch(&mut state, batch_id);
+        assert_eq!(stored_count(), stored_before);
+    }
+
+    #[test]
+    fn frees_chunks_of_expired_batches_and_upgrades() {
+        let mut state = State::default();
+        let time_now = 100_000_000_000;
+        let stored_before = stored_count();
+
+        for _ in 0..2 {
+            let batch_id = state.create_batch(time_now).unwrap();
+            state
+                .create_chunk(
+                    CreateChunkArg {
+                        batch_id,
+                        content: ByteBuf::from(INDEX_BODY.to_vec()),
+                    },
+                    time_now,
+                )
+                .unwrap();
+        }
+        assert_eq!(stored_count(), stored_before + 2);
+
+        state
+            .create_batch(time_now + BATCH_EXPIRY_NANOS + 1)
+            .unwrap();
+        assert_eq!(stored_count(), stored_before);
+
+        let batch_id = state.create_batch(time_now).unwrap();
+        state
+            .create_chunk(
+                CreateChunkArg {
+                    batch_id,
+                    content: ByteBuf::from(INDEX_BODY.to_vec()),
+                },
+                time_now,
+            )
+            .unwrap();
+        let _stable_state = StableState::from(state);
+        assert_eq!(stored_count(), stored_before);
+    }
+}
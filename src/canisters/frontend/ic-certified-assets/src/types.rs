@@ -58,6 +58,29 @@ pub struct DeleteAssetArguments {
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct ClearArguments {}
 
+/// What to do with requests for the source path of a route.
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub enum RouteTarget {
+    /// Respond with a redirect to `location`.
+    Redirect { location: String, status_code: u16 },
+    /// Serve the asset with the given key, as if it had been requested directly.
+    Rewrite { key: AssetKey },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CandidType, Deserialize)]
+pub struct Route {
+    /// The exact request path this route applies to.
+    pub source: String,
+    pub target: RouteTarget,
+}
+
+pub type SetRouteArguments = Route;
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct DeleteRouteArguments {
+    pub source: String,
+}
+
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub enum BatchOperation {
     CreateAsset(CreateAssetArguments),
@@ -66,6 +89,8 @@ pub enum BatchOperation {
     DeleteAsset(DeleteAssetArguments),
     Clear(ClearArguments),
     SetAssetProperties(SetAssetPropertiesArguments),
+    SetRoute(SetRouteArguments),
+    DeleteRoute(DeleteRouteArguments),
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
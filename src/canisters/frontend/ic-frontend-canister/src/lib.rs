@@ -1,36 +1,58 @@
-use candid::ser::IDLBuilder;
-use ic_cdk::api::stable;
 use ic_cdk::{init, post_upgrade, pre_upgrade};
 use ic_certified_assets::types::AssetCanisterArgs;
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager};
+use ic_stable_structures::{DefaultMemoryImpl, Memory};
+
+const ASSETS: MemoryId = MemoryId::new(0);
+
+/// The memory manager marks stable memory it has initialized with these bytes.
+/// Versions that kept asset content on the heap wrote a candid-encoded `StableState` instead.
+const MEMORY_MANAGER_MAGIC: &[u8; 3] = b"MGR";
+
+thread_local! {
+    static MEMORY_MANAGER: MemoryManager<DefaultMemoryImpl> =
+        MemoryManager::init(DefaultMemoryImpl::default());
+}
+
+fn init_stable_memory() {
+    MEMORY_MANAGER.with(|m| ic_certified_assets::init_stable_memory(m.get(ASSETS)));
+}
+
+/// Returns true if stable memory was written by a version that kept asset content on the heap.
+///
+/// This must be called before [MEMORY_MANAGER] is first used, because that overwrites it.
+fn is_heap_layout() -> bool {
+    let memory = DefaultMemoryImpl::default();
+    if memory.size() == 0 {
+        return false;
+    }
+    let mut magic = [0; 3];
+    memory.read(0, &mut magic);
+    &magic != MEMORY_MANAGER_MAGIC
+}
 
 #[init]
 fn init(args: Option<AssetCanisterArgs>) {
+    init_stable_memory();
     ic_certified_assets::init(args);
 }
 
 #[pre_upgrade]
 fn pre_upgrade() {
     let stable_state = ic_certified_assets::pre_upgrade();
-    let value_serializer_estimate = stable_state.estimate_size();
-    stable_save_with_capacity((stable_state,), value_serializer_estimate)
-        .expect("failed to save stable state");
-}
-
-// this is the same as ic_cdk::storage::stable_save,
-// but reserves the capacity for the value serializer
-fn stable_save_with_capacity<T>(t: T, value_capacity: usize) -> Result<(), candid::Error>
-where
-    T: candid::utils::ArgumentEncoder,
-{
-    let mut ser = IDLBuilder::new();
-    ser.try_reserve_value_serializer_capacity(value_capacity)?;
-    t.encode(&mut ser)?;
-    ser.serialize(stable::StableWriter::default())
+    ic_certified_assets::save_stable_state(stable_state).expect("failed to save stable state");
 }
 
 #[post_upgrade]
 fn post_upgrade(args: Option<AssetCanisterArgs>) {
-    let (stable_state,): (ic_certified_assets::StableState,) =
-        ic_cdk::storage::stable_restore().expect("failed to restore stable state");
+    let stable_state = if is_heap_layout() {
+        let (stable_state,) =
+            ic_cdk::storage::stable_restore().expect("failed to restore stable state");
+        init_stable_memory();
+        stable_state
+    } else {
+        init_stable_memory();
+        ic_certified_assets::restore_stable_state().expect("failed to restore stable state")
+    };
     ic_certified_assets::post_upgrade(stable_state, args);
 }
@@ -0,0 +1,12 @@
+use crate::DownloadOpts;
+use ic_utils::Canister;
+use slog::Logger;
+
+pub(crate) async fn download(
+    canister: &Canister<'_>,
+    o: &DownloadOpts,
+    logger: &Logger,
+) -> anyhow::Result<()> {
+    ic_asset::download(canister, &o.directory, logger).await?;
+    Ok(())
+}
@@ -1,3 +1,4 @@
+pub mod download;
 pub mod list;
 pub mod sync;
 pub mod upload;
@@ -1,5 +1,6 @@
 mod commands;
 mod support;
+use crate::commands::download::download;
 use crate::commands::list::list;
 use crate::commands::sync::sync;
 use crate::commands::upload::upload;
@@ -75,6 +76,9 @@ enum SubCommand {
 
     /// Uploads an asset to an asset canister.
     Upload(UploadOpts),
+
+    /// Downloads the assets of an asset canister to a directory.
+    Download(DownloadOpts),
 }
 
 #[derive(Parser)]
@@ -105,6 +109,15 @@ struct UploadOpts {
     files: Vec<String>,
 }
 
+#[derive(Parser)]
+struct DownloadOpts {
+    /// The canister ID.
+    canister_id: String,
+
+    /// The directory to download the assets to. It must be empty or not exist yet.
+    directory: PathBuf,
+}
+
 fn create_identity(maybe_pem: Option<PathBuf>) -> Box<dyn Identity + Sync + Send> {
     if let Some(pem_path) = maybe_pem {
         if let Ok(secp256k_identity) = Secp256k1Identity::from_pem_file(&pem_path) {
@@ -170,6 +183,13 @@ async fn main() -> anyhow::Result<()> {
                 .build()?;
             upload(&canister, o, &logger).await?;
         }
+        SubCommand::Download(o) => {
+            let canister = ic_utils::Canister::builder()
+                .with_agent(&agent)
+                .with_canister_id(Principal::from_text(&o.canister_id)?)
+                .build()?;
+            download(&canister, o, &logger).await?;
+        }
     }
 
     Ok(())
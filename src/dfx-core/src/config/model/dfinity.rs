@@ -8,6 +8,7 @@ use crate::error::config::{GetOutputEnvFileError, GetTempPathError};
 use crate::error::dfx_config::AddDependenciesError::CanisterCircularDependency;
 use crate::error::dfx_config::GetCanisterNamesWithDependenciesError::AddDependenciesFailed;
 use crate::error::dfx_config::GetComputeAllocationError::GetComputeAllocationFailed;
+use crate::error::dfx_config::GetCyclesPolicyError::GetCyclesPolicyFailed;
 use crate::error::dfx_config::GetFreezingThresholdError::GetFreezingThresholdFailed;
 use crate::error::dfx_config::GetLogVisibilityError::GetLogVisibilityFailed;
 use crate::error::dfx_config::GetMemoryAllocationError::GetMemoryAllocationFailed;
@@ -15,14 +16,15 @@ use crate::error::dfx_config::GetPullCanistersError::PullCanistersSameId;
 use crate::error::dfx_config::GetRemoteCanisterIdError::GetRemoteCanisterIdFailed;
 use crate::error::dfx_config::GetReservedCyclesLimitError::GetReservedCyclesLimitFailed;
 use crate::error::dfx_config::GetSpecifiedIdError::GetSpecifiedIdFailed;
+use crate::error::dfx_config::GetSubnetError::GetSubnetFailed;
 use crate::error::dfx_config::GetWasmMemoryLimitError::GetWasmMemoryLimitFailed;
 use crate::error::dfx_config::GetWasmMemoryThresholdError::GetWasmMemoryThresholdFailed;
 use crate::error::dfx_config::{
     AddDependenciesError, GetCanisterConfigError, GetCanisterNamesWithDependenciesError,
-    GetComputeAllocationError, GetFreezingThresholdError, GetLogVisibilityError,
-    GetMemoryAllocationError, GetPullCanistersError, GetRemoteCanisterIdError,
-    GetReservedCyclesLimitError, GetSpecifiedIdError, GetWasmMemoryLimitError,
-    GetWasmMemoryThresholdError,
+    GetComputeAllocationError, GetCyclesPolicyError, GetFreezingThresholdError,
+    GetLogVisibilityError, GetMemoryAllocationError, GetPullCanistersError,
+    GetRemoteCanisterIdError, GetReservedCyclesLimitError, GetSpecifiedIdError, GetSubnetError,
+    GetWasmMemoryLimitError, GetWasmMemoryThresholdError,
 };
 use crate::error::fs::CanonicalizePathError;
 use crate::error::load_dfx_config::LoadDfxConfigError;
@@ -290,6 +292,13 @@ pub struct ConfigCanistersCanister {
     #[serde(default)]
     pub post_install: SerdeVec<String>,
 
+    /// # Watched Paths
+    /// Files and directories, relative to the project root, whose changes redeploy the canister in `dfx deploy --watch`.
+    /// They are watched in addition to the sources that dfx knows for the canister type.
+    /// For custom canisters these are only the Wasm module and the Candid file, so list the sources of the build here.
+    #[serde(default)]
+    pub watch: Vec<PathBuf>,
+
     /// # Path to Canister Entry Point
     /// Entry point for e.g. Motoko Compiler.
     pub main: Option<PathBuf>,
@@ -333,6 +342,17 @@ pub struct ConfigCanistersCanister {
     #[schemars(with = "Option<String>")]
     pub specified_id: Option<Principal>,
 
+    /// # Subnet
+    /// The subnet to create the canister on.
+    /// Either the name of a subnet declared in `networks.local.replica.subnets`, or the principal of a subnet.
+    /// On a local network, the subnet must be declared there. On other networks, names are ignored with a warning.
+    /// If the `--subnet`, `--subnet-type` or `--next-to` argument is also provided, this `subnet` field will be ignored.
+    pub subnet: Option<String>,
+
+    /// # Cycles Top-Up Policy
+    /// The cycles balance `dfx cycles top-up --project` keeps this canister at.
+    pub cycles: Option<ConfigCanisterCyclesPolicy>,
+
     /// # Init Arg
     /// The Candid initialization argument for installing the canister.
     /// If the `--argument` or `--argument-file` argument is also provided, this `init_arg` field will be ignored.
@@ -340,6 +360,7 @@ pub struct ConfigCanistersCanister {
 
     /// # Init Arg File
     /// The Candid initialization argument file for installing the canister.
+    /// Files with a `.json`, `.yaml` or `.yml` extension are converted using the Candid type of the init argument.
     /// If the `--argument` or `--argument-file` argument is also provided, this `init_arg_file` field will be ignored.
     pub init_arg_file: Option<String>,
 }
@@ -449,6 +470,33 @@ impl From<CanisterLogVisibility> for LogVisibility {
     }
 }
 
+/// # Cycles Top-Up Policy
+/// When the canister's cycles balance is below `min_balance`, `dfx cycles top-up --project` tops it up to `target_balance`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ConfigCanisterCyclesPolicy {
+    /// # Minimum Balance
+    /// The canister is topped up if it has fewer cycles than this.
+    #[schemars(with = "u128")]
+    pub min_balance: u128,
+
+    /// # Target Balance
+    /// The number of cycles the canister has after a top-up.
+    /// Defaults to `min_balance`. Must not be less than `min_balance`.
+    #[schemars(with = "Option<u128>")]
+    pub target_balance: Option<u128>,
+}
+
+impl ConfigCanisterCyclesPolicy {
+    pub fn target_balance(&self) -> u128 {
+        self.target_balance.unwrap_or(self.min_balance)
+    }
+
+    /// Returns the number of cycles to add to a canister with `balance` cycles.
+    pub fn top_up_amount(&self, balance: u128) -> Option<u128> {
+        (balance < self.min_balance).then(|| self.target_balance().saturating_sub(balance))
+    }
+}
+
 /// # Initial Resource Allocations
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
@@ -518,6 +566,41 @@ pub struct InitializationValues {
     /// Can be "public", "controllers" or "allowed_viewers" with a list of principals.
     #[schemars(with = "Option<CanisterLogVisibility>")]
     pub log_visibility: Option<CanisterLogVisibility>,
+
+    /// # Per-Network Overrides
+    /// Values for specific networks, e.g. `"networks": { "ic": { "freezing_threshold": "90days" } }`.
+    /// On a network listed here, its values take precedence over the values above.
+    /// `networks` is ignored inside of a network's values.
+    pub networks: BTreeMap<String, InitializationValues>,
+}
+
+impl InitializationValues {
+    /// Returns the values that apply on `network`.
+    pub fn for_network(&self, network: &str) -> InitializationValues {
+        let Some(overrides) = self.networks.get(network) else {
+            return InitializationValues {
+                networks: BTreeMap::new(),
+                ..self.clone()
+            };
+        };
+        InitializationValues {
+            compute_allocation: overrides.compute_allocation.or(self.compute_allocation),
+            memory_allocation: overrides.memory_allocation.or(self.memory_allocation),
+            freezing_threshold: overrides.freezing_threshold.or(self.freezing_threshold),
+            reserved_cycles_limit: overrides
+                .reserved_cycles_limit
+                .or(self.reserved_cycles_limit),
+            wasm_memory_limit: overrides.wasm_memory_limit.or(self.wasm_memory_limit),
+            wasm_memory_threshold: overrides
+                .wasm_memory_threshold
+                .or(self.wasm_memory_threshold),
+            log_visibility: overrides
+                .log_visibility
+                .clone()
+                .or_else(|| self.log_visibility.clone()),
+            networks: BTreeMap::new(),
+        }
+    }
 }
 
 /// # Declarations Configuration
@@ -532,7 +615,7 @@ pub struct CanisterDeclarationsConfig {
 
     /// # Languages to generate
     /// A list of languages to generate type declarations.
-    /// Supported options are 'js', 'ts', 'did', 'mo'.
+    /// Supported options are 'js', 'ts', 'did', 'mo', 'rs' (Rust, based on ic-agent) and 'py' (Python type definitions).
     /// Default is ['js', 'ts', 'did'].
     pub bindings: Option<Vec<String>>,
 
@@ -718,6 +801,12 @@ pub struct ConfigDefaultsReplica {
     /// Defaults to 'application'.
     pub subnet_type: Option<ReplicaSubnetType>,
 
+    /// # Subnets
+    /// Named subnets the local replica runs in addition to the system subnets.
+    /// If set, replaces the single subnet of type `subnet_type`.
+    /// Canisters are placed on one of these subnets with their `subnet` field.
+    pub subnets: Option<Vec<ConfigReplicaSubnet>>,
+
     /// Run replica with the provided log level. Default is 'error'. Debug prints still get displayed
     pub log_level: Option<ReplicaLogLevel>,
 }
@@ -753,6 +842,19 @@ impl NetworkType {
     }
 }
 
+/// # Local Subnet Configuration
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigReplicaSubnet {
+    /// # Name
+    /// Name that canisters use to refer to this subnet.
+    pub name: String,
+
+    /// # Subnet Type
+    /// Defaults to 'application'.
+    #[serde(default)]
+    pub subnet_type: ReplicaSubnetType,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ReplicaSubnetType {
@@ -996,11 +1098,13 @@ impl ConfigInterface {
     pub fn get_compute_allocation(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<u64>, GetComputeAllocationError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetComputeAllocationFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .compute_allocation
             .map(|x| x.0))
     }
@@ -1008,68 +1112,79 @@ impl ConfigInterface {
     pub fn get_memory_allocation(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<Byte>, GetMemoryAllocationError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetMemoryAllocationFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .memory_allocation)
     }
 
     pub fn get_freezing_threshold(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<Duration>, GetFreezingThresholdError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetFreezingThresholdFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .freezing_threshold)
     }
 
     pub fn get_reserved_cycles_limit(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<u128>, GetReservedCyclesLimitError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetReservedCyclesLimitFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .reserved_cycles_limit)
     }
 
     pub fn get_wasm_memory_limit(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<Byte>, GetWasmMemoryLimitError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetWasmMemoryLimitFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .wasm_memory_limit)
     }
 
     pub fn get_wasm_memory_threshold(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<Byte>, GetWasmMemoryThresholdError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetWasmMemoryThresholdFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .wasm_memory_threshold)
     }
 
     pub fn get_log_visibility(
         &self,
         canister_name: &str,
+        network: &str,
     ) -> Result<Option<LogVisibility>, GetLogVisibilityError> {
         Ok(self
             .get_canister_config(canister_name)
             .map_err(|e| GetLogVisibilityFailed(canister_name.to_string(), e))?
             .initialization_values
+            .for_network(network)
             .log_visibility
-            .clone()
             .map(|visibility| visibility.into()))
     }
 
@@ -1110,6 +1225,25 @@ impl ConfigInterface {
             .map_err(|e| GetSpecifiedIdFailed(canister_name.to_string(), e))?
             .specified_id)
     }
+
+    pub fn get_cycles_policy(
+        &self,
+        canister_name: &str,
+    ) -> Result<Option<ConfigCanisterCyclesPolicy>, GetCyclesPolicyError> {
+        Ok(self
+            .get_canister_config(canister_name)
+            .map_err(|e| GetCyclesPolicyFailed(canister_name.to_string(), e))?
+            .cycles
+            .clone())
+    }
+
+    pub fn get_subnet(&self, canister_name: &str) -> Result<Option<String>, GetSubnetError> {
+        Ok(self
+            .get_canister_config(canister_name)
+            .map_err(|e| GetSubnetFailed(canister_name.to_string(), e))?
+            .subnet
+            .clone())
+    }
 }
 
 fn add_dependencies(
@@ -1674,13 +1808,13 @@ mod tests {
 
         let config_interface = config.get_config();
         let compute_allocation = config_interface
-            .get_compute_allocation("test_project")
+            .get_compute_allocation("test_project", "local")
             .unwrap()
             .unwrap();
         assert_eq!(100, compute_allocation);
 
         let memory_allocation = config_interface
-            .get_memory_allocation("test_project")
+            .get_memory_allocation("test_project", "local")
             .unwrap()
             .unwrap();
         assert_eq!("8GB".parse::<Byte>().unwrap(), memory_allocation);
@@ -1696,12 +1830,118 @@ mod tests {
         .unwrap();
         let config_interface = config_no_values.get_config();
         let compute_allocation = config_interface
-            .get_compute_allocation("test_project_two")
+            .get_compute_allocation("test_project_two", "local")
             .unwrap();
         let memory_allocation = config_interface
-            .get_memory_allocation("test_project_two")
+            .get_memory_allocation("test_project_two", "local")
             .unwrap();
         assert_eq!(None, compute_allocation);
         assert_eq!(None, memory_allocation);
     }
+
+    #[test]
+    fn get_initialization_values_for_network() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "test_project": {
+                  "initialization_values": {
+                    "compute_allocation": 10,
+                    "freezing_threshold": "30days",
+                    "networks": {
+                      "ic": {
+                        "freezing_threshold": "90days",
+                        "log_visibility": "public"
+                      }
+                    }
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+        let config_interface = config.get_config();
+
+        let freezing_threshold = |network| {
+            config_interface
+                .get_freezing_threshold("test_project", network)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(Duration::from_secs(30 * 86400), freezing_threshold("local"));
+        assert_eq!(Duration::from_secs(90 * 86400), freezing_threshold("ic"));
+
+        // Values that a network does not override are inherited.
+        for network in ["local", "ic"] {
+            assert_eq!(
+                Some(10),
+                config_interface
+                    .get_compute_allocation("test_project", network)
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(
+            None,
+            config_interface
+                .get_log_visibility("test_project", "local")
+                .unwrap()
+        );
+        assert_eq!(
+            Some(LogVisibility::Public),
+            config_interface
+                .get_log_visibility("test_project", "ic")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn get_cycles_policy() {
+        let config = Config::from_str(
+            r#"{
+              "canisters": {
+                "topped_up": {
+                  "cycles": {
+                    "min_balance": 2000000000000,
+                    "target_balance": 5000000000000
+                  }
+                },
+                "no_target": {
+                  "cycles": {
+                    "min_balance": 2000000000000
+                  }
+                },
+                "no_policy": {
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+        let config_interface = config.get_config();
+
+        let policy = config_interface
+            .get_cycles_policy("topped_up")
+            .unwrap()
+            .unwrap();
+        assert_eq!(None, policy.top_up_amount(2_000_000_000_000));
+        assert_eq!(
+            Some(3_500_000_000_000),
+            policy.top_up_amount(1_500_000_000_000)
+        );
+
+        let policy = config_interface
+            .get_cycles_policy("no_target")
+            .unwrap()
+            .unwrap();
+        assert_eq!(2_000_000_000_000, policy.target_balance());
+        assert_eq!(
+            Some(500_000_000_000),
+            policy.top_up_amount(1_500_000_000_000)
+        );
+
+        assert_eq!(
+            None,
+            config_interface.get_cycles_policy("no_policy").unwrap()
+        );
+    }
 }
@@ -287,6 +287,9 @@ impl LocalServerDescriptor {
             "".to_string()
         };
         debug!(log, "    subnet type: {:?}{}", subnet_type, diffs);
+        for subnet in self.replica.subnets.iter().flatten() {
+            debug!(log, "    subnet {}: {:?}", subnet.name, subnet.subnet_type);
+        }
 
         let log_level = self.replica.log_level.unwrap_or_default();
         let diffs: String = if log_level != ReplicaLogLevel::default() {
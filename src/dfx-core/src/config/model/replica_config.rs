@@ -1,7 +1,8 @@
-use crate::config::model::dfinity::{ReplicaLogLevel, ReplicaSubnetType};
+use crate::config::model::dfinity::{ConfigReplicaSubnet, ReplicaLogLevel, ReplicaSubnetType};
 use candid::Principal;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::default::Default;
 use std::path::{Path, PathBuf};
 
@@ -51,6 +52,8 @@ pub struct ReplicaConfig {
     pub crypto: CryptoConfig,
     pub artifact_pool: ArtifactPoolConfig,
     pub subnet_type: ReplicaSubnetType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subnets: Vec<ConfigReplicaSubnet>,
     pub btc_adapter: BtcAdapterConfig,
     pub canister_http_adapter: CanisterHttpAdapterConfig,
     pub log_level: ReplicaLogLevel,
@@ -79,6 +82,7 @@ impl ReplicaConfig {
                 consensus_pool_path: state_root.join("consensus_pool"),
             },
             subnet_type,
+            subnets: vec![],
             btc_adapter: BtcAdapterConfig {
                 enabled: false,
                 socket_path: None,
@@ -106,6 +110,10 @@ impl ReplicaConfig {
         }
     }
 
+    pub fn with_subnets(self, subnets: Vec<ConfigReplicaSubnet>) -> Self {
+        ReplicaConfig { subnets, ..self }
+    }
+
     pub fn with_btc_adapter_enabled(self) -> Self {
         ReplicaConfig {
             btc_adapter: self.btc_adapter.with_enabled(),
@@ -190,10 +198,20 @@ pub enum CachedReplicaConfig<'a> {
     PocketIc { config: Cow<'a, ReplicaConfig> },
 }
 
+/// A named subnet of a running local replica.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LocalSubnet {
+    pub subnet_id: Principal,
+    /// A canister id in the canister range of the subnet, used to create canisters on it.
+    pub effective_canister_id: Principal,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct CachedConfig<'a> {
     pub replica_rev: String,
     pub effective_canister_id: Option<Principal>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subnets: BTreeMap<String, LocalSubnet>,
     #[serde(flatten)]
     pub config: CachedReplicaConfig<'a>,
 }
@@ -203,6 +221,7 @@ impl<'a> CachedConfig<'a> {
         Self {
             replica_rev,
             effective_canister_id: None,
+            subnets: BTreeMap::new(),
             config: CachedReplicaConfig::Replica {
                 config: Cow::Borrowed(config),
             },
@@ -216,16 +235,29 @@ impl<'a> CachedConfig<'a> {
         Self {
             replica_rev,
             effective_canister_id,
+            subnets: BTreeMap::new(),
             config: CachedReplicaConfig::PocketIc {
                 config: Cow::Borrowed(config),
             },
         }
     }
+    pub fn with_subnets(self, subnets: BTreeMap<String, LocalSubnet>) -> Self {
+        Self { subnets, ..self }
+    }
     pub fn can_share_state(&self, other: &Self) -> bool {
-        // effective canister id does not matter for ability to share state
+        // effective canister id and subnet ids do not matter for ability to share state
         self.replica_rev == other.replica_rev && self.config == other.config
     }
     pub fn get_effective_canister_id(&self) -> Option<Principal> {
         self.effective_canister_id
     }
+    pub fn get_subnet(&self, name: &str) -> Option<LocalSubnet> {
+        self.subnets.get(name).copied()
+    }
+    pub fn find_subnet(&self, subnet_id: Principal) -> Option<LocalSubnet> {
+        self.subnets
+            .values()
+            .find(|subnet| subnet.subnet_id == subnet_id)
+            .copied()
+    }
 }
@@ -1,4 +1,4 @@
-use crate::config::model::dfinity::{ReplicaLogLevel, ReplicaSubnetType};
+use crate::config::model::dfinity::{ConfigReplicaSubnet, ReplicaLogLevel, ReplicaSubnetType};
 use crate::config::model::local_server_descriptor::LocalServerDescriptor;
 use candid::Deserialize;
 use serde::Serialize;
@@ -34,6 +34,8 @@ struct CanisterHttpSettings {
 struct ReplicaSettings {
     pub http_handler: HttpHandlerSettings,
     pub subnet_type: ReplicaSubnetType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subnets: Vec<ConfigReplicaSubnet>,
     pub btc_adapter: BtcSettings,
     pub canister_http_adapter: CanisterHttpSettings,
     pub log_level: ReplicaLogLevel,
@@ -90,6 +92,11 @@ fn get_replica_backend_settings(
             .replica
             .subnet_type
             .unwrap_or_default(),
+        subnets: local_server_descriptor
+            .replica
+            .subnets
+            .clone()
+            .unwrap_or_default(),
         btc_adapter,
         canister_http_adapter,
         log_level: local_server_descriptor
@@ -87,3 +87,15 @@ pub enum GetSpecifiedIdError {
     #[error("Failed to get specified_id for canister '{0}'")]
     GetSpecifiedIdFailed(String, #[source] GetCanisterConfigError),
 }
+
+#[derive(Error, Debug)]
+pub enum GetCyclesPolicyError {
+    #[error("Failed to get cycles policy for canister '{0}'")]
+    GetCyclesPolicyFailed(String, #[source] GetCanisterConfigError),
+}
+
+#[derive(Error, Debug)]
+pub enum GetSubnetError {
+    #[error("Failed to get subnet for canister '{0}'")]
+    GetSubnetFailed(String, #[source] GetCanisterConfigError),
+}
@@ -1,7 +1,7 @@
 use crate::error::config::GetSharedWalletConfigPathError;
 use crate::error::fs::{
-    ReadFileError, ReadPermissionsError, RemoveDirectoryAndContentsError, RemoveDirectoryError,
-    RemoveFileError, RenameError, SetPermissionsError, WriteFileError,
+    CanonicalizePathError, ReadFileError, ReadPermissionsError, RemoveDirectoryAndContentsError,
+    RemoveDirectoryError, RemoveFileError, RenameError, SetPermissionsError, WriteFileError,
 };
 use crate::error::{
     config::ConfigError,
@@ -16,6 +16,7 @@ use candid::types::principal::PrincipalError;
 use ic_agent::identity::PemError;
 use ic_identity_hsm::HardwareIdentityError;
 use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 
@@ -80,6 +81,9 @@ pub enum CreateNewIdentityError {
     #[error("Failed to remove identity")]
     RemoveIdentityFailed(#[source] RemoveIdentityError),
 
+    #[error("Failed to resolve signer command")]
+    ResolveSignerCommandFailed(#[source] SignerIdentityError),
+
     #[error("Failed to rename temporary directory to permanent identity directory")]
     RenameTemporaryIdentityDirectoryFailed(#[from] RenameError),
 
@@ -101,6 +105,9 @@ pub enum CreateNewIdentityError {
 
 #[derive(Error, Debug)]
 pub enum ExportIdentityError {
+    #[error("Identity '{0}' signs with an external signer command and has no PEM file to export")]
+    CannotExportSignerIdentity(String),
+
     #[error("Failed to get identity config")]
     GetIdentityConfigFailed(#[source] GetIdentityConfigOrDefaultError),
 
@@ -235,6 +242,9 @@ pub enum NewIdentityError {
 
     #[error("Failed to instantiate hardware identity")]
     NewHardwareIdentityFailed(#[source] NewHardwareIdentityError),
+
+    #[error("Failed to instantiate signer identity for identity '{0}'")]
+    NewSignerIdentityFailed(String, #[source] SignerIdentityError),
 }
 
 #[derive(Error, Debug)]
@@ -350,6 +360,9 @@ pub enum SavePemError {
     #[error("Cannot save PEM content for an HSM.")]
     CannotSavePemContentForHsm(),
 
+    #[error("Cannot save PEM content for a signer identity.")]
+    CannotSavePemContentForSigner(),
+
     #[error("Failed to write PEM to file")]
     WritePemToFileFailed(#[source] WritePemToFileError),
 
@@ -357,6 +370,39 @@ pub enum SavePemError {
     WritePemToKeyringFailed(#[source] KeyringError),
 }
 
+#[derive(Error, Debug)]
+pub enum SignerIdentityError {
+    #[error("Failed to parse signer command '{0}'")]
+    ParseSignerCommandFailed(String, #[source] shell_words::ParseError),
+
+    #[error("The signer command is empty")]
+    EmptySignerCommand(),
+
+    #[error("Failed to find signer program '{0}'")]
+    FindSignerProgramFailed(String, #[source] CanonicalizePathError),
+
+    #[error("Failed to run signer command '{0}'")]
+    SpawnSignerCommandFailed(String, #[source] std::io::Error),
+
+    #[error("Failed to communicate with signer command '{0}'")]
+    CommunicateWithSignerCommandFailed(String, #[source] std::io::Error),
+
+    #[error("Signer command '{0}' exited with {1}")]
+    SignerCommandFailed(String, ExitStatus),
+
+    #[error("Failed to parse the response of signer command '{0}'")]
+    ParseSignerResponseFailed(String, #[source] serde_json::Error),
+
+    #[error("Signer command '{0}' reported an error: {1}")]
+    SignerReportedError(String, String),
+
+    #[error("The response of signer command '{0}' is missing the field '{1}'")]
+    MissingSignerResponseField(String, &'static str),
+
+    #[error("The field '{1}' in the response of signer command '{0}' is not valid hex")]
+    DecodeSignerResponseFieldFailed(String, &'static str, #[source] hex::FromHexError),
+}
+
 #[derive(Error, Debug)]
 pub enum UseIdentityByNameError {
     #[error("Identity must exist")]
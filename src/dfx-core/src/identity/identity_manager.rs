@@ -54,6 +54,7 @@ use crate::foundation::get_user_home;
 use crate::fs::composite::ensure_parent_dir_exists;
 use crate::identity::identity_file_locations::{IdentityFileLocations, IDENTITY_PEM};
 use crate::identity::identity_manager::IdentityStorageModeError::UnknownStorageMode;
+use crate::identity::signer::resolve_signer_command;
 use crate::identity::{
     pem_safekeeping, pem_utils, Identity as DfxIdentity, ANONYMOUS_IDENTITY_NAME, IDENTITY_JSON,
     TEMP_IDENTITY_PREFIX,
@@ -95,6 +96,9 @@ pub struct IdentityConfiguration {
 
     /// If the identity's PEM file is stored in the system's keyring, this field contains the identity's name WITHOUT the common prefix.
     pub keyring_identity_suffix: Option<String>,
+
+    /// If the identity signs with an external signer command, this contains how to run it.
+    pub signer: Option<SignerIdentityConfiguration>,
 }
 
 /// The information necessary to de- and encrypt (except the password) the identity's .pem file
@@ -142,6 +146,12 @@ pub struct HardwareIdentityConfiguration {
     pub key_id: String,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SignerIdentityConfiguration {
+    /// The signer command, which speaks the protocol described in [crate::identity::signer].
+    pub command: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Copy, PartialEq, Eq)]
 pub enum IdentityStorageMode {
     Keyring,
@@ -189,6 +199,9 @@ pub enum IdentityCreationParameters {
     Hardware {
         hsm: HardwareIdentityConfiguration,
     },
+    Signer {
+        signer: SignerIdentityConfiguration,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -416,6 +429,16 @@ impl IdentityManager {
                 crate::fs::create_dir_all(&temp_identity_dir)
                     .map_err(CreateTemporaryIdentityDirectoryFailed)?;
             }
+            IdentityCreationParameters::Signer { signer } => {
+                let command = resolve_signer_command(&signer.command)
+                    .map_err(CreateNewIdentityError::ResolveSignerCommandFailed)?;
+                identity_config = IdentityConfiguration {
+                    signer: Some(SignerIdentityConfiguration { command }),
+                    ..Default::default()
+                };
+                crate::fs::create_dir_all(&temp_identity_dir)
+                    .map_err(CreateTemporaryIdentityDirectoryFailed)?;
+            }
             IdentityCreationParameters::SeedPhrase { mnemonic, mode } => {
                 identity_config = create_identity_config(log, mode, name, None)
                     .map_err(CreateNewIdentityError::CreateIdentityConfigFailed)?;
@@ -488,6 +511,11 @@ impl IdentityManager {
         let config = self
             .get_identity_config_or_default(name)
             .map_err(ExportIdentityError::GetIdentityConfigFailed)?;
+        if config.signer.is_some() {
+            return Err(ExportIdentityError::CannotExportSignerIdentity(
+                name.to_string(),
+            ));
+        }
         let (pem_content, _) = pem_safekeeping::load_pem(log, &self.file_locations, name, &config)
             .map_err(ExportIdentityError::LoadPemFailed)?;
 
@@ -687,6 +715,7 @@ impl IdentityManager {
                         encryption: None,
                         keyring_identity_suffix: None,
                         hsm: None,
+                        signer: None,
                     } = config
                     {
                         let sender = self.load_identity(name, log).ok()?.sender().ok()?;
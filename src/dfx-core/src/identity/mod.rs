@@ -18,13 +18,16 @@ use crate::error::{
         MapWalletsToRenamedIdentityError::RenameWalletGlobalConfigKeyFailed,
         NewHardwareIdentityError,
         NewHardwareIdentityError::InstantiateHardwareIdentityFailed,
-        NewIdentityError, RenameWalletGlobalConfigKeyError,
+        NewIdentityError,
+        NewIdentityError::NewSignerIdentityFailed,
+        RenameWalletGlobalConfigKeyError,
         RenameWalletGlobalConfigKeyError::RenameWalletFailed,
     },
     wallet_config::{WalletConfigError, WalletConfigError::LoadWalletConfigFailed},
 };
 use crate::fs::composite::ensure_parent_dir_exists;
 use crate::identity::identity_file_locations::IdentityFileLocations;
+use crate::identity::signer::SignerIdentity;
 use crate::identity::wallet::wallet_canister_id;
 use crate::json::{load_json_file, save_json_file};
 use candid::Principal;
@@ -36,7 +39,7 @@ use ic_agent::Signature;
 use ic_identity_hsm::HardwareIdentity;
 pub use identity_manager::{
     HardwareIdentityConfiguration, IdentityConfiguration, IdentityCreationParameters,
-    IdentityManager,
+    IdentityManager, SignerIdentityConfiguration,
 };
 use serde::{Deserialize, Serialize};
 use slog::{info, Logger};
@@ -48,6 +51,7 @@ pub mod identity_manager;
 pub mod keyring_mock;
 pub mod pem_safekeeping;
 pub mod pem_utils;
+pub mod signer;
 pub mod wallet;
 
 pub const ANONYMOUS_IDENTITY_NAME: &str = "anonymous";
@@ -148,6 +152,18 @@ impl Identity {
         })
     }
 
+    fn signer(name: &str, signer: SignerIdentityConfiguration) -> Result<Self, NewIdentityError> {
+        let inner = Box::new(
+            SignerIdentity::new(&signer).map_err(|e| NewSignerIdentityFailed(name.into(), e))?,
+        );
+        Ok(Self {
+            name: name.to_string(),
+            inner,
+            insecure: false,
+            identity_type: IdentityType::Signer,
+        })
+    }
+
     pub(crate) fn new(
         name: &str,
         config: IdentityConfiguration,
@@ -156,6 +172,8 @@ impl Identity {
     ) -> Result<Self, NewIdentityError> {
         if let Some(hsm) = config.hsm {
             Identity::hardware(name, hsm).map_err(NewIdentityError::NewHardwareIdentityFailed)
+        } else if let Some(signer) = config.signer {
+            Identity::signer(name, signer)
         } else {
             let (pem_content, identity_type) =
                 pem_safekeeping::load_pem(log, locations, name, &config)
@@ -320,6 +338,7 @@ pub enum IdentityType {
     Plaintext,
     EncryptedLocal,
     Hsm,
+    Signer,
     Anonymous,
 }
 
@@ -12,7 +12,9 @@ use crate::error::{
         LoadPemFromFileError,
         LoadPemFromFileError::DecryptPemFileFailed,
         SavePemError,
-        SavePemError::{CannotSavePemContentForHsm, WritePemToKeyringFailed},
+        SavePemError::{
+            CannotSavePemContentForHsm, CannotSavePemContentForSigner, WritePemToKeyringFailed,
+        },
         WritePemToFileError,
         WritePemToFileError::{EncryptPemFileFailed, WritePemContentFailed},
     },
@@ -64,6 +66,8 @@ pub(crate) fn save_pem(
     );
     if identity_config.hsm.is_some() {
         Err(CannotSavePemContentForHsm())
+    } else if identity_config.signer.is_some() {
+        Err(CannotSavePemContentForSigner())
     } else if let Some(keyring_identity) = &identity_config.keyring_identity_suffix {
         debug!(log, "Saving keyring identity.");
         keyring_mock::write_pem_to_keyring(keyring_identity, pem_content)
@@ -0,0 +1,320 @@
+//! An identity that delegates signing to an external process.
+//!
+//! The signer command is split into a program and its arguments like a shell would do it.
+//! A relative program path is made absolute when the identity is created, so that the identity
+//! works from any directory. A program name without a path is looked up in `PATH`.
+//!
+//! The signer command is run once per request. dfx writes a single JSON object to its stdin,
+//! closes stdin, and reads a single JSON object from its stdout. Anything the command writes
+//! to stderr is shown to the user, so it can be used for prompts and approval messages.
+//!
+//! Requests:
+//! - `{"version": 1, "method": "get_public_key"}` asks for the DER-encoded public key,
+//!   which the command returns hex-encoded as `{"public_key": "302a30..."}`.
+//! - `{"version": 1, "method": "sign", "content": "0a6963..."}` asks for a signature of the
+//!   hex-encoded bytes, which the command returns hex-encoded as `{"signature": "9586..."}`.
+//!
+//! To reject a request, the command returns `{"error": "<message>"}` or exits with a non-zero status.
+use crate::error::identity::SignerIdentityError;
+use crate::error::identity::SignerIdentityError::{
+    CommunicateWithSignerCommandFailed, DecodeSignerResponseFieldFailed, EmptySignerCommand,
+    FindSignerProgramFailed, MissingSignerResponseField, ParseSignerCommandFailed,
+    ParseSignerResponseFailed, SignerCommandFailed, SignerReportedError, SpawnSignerCommandFailed,
+};
+use crate::identity::identity_manager::SignerIdentityConfiguration;
+use candid::Principal;
+use ic_agent::agent::EnvelopeContent;
+use ic_agent::identity::Delegation;
+use ic_agent::{Identity, Signature};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The version of the signer protocol spoken by this version of dfx.
+const SIGNER_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SignerRequest<'a> {
+    version: u32,
+    #[serde(flatten)]
+    method: SignerMethod<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum SignerMethod<'a> {
+    GetPublicKey,
+    Sign {
+        #[serde(serialize_with = "hex::serde::serialize")]
+        content: &'a [u8],
+    },
+}
+
+#[derive(Deserialize)]
+struct SignerResponse {
+    public_key: Option<String>,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+pub struct SignerIdentity {
+    command: String,
+    public_key: Vec<u8>,
+}
+
+impl SignerIdentity {
+    /// Asks the signer command for its public key.
+    pub fn new(config: &SignerIdentityConfiguration) -> Result<Self, SignerIdentityError> {
+        let command = config.command.clone();
+        let response = call_signer(&command, SignerMethod::GetPublicKey)?;
+        let public_key = decode_field(&command, "public_key", response.public_key)?;
+        Ok(Self {
+            command,
+            public_key,
+        })
+    }
+}
+
+impl Identity for SignerIdentity {
+    fn sender(&self) -> Result<Principal, String> {
+        Ok(Principal::self_authenticating(&self.public_key))
+    }
+
+    fn public_key(&self) -> Option<Vec<u8>> {
+        Some(self.public_key.clone())
+    }
+
+    fn sign(&self, content: &EnvelopeContent) -> Result<Signature, String> {
+        self.sign_arbitrary(&content.to_request_id().signable())
+    }
+
+    fn sign_delegation(&self, content: &Delegation) -> Result<Signature, String> {
+        self.sign_arbitrary(&content.signable())
+    }
+
+    fn sign_arbitrary(&self, content: &[u8]) -> Result<Signature, String> {
+        let signature = call_signer(&self.command, SignerMethod::Sign { content })
+            .and_then(|response| decode_field(&self.command, "signature", response.signature))
+            .map_err(|e| match std::error::Error::source(&e) {
+                Some(source) => format!("{e}: {source}"),
+                None => e.to_string(),
+            })?;
+        Ok(Signature {
+            public_key: Some(self.public_key.clone()),
+            signature: Some(signature),
+            delegations: None,
+        })
+    }
+}
+
+/// Splits the signer command into the program and its arguments.
+fn split_signer_command(command: &str) -> Result<Vec<String>, SignerIdentityError> {
+    let words = shell_words::split(command)
+        .map_err(|e| ParseSignerCommandFailed(command.to_string(), e))?;
+    if words.is_empty() {
+        return Err(EmptySignerCommand());
+    }
+    Ok(words)
+}
+
+/// Makes a relative program path in the signer command absolute, so that it does not depend on the current directory.
+pub fn resolve_signer_command(command: &str) -> Result<String, SignerIdentityError> {
+    let mut words = split_signer_command(command)?;
+    let program = Path::new(&words[0]);
+    if program.is_relative() && program.components().count() > 1 {
+        let program = crate::fs::canonicalize(program)
+            .map_err(|e| FindSignerProgramFailed(words[0].clone(), e))?;
+        words[0] = program.to_string_lossy().into_owned();
+    }
+    Ok(shell_words::join(words))
+}
+
+fn call_signer(command: &str, method: SignerMethod) -> Result<SignerResponse, SignerIdentityError> {
+    let request = serde_json::to_vec(&SignerRequest {
+        version: SIGNER_PROTOCOL_VERSION,
+        method,
+    })
+    .expect("failed to serialize signer request");
+
+    let words = split_signer_command(command)?;
+    let mut child = Command::new(&words[0])
+        .args(&words[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| SpawnSignerCommandFailed(command.to_string(), e))?;
+    // stdin is dropped after writing, so that the command sees the end of the request.
+    child
+        .stdin
+        .take()
+        .expect("signer stdin is piped")
+        .write_all(&request)
+        .map_err(|e| CommunicateWithSignerCommandFailed(command.to_string(), e))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| CommunicateWithSignerCommandFailed(command.to_string(), e))?;
+    if !output.status.success() {
+        return Err(SignerCommandFailed(command.to_string(), output.status));
+    }
+
+    let response: SignerResponse = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ParseSignerResponseFailed(command.to_string(), e))?;
+    if let Some(error) = response.error {
+        return Err(SignerReportedError(command.to_string(), error));
+    }
+    Ok(response)
+}
+
+fn decode_field(
+    command: &str,
+    field: &'static str,
+    value: Option<String>,
+) -> Result<Vec<u8>, SignerIdentityError> {
+    let value = value.ok_or_else(|| MissingSignerResponseField(command.to_string(), field))?;
+    hex::decode(value).map_err(|e| DecodeSignerResponseFieldFailed(command.to_string(), field, e))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A signer command that saves the request to `request_path` and responds with `response`.
+    fn signer_command(request_path: &Path, response: &str) -> String {
+        shell_words::join([
+            "sh",
+            "-c",
+            r#"cat > "$0"; printf '%s' "$1""#,
+            &request_path.to_string_lossy(),
+            response,
+        ])
+    }
+
+    fn new_identity(command: &str) -> Result<SignerIdentity, SignerIdentityError> {
+        SignerIdentity::new(&SignerIdentityConfiguration {
+            command: command.to_string(),
+        })
+    }
+
+    fn new_identity_error(command: &str) -> SignerIdentityError {
+        match new_identity(command) {
+            Ok(_) => panic!("expected signer command '{command}' to fail"),
+            Err(e) => e,
+        }
+    }
+
+    fn read_request(request_path: &Path) -> serde_json::Value {
+        serde_json::from_slice(&std::fs::read(request_path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn get_public_key_and_sign() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("request.json");
+        let command = signer_command(
+            &request_path,
+            r#"{"public_key": "0102", "signature": "0a0b0c"}"#,
+        );
+
+        let identity = new_identity(&command).unwrap();
+        assert_eq!(
+            read_request(&request_path),
+            serde_json::json!({"version": 1, "method": "get_public_key"})
+        );
+        assert_eq!(identity.public_key(), Some(vec![1, 2]));
+        assert_eq!(
+            identity.sender().unwrap(),
+            Principal::self_authenticating([1u8, 2])
+        );
+
+        let signature = identity.sign_arbitrary(&[0xc0, 0xff, 0xee]).unwrap();
+        assert_eq!(
+            read_request(&request_path),
+            serde_json::json!({"version": 1, "method": "sign", "content": "c0ffee"})
+        );
+        assert_eq!(signature.public_key, Some(vec![1, 2]));
+        assert_eq!(signature.signature, Some(vec![0x0a, 0x0b, 0x0c]));
+        assert!(signature.delegations.is_none());
+    }
+
+    #[test]
+    fn signer_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_path = dir.path().join("request.json");
+
+        assert!(matches!(new_identity_error(""), EmptySignerCommand()));
+        assert!(matches!(
+            new_identity_error("sh -c 'unterminated"),
+            ParseSignerCommandFailed(..)
+        ));
+        assert!(matches!(
+            new_identity_error("/nonexistent/signer --key foo"),
+            SpawnSignerCommandFailed(..)
+        ));
+        assert!(matches!(
+            new_identity_error("sh -c 'cat > /dev/null; exit 3'"),
+            SignerCommandFailed(_, status) if status.code() == Some(3)
+        ));
+        assert!(matches!(
+            new_identity_error(&signer_command(&request_path, "not json")),
+            ParseSignerResponseFailed(..)
+        ));
+        assert!(matches!(
+            new_identity_error(&signer_command(&request_path, r#"{"error": "denied"}"#)),
+            SignerReportedError(_, message) if message == "denied"
+        ));
+        assert!(matches!(
+            new_identity_error(&signer_command(&request_path, "{}")),
+            MissingSignerResponseField(_, "public_key")
+        ));
+        assert!(matches!(
+            new_identity_error(&signer_command(&request_path, r#"{"public_key": "xyz"}"#)),
+            DecodeSignerResponseFieldFailed(_, "public_key", _)
+        ));
+
+        let identity =
+            new_identity(&signer_command(&request_path, r#"{"public_key": "0102"}"#)).unwrap();
+        assert_eq!(
+            identity.sign_arbitrary(&[1]).unwrap_err(),
+            format!(
+                "The response of signer command '{}' is missing the field 'signature'",
+                identity.command
+            )
+        );
+    }
+
+    #[test]
+    fn resolve_relative_signer_programs() {
+        // Tests run in the directory of the crate.
+        let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        assert_eq!(
+            resolve_signer_command("./Cargo.toml --key 'my key'").unwrap(),
+            shell_words::join([
+                crate::fs::canonicalize(&manifest)
+                    .unwrap()
+                    .to_string_lossy()
+                    .as_ref(),
+                "--key",
+                "my key",
+            ])
+        );
+        assert_eq!(
+            resolve_signer_command("my-signer --key foo").unwrap(),
+            "my-signer --key foo"
+        );
+        assert_eq!(
+            resolve_signer_command("/usr/bin/my-signer").unwrap(),
+            "/usr/bin/my-signer"
+        );
+        assert!(matches!(
+            resolve_signer_command("./no-such-signer"),
+            Err(FindSignerProgramFailed(program, _)) if program == "./no-such-signer"
+        ));
+        assert!(matches!(
+            resolve_signer_command("  "),
+            Err(EmptySignerCommand())
+        ));
+    }
+}
@@ -483,10 +483,12 @@ mod tests {
     use super::*;
     use crate::config::model::bitcoin_adapter::BitcoinAdapterLogLevel;
     use crate::config::model::canister_http_adapter::HttpAdapterLogLevel;
-    use crate::config::model::dfinity::ReplicaSubnetType::{System, VerifiedApplication};
+    use crate::config::model::dfinity::ReplicaSubnetType::{
+        Application, System, VerifiedApplication,
+    };
     use crate::config::model::dfinity::{
         to_socket_addr, ConfigDefaultsBitcoin, ConfigDefaultsCanisterHttp, ConfigDefaultsReplica,
-        ReplicaLogLevel,
+        ConfigReplicaSubnet, ReplicaLogLevel,
     };
     use std::fs;
     use std::net::SocketAddr;
@@ -935,6 +937,7 @@ mod tests {
             &ConfigDefaultsReplica {
                 subnet_type: Some(VerifiedApplication),
                 port: Some(17001),
+                subnets: None,
                 log_level: Some(ReplicaLogLevel::Trace)
             }
         );
@@ -982,11 +985,64 @@ mod tests {
             &ConfigDefaultsReplica {
                 subnet_type: Some(System),
                 port: None,
+                subnets: None,
                 log_level: None
             }
         );
     }
 
+    #[test]
+    fn replica_config_with_named_subnets() {
+        let config = Config::from_str(
+            r#"{
+              "networks": {
+                "local": {
+                  "bind": "127.0.0.1:8000",
+                  "replica": {
+                    "subnets": [
+                      { "name": "app1" },
+                      { "name": "app2", "subnet_type": "application" },
+                      { "name": "sys", "subnet_type": "system" }
+                    ]
+                  }
+                }
+              }
+        }"#,
+        )
+        .unwrap();
+
+        let network_descriptor = create_network_descriptor(
+            Some(Arc::new(config)),
+            Arc::new(NetworksConfig::new().unwrap()),
+            None,
+            None,
+            LocalBindDetermination::AsConfigured,
+        )
+        .unwrap();
+        let replica_config = &network_descriptor
+            .local_server_descriptor()
+            .unwrap()
+            .replica;
+
+        assert_eq!(
+            replica_config.subnets,
+            Some(vec![
+                ConfigReplicaSubnet {
+                    name: "app1".to_string(),
+                    subnet_type: Application,
+                },
+                ConfigReplicaSubnet {
+                    name: "app2".to_string(),
+                    subnet_type: Application,
+                },
+                ConfigReplicaSubnet {
+                    name: "sys".to_string(),
+                    subnet_type: System,
+                },
+            ])
+        );
+    }
+
     #[test]
     fn canister_http_config_on_local_network() {
         let config = Config::from_str(
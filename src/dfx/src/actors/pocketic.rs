@@ -21,11 +21,15 @@ use anyhow::{anyhow, bail};
 use candid::Principal;
 use crossbeam::channel::{unbounded, Receiver, Sender};
 #[cfg(unix)]
-use dfx_core::config::model::replica_config::CachedConfig;
+use dfx_core::config::model::dfinity::ConfigReplicaSubnet;
 use dfx_core::config::model::replica_config::ReplicaConfig;
 #[cfg(unix)]
+use dfx_core::config::model::replica_config::{CachedConfig, LocalSubnet};
+#[cfg(unix)]
 use dfx_core::json::save_json_file;
 use slog::{debug, error, warn, Logger};
+#[cfg(unix)]
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::ops::ControlFlow::{self, *};
 use std::path::{Path, PathBuf};
@@ -364,11 +368,22 @@ async fn initialize_pocketic(
         verified_application: vec![],
         application: vec![],
     };
-    match replica_config.subnet_type {
-        ReplicaSubnetType::Application => subnet_config_set.application.push(<_>::default()),
-        ReplicaSubnetType::System => subnet_config_set.system.push(<_>::default()),
-        ReplicaSubnetType::VerifiedApplication => {
-            subnet_config_set.verified_application.push(<_>::default())
+    let subnet_types = if replica_config.subnets.is_empty() {
+        vec![replica_config.subnet_type]
+    } else {
+        replica_config
+            .subnets
+            .iter()
+            .map(|subnet| subnet.subnet_type)
+            .collect()
+    };
+    for subnet_type in subnet_types {
+        match subnet_type {
+            ReplicaSubnetType::Application => subnet_config_set.application.push(<_>::default()),
+            ReplicaSubnetType::System => subnet_config_set.system.push(<_>::default()),
+            ReplicaSubnetType::VerifiedApplication => {
+                subnet_config_set.verified_application.push(<_>::default())
+            }
         }
     }
     let resp = init_client
@@ -399,7 +414,8 @@ async fn initialize_pocketic(
                 replica_config,
                 replica_rev().into(),
                 Some(default_effective_canister_id),
-            );
+            )
+            .with_subnets(name_subnets(&replica_config.subnets, &topology)?);
             save_json_file(effective_config_path, &effective_config)?;
             instance_id
         }
@@ -442,6 +458,57 @@ async fn initialize_pocketic(
     Ok(instance)
 }
 
+/// Matches the named subnets of the replica configuration with the subnets of the created instance.
+///
+/// Subnets are matched by their kind in the topology. All subnets of one type are created
+/// with the same configuration, so they are interchangeable: the names of each type are
+/// assigned to its subnets in the order of their subnet ids.
+#[cfg(unix)]
+fn name_subnets(
+    subnets: &[ConfigReplicaSubnet],
+    topology: &pocket_ic::common::rest::Topology,
+) -> DfxResult<BTreeMap<String, LocalSubnet>> {
+    use dfx_core::config::model::dfinity::ReplicaSubnetType;
+    use pocket_ic::common::rest::SubnetKind;
+
+    let mut named = BTreeMap::new();
+    for (subnet_type, kind) in [
+        (ReplicaSubnetType::Application, SubnetKind::Application),
+        (ReplicaSubnetType::System, SubnetKind::System),
+        (
+            ReplicaSubnetType::VerifiedApplication,
+            SubnetKind::VerifiedApplication,
+        ),
+    ] {
+        let mut created: Vec<(Principal, Principal)> = topology
+            .subnet_configs
+            .iter()
+            .filter(|(_, config)| config.subnet_kind == kind)
+            .filter_map(|(subnet_id, config)| {
+                let range = config.canister_ranges.first()?;
+                Some((*subnet_id, range.start.clone().into()))
+            })
+            .collect();
+        created.sort();
+        let configured = subnets
+            .iter()
+            .filter(|subnet| subnet.subnet_type == subnet_type);
+        if configured.clone().count() > created.len() {
+            bail!("PocketIC created fewer {subnet_type:?} subnets than configured.");
+        }
+        for (subnet, (subnet_id, effective_canister_id)) in configured.zip(created) {
+            named.insert(
+                subnet.name.clone(),
+                LocalSubnet {
+                    subnet_id,
+                    effective_canister_id,
+                },
+            );
+        }
+    }
+    Ok(named)
+}
+
 #[cfg(not(unix))]
 fn initialize_pocketic(
     _: u16,
@@ -6,6 +6,8 @@ use crate::lib::error::DfxResult;
 use crate::lib::models::canister::CanisterPool;
 use crate::lib::network::network_opt::NetworkOpt;
 use crate::lib::operations::canister::add_canisters_with_ids;
+use crate::util::clap::build_cache_opt::BuildCacheOpt;
+use crate::util::clap::parsers::jobs_parser;
 use clap::Parser;
 use std::path::PathBuf;
 use tokio::runtime::Runtime;
@@ -31,6 +33,13 @@ pub struct CanisterBuildOpts {
 
     #[command(flatten)]
     network: NetworkOpt,
+
+    /// Builds up to this many canisters at the same time. Canisters are built after the canisters they depend on.
+    #[arg(long, short, default_value = "1", value_parser = jobs_parser)]
+    jobs: usize,
+
+    #[command(flatten)]
+    cache: BuildCacheOpt,
 }
 
 pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
@@ -82,10 +91,19 @@ pub fn exec(env: &dyn Environment, opts: CanisterBuildOpts) -> DfxResult {
         }
     }
 
+    // Canister IDs are random when checking, so such builds are never cached.
+    let build_cache = if build_mode_check {
+        None
+    } else {
+        opts.cache.into_build_cache()?
+    };
+
     let runtime = Runtime::new().expect("Unable to create a runtime");
     let build_config = BuildConfig::from_config(&config)?
         .with_canisters_to_build(canisters_to_build)
-        .with_env_file(env_file);
+        .with_env_file(env_file)
+        .with_jobs(opts.jobs)
+        .with_cache(build_cache);
     runtime.block_on(canister_pool.build_or_fail(&env, logger, &build_config))?;
 
     slog::info!(logger, "Finished building canisters.");
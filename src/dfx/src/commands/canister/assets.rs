@@ -0,0 +1,47 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use anyhow::Context;
+use candid::Principal;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Manages the assets of an asset canister.
+#[derive(Parser)]
+pub struct AssetsOpts {
+    #[command(subcommand)]
+    subcmd: AssetsSubcommand,
+}
+
+#[derive(Subcommand)]
+enum AssetsSubcommand {
+    /// Downloads the assets of an asset canister to a local directory,
+    /// with an .ic-assets.json that reproduces their properties and the routes of the canister.
+    Download {
+        /// The asset canister to download the assets from.
+        canister: String,
+        /// The directory to write the assets to. It must be empty or not exist yet.
+        #[arg(long)]
+        dir: PathBuf,
+    },
+}
+
+pub async fn exec(env: &dyn Environment, opts: AssetsOpts) -> DfxResult {
+    fetch_root_key_if_needed(env).await?;
+    match opts.subcmd {
+        AssetsSubcommand::Download { canister, dir } => download(env, canister, dir).await,
+    }
+}
+
+async fn download(env: &dyn Environment, canister: String, dir: PathBuf) -> DfxResult {
+    let canister_id =
+        Principal::from_text(&canister).or_else(|_| env.get_canister_id_store()?.get(&canister))?;
+    let canister = ic_utils::Canister::builder()
+        .with_agent(env.get_agent())
+        .with_canister_id(canister_id)
+        .build()
+        .context("Failed to build asset canister caller.")?;
+    ic_asset::download(&canister, &dir, env.get_logger())
+        .await
+        .with_context(|| format!("Failed to download assets of canister {canister_id}."))
+}
@@ -0,0 +1,589 @@
+use crate::commands::canister::call::{
+    get_call_effective_canister_id, get_method_type, is_query_call, query_call, update_call,
+};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::canister::get_canister_id_and_candid_path;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::util::{arguments_from_file, blob_from_arguments, print_idl_blob};
+use anyhow::{anyhow, bail, Context};
+use candid::types::value::{IDLField, IDLValue, VariantValue};
+use candid::types::{Function, Label, TypeEnv};
+use candid::{IDLArgs, Principal};
+use clap::Parser;
+use dfx_core::identity::CallSender;
+use slog::info;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Makes the calls of a script file in order, with a single agent.
+///
+/// Each statement of the script is one of:
+///   <canister>.<method>(<arguments>)          calls a method and prints the result
+///   let <name> = <canister>.<method>(<arguments>)   calls a method and saves the result
+///   assert <call or $name> == <value>         fails unless the result equals the Candid value
+///
+/// `$name` in arguments, values and canisters is replaced by a saved result.
+/// `$name.field` selects a record field, a variant case, a vector element or the value in an option.
+/// Lines starting with `#` or `//` are comments. A statement continues on the next line while it has unclosed parentheses.
+#[derive(Parser)]
+pub struct CanisterBatchOpts {
+    /// Path of the script file to run. Use "-" to read it from stdin.
+    script: PathBuf,
+
+    /// Specifies the format for displaying the results of calls that are not saved.
+    #[arg(long, value_parser = ["idl", "raw", "pp", "json"])]
+    output: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Call {
+    canister: String,
+    method: String,
+    arguments: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Expression {
+    Call(Call),
+    Variable(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Statement {
+    Call {
+        variable: Option<String>,
+        call: Call,
+    },
+    Assert {
+        actual: Expression,
+        expected: String,
+    },
+}
+
+type MethodType = Option<(TypeEnv, Function)>;
+
+pub async fn exec(
+    env: &dyn Environment,
+    opts: CanisterBatchOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let script = arguments_from_file(&opts.script)?;
+    let statements = parse_script(&script)?;
+
+    fetch_root_key_if_needed(env).await?;
+
+    let mut runner = Runner {
+        env,
+        call_sender,
+        variables: BTreeMap::new(),
+        method_types: HashMap::new(),
+    };
+    let mut calls = 0;
+    let mut assertions = 0;
+    for (line, statement) in statements {
+        match statement {
+            Statement::Call { variable, call } => {
+                let (result, blob, method_type) = runner
+                    .call(&call)
+                    .await
+                    .with_context(|| format!("Line {line}: failed to call {}.", call))?;
+                calls += 1;
+                match variable {
+                    Some(variable) => {
+                        runner.variables.insert(variable, result);
+                    }
+                    None => print_idl_blob(&blob, opts.output.as_deref(), &method_type)?,
+                }
+            }
+            Statement::Assert { actual, expected } => {
+                let actual = match &actual {
+                    Expression::Call(call) => {
+                        calls += 1;
+                        runner
+                            .call(call)
+                            .await
+                            .with_context(|| format!("Line {line}: failed to call {}.", call))?
+                            .0
+                    }
+                    Expression::Variable(reference) => {
+                        IDLArgs::new(&[runner.resolve(reference).with_context(|| {
+                            format!("Line {line}: failed to resolve {reference}.")
+                        })?])
+                    }
+                };
+                let expected = runner
+                    .substitute(&expected)
+                    .with_context(|| format!("Line {line}: invalid expected value."))?;
+                if !results_equal(&actual, &expected)
+                    .with_context(|| format!("Line {line}: invalid expected value."))?
+                {
+                    bail!("Line {line}: assertion failed.\n  Expected: {expected}\n  Actual: {actual}");
+                }
+                assertions += 1;
+            }
+        }
+    }
+    info!(
+        env.get_logger(),
+        "Made {} calls, {} assertions passed.", calls, assertions
+    );
+    Ok(())
+}
+
+struct Runner<'a> {
+    env: &'a dyn Environment,
+    call_sender: &'a CallSender,
+    variables: BTreeMap<String, IDLArgs>,
+    method_types: HashMap<(Principal, String), MethodType>,
+}
+
+impl Runner<'_> {
+    async fn call(&mut self, call: &Call) -> DfxResult<(IDLArgs, Vec<u8>, MethodType)> {
+        let env = self.env;
+        let method_name = call.method.as_str();
+        let (canister_id, maybe_local_candid_path) = if call.canister.starts_with('$') {
+            match self.resolve(&call.canister)? {
+                IDLValue::Principal(principal) => (principal, None),
+                other => bail!("{} is not a principal: {other}", call.canister),
+            }
+        } else {
+            get_canister_id_and_candid_path(env, &call.canister)?
+        };
+
+        let key = (canister_id, method_name.to_string());
+        if !self.method_types.contains_key(&key) {
+            let method_type = get_method_type(
+                env,
+                canister_id,
+                None,
+                maybe_local_candid_path.as_deref(),
+                method_name,
+            )
+            .await;
+            self.method_types.insert(key.clone(), method_type);
+        }
+        let method_type = self.method_types[&key].clone();
+
+        let arguments = self.substitute(&call.arguments)?;
+        let arg_value = blob_from_arguments(
+            Some(env),
+            Some(&arguments),
+            None,
+            None,
+            &method_type,
+            false,
+            false,
+        )?;
+        let effective_canister_id =
+            get_call_effective_canister_id(self.call_sender, canister_id, method_name, &arg_value)?;
+        let blob = if is_query_call(&method_type, method_name, false, false)? {
+            query_call(
+                env,
+                self.call_sender,
+                canister_id,
+                effective_canister_id,
+                method_name,
+                arg_value,
+                0,
+            )
+            .await?
+        } else {
+            update_call(
+                env,
+                self.call_sender,
+                canister_id,
+                effective_canister_id,
+                method_name,
+                arg_value,
+                0,
+            )
+            .await?
+        };
+        let result = match &method_type {
+            Some((type_env, func)) => IDLArgs::from_bytes_with_types(&blob, type_env, &func.rets),
+            None => IDLArgs::from_bytes(&blob),
+        }
+        .context("Failed to decode the result.")?;
+        Ok((result, blob, method_type))
+    }
+
+    /// Resolves a `$name.field...` reference to a saved value.
+    fn resolve(&self, reference: &str) -> DfxResult<IDLValue> {
+        resolve(&self.variables, reference)
+    }
+
+    /// Replaces the `$name.field...` references outside of text literals with their values.
+    fn substitute(&self, text: &str) -> DfxResult<String> {
+        let mut result = String::new();
+        let mut chars = text.char_indices().peekable();
+        let mut in_text = false;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => in_text = !in_text,
+                '\\' if in_text => {
+                    result.push(c);
+                    if let Some((_, escaped)) = chars.next() {
+                        result.push(escaped);
+                    }
+                    continue;
+                }
+                '$' if !in_text => {
+                    let end = reference_end(text, i);
+                    if end > i + 1 {
+                        let value = self.resolve(&text[i..end])?;
+                        result.push_str(&value.to_string());
+                        while chars.peek().is_some_and(|(j, _)| *j < end) {
+                            chars.next();
+                        }
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            result.push(c);
+        }
+        Ok(result)
+    }
+}
+
+impl std::fmt::Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.canister, self.method)
+    }
+}
+
+fn resolve(variables: &BTreeMap<String, IDLArgs>, reference: &str) -> DfxResult<IDLValue> {
+    let mut path = reference.trim_start_matches('$').split('.');
+    let name = path.next().unwrap_or_default();
+    let args = variables
+        .get(name)
+        .ok_or_else(|| anyhow!("${name} is not defined."))?;
+    let mut value = match args.args.as_slice() {
+        [value] => value,
+        values => {
+            let index = path
+                .next()
+                .and_then(|index| index.parse::<usize>().ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "${name} has {} values. Select one with ${name}.<index>.",
+                        values.len()
+                    )
+                })?;
+            values
+                .get(index)
+                .ok_or_else(|| anyhow!("${name} has no value {index}."))?
+        }
+    };
+    for field in path {
+        value = get_field(value, field)
+            .ok_or_else(|| anyhow!("{reference}: there is no {field} in {value}"))?;
+    }
+    Ok(value.clone())
+}
+
+fn get_field<'a>(value: &'a IDLValue, field: &str) -> Option<&'a IDLValue> {
+    let matches = |label: &Label| match label {
+        Label::Named(name) => name == field,
+        Label::Id(id) | Label::Unnamed(id) => field.parse::<u32>() == Ok(*id),
+    };
+    match value {
+        IDLValue::Opt(value) => get_field(value, field),
+        IDLValue::Record(fields) => fields
+            .iter()
+            .find(|IDLField { id, .. }| matches(id))
+            .map(|IDLField { val, .. }| val),
+        IDLValue::Variant(VariantValue(variant, _)) => matches(&variant.id).then_some(&variant.val),
+        IDLValue::Vec(values) => field.parse::<usize>().ok().and_then(|i| values.get(i)),
+        _ => None,
+    }
+}
+
+/// Compares a result with the expected Candid value, or values if the result has several.
+fn results_equal(actual: &IDLArgs, expected: &str) -> DfxResult<bool> {
+    let expected = match actual.args.len() {
+        1 => vec![candid_parser::parse_idl_value(expected)?],
+        _ => candid_parser::parse_idl_args(expected)?.args,
+    };
+    if expected.len() != actual.args.len() {
+        return Ok(false);
+    }
+    Ok(actual.args.iter().zip(expected).all(|(actual, expected)| {
+        // Number literals are only typed once they are annotated with the type of the actual value.
+        let expected = expected
+            .annotate_type(true, &TypeEnv::new(), &actual.value_ty())
+            .unwrap_or(expected);
+        *actual == expected
+    }))
+}
+
+/// The end of the `$name.field...` reference starting at `start`.
+fn reference_end(text: &str, start: usize) -> usize {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let bytes = text.as_bytes();
+    let mut end = start + 1;
+    while end < bytes.len() && is_word(bytes[end] as char) {
+        end += 1;
+    }
+    while end + 1 < bytes.len() && bytes[end] == b'.' && is_word(bytes[end + 1] as char) {
+        end += 1;
+        while end < bytes.len() && is_word(bytes[end] as char) {
+            end += 1;
+        }
+    }
+    end
+}
+
+/// The change of the parenthesis depth over a line, ignoring text literals.
+fn depth_change(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut in_text = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_text => escaped = true,
+            '"' => in_text = !in_text,
+            '(' if !in_text => depth += 1,
+            ')' if !in_text => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn parse_script(script: &str) -> DfxResult<Vec<(usize, Statement)>> {
+    let mut statements = vec![];
+    let mut current: Option<(usize, String, i32)> = None;
+    for (i, line) in script.lines().enumerate() {
+        let trimmed = line.trim();
+        if current.is_none()
+            && (trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//"))
+        {
+            continue;
+        }
+        let (start, mut text, depth) = current.take().unwrap_or((i + 1, String::new(), 0));
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(trimmed);
+        let depth = depth + depth_change(trimmed);
+        if depth > 0 {
+            current = Some((start, text, depth));
+        } else {
+            let statement = parse_statement(&text)
+                .with_context(|| format!("Line {start}: invalid statement."))?;
+            statements.push((start, statement));
+        }
+    }
+    if let Some((start, _, _)) = current {
+        bail!("Line {start}: unclosed parenthesis.");
+    }
+    Ok(statements)
+}
+
+/// Splits `text` at the first occurrence of `separator` that is not inside a text literal.
+fn split_outside_text<'a>(text: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let mut in_text = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_text => escaped = true,
+            '"' => in_text = !in_text,
+            _ if !in_text && text[i..].starts_with(separator) => {
+                return Some((&text[..i], &text[i + separator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_statement(text: &str) -> DfxResult<Statement> {
+    if let Some(rest) = text.strip_prefix("let ") {
+        let (variable, call) = rest
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected `let <name> = <call>`."))?;
+        let variable = variable.trim();
+        if variable.is_empty()
+            || !variable
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("Invalid variable name '{variable}'.");
+        }
+        Ok(Statement::Call {
+            variable: Some(variable.to_string()),
+            call: parse_call(call)?,
+        })
+    } else if let Some(rest) = text.strip_prefix("assert ") {
+        let (actual, expected) = split_outside_text(rest, "==")
+            .ok_or_else(|| anyhow!("Expected `assert <call or $name> == <value>`."))?;
+        let actual = actual.trim();
+        let actual = if actual.starts_with('$') {
+            Expression::Variable(actual.to_string())
+        } else {
+            Expression::Call(parse_call(actual)?)
+        };
+        Ok(Statement::Assert {
+            actual,
+            expected: expected.trim().to_string(),
+        })
+    } else {
+        Ok(Statement::Call {
+            variable: None,
+            call: parse_call(text)?,
+        })
+    }
+}
+
+fn parse_call(text: &str) -> DfxResult<Call> {
+    let text = text.trim();
+    let open = text
+        .find('(')
+        .ok_or_else(|| anyhow!("Expected `<canister>.<method>(<arguments>)`."))?;
+    if !text.ends_with(')') {
+        bail!("Expected `<canister>.<method>(<arguments>)`.");
+    }
+    let (canister, method) = text[..open]
+        .trim()
+        .rsplit_once('.')
+        .ok_or_else(|| anyhow!("Expected `<canister>.<method>(<arguments>)`."))?;
+    if canister.is_empty() || method.is_empty() || method.contains(char::is_whitespace) {
+        bail!("Expected `<canister>.<method>(<arguments>)`.");
+    }
+    Ok(Call {
+        canister: canister.trim().to_string(),
+        method: method.to_string(),
+        arguments: text[open..].to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(canister: &str, method: &str, arguments: &str) -> Call {
+        Call {
+            canister: canister.to_string(),
+            method: method.to_string(),
+            arguments: arguments.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_statements() {
+        let script = r#"
+# seed the counter
+counter.set(5)
+let total = counter.inc_by(record {
+  amount = 2; memo = "(";
+})
+// comments are skipped
+assert $total == 7
+assert counter.get() == (7 : nat)
+"#;
+        assert_eq!(
+            parse_script(script).unwrap(),
+            vec![
+                (
+                    3,
+                    Statement::Call {
+                        variable: None,
+                        call: call("counter", "set", "(5)"),
+                    }
+                ),
+                (
+                    4,
+                    Statement::Call {
+                        variable: Some("total".to_string()),
+                        call: call(
+                            "counter",
+                            "inc_by",
+                            "(record {\namount = 2; memo = \"(\";\n})"
+                        ),
+                    }
+                ),
+                (
+                    8,
+                    Statement::Assert {
+                        actual: Expression::Variable("$total".to_string()),
+                        expected: "7".to_string(),
+                    }
+                ),
+                (
+                    9,
+                    Statement::Assert {
+                        actual: Expression::Call(call("counter", "get", "()")),
+                        expected: "(7 : nat)".to_string(),
+                    }
+                ),
+            ]
+        );
+
+        assert_eq!(
+            parse_statement(r#"assert counter.echo("a == b") == ("a == b")"#).unwrap(),
+            Statement::Assert {
+                actual: Expression::Call(call("counter", "echo", r#"("a == b")"#)),
+                expected: r#"("a == b")"#.to_string(),
+            }
+        );
+        assert_eq!(
+            parse_statement(r#"assert counter.echo("\"==") == ("\"==")"#).unwrap(),
+            Statement::Assert {
+                actual: Expression::Call(call("counter", "echo", r#"("\"==")"#)),
+                expected: r#"("\"==")"#.to_string(),
+            }
+        );
+
+        assert!(parse_script("counter.set(5").is_err());
+        assert!(parse_script("counter set(5)").is_err());
+        assert!(parse_script("let a b = counter.get()").is_err());
+    }
+
+    #[test]
+    fn resolve_references() {
+        let result = candid_parser::parse_idl_args(
+            r#"(record { id = principal "aaaaa-aa"; items = vec { "a"; "b" } }, variant { Ok = opt 3 })"#,
+        )
+        .unwrap();
+        let variables = BTreeMap::from([("r".to_string(), result)]);
+        assert_eq!(
+            resolve(&variables, "$r.0.id").unwrap(),
+            IDLValue::Principal(Principal::management_canister())
+        );
+        assert_eq!(
+            resolve(&variables, "$r.0.items.1").unwrap(),
+            IDLValue::Text("b".to_string())
+        );
+        assert_eq!(
+            resolve(&variables, "$r.1.Ok").unwrap(),
+            IDLValue::Number("3".to_string())
+        );
+        assert!(resolve(&variables, "$r").is_err());
+        assert!(resolve(&variables, "$r.1.Err").is_err());
+        assert!(resolve(&variables, "$s").is_err());
+    }
+
+    #[test]
+    fn reference_ends() {
+        assert_eq!(reference_end("$a.b_c.0)", 0), 7);
+        assert_eq!(reference_end("($a; 1)", 1), 3);
+        assert_eq!(reference_end("$a.", 0), 2);
+    }
+
+    #[test]
+    fn compare_results() {
+        let actual = IDLArgs::new(&[IDLValue::Nat(7u8.into())]);
+        assert!(results_equal(&actual, "7").unwrap());
+        assert!(results_equal(&actual, "7 : nat").unwrap());
+        assert!(!results_equal(&actual, "8").unwrap());
+        assert!(!results_equal(&actual, "\"7\"").unwrap());
+
+        let actual = IDLArgs::new(&[IDLValue::Text("a".to_string()), IDLValue::Bool(true)]);
+        assert!(results_equal(&actual, r#"("a", true)"#).unwrap());
+        assert!(!results_equal(&actual, r#"("a")"#).unwrap());
+    }
+}
@@ -9,6 +9,7 @@ use crate::util::clap::parsers::cycle_amount_parser;
 use crate::util::{blob_from_arguments, fetch_remote_did_file, get_candid_type, print_idl_blob};
 use anyhow::bail;
 use anyhow::{anyhow, Context};
+use candid::types::{Function, TypeEnv};
 use candid::Principal as CanisterId;
 use candid::{CandidType, Decode, Deserialize, Principal};
 use candid_parser::utils::CandidSource;
@@ -25,7 +26,7 @@ use ic_utils::interfaces::WalletCanister;
 use pocket_ic::common::rest::RawEffectivePrincipal;
 use slog::warn;
 use std::option::Option;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 /// Calls a method on a deployed canister.
@@ -231,28 +232,14 @@ pub async fn exec(
     let (canister_id, maybe_local_candid_path) =
         get_canister_id_and_candid_path(env, opts.canister_name.as_str())?;
 
-    let method_type = if let Some(path) = opts.candid {
-        get_candid_type(CandidSource::File(&path), method_name)
-    } else if let Some(did) = fetch_remote_did_file(agent, canister_id).await {
-        get_candid_type(CandidSource::Text(&did), method_name)
-    } else if let Some(path) = maybe_local_candid_path {
-        warn!(env.get_logger(), "DEPRECATION WARNING: Cannot fetch Candid interface from canister metadata, reading Candid interface from the local build artifact. In a future dfx release, we will only read candid interface from canister metadata.");
-        warn!(
-            env.get_logger(),
-            r#"Please add the following to dfx.json to store local candid file into metadata:
-"metadata": [
-   {{
-     "name": "candid:service"
-   }}
-]"#
-        );
-        get_candid_type(CandidSource::File(&path), method_name)
-    } else {
-        None
-    };
-    if method_type.is_none() {
-        warn!(env.get_logger(), "Cannot fetch Candid interface for {method_name}, sending arguments with inferred types.");
-    }
+    let method_type = get_method_type(
+        env,
+        canister_id,
+        opts.candid.as_deref(),
+        maybe_local_candid_path.as_deref(),
+        method_name,
+    )
+    .await;
 
     let (argument_from_cli, argument_type) = opts.argument_from_cli.get_argument_and_type()?;
 
@@ -268,137 +255,32 @@ pub async fn exec(
         opts.always_assist,
     )?;
 
-    let effective_canister_id = if canister_id == CanisterId::management_canister() {
-        let management_method = MgmtMethod::from_str(method_name).map_err(|_| {
-            anyhow!(
-                "Attempted to call an unsupported management canister method: {}",
-                method_name
-            )
-        })?;
-
-        if matches!(call_sender, CallSender::SelectedId)
-            && matches!(
-                management_method,
-                MgmtMethod::CreateCanister
-                    | MgmtMethod::RawRand
-                    | MgmtMethod::BitcoinGetBalance
-                    | MgmtMethod::BitcoinGetUtxos
-                    | MgmtMethod::BitcoinSendTransaction
-                    | MgmtMethod::BitcoinGetCurrentFeePercentiles
-                    | MgmtMethod::EcdsaPublicKey
-                    | MgmtMethod::SignWithEcdsa
-                    | MgmtMethod::NodeMetricsHistory
-            )
-        {
-            return Err(DiagnosedError::new(
-                format!(
-                    "{} can only be called by a canister, not by an external user.",
-                    method_name
-                ),
-                format!(
-                    "The easiest way to call {} externally is to proxy this call through a wallet.
-Try calling this with 'dfx canister call <other arguments> (--network ic) --wallet <wallet id>'.
-To figure out the id of your wallet, run 'dfx identity get-wallet (--network ic)'.",
-                    method_name
-                ),
-            ))
-            .context("Method only callable by a canister.");
-        }
-
-        get_effective_canister_id(&management_method, &arg_value)?
-    } else {
-        canister_id
-    };
-
-    let is_query_method = method_type.as_ref().map(|(_, f)| f.is_query());
+    let effective_canister_id =
+        get_call_effective_canister_id(call_sender, canister_id, method_name, &arg_value)?;
 
     let output_type = opts.output.as_deref();
     let is_query = if opts.r#async {
         false
     } else {
-        match is_query_method {
-            Some(true) => !opts.update,
-            Some(false) => {
-                if opts.query {
-                    return Err(DiagnosedError::new(
-                        format!("{} is an update method, not a query method.", method_name),
-                        "Run the command without '--query'.".to_string(),
-                    ))
-                    .context("Not a query method.");
-                } else {
-                    false
-                }
-            }
-            None => opts.query,
-        }
+        is_query_call(&method_type, method_name, opts.query, opts.update)?
     };
 
     // amount has been validated by cycle_amount_validator
     let cycles = opts.with_cycles.unwrap_or(0);
 
-    if cycles != 0 {
-        match call_sender {
-            CallSender::SelectedId => {
-                let explanation = "It is only possible to send cycles from a canister.";
-                let action_suggestion = "To send the same function call from your wallet (a canister), run the command using 'dfx canister call <other arguments> (--network ic) --wallet <wallet id>'.\n\
-        To figure out the id of your wallet, run 'dfx identity get-wallet (--network ic)'.";
-                return Err(DiagnosedError::new(explanation, action_suggestion))
-                    .context("Function caller is not a canister.");
-            }
-            CallSender::Wallet(_) => Telemetry::set_cycles_host(CyclesHost::CyclesWallet),
-            _ => {}
-        }
-    }
+    check_cycles_sender(call_sender, cycles)?;
 
     if is_query {
-        let blob = match call_sender {
-            CallSender::SelectedId => {
-                let query_builder = agent
-                    .query(&canister_id, method_name)
-                    .with_effective_canister_id(effective_canister_id)
-                    .with_arg(arg_value);
-                query_builder.call().await.context("Failed query call.")?
-            }
-            CallSender::Impersonate(sender) => {
-                let pocketic = env.get_pocketic();
-                if let Some(pocketic) = pocketic {
-                    pocketic
-                        .query_call_with_effective_principal(
-                            canister_id,
-                            RawEffectivePrincipal::CanisterId(
-                                effective_canister_id.as_slice().to_vec(),
-                            ),
-                            *sender,
-                            method_name,
-                            arg_value,
-                        )
-                        .await
-                        .map_err(|err| {
-                            anyhow!(
-                                "Failed to perform query call: {} ({})",
-                                err.reject_message,
-                                err.error_code
-                            )
-                        })?
-                } else {
-                    bail!("Impersonating sender is only supported for a local PocketIC instance.")
-                }
-            }
-            CallSender::Wallet(wallet_id) => {
-                let wallet = build_wallet_canister(*wallet_id, agent).await?;
-                do_wallet_call(
-                    &wallet,
-                    &CallIn {
-                        canister: canister_id,
-                        method_name: method_name.to_string(),
-                        args: arg_value,
-                        cycles,
-                    },
-                )
-                .await
-                .context("Failed wallet call.")?
-            }
-        };
+        let blob = query_call(
+            env,
+            call_sender,
+            canister_id,
+            effective_canister_id,
+            method_name,
+            arg_value,
+            cycles,
+        )
+        .await?;
         print_idl_blob(&blob, output_type, &method_type)?;
     } else if opts.r#async {
         let call_response = match call_sender {
@@ -458,62 +340,274 @@ To figure out the id of your wallet, run 'dfx identity get-wallet (--network ic)
             }
         }
     } else {
-        let blob = match call_sender {
-            CallSender::SelectedId => agent
-                .update(&canister_id, method_name)
+        let blob = update_call(
+            env,
+            call_sender,
+            canister_id,
+            effective_canister_id,
+            method_name,
+            arg_value,
+            cycles,
+        )
+        .await?;
+        print_idl_blob(&blob, output_type, &method_type)?;
+    }
+
+    Ok(())
+}
+
+/// Gets the Candid type of a method, from the `--candid` file, the canister metadata or the local build artifact.
+pub async fn get_method_type(
+    env: &dyn Environment,
+    canister_id: Principal,
+    candid: Option<&Path>,
+    maybe_local_candid_path: Option<&Path>,
+    method_name: &str,
+) -> Option<(TypeEnv, Function)> {
+    let method_type = if let Some(path) = candid {
+        get_candid_type(CandidSource::File(path), method_name)
+    } else if let Some(did) = fetch_remote_did_file(env.get_agent(), canister_id).await {
+        get_candid_type(CandidSource::Text(&did), method_name)
+    } else if let Some(path) = maybe_local_candid_path {
+        warn!(env.get_logger(), "DEPRECATION WARNING: Cannot fetch Candid interface from canister metadata, reading Candid interface from the local build artifact. In a future dfx release, we will only read candid interface from canister metadata.");
+        warn!(
+            env.get_logger(),
+            r#"Please add the following to dfx.json to store local candid file into metadata:
+"metadata": [
+   {{
+     "name": "candid:service"
+   }}
+]"#
+        );
+        get_candid_type(CandidSource::File(path), method_name)
+    } else {
+        None
+    };
+    if method_type.is_none() {
+        warn!(env.get_logger(), "Cannot fetch Candid interface for {method_name}, sending arguments with inferred types.");
+    }
+    method_type
+}
+
+/// Gets the effective canister id of a call, which differs from the canister id for calls to the management canister.
+pub fn get_call_effective_canister_id(
+    call_sender: &CallSender,
+    canister_id: Principal,
+    method_name: &str,
+    arg_value: &[u8],
+) -> DfxResult<Principal> {
+    if canister_id == CanisterId::management_canister() {
+        let management_method = MgmtMethod::from_str(method_name).map_err(|_| {
+            anyhow!(
+                "Attempted to call an unsupported management canister method: {}",
+                method_name
+            )
+        })?;
+
+        if matches!(call_sender, CallSender::SelectedId)
+            && matches!(
+                management_method,
+                MgmtMethod::CreateCanister
+                    | MgmtMethod::RawRand
+                    | MgmtMethod::BitcoinGetBalance
+                    | MgmtMethod::BitcoinGetUtxos
+                    | MgmtMethod::BitcoinSendTransaction
+                    | MgmtMethod::BitcoinGetCurrentFeePercentiles
+                    | MgmtMethod::EcdsaPublicKey
+                    | MgmtMethod::SignWithEcdsa
+                    | MgmtMethod::NodeMetricsHistory
+            )
+        {
+            return Err(DiagnosedError::new(
+                format!(
+                    "{} can only be called by a canister, not by an external user.",
+                    method_name
+                ),
+                format!(
+                    "The easiest way to call {} externally is to proxy this call through a wallet.
+Try calling this with 'dfx canister call <other arguments> (--network ic) --wallet <wallet id>'.
+To figure out the id of your wallet, run 'dfx identity get-wallet (--network ic)'.",
+                    method_name
+                ),
+            ))
+            .context("Method only callable by a canister.");
+        }
+
+        get_effective_canister_id(&management_method, arg_value)
+    } else {
+        Ok(canister_id)
+    }
+}
+
+/// Whether to send a query call, based on the Candid type of the method and the `--query`/`--update` flags.
+pub fn is_query_call(
+    method_type: &Option<(TypeEnv, Function)>,
+    method_name: &str,
+    query: bool,
+    update: bool,
+) -> DfxResult<bool> {
+    let is_query_method = method_type.as_ref().map(|(_, f)| f.is_query());
+    match is_query_method {
+        Some(true) => Ok(!update),
+        Some(false) => {
+            if query {
+                Err(DiagnosedError::new(
+                    format!("{} is an update method, not a query method.", method_name),
+                    "Run the command without '--query'.".to_string(),
+                ))
+                .context("Not a query method.")
+            } else {
+                Ok(false)
+            }
+        }
+        None => Ok(query),
+    }
+}
+
+/// Checks that cycles are only sent from a wallet.
+pub fn check_cycles_sender(call_sender: &CallSender, cycles: u128) -> DfxResult {
+    if cycles != 0 {
+        match call_sender {
+            CallSender::SelectedId => {
+                let explanation = "It is only possible to send cycles from a canister.";
+                let action_suggestion = "To send the same function call from your wallet (a canister), run the command using 'dfx canister call <other arguments> (--network ic) --wallet <wallet id>'.\n\
+        To figure out the id of your wallet, run 'dfx identity get-wallet (--network ic)'.";
+                return Err(DiagnosedError::new(explanation, action_suggestion))
+                    .context("Function caller is not a canister.");
+            }
+            CallSender::Wallet(_) => Telemetry::set_cycles_host(CyclesHost::CyclesWallet),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Sends a query call and returns the reply.
+pub async fn query_call(
+    env: &dyn Environment,
+    call_sender: &CallSender,
+    canister_id: Principal,
+    effective_canister_id: Principal,
+    method_name: &str,
+    arg_value: Vec<u8>,
+    cycles: u128,
+) -> DfxResult<Vec<u8>> {
+    let agent = env.get_agent();
+    let blob = match call_sender {
+        CallSender::SelectedId => {
+            let query_builder = agent
+                .query(&canister_id, method_name)
                 .with_effective_canister_id(effective_canister_id)
-                .with_arg(arg_value)
-                .await
-                .context("Failed update call.")?,
-            CallSender::Impersonate(sender) => {
-                let pocketic = env.get_pocketic();
-                if let Some(pocketic) = pocketic {
-                    let msg_id = pocketic
-                        .submit_call_with_effective_principal(
-                            canister_id,
-                            RawEffectivePrincipal::CanisterId(
-                                effective_canister_id.as_slice().to_vec(),
-                            ),
-                            *sender,
-                            method_name,
-                            arg_value,
-                        )
-                        .await
-                        .map_err(|err| {
-                            anyhow!(
-                                "Failed to submit canister call: {} ({})",
-                                err.reject_message,
-                                err.error_code
-                            )
-                        })?;
-                    pocketic.await_call_no_ticks(msg_id).await.map_err(|err| {
+                .with_arg(arg_value);
+            query_builder.call().await.context("Failed query call.")?
+        }
+        CallSender::Impersonate(sender) => {
+            let pocketic = env.get_pocketic();
+            if let Some(pocketic) = pocketic {
+                pocketic
+                    .query_call_with_effective_principal(
+                        canister_id,
+                        RawEffectivePrincipal::CanisterId(
+                            effective_canister_id.as_slice().to_vec(),
+                        ),
+                        *sender,
+                        method_name,
+                        arg_value,
+                    )
+                    .await
+                    .map_err(|err| {
                         anyhow!(
-                            "Canister call failed: {} ({})",
+                            "Failed to perform query call: {} ({})",
                             err.reject_message,
                             err.error_code
                         )
                     })?
-                } else {
-                    bail!("Impersonating sender is only supported for a local PocketIC instance.")
-                }
-            }
-            CallSender::Wallet(wallet_id) => {
-                let wallet = build_wallet_canister(*wallet_id, agent).await?;
-                do_wallet_call(
-                    &wallet,
-                    &CallIn {
-                        canister: canister_id,
-                        method_name: method_name.to_string(),
-                        args: arg_value,
-                        cycles,
-                    },
-                )
-                .await
-                .context("Failed to do wallet call.")?
+            } else {
+                bail!("Impersonating sender is only supported for a local PocketIC instance.")
             }
-        };
-        print_idl_blob(&blob, output_type, &method_type)?;
-    }
+        }
+        CallSender::Wallet(wallet_id) => {
+            let wallet = build_wallet_canister(*wallet_id, agent).await?;
+            do_wallet_call(
+                &wallet,
+                &CallIn {
+                    canister: canister_id,
+                    method_name: method_name.to_string(),
+                    args: arg_value,
+                    cycles,
+                },
+            )
+            .await
+            .context("Failed wallet call.")?
+        }
+    };
+    Ok(blob)
+}
 
-    Ok(())
+/// Sends an update call and waits for the reply.
+pub async fn update_call(
+    env: &dyn Environment,
+    call_sender: &CallSender,
+    canister_id: Principal,
+    effective_canister_id: Principal,
+    method_name: &str,
+    arg_value: Vec<u8>,
+    cycles: u128,
+) -> DfxResult<Vec<u8>> {
+    let agent = env.get_agent();
+    let blob = match call_sender {
+        CallSender::SelectedId => agent
+            .update(&canister_id, method_name)
+            .with_effective_canister_id(effective_canister_id)
+            .with_arg(arg_value)
+            .await
+            .context("Failed update call.")?,
+        CallSender::Impersonate(sender) => {
+            let pocketic = env.get_pocketic();
+            if let Some(pocketic) = pocketic {
+                let msg_id = pocketic
+                    .submit_call_with_effective_principal(
+                        canister_id,
+                        RawEffectivePrincipal::CanisterId(
+                            effective_canister_id.as_slice().to_vec(),
+                        ),
+                        *sender,
+                        method_name,
+                        arg_value,
+                    )
+                    .await
+                    .map_err(|err| {
+                        anyhow!(
+                            "Failed to submit canister call: {} ({})",
+                            err.reject_message,
+                            err.error_code
+                        )
+                    })?;
+                pocketic.await_call_no_ticks(msg_id).await.map_err(|err| {
+                    anyhow!(
+                        "Canister call failed: {} ({})",
+                        err.reject_message,
+                        err.error_code
+                    )
+                })?
+            } else {
+                bail!("Impersonating sender is only supported for a local PocketIC instance.")
+            }
+        }
+        CallSender::Wallet(wallet_id) => {
+            let wallet = build_wallet_canister(*wallet_id, agent).await?;
+            do_wallet_call(
+                &wallet,
+                &CallIn {
+                    canister: canister_id,
+                    method_name: method_name.to_string(),
+                    args: arg_value,
+                    cycles,
+                },
+            )
+            .await
+            .context("Failed to do wallet call.")?
+        }
+    };
+    Ok(blob)
 }
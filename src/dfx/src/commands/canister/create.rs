@@ -194,36 +194,42 @@ pub async fn exec(
             opts.compute_allocation,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read compute allocation of {canister_name}."))?;
         let memory_allocation = get_memory_allocation(
             opts.memory_allocation,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read memory allocation of {canister_name}."))?;
         let freezing_threshold = get_freezing_threshold(
             opts.freezing_threshold,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read freezing threshold of {canister_name}."))?;
         let reserved_cycles_limit = get_reserved_cycles_limit(
             opts.reserved_cycles_limit,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read reserved cycles limit of {canister_name}."))?;
         let wasm_memory_limit = get_wasm_memory_limit(
             opts.wasm_memory_limit,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read Wasm memory limit of {canister_name}."))?;
         let wasm_memory_threshold = get_wasm_memory_threshold(
             opts.wasm_memory_threshold,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read Wasm memory threshold of {canister_name}."))?;
         let log_visibility = get_log_visibility(
@@ -232,6 +238,7 @@ pub async fn exec(
             None,
             Some(config_interface),
             Some(canister_name),
+            &network.name,
         )
         .with_context(|| format!("Failed to read log visibility of {canister_name}."))?;
         create_canister(
@@ -272,6 +279,7 @@ pub async fn exec(
                     opts.compute_allocation,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to read compute allocation of {canister_name}.")
@@ -280,12 +288,14 @@ pub async fn exec(
                     opts.memory_allocation,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to read memory allocation of {canister_name}."))?;
                 let freezing_threshold = get_freezing_threshold(
                     opts.freezing_threshold,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to read freezing threshold of {canister_name}.")
@@ -294,6 +304,7 @@ pub async fn exec(
                     opts.reserved_cycles_limit,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to read reserved cycles limit of {canister_name}.")
@@ -302,12 +313,14 @@ pub async fn exec(
                     opts.wasm_memory_limit,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to read Wasm memory limit of {canister_name}."))?;
                 let wasm_memory_threshold = get_wasm_memory_threshold(
                     opts.wasm_memory_threshold,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to read Wasm memory threshold of {canister_name}.")
@@ -318,6 +331,7 @@ pub async fn exec(
                     None,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to read log visibility of {canister_name}."))?;
                 create_canister(
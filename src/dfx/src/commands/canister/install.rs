@@ -2,15 +2,18 @@ use crate::lib::canister_info::CanisterInfo;
 use crate::lib::deps::get_pull_canisters_in_config;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use crate::lib::operations::canister::install_canister::install_canister;
+use crate::lib::operations::canister::estimate_cost::{estimate_deploy_cost, print_cost_estimates};
+use crate::lib::operations::canister::install_canister::{install_canister, InstallSteps};
 use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::sign::signer::{save_signed_messages, MessageSigner};
 use crate::util::clap::argument_from_cli::ArgumentFromCliLongOpt;
 use crate::util::clap::install_mode::{InstallModeHint, InstallModeOpt};
-use crate::util::{ask_for_consent, blob_from_arguments};
+use crate::util::{ask_for_consent, blob_from_arguments, get_candid_init_type};
 use dfx_core::canister::{
     install_canister_wasm, install_mode_to_past_tense, install_mode_to_present_tense,
 };
 use dfx_core::identity::CallSender;
+use ic_utils::interfaces::management_canister::builders::InstallMode;
 
 use crate::lib::operations::canister::skip_remote_canister;
 use anyhow::bail;
@@ -72,6 +75,34 @@ pub struct CanisterInstallOpts {
         conflicts_with("yes")
     )]
     always_assist: bool,
+
+    /// Signs the management canister calls that install the code and saves them to a file, instead of sending them.
+    /// Large modules are uploaded in chunks, which takes several messages.
+    /// The messages can be sent later with `dfx canister send`. Requires an explicit --mode.
+    #[arg(
+        long,
+        conflicts_with("all"),
+        conflicts_with("async_call"),
+        conflicts_with("always_assist")
+    )]
+    sign_only: bool,
+
+    /// Specifies how long the signed messages will be valid, default to be 5 minutes.
+    #[arg(long, default_value = "5m", requires("sign_only"))]
+    expire_after: String,
+
+    /// Specifies the file name of the signed messages.
+    #[arg(long, default_value = "message.json", requires("sign_only"))]
+    message_file: PathBuf,
+
+    /// Prints the expected cycles cost of installing the built canisters instead of installing them.
+    #[arg(
+        long,
+        conflicts_with("sign_only"),
+        conflicts_with("async_call"),
+        conflicts_with("wasm")
+    )]
+    estimate: bool,
 }
 
 pub async fn exec(
@@ -79,9 +110,16 @@ pub async fn exec(
     opts: CanisterInstallOpts,
     call_sender: &CallSender,
 ) -> DfxResult {
+    let mode_hint = opts.install_mode.mode_for_canister_install()?;
+    if opts.sign_only {
+        return sign_install(env, &opts, &mode_hint, call_sender);
+    }
+    if opts.estimate {
+        return estimate_install(env, &opts).await;
+    }
+
     fetch_root_key_if_needed(env).await?;
 
-    let mode_hint = opts.install_mode.mode_for_canister_install()?;
     let canister_id_store = env.get_canister_id_store()?;
     let network = env.get_network_descriptor();
 
@@ -178,6 +216,7 @@ pub async fn exec(
                     None,
                     opts.no_asset_upgrade,
                     opts.always_assist,
+                    InstallSteps::All,
                 )
                 .await
                 .map_err(Into::into)
@@ -198,6 +237,7 @@ pub async fn exec(
                     env_file.as_deref(),
                     opts.no_asset_upgrade,
                     opts.always_assist,
+                    InstallSteps::All,
                 )
                 .await
                 .map_err(Into::into)
@@ -235,6 +275,7 @@ pub async fn exec(
                     env_file.as_deref(),
                     opts.no_asset_upgrade,
                     opts.always_assist,
+                    InstallSteps::All,
                 )
                 .await?;
             }
@@ -247,3 +288,117 @@ pub async fn exec(
         unreachable!()
     }
 }
+
+/// Prints the cycles cost estimate of installing the selected canisters.
+async fn estimate_install(env: &dyn Environment, opts: &CanisterInstallOpts) -> DfxResult {
+    let canister_names = if let Some(canister) = opts.canister.as_deref() {
+        if Principal::from_text(canister).is_ok() {
+            bail!("--estimate requires a canister name from dfx.json.");
+        }
+        vec![canister.to_string()]
+    } else {
+        let config = env.get_config_or_anyhow()?;
+        let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
+        let mut canister_names = vec![];
+        for canister in config.get_config().canisters.iter().flat_map(|c| c.keys()) {
+            if pull_canisters_in_config.contains_key(canister)
+                || skip_remote_canister(env, canister)?
+            {
+                continue;
+            }
+            canister_names.push(canister.clone());
+        }
+        canister_names
+    };
+    let estimates = estimate_deploy_cost(env, &canister_names, None).await?;
+    print_cost_estimates(env, &estimates);
+    Ok(())
+}
+
+/// Signs the install messages of a single canister without contacting the network.
+fn sign_install(
+    env: &dyn Environment,
+    opts: &CanisterInstallOpts,
+    mode_hint: &InstallModeHint,
+    call_sender: &CallSender,
+) -> DfxResult {
+    if *call_sender != CallSender::SelectedId {
+        bail!("--sign-only doesn't support proxying through the wallet canister, please use `--no-wallet`.");
+    }
+    let mode = match mode_hint {
+        InstallModeHint::Auto(_) => {
+            bail!("--sign-only cannot detect whether the canister is installed, please specify --mode install, reinstall or upgrade.")
+        }
+        _ => mode_hint.to_install_mode_with_wasm_path()?,
+    };
+    let Some(canister) = opts.canister.as_deref() else {
+        bail!("--sign-only requires a canister name or id.");
+    };
+    let (argument_from_cli, argument_type) = opts.argument_from_cli.get_argument_and_type()?;
+
+    let (canister_id, wasm_path, init_type, argument, argument_type) = if let Ok(canister_id) =
+        Principal::from_text(canister)
+    {
+        let Some(wasm_path) = opts.wasm.clone() else {
+            bail!("When installing a canister by its ID, you must specify `--wasm` option.")
+        };
+        (
+            canister_id,
+            wasm_path,
+            None,
+            argument_from_cli,
+            argument_type,
+        )
+    } else {
+        let config = env.get_config_or_anyhow()?;
+        let canister_id = env.get_canister_id_store()?.get(canister)?;
+        let canister_info = CanisterInfo::load(&config, canister, Some(canister_id))?;
+        let (wasm_path, init_type) = match &opts.wasm {
+            Some(wasm_path) => (wasm_path.clone(), None),
+            None => {
+                let build_wasm_path = canister_info.get_build_wasm_path();
+                if !build_wasm_path.exists() {
+                    bail!("The canister must be built before install. Please run `dfx build`.");
+                }
+                (
+                    build_wasm_path,
+                    get_candid_init_type(&canister_info.get_constructor_idl_path()),
+                )
+            }
+        };
+        // The argument from the CLI takes precedence over the dfx.json configuration.
+        let (argument, argument_type) = match (argument_from_cli, canister_info.get_init_arg()?) {
+            (None, Some((a_json, json_type))) => (Some(a_json), Some(json_type.to_string())),
+            (argument, _) => (argument, argument_type),
+        };
+        (canister_id, wasm_path, init_type, argument, argument_type)
+    };
+
+    if matches!(mode, InstallMode::Reinstall) && !opts.yes {
+        ask_for_consent(env, "You are about to sign a reinstall, which removes all data of the canister when it is sent.")?;
+    }
+    let arg = blob_from_arguments(
+        Some(env),
+        argument.as_deref(),
+        None,
+        argument_type.as_deref(),
+        &init_type,
+        true,
+        false,
+    )?;
+    let wasm_module = dfx_core::fs::read(&wasm_path)?;
+
+    let signer = MessageSigner::new(env, &opts.expire_after)?;
+    let messages = signer.sign_install(canister_id, &wasm_module, &arg, mode)?;
+    let count = messages.len();
+    save_signed_messages(&opts.message_file, messages)?;
+    info!(
+        env.get_logger(),
+        "Signed {} message(s) to install code in canister {} to [{}]. Send them with `dfx canister send {}`.",
+        count,
+        canister_id,
+        opts.message_file.display(),
+        opts.message_file.display()
+    );
+    Ok(())
+}
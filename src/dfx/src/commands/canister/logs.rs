@@ -3,10 +3,14 @@ use crate::lib::error::DfxResult;
 use crate::lib::operations::canister;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::clap::parsers::{duration_parser, timestamp_parser};
+use anyhow::bail;
 use candid::Principal;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use dfx_core::identity::CallSender;
 use ic_utils::interfaces::management_canister::{CanisterLogRecord, FetchCanisterLogsResponse};
+use regex::Regex;
+use serde::Serialize;
+use slog::warn;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
@@ -14,8 +18,10 @@ use time::OffsetDateTime;
 /// Get the canister logs.
 #[derive(Parser)]
 pub struct LogsOpts {
-    /// Specifies the name or id of the canister to get the logs of.
-    canister: String,
+    /// Specifies the names or ids of the canisters to get the logs of.
+    /// When more than one canister is given, each text log line is prefixed with the canister.
+    #[arg(required = true, num_args = 1..)]
+    canisters: Vec<String>,
 
     /// Specifies to show the last number of the logs.
     #[arg(long)]
@@ -42,6 +48,36 @@ pub struct LogsOpts {
     /// Specifies the interval in seconds between log fetches when following logs. Defaults to 2 seconds.
     #[arg(long, requires("follow"))]
     interval: Option<u64>,
+
+    /// Specifies to only show the logs whose message matches this regular expression.
+    #[arg(long)]
+    grep: Option<Regex>,
+
+    /// Specifies the format of the output.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// One line per log record.
+    Text,
+    /// A JSON array of log records.
+    Json,
+    /// One JSON object per log record and line.
+    Ndjson,
+}
+
+/// A log record as printed with `--output json` or `--output ndjson`.
+///
+/// The u64 fields are strings, because JSON numbers lose precision past 2^53.
+#[derive(Serialize)]
+struct LogRecordOutput<'a> {
+    canister: &'a str,
+    idx: String,
+    timestamp_nanos: String,
+    timestamp: String,
+    content: String,
 }
 
 struct FilterOpts {
@@ -90,58 +126,137 @@ fn format_bytes(bytes: &[u8]) -> String {
     format!("(bytes) 0x{}", hex::encode(bytes))
 }
 
+fn format_message(content: &[u8]) -> String {
+    if let Ok(s) = String::from_utf8(content.to_vec()) {
+        if format!("{s:?}").contains("\\u{") {
+            format_bytes(content)
+        } else {
+            s
+        }
+    } else {
+        format_bytes(content)
+    }
+}
+
+fn format_timestamp(timestamp_nanos: u64) -> String {
+    OffsetDateTime::from_unix_timestamp_nanos(timestamp_nanos as i128)
+        .expect("Invalid canister log record timestamp")
+        .format(&Rfc3339)
+        .expect("Failed to format timestamp")
+}
+
 fn format_canister_logs(logs: &[CanisterLogRecord]) -> Vec<String> {
     logs.iter()
         .map(|r| {
-            let time = OffsetDateTime::from_unix_timestamp_nanos(r.timestamp_nanos as i128)
-                .expect("Invalid canister log record timestamp");
-
-            let message = if let Ok(s) = String::from_utf8(r.content.clone()) {
-                if format!("{s:?}").contains("\\u{") {
-                    format_bytes(&r.content)
-                } else {
-                    s
-                }
-            } else {
-                format_bytes(&r.content)
-            };
-
             format!(
                 "[{}. {}]: {}",
                 r.idx,
-                time.format(&Rfc3339).expect("Failed to format timestamp"),
-                message
+                format_timestamp(r.timestamp_nanos),
+                format_message(&r.content)
             )
         })
         .collect()
 }
 
+fn grep_canister_logs<'a>(
+    logs: &'a [CanisterLogRecord],
+    grep: Option<&Regex>,
+) -> Vec<&'a CanisterLogRecord> {
+    logs.iter()
+        .filter(|r| grep.map_or(true, |re| re.is_match(&format_message(&r.content))))
+        .collect()
+}
+
+/// Formats the logs of one canister as lines of output.
+///
+/// `prefix` is only used for text output, which otherwise would not tell the canisters apart.
+/// With `OutputFormat::Json`, the records are returned one per line without the surrounding array.
+fn format_canister_logs_as(
+    canister: &str,
+    logs: &[&CanisterLogRecord],
+    prefix: bool,
+    output: OutputFormat,
+) -> DfxResult<Vec<String>> {
+    logs.iter()
+        .map(|r| {
+            Ok(match output {
+                OutputFormat::Text => {
+                    let line = format_canister_logs(std::slice::from_ref(*r)).remove(0);
+                    if prefix {
+                        format!("[{canister}] {line}")
+                    } else {
+                        line
+                    }
+                }
+                OutputFormat::Json | OutputFormat::Ndjson => {
+                    serde_json::to_string(&LogRecordOutput {
+                        canister,
+                        idx: r.idx.to_string(),
+                        timestamp_nanos: r.timestamp_nanos.to_string(),
+                        timestamp: format_timestamp(r.timestamp_nanos),
+                        content: format_message(&r.content),
+                    })?
+                }
+            })
+        })
+        .collect()
+}
+
 pub async fn exec(env: &dyn Environment, opts: LogsOpts, call_sender: &CallSender) -> DfxResult {
-    let callee_canister = opts.canister.as_str();
-    let canister_id_store = env.get_canister_id_store()?;
+    if opts.follow && opts.output == OutputFormat::Json {
+        bail!("`--output json` cannot be combined with `--follow`. Use `--output ndjson` instead.");
+    }
 
-    let canister_id = Principal::from_text(callee_canister)
-        .or_else(|_| canister_id_store.get(callee_canister))?;
+    let canister_id_store = env.get_canister_id_store()?;
+    let canisters = opts
+        .canisters
+        .iter()
+        .map(|canister| {
+            let canister_id =
+                Principal::from_text(canister).or_else(|_| canister_id_store.get(canister))?;
+            Ok((canister.as_str(), canister_id))
+        })
+        .collect::<DfxResult<Vec<_>>>()?;
+    let prefix = canisters.len() > 1;
+    let grep = opts.grep.as_ref();
 
     fetch_root_key_if_needed(env).await?;
 
     if opts.follow {
         let interval = opts.interval.unwrap_or(2);
-        let mut last_idx = 0u64;
+        let mut last_idx = vec![0u64; canisters.len()];
 
         loop {
-            let logs = canister::get_canister_logs(env, canister_id, call_sender).await?;
-            let filter_opts = FilterOpts {
-                tail: None,
-                since: None,
-                since_time: None,
-                last_idx: Some(last_idx),
-            };
-            let filtered_logs = filter_canister_logs(&logs, filter_opts);
+            for ((canister, canister_id), last_idx) in canisters.iter().zip(last_idx.iter_mut()) {
+                let logs = match canister::get_canister_logs(env, *canister_id, call_sender).await {
+                    Ok(logs) => logs,
+                    // Keep following the other canisters.
+                    Err(e) if prefix => {
+                        warn!(
+                            env.get_logger(),
+                            "Failed to fetch the logs of canister '{canister}': {e:#}"
+                        );
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                let filter_opts = FilterOpts {
+                    tail: None,
+                    since: None,
+                    since_time: None,
+                    last_idx: Some(*last_idx),
+                };
+                let filtered_logs = filter_canister_logs(&logs, filter_opts);
 
-            if !filtered_logs.is_empty() {
-                println!("{}", format_canister_logs(filtered_logs).join("\n"));
-                last_idx = filtered_logs.last().unwrap().idx;
+                if let Some(last) = filtered_logs.last() {
+                    *last_idx = last.idx;
+                    let grepped_logs = grep_canister_logs(filtered_logs, grep);
+                    if !grepped_logs.is_empty() {
+                        let lines =
+                            format_canister_logs_as(canister, &grepped_logs, prefix, opts.output)?;
+                        println!("{}", lines.join("\n"));
+                    }
+                }
             }
 
             tokio::select! {
@@ -150,17 +265,35 @@ pub async fn exec(env: &dyn Environment, opts: LogsOpts, call_sender: &CallSende
             }
         }
     } else {
-        let logs = canister::get_canister_logs(env, canister_id, call_sender).await?;
+        let mut lines = vec![];
+        for (canister, canister_id) in &canisters {
+            let logs = canister::get_canister_logs(env, *canister_id, call_sender).await?;
 
-        let filter_opts = FilterOpts {
-            tail: opts.tail,
-            since: opts.since,
-            since_time: opts.since_time,
-            last_idx: None,
-        };
-        let filtered_logs = filter_canister_logs(&logs, filter_opts);
+            let filter_opts = FilterOpts {
+                tail: opts.tail,
+                since: opts.since,
+                since_time: opts.since_time,
+                last_idx: None,
+            };
+            let filtered_logs = filter_canister_logs(&logs, filter_opts);
+            let grepped_logs = grep_canister_logs(filtered_logs, grep);
+            lines.extend(format_canister_logs_as(
+                canister,
+                &grepped_logs,
+                prefix,
+                opts.output,
+            )?);
+        }
 
-        println!("{}", format_canister_logs(filtered_logs).join("\n"));
+        match opts.output {
+            OutputFormat::Text => println!("{}", lines.join("\n")),
+            OutputFormat::Json => println!("[{}]", lines.join(",")),
+            OutputFormat::Ndjson => {
+                for line in lines {
+                    println!("{line}");
+                }
+            }
+        }
     }
 
     Ok(())
@@ -306,3 +439,82 @@ fn test_format_canister_logs() {
         ]
     );
 }
+
+#[test]
+fn test_format_canister_logs_as() {
+    let logs = vec![
+        CanisterLogRecord {
+            idx: 42,
+            timestamp_nanos: 1_620_328_630_000_000_001,
+            content: b"Hello, Alice!".to_vec(),
+        },
+        CanisterLogRecord {
+            idx: 43,
+            timestamp_nanos: 1_620_328_630_000_000_002,
+            content: vec![192, 255, 238],
+        },
+        CanisterLogRecord {
+            idx: 44,
+            timestamp_nanos: 1_620_328_635_000_000_001,
+            content: b"Hello, Bob!".to_vec(),
+        },
+    ];
+
+    // Test grep
+    let grep = Regex::new("Hello, (Alice|Carol)").unwrap();
+    assert_eq!(
+        format_canister_logs_as(
+            "hello",
+            &grep_canister_logs(&logs, Some(&grep)),
+            false,
+            OutputFormat::Text
+        )
+        .unwrap(),
+        vec!["[42. 2021-05-06T19:17:10.000000001Z]: Hello, Alice!".to_string()]
+    );
+    let grep = Regex::new("^\\(bytes\\)").unwrap();
+    assert_eq!(
+        grep_canister_logs(&logs, Some(&grep))
+            .iter()
+            .map(|r| r.idx)
+            .collect::<Vec<_>>(),
+        vec![43]
+    );
+
+    // Test prefix
+    let all_logs = grep_canister_logs(&logs, None);
+    assert_eq!(
+        format_canister_logs_as("hello", &all_logs, true, OutputFormat::Text).unwrap(),
+        vec![
+            "[hello] [42. 2021-05-06T19:17:10.000000001Z]: Hello, Alice!".to_string(),
+            "[hello] [43. 2021-05-06T19:17:10.000000002Z]: (bytes) 0xc0ffee".to_string(),
+            "[hello] [44. 2021-05-06T19:17:15.000000001Z]: Hello, Bob!".to_string(),
+        ]
+    );
+
+    // Test JSON
+    let lines =
+        format_canister_logs_as("hello", &all_logs[..2], true, OutputFormat::Ndjson).unwrap();
+    assert_eq!(
+        lines
+            .iter()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>(),
+        vec![
+            serde_json::json!({
+                "canister": "hello",
+                "idx": "42",
+                "timestamp_nanos": "1620328630000000001",
+                "timestamp": "2021-05-06T19:17:10.000000001Z",
+                "content": "Hello, Alice!",
+            }),
+            serde_json::json!({
+                "canister": "hello",
+                "idx": "43",
+                "timestamp_nanos": "1620328630000000002",
+                "timestamp": "2021-05-06T19:17:10.000000002Z",
+                "content": "(bytes) 0xc0ffee",
+            }),
+        ]
+    );
+}
@@ -6,6 +6,8 @@ use clap::{Parser, Subcommand};
 use dfx_core::identity::CallSender;
 use tokio::runtime::Runtime;
 
+mod assets;
+mod batch;
 mod call;
 mod create;
 mod delete;
@@ -26,6 +28,7 @@ mod stop;
 mod uninstall_code;
 mod update_settings;
 mod url;
+mod verify;
 
 /// Manages canisters deployed on a network replica.
 #[derive(Parser)]
@@ -45,6 +48,8 @@ pub struct CanisterOpts {
 
 #[derive(Subcommand)]
 pub enum SubCommand {
+    Assets(assets::AssetsOpts),
+    Batch(batch::CanisterBatchOpts),
     Call(call::CanisterCallOpts),
     Create(create::CanisterCreateOpts),
     Delete(delete::CanisterDeleteOpts),
@@ -65,6 +70,7 @@ pub enum SubCommand {
     UpdateSettings(update_settings::UpdateSettingsOpts),
     Logs(logs::LogsOpts),
     Url(url::CanisterUrlOpts),
+    Verify(verify::CanisterVerifyOpts),
 }
 
 pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
@@ -80,6 +86,8 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
     runtime.block_on(async {
         let call_sender = || CallSender::from(&opts.wallet, env.get_network_descriptor());
         match opts.subcmd {
+            SubCommand::Assets(v) => assets::exec(env, v).await,
+            SubCommand::Batch(v) => batch::exec(env, v, &call_sender()?).await,
             SubCommand::Call(v) => call::exec(env, v, &call_sender()?).await,
             SubCommand::Create(v) => create::exec(env, v, &call_sender()?).await,
             SubCommand::Delete(v) => delete::exec(env, v, &call_sender()?).await,
@@ -100,6 +108,7 @@ pub fn exec(env: &dyn Environment, opts: CanisterOpts) -> DfxResult {
             SubCommand::UpdateSettings(v) => update_settings::exec(env, v, &call_sender()?).await,
             SubCommand::Logs(v) => logs::exec(env, v, &call_sender()?).await,
             SubCommand::Url(v) => url::exec(env, v).await,
+            SubCommand::Verify(v) => verify::exec(env, v).await,
         }
     })
 }
@@ -1,6 +1,6 @@
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use crate::lib::sign::signed_message::SignedMessageV1;
+use crate::lib::sign::signed_message::{SignedMessageFile, SignedMessageV1};
 use anyhow::{bail, Context};
 use candid::{IDLArgs, Principal};
 use clap::Parser;
@@ -10,8 +10,10 @@ use ic_agent::agent::{CallResponse, RequestStatusResponse};
 use ic_agent::Agent;
 use ic_agent::RequestId;
 use std::path::PathBuf;
+use std::time::Duration;
+use time::OffsetDateTime;
 
-/// Send a previously-signed message.
+/// Send a previously-signed message, or the messages of a batch in order.
 #[derive(Parser)]
 pub struct CanisterSendOpts {
     /// Specifies the file name of the message
@@ -31,67 +33,71 @@ pub async fn exec(
         bail!("`send` currently doesn't support proxying through the wallet canister, please use `dfx canister send --no-wallet ...`.");
     }
     let file_name = opts.file_name;
-    let message: SignedMessageV1 = load_json_file(&file_name)?;
-    message.validate()?;
+    let messages = match load_json_file(&file_name)? {
+        SignedMessageFile::Batch(batch) => {
+            batch.validate()?;
+            batch.messages
+        }
+        SignedMessageFile::Single(message) => {
+            message.validate()?;
+            vec![message]
+        }
+    };
 
-    let network = message.network.clone();
+    let network = messages[0].network.clone();
     let agent = Agent::builder().with_url(&network).build()?;
-    if !message.is_ic {
+    if !messages[0].is_ic {
         agent.fetch_root_key().await?;
     }
-    let content = hex::decode(&message.content).context("Failed to decode message content.")?;
-    let canister_id = Principal::from_text(&message.canister_id)
-        .with_context(|| format!("Failed to parse canister id {:?}.", message.canister_id))?;
 
     if opts.status {
-        if message.call_type != "update" {
-            bail!("Can only check request_status on update calls.");
-        }
-        let Some(signed_request_status) = message.signed_request_status else {
-            bail!("No signed_request_status in [{}].", file_name.display());
-        };
-        let envelope = hex::decode(signed_request_status).context("Failed to decode envelope.")?;
-        let Some(request_id) = message.request_id else {
-            bail!("No request_id in [{}].", file_name.display());
-        };
-        let request_id = request_id
-            .parse::<RequestId>()
-            .context("Failed to decode request ID.")?;
-        let (response, _cert) = agent
-            .request_status_signed(&request_id, canister_id, envelope)
-            .await
-            .with_context(|| format!("Failed to read canister state of {}.", canister_id))?;
-        eprint!("Response: ");
-        match response {
-            RequestStatusResponse::Received => eprintln!("Received, not yet processing"),
-            RequestStatusResponse::Processing => eprintln!("Processing, not yet done"),
-            RequestStatusResponse::Rejected(response) => {
-                if let Some(error_code) = response.error_code {
-                    println!(
-                        "Rejected ({:?}): {}, error code {}",
-                        response.reject_code, response.reject_message, error_code
-                    );
-                } else {
-                    println!(
-                        "Rejected ({:?}): {}",
-                        response.reject_code, response.reject_message
-                    );
-                }
+        for (i, message) in messages.iter().enumerate() {
+            if messages.len() > 1 {
+                eprint!(
+                    "Message {}/{} ({}): ",
+                    i + 1,
+                    messages.len(),
+                    message.method_name
+                );
             }
-            RequestStatusResponse::Replied(response) => {
-                eprint!("Replied: ");
-                if let Ok(idl) = IDLArgs::from_bytes(&response.arg) {
-                    println!("{idl}");
-                } else {
-                    println!("{}", hex::encode(&response.arg));
-                }
+            if message.call_type != "update" {
+                bail!("Can only check request_status on update calls.");
             }
-            RequestStatusResponse::Done => println!("Done, response no longer available"),
-            RequestStatusResponse::Unknown => println!("Unknown"),
+            let Some(signed_request_status) = &message.signed_request_status else {
+                bail!("No signed_request_status in [{}].", file_name.display());
+            };
+            let envelope =
+                hex::decode(signed_request_status).context("Failed to decode envelope.")?;
+            let Some(request_id) = &message.request_id else {
+                bail!("No request_id in [{}].", file_name.display());
+            };
+            let request_id = request_id
+                .parse::<RequestId>()
+                .context("Failed to decode request ID.")?;
+            let effective_canister_id = message.get_effective_canister_id()?;
+            let (response, _cert) = agent
+                .request_status_signed(&request_id, effective_canister_id, envelope)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to read canister state of {}.",
+                        effective_canister_id
+                    )
+                })?;
+            print_request_status(response);
         }
         return Ok(());
     }
 
+    if messages.len() > 1 {
+        return send_batch(&agent, &messages).await;
+    }
+    let message = &messages[0];
+    let content = hex::decode(&message.content).context("Failed to decode message content.")?;
+    let canister_id = Principal::from_text(&message.canister_id)
+        .with_context(|| format!("Failed to parse canister id {:?}.", message.canister_id))?;
+    let effective_canister_id = message.get_effective_canister_id()?;
+
     eprintln!("Will send message:");
     eprintln!("  Creation:    {}", message.creation);
     eprintln!("  Expiration:  {}", message.expiration);
@@ -102,20 +108,14 @@ pub async fn exec(
     eprintln!("  Method name: {}", message.method_name);
     eprintln!("  Arg:         {:?}", message.arg);
 
-    // Not using dialoguer because it doesn't support non terminal env like bats e2e
-    eprintln!("\nOkay? [y/N]");
-    let mut input = String::new();
-    std::io::stdin()
-        .read_line(&mut input)
-        .context("Failed to read stdin.")?;
-    if !["y", "yes"].contains(&input.to_lowercase().trim()) {
+    if !confirm()? {
         return Ok(());
     }
 
     match message.call_type.as_str() {
         "query" => {
             let response = agent
-                .query_signed(canister_id, content)
+                .query_signed(effective_canister_id, content)
                 .await
                 .with_context(|| format!("Query call to {} failed.", canister_id))?;
             eprint!("Response: ");
@@ -127,7 +127,7 @@ pub async fn exec(
         }
         "update" => {
             let call_response = agent
-                .update_signed(canister_id, content)
+                .update_signed(effective_canister_id, content)
                 .await
                 .with_context(|| format!("Update call to {} failed.", canister_id))?;
             match call_response {
@@ -144,11 +144,7 @@ pub async fn exec(
                 }
                 CallResponse::Response(response) => {
                     eprint!("Response: ");
-                    if let Ok(idl) = IDLArgs::from_bytes(&response) {
-                        println!("{idl}");
-                    } else {
-                        println!("{}", hex::encode(&response));
-                    }
+                    print_reply(&response);
                 }
             }
         }
@@ -157,3 +153,147 @@ pub async fn exec(
     }
     Ok(())
 }
+
+/// Sends the update calls of a batch in order, waiting for each call to complete before sending the next one.
+async fn send_batch(agent: &Agent, messages: &[SignedMessageV1]) -> DfxResult {
+    let first = &messages[0];
+    eprintln!("Will send {} messages:", messages.len());
+    eprintln!("  Creation:    {}", first.creation);
+    eprintln!("  Expiration:  {}", first.expiration);
+    eprintln!("  Network:     {}", first.network);
+    eprintln!("  Sender:      {}", first.sender);
+    for (i, message) in messages.iter().enumerate() {
+        eprintln!(
+            "  {}. {} on {} ({} bytes of argument)",
+            i + 1,
+            message.method_name,
+            message
+                .effective_canister_id
+                .as_ref()
+                .unwrap_or(&message.canister_id),
+            message.arg.len()
+        );
+    }
+
+    if !confirm()? {
+        return Ok(());
+    }
+
+    for (i, message) in messages.iter().enumerate() {
+        let content = hex::decode(&message.content).context("Failed to decode message content.")?;
+        let effective_canister_id = message.get_effective_canister_id()?;
+        let call_response = agent
+            .update_signed(effective_canister_id, content)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to send message {} ({}).",
+                    i + 1,
+                    message.method_name
+                )
+            })?;
+        let response = match call_response {
+            CallResponse::Response(response) => response,
+            CallResponse::Poll(request_id) => wait_for_reply(agent, message, request_id)
+                .await
+                .with_context(|| format!("Message {} ({}) failed.", i + 1, message.method_name))?,
+        };
+        eprintln!(
+            "Message {}/{} ({}) completed.",
+            i + 1,
+            messages.len(),
+            message.method_name
+        );
+        if i + 1 == messages.len() {
+            eprint!("Response: ");
+            print_reply(&response);
+        }
+    }
+    Ok(())
+}
+
+/// Polls the signed request_status call of a message until its update call completed.
+async fn wait_for_reply(
+    agent: &Agent,
+    message: &SignedMessageV1,
+    request_id: RequestId,
+) -> DfxResult<Vec<u8>> {
+    let Some(signed_request_status) = &message.signed_request_status else {
+        bail!("No signed_request_status in the message.");
+    };
+    let envelope = hex::decode(signed_request_status).context("Failed to decode envelope.")?;
+    let effective_canister_id = message.get_effective_canister_id()?;
+    loop {
+        let (response, _cert) = agent
+            .request_status_signed(&request_id, effective_canister_id, envelope.clone())
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to read canister state of {}.",
+                    effective_canister_id
+                )
+            })?;
+        match response {
+            RequestStatusResponse::Replied(response) => return Ok(response.arg),
+            RequestStatusResponse::Rejected(response) => bail!(
+                "Rejected ({:?}): {}",
+                response.reject_code,
+                response.reject_message
+            ),
+            RequestStatusResponse::Done => bail!("Done, response no longer available"),
+            RequestStatusResponse::Received
+            | RequestStatusResponse::Processing
+            | RequestStatusResponse::Unknown => {
+                if OffsetDateTime::now_utc() > message.expiration {
+                    bail!("The message expired before its call completed.");
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+fn confirm() -> DfxResult<bool> {
+    // Not using dialoguer because it doesn't support non terminal env like bats e2e
+    eprintln!("\nOkay? [y/N]");
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read stdin.")?;
+    Ok(["y", "yes"].contains(&input.to_lowercase().trim()))
+}
+
+fn print_reply(response: &[u8]) {
+    if let Ok(idl) = IDLArgs::from_bytes(response) {
+        println!("{idl}");
+    } else {
+        println!("{}", hex::encode(response));
+    }
+}
+
+fn print_request_status(response: RequestStatusResponse) {
+    eprint!("Response: ");
+    match response {
+        RequestStatusResponse::Received => eprintln!("Received, not yet processing"),
+        RequestStatusResponse::Processing => eprintln!("Processing, not yet done"),
+        RequestStatusResponse::Rejected(response) => {
+            if let Some(error_code) = response.error_code {
+                println!(
+                    "Rejected ({:?}): {}, error code {}",
+                    response.reject_code, response.reject_message, error_code
+                );
+            } else {
+                println!(
+                    "Rejected ({:?}): {}",
+                    response.reject_code, response.reject_message
+                );
+            }
+        }
+        RequestStatusResponse::Replied(response) => {
+            eprint!("Replied: ");
+            print_reply(&response.arg);
+        }
+        RequestStatusResponse::Done => println!("Done, response no longer available"),
+        RequestStatusResponse::Unknown => println!("Unknown"),
+    }
+}
@@ -2,7 +2,7 @@ use crate::commands::canister::call::get_effective_canister_id;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::operations::canister::get_canister_id_and_candid_path;
-use crate::lib::sign::signed_message::SignedMessageV1;
+use crate::lib::sign::signer::MessageSigner;
 use crate::util::clap::argument_from_cli::ArgumentFromCliPositionalOpt;
 use crate::util::{blob_from_arguments, get_candid_type};
 use anyhow::{anyhow, bail};
@@ -13,11 +13,8 @@ use dfx_core::identity::CallSender;
 use dfx_core::json::save_json_file;
 use ic_utils::interfaces::management_canister::MgmtMethod;
 use slog::info;
-use std::convert::TryInto;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::SystemTime;
-use time::OffsetDateTime;
 
 /// Sign a canister call and generate message file.
 #[derive(Parser)]
@@ -112,40 +109,7 @@ pub async fn exec(
         false,
         opts.always_assist,
     )?;
-    let agent = env.get_agent();
-
-    let network_descriptor = env.get_network_descriptor();
-    let network = network_descriptor
-        .providers
-        .first()
-        .expect("Cannot get network provider (url).")
-        .to_string();
-
-    let sender = env
-        .get_selected_identity_principal()
-        .expect("Selected identity not instantiated.");
-
-    let timeout = humantime::parse_duration(&opts.expire_after)
-        .map_err(|_| anyhow!("Cannot parse expire_after as a duration (e.g. `1h`, `1h 30m`)"))?;
-    //let timeout = Duration::from_secs(opts.expire_after);
-    let expiration_system_time = SystemTime::now()
-        .checked_add(timeout)
-        .ok_or_else(|| anyhow!("Time wrapped around."))?;
-    let creation = OffsetDateTime::now_utc();
-    let expiration = creation
-        .checked_add(timeout.try_into()?)
-        .ok_or_else(|| anyhow!("Expiration datetime overflow."))?;
-
-    let message_template = SignedMessageV1::new(
-        creation,
-        expiration,
-        network,
-        network_descriptor.is_ic,
-        sender,
-        canister_id,
-        method_name.to_string(),
-        arg_value.clone(),
-    );
+    let signer = MessageSigner::new(env, &opts.expire_after)?;
 
     let file_name = opts.file;
     if Path::new(&file_name).exists() {
@@ -168,35 +132,24 @@ pub async fn exec(
     };
 
     if is_query {
-        let signed_query = agent
+        let signed_query = signer
+            .agent()
             .query(&canister_id, method_name)
             .with_effective_canister_id(effective_canister_id)
-            .with_arg(arg_value)
-            .expire_at(expiration_system_time)
+            .with_arg(arg_value.clone())
+            .expire_at(signer.expiration_system_time())
             .sign()?;
-        let message = message_template
-            .clone()
+        let message = signer
+            .message_template(canister_id, method_name, arg_value)
             .with_call_type("query".to_string())
+            .with_effective_canister_id(effective_canister_id)
             .with_content(hex::encode(signed_query.signed_query));
         save_json_file(&file_name, &message)?;
         info!(log, "Query message generated at [{}]", file_name.display());
         Ok(())
     } else {
-        let signed_update = agent
-            .update(&canister_id, method_name)
-            .with_effective_canister_id(effective_canister_id)
-            .with_arg(arg_value)
-            .expire_at(expiration_system_time)
-            .sign()?;
-        let request_id = signed_update.request_id;
-        let message = message_template
-            .clone()
-            .with_call_type("update".to_string())
-            .with_request_id(request_id)
-            .with_content(hex::encode(&signed_update.signed_update));
-        let signed_request_status = agent.sign_request_status(canister_id, request_id)?;
-        let message = message
-            .with_signed_request_status(hex::encode(signed_request_status.signed_request_status));
+        let message =
+            signer.sign_update(canister_id, effective_canister_id, method_name, arg_value)?;
         save_json_file(&file_name, &message)?;
         info!(
             log,
@@ -1,5 +1,8 @@
 use std::{
     fmt::{self, Display, Formatter},
+    fs::File,
+    io::{Read, Write},
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
@@ -17,7 +20,9 @@ use crate::lib::{
     error::{DfxError, DfxResult},
     operations::canister::{
         delete_canister_snapshot, get_canister_status, list_canister_snapshots,
-        load_canister_snapshot, take_canister_snapshot,
+        load_canister_snapshot, read_canister_snapshot_data, read_canister_snapshot_metadata,
+        snapshot_data::{SnapshotMetadata, SnapshotReadKind, SnapshotUploadKind},
+        take_canister_snapshot, upload_canister_snapshot_data, upload_canister_snapshot_metadata,
     },
     root_key::fetch_root_key_if_needed,
 };
@@ -58,8 +63,39 @@ enum SnapshotSubcommand {
         /// The ID of the snapshot to delete.
         snapshot: SnapshotId,
     },
+    /// Downloads a snapshot's wasm module, memories and metadata to a local directory.
+    Download {
+        /// The canister to download the snapshot from.
+        canister: String,
+        /// The ID of the snapshot to download.
+        snapshot: SnapshotId,
+        /// The directory to write the snapshot to. It must be empty or not exist yet.
+        #[arg(long)]
+        dir: PathBuf,
+    },
+    /// Uploads a snapshot previously saved with `dfx canister snapshot download` to a canister.
+    /// It can then be applied with `dfx canister snapshot load`.
+    Upload {
+        /// The canister to upload the snapshot to.
+        canister: String,
+        /// The directory containing the downloaded snapshot.
+        #[arg(long)]
+        dir: PathBuf,
+        /// If a snapshot ID is specified, the uploaded snapshot will replace it and reuse the ID.
+        #[arg(long)]
+        replace: Option<SnapshotId>,
+    },
 }
 
+/// Maximum number of bytes transferred in a single snapshot data call.
+const SNAPSHOT_CHUNK_SIZE: u64 = 2_000_000;
+
+const METADATA_FILE: &str = "metadata.json";
+const WASM_MODULE_FILE: &str = "wasm_module.bin";
+const MAIN_MEMORY_FILE: &str = "main_memory.bin";
+const STABLE_MEMORY_FILE: &str = "stable_memory.bin";
+const WASM_CHUNK_STORE_DIR: &str = "wasm_chunk_store";
+
 #[derive(Clone)]
 struct SnapshotId(Vec<u8>);
 
@@ -93,6 +129,16 @@ pub async fn exec(
             delete(env, canister, snapshot, call_sender).await?
         }
         SnapshotSubcommand::List { canister } => list(env, canister, call_sender).await?,
+        SnapshotSubcommand::Download {
+            canister,
+            snapshot,
+            dir,
+        } => download(env, canister, snapshot, dir, call_sender).await?,
+        SnapshotSubcommand::Upload {
+            canister,
+            dir,
+            replace,
+        } => upload(env, canister, dir, replace, call_sender).await?,
     }
     Ok(())
 }
@@ -206,3 +252,206 @@ async fn list(env: &dyn Environment, canister: String, call_sender: &CallSender)
     }
     Ok(())
 }
+
+async fn download(
+    env: &dyn Environment,
+    canister: String,
+    snapshot: SnapshotId,
+    dir: PathBuf,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister
+        .parse()
+        .or_else(|_| env.get_canister_id_store()?.get(&canister))?;
+    if dir.exists() && dfx_core::fs::read_dir(&dir)?.next().is_some() {
+        bail!(
+            "Directory {} is not empty. Choose an empty or new directory to download the snapshot to",
+            dir.display()
+        );
+    }
+    dfx_core::fs::create_dir_all(&dir.join(WASM_CHUNK_STORE_DIR))?;
+
+    let metadata = read_canister_snapshot_metadata(env, canister_id, &snapshot.0, call_sender)
+        .await
+        .with_context(|| format!("Failed to read snapshot {snapshot} in canister {canister}"))?;
+
+    let spinner = env.new_spinner(format!("Downloading snapshot {snapshot}...").into());
+    let sections: [(&str, u64, fn(u64, u64) -> SnapshotReadKind); 3] = [
+        (
+            WASM_MODULE_FILE,
+            metadata.wasm_module_size,
+            |offset, size| SnapshotReadKind::WasmModule { offset, size },
+        ),
+        (
+            MAIN_MEMORY_FILE,
+            metadata.wasm_memory_size,
+            |offset, size| SnapshotReadKind::MainMemory { offset, size },
+        ),
+        (
+            STABLE_MEMORY_FILE,
+            metadata.stable_memory_size,
+            |offset, size| SnapshotReadKind::StableMemory { offset, size },
+        ),
+    ];
+    for (file_name, total_size, kind) in sections {
+        spinner.set_message(format!("Downloading {file_name}...").into());
+        let path = dir.join(file_name);
+        let mut file =
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut offset = 0;
+        while offset < total_size {
+            let size = SNAPSHOT_CHUNK_SIZE.min(total_size - offset);
+            let chunk = read_canister_snapshot_data(
+                env,
+                canister_id,
+                &snapshot.0,
+                &kind(offset, size),
+                call_sender,
+            )
+            .await?;
+            file.write_all(&chunk)
+                .with_context(|| format!("Failed to write to {}", path.display()))?;
+            offset += size;
+        }
+    }
+    spinner.set_message("Downloading wasm chunk store...".into());
+    for chunk_hash in &metadata.wasm_chunk_store {
+        let chunk = read_canister_snapshot_data(
+            env,
+            canister_id,
+            &snapshot.0,
+            &SnapshotReadKind::WasmChunk {
+                hash: chunk_hash.hash.clone(),
+            },
+            call_sender,
+        )
+        .await?;
+        dfx_core::fs::write(chunk_store_path(&dir, &chunk_hash.hash), chunk)?;
+    }
+    // The metadata is written last so that an interrupted download cannot be uploaded by accident.
+    let metadata_json = serde_json::to_string_pretty(&metadata)?;
+    dfx_core::fs::write(dir.join(METADATA_FILE), metadata_json)?;
+    spinner.finish_and_clear();
+
+    info!(
+        env.get_logger(),
+        "Downloaded snapshot {snapshot} of canister {canister} to {}",
+        dir.display()
+    );
+    Ok(())
+}
+
+async fn upload(
+    env: &dyn Environment,
+    canister: String,
+    dir: PathBuf,
+    replace: Option<SnapshotId>,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let canister_id = canister
+        .parse()
+        .or_else(|_| env.get_canister_id_store()?.get(&canister))?;
+    let metadata_path = dir.join(METADATA_FILE);
+    let metadata: SnapshotMetadata = serde_json::from_str(&dfx_core::fs::read_to_string(
+        &metadata_path,
+    )?)
+    .with_context(|| {
+        format!(
+            "Failed to parse snapshot metadata in {}",
+            metadata_path.display()
+        )
+    })?;
+    let sections: [(&str, u64, fn(u64) -> SnapshotUploadKind); 3] = [
+        (WASM_MODULE_FILE, metadata.wasm_module_size, |offset| {
+            SnapshotUploadKind::WasmModule { offset }
+        }),
+        (MAIN_MEMORY_FILE, metadata.wasm_memory_size, |offset| {
+            SnapshotUploadKind::MainMemory { offset }
+        }),
+        (STABLE_MEMORY_FILE, metadata.stable_memory_size, |offset| {
+            SnapshotUploadKind::StableMemory { offset }
+        }),
+    ];
+    for (file_name, expected_size, _) in &sections {
+        let path = dir.join(file_name);
+        let actual_size = dfx_core::fs::metadata(&path)?.len();
+        if actual_size != *expected_size {
+            bail!(
+                "{} is {actual_size} bytes but the snapshot metadata expects {expected_size} bytes",
+                path.display()
+            );
+        }
+    }
+    for chunk_hash in &metadata.wasm_chunk_store {
+        let path = chunk_store_path(&dir, &chunk_hash.hash);
+        if !path.exists() {
+            bail!("Wasm chunk {} is missing", path.display());
+        }
+    }
+
+    let snapshot_id = upload_canister_snapshot_metadata(
+        env,
+        canister_id,
+        replace.as_ref().map(|x| &*x.0),
+        &metadata,
+        call_sender,
+    )
+    .await
+    .with_context(|| format!("Failed to upload snapshot to canister {canister}"))?;
+
+    let spinner = env.new_spinner("Uploading snapshot...".into());
+    for (file_name, _, kind) in sections {
+        spinner.set_message(format!("Uploading {file_name}...").into());
+        let path = dir.join(file_name);
+        let mut file =
+            File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut offset = 0;
+        loop {
+            let mut chunk = Vec::with_capacity(SNAPSHOT_CHUNK_SIZE as usize);
+            (&mut file)
+                .take(SNAPSHOT_CHUNK_SIZE)
+                .read_to_end(&mut chunk)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            if chunk.is_empty() {
+                break;
+            }
+            upload_canister_snapshot_data(
+                env,
+                canister_id,
+                &snapshot_id,
+                &kind(offset),
+                &chunk,
+                call_sender,
+            )
+            .await?;
+            offset += chunk.len() as u64;
+        }
+    }
+    spinner.set_message("Uploading wasm chunk store...".into());
+    for chunk_hash in &metadata.wasm_chunk_store {
+        let chunk = dfx_core::fs::read(&chunk_store_path(&dir, &chunk_hash.hash))?;
+        upload_canister_snapshot_data(
+            env,
+            canister_id,
+            &snapshot_id,
+            &SnapshotUploadKind::WasmChunk,
+            &chunk,
+            call_sender,
+        )
+        .await?;
+    }
+    spinner.finish_and_clear();
+
+    info!(
+        env.get_logger(),
+        "Uploaded snapshot from {} to canister {canister}. Snapshot ID: {}",
+        dir.display(),
+        SnapshotId(snapshot_id)
+    );
+    Ok(())
+}
+
+fn chunk_store_path(dir: &Path, hash: &[u8]) -> PathBuf {
+    dir.join(WASM_CHUNK_STORE_DIR)
+        .join(format!("{}.bin", hex::encode(hash)))
+}
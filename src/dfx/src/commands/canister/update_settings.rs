@@ -7,9 +7,10 @@ use crate::lib::ic_attributes::{
     get_reserved_cycles_limit, get_wasm_memory_limit, get_wasm_memory_threshold, CanisterSettings,
 };
 use crate::lib::operations::canister::{
-    get_canister_status, skip_remote_canister, update_settings,
+    get_canister_status, reconcile_settings, skip_remote_canister, update_settings,
 };
 use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::sign::signer::{save_signed_messages, MessageSigner};
 use crate::util::ask_for_consent;
 use crate::util::clap::parsers::{
     compute_allocation_parser, freezing_threshold_parser, memory_allocation_parser,
@@ -25,6 +26,7 @@ use dfx_core::identity::CallSender;
 use fn_error_context::context;
 use ic_agent::identity::Identity;
 use ic_utils::interfaces::management_canister::StatusCallResult;
+use std::path::PathBuf;
 
 /// Update one or more of a canister's settings (i.e its controller, compute allocation, or memory allocation.)
 #[derive(Parser, Debug)]
@@ -124,6 +126,52 @@ pub struct UpdateSettingsOpts {
     /// This option only works for a local PocketIC instance.
     #[arg(long)]
     impersonate: Option<Principal>,
+
+    /// Updates the settings that differ from the ones configured in dfx.json for the current network.
+    /// The differences are displayed and need to be confirmed before they are applied, unless --yes is given.
+    #[arg(
+        long,
+        conflicts_with_all([
+            "set_controller",
+            "add_controller",
+            "remove_controller",
+            "compute_allocation",
+            "memory_allocation",
+            "freezing_threshold",
+            "reserved_cycles_limit",
+            "wasm_memory_limit",
+            "wasm_memory_threshold",
+            "log_visibility",
+            "add_log_viewer",
+            "remove_log_viewer",
+            "set_log_viewer",
+        ])
+    )]
+    from_config: bool,
+
+    /// Signs the update_settings call and saves it to a file, instead of sending it.
+    /// The call can be sent later with `dfx canister send`.
+    #[arg(
+        long,
+        conflicts_with_all([
+            "all",
+            "add_controller",
+            "remove_controller",
+            "add_log_viewer",
+            "remove_log_viewer",
+            "impersonate",
+            "from_config",
+        ])
+    )]
+    sign_only: bool,
+
+    /// Specifies how long the signed message will be valid, default to be 5 minutes.
+    #[arg(long, default_value = "5m", requires("sign_only"))]
+    expire_after: String,
+
+    /// Specifies the file name of the signed message.
+    #[arg(long, default_value = "message.json", requires("sign_only"))]
+    message_file: PathBuf,
 }
 
 pub async fn exec(
@@ -154,7 +202,17 @@ pub async fn exec(
         }
     }
 
-    fetch_root_key_if_needed(env).await?;
+    if opts.sign_only {
+        if *call_sender != CallSender::SelectedId {
+            bail!("--sign-only doesn't support proxying through the wallet canister, please use `--no-wallet`.");
+        }
+    } else {
+        fetch_root_key_if_needed(env).await?;
+    }
+
+    if opts.from_config {
+        return update_settings_from_config(env, &opts, call_sender).await;
+    }
 
     if !opts.yes && user_is_removing_themselves_as_controller(env, call_sender, &opts)? {
         ask_for_consent(env, "You are trying to remove yourself as a controller of this canister. This may leave this canister un-upgradeable.")?
@@ -172,6 +230,7 @@ pub async fn exec(
         .context("Failed to determine all new controllers given in --set-controller.")?;
 
     let canister_id_store = env.get_canister_id_store()?;
+    let network = env.get_network_descriptor();
 
     if let Some(canister_name_or_id) = opts.canister.as_deref() {
         let config = env.get_config()?;
@@ -183,18 +242,42 @@ pub async fn exec(
         let canister_name = canister_id_store.get_name(&textual_cid);
         let canister_name = canister_name.as_deref();
 
-        let compute_allocation =
-            get_compute_allocation(opts.compute_allocation, config_interface, canister_name)?;
-        let memory_allocation =
-            get_memory_allocation(opts.memory_allocation, config_interface, canister_name)?;
-        let freezing_threshold =
-            get_freezing_threshold(opts.freezing_threshold, config_interface, canister_name)?;
-        let reserved_cycles_limit =
-            get_reserved_cycles_limit(opts.reserved_cycles_limit, config_interface, canister_name)?;
-        let wasm_memory_limit =
-            get_wasm_memory_limit(opts.wasm_memory_limit, config_interface, canister_name)?;
-        let wasm_memory_threshold =
-            get_wasm_memory_threshold(opts.wasm_memory_threshold, config_interface, canister_name)?;
+        let compute_allocation = get_compute_allocation(
+            opts.compute_allocation,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
+        let memory_allocation = get_memory_allocation(
+            opts.memory_allocation,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
+        let freezing_threshold = get_freezing_threshold(
+            opts.freezing_threshold,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
+        let reserved_cycles_limit = get_reserved_cycles_limit(
+            opts.reserved_cycles_limit,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
+        let wasm_memory_limit = get_wasm_memory_limit(
+            opts.wasm_memory_limit,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
+        let wasm_memory_threshold = get_wasm_memory_threshold(
+            opts.wasm_memory_threshold,
+            config_interface,
+            canister_name,
+            &network.name,
+        )?;
         let mut current_status: Option<StatusCallResult> = None;
         if let Some(log_visibility) = &opts.log_visibility_opt {
             if log_visibility.require_current_settings() {
@@ -207,6 +290,7 @@ pub async fn exec(
             current_status.as_ref(),
             config_interface,
             canister_name,
+            &network.name,
         )?;
         if let Some(added) = &opts.add_controller {
             if current_status.is_none() {
@@ -254,8 +338,19 @@ pub async fn exec(
             wasm_memory_threshold,
             log_visibility,
         };
-        update_settings(env, canister_id, settings, call_sender).await?;
-        display_controller_update(&opts, canister_name_or_id);
+        if opts.sign_only {
+            let signer = MessageSigner::new(env, &opts.expire_after)?;
+            let message = signer.sign_update_settings(canister_id, settings)?;
+            save_signed_messages(&opts.message_file, vec![message])?;
+            eprintln!(
+                "Signed the settings update of {:?}. Send it with `dfx canister send {}`.",
+                canister_name_or_id,
+                opts.message_file.display()
+            );
+        } else {
+            update_settings(env, canister_id, settings, call_sender).await?;
+            display_controller_update(&opts, canister_name_or_id);
+        }
     } else if opts.all {
         // Update all canister settings.
         let config = env.get_config_or_anyhow()?;
@@ -272,6 +367,7 @@ pub async fn exec(
                     opts.compute_allocation,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to get compute allocation for {canister_name}.")
@@ -280,12 +376,14 @@ pub async fn exec(
                     opts.memory_allocation,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to get memory allocation for {canister_name}."))?;
                 let freezing_threshold = get_freezing_threshold(
                     opts.freezing_threshold,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to get freezing threshold for {canister_name}.")
@@ -294,6 +392,7 @@ pub async fn exec(
                     opts.reserved_cycles_limit,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to get reserved cycles limit for {canister_name}.")
@@ -302,12 +401,14 @@ pub async fn exec(
                     opts.wasm_memory_limit,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to get Wasm memory limit for {canister_name}."))?;
                 let wasm_memory_threshold = get_wasm_memory_threshold(
                     opts.wasm_memory_threshold,
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| {
                     format!("Failed to get Wasm memory threshold for {canister_name}.")
@@ -325,6 +426,7 @@ pub async fn exec(
                     current_status.as_ref(),
                     Some(config_interface),
                     Some(canister_name),
+                    &network.name,
                 )
                 .with_context(|| format!("Failed to get log visibility for {canister_name}."))?;
                 if let Some(added) = &opts.add_controller {
@@ -385,6 +487,47 @@ pub async fn exec(
     Ok(())
 }
 
+async fn update_settings_from_config(
+    env: &dyn Environment,
+    opts: &UpdateSettingsOpts,
+    call_sender: &CallSender,
+) -> DfxResult {
+    let config = env.get_config_or_anyhow()?;
+    let config_interface = config.get_config();
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let canisters = if let Some(canister_name_or_id) = opts.canister.as_deref() {
+        let canister_id = CanisterId::from_text(canister_name_or_id)
+            .or_else(|_| canister_id_store.get(canister_name_or_id))?;
+        let canister_name = canister_id_store
+            .get_name(&canister_id.to_text())
+            .unwrap_or_else(|| canister_name_or_id.to_string());
+        vec![(canister_name, canister_id)]
+    } else {
+        let mut canisters = vec![];
+        for canister_name in config_interface.canisters.iter().flat_map(|c| c.keys()) {
+            if skip_remote_canister(env, canister_name)? {
+                continue;
+            }
+            canisters.push((canister_name.clone(), canister_id_store.get(canister_name)?));
+        }
+        canisters
+    };
+
+    for (canister_name, canister_id) in canisters {
+        reconcile_settings(
+            env,
+            config_interface,
+            &canister_name,
+            canister_id,
+            call_sender,
+            opts.yes,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
 fn user_is_removing_themselves_as_controller(
     env: &dyn Environment,
     call_sender: &CallSender,
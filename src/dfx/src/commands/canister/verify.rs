@@ -0,0 +1,185 @@
+use crate::config::cache::VersionCache;
+use crate::lib::builders::{BuildConfig, WasmBuildOutput};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::CanisterPool;
+use crate::lib::operations::canister::add_canisters_with_ids;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
+use crate::lib::wasm::file::{compress_bytes, decompress_bytes, read_wasm_module};
+use anyhow::{anyhow, bail};
+use clap::Parser;
+use ic_agent::AgentError;
+use ic_wasm::metadata::{get_metadata, list_metadata};
+use sha2::{Digest, Sha256};
+use std::ffi::OsStr;
+
+/// Verifies that the Wasm module installed in a canister matches a local build of the canister.
+#[derive(Parser)]
+pub struct CanisterVerifyOpts {
+    /// Specifies the name of the canister to verify.
+    canister_name: String,
+
+    /// Compares against the existing build artifact instead of building the canister first.
+    #[arg(long)]
+    no_build: bool,
+}
+
+pub async fn exec(env: &dyn Environment, opts: CanisterVerifyOpts) -> DfxResult {
+    let log = env.get_logger();
+    let config = env.get_config_or_anyhow()?;
+    let canister_name = opts.canister_name.as_str();
+    let network_name = &env.get_network_descriptor().name;
+    if config
+        .get_config()
+        .is_remote_canister(canister_name, network_name)?
+    {
+        bail!("Canister '{canister_name}' is a remote canister on network '{network_name}' and cannot be built locally.");
+    }
+    let canister_id = env.get_canister_id_store()?.get(canister_name)?;
+
+    let required_canisters = config
+        .get_config()
+        .get_canister_names_with_dependencies(Some(canister_name))?;
+    let canisters_to_load = add_canisters_with_ids(&required_canisters, env, &config);
+    let canister_pool = CanisterPool::load(env, false, &canisters_to_load)?;
+    let canister = canister_pool
+        .get_first_canister_with_name(canister_name)
+        .ok_or_else(|| anyhow!("Canister '{canister_name}' was not found in dfx.json."))?;
+
+    if !opts.no_build {
+        VersionCache::install(env, &env.get_cache().version_str())?;
+        let canisters_to_build = required_canisters
+            .into_iter()
+            .filter(|name| {
+                !config
+                    .get_config()
+                    .is_remote_canister(name, network_name)
+                    .unwrap_or(false)
+            })
+            .collect();
+        // Verify a fresh build, rather than one restored from the build cache.
+        let build_config = BuildConfig::from_config(&config)?
+            .with_canisters_to_build(canisters_to_build)
+            .with_cache(None);
+        canister_pool.build_or_fail(env, log, &build_config).await?;
+    }
+
+    let wasm_path = canister.get_info().get_build_wasm_path();
+    let wasm = dfx_core::fs::read(&wasm_path)?;
+    let local_hash = Sha256::digest(&wasm).to_vec();
+
+    fetch_root_key_if_needed(env).await?;
+    let agent = env.get_agent();
+    let deployed_hash = read_state_tree_canister_module_hash(agent, canister_id)
+        .await?
+        .ok_or_else(|| anyhow!("Canister '{canister_name}' has no module installed."))?;
+
+    println!("Local module hash:    0x{}", hex::encode(&local_hash));
+    println!("Deployed module hash: 0x{}", hex::encode(&deployed_hash));
+    if local_hash == deployed_hash {
+        println!("The deployed module of canister '{canister_name}' matches the local build.");
+        return Ok(());
+    }
+
+    // Check whether the deployed module is the local build with different post-processing.
+    let gzipped = wasm_path.extension() == Some(OsStr::new("gz"));
+    let mut variants = vec![if gzipped {
+        ("without gzip compression", decompress_bytes(&wasm)?)
+    } else {
+        ("with gzip compression", compress_bytes(&wasm)?)
+    }];
+    if let Some(WasmBuildOutput::File(build_output_path)) =
+        canister.get_build_output().map(|output| &output.wasm)
+    {
+        if *build_output_path != wasm_path {
+            let unprocessed = dfx_core::fs::read(build_output_path)?;
+            variants.push((
+                "with gzip compression, but without shrinking, optimization or metadata",
+                compress_bytes(&unprocessed)?,
+            ));
+            variants.push((
+                "without shrinking, optimization, metadata or gzip compression",
+                unprocessed,
+            ));
+        }
+    }
+    let matching_variant = variants
+        .iter()
+        .find(|(_, bytes)| Sha256::digest(bytes).as_slice() == deployed_hash);
+    if let Some((description, _)) = matching_variant {
+        println!("The deployed module matches the local build {description}. Check the `gzip`, `shrink`, `optimize` and `metadata` settings in dfx.json.");
+    }
+
+    // Compare the metadata sections that are readable from the state tree.
+    let module = read_wasm_module(&wasm_path)?;
+    let sections = list_metadata_sections(&module);
+    let mut sections_differ = false;
+    if !sections.is_empty() {
+        println!("Metadata sections:");
+    }
+    for (name, local_content) in sections {
+        let status = match agent.read_state_canister_metadata(canister_id, &name).await {
+            Ok(deployed_content) if deployed_content == local_content => "matches".to_string(),
+            Ok(_) => {
+                sections_differ = true;
+                "differs".to_string()
+            }
+            Err(AgentError::LookupPathAbsent(_)) => {
+                sections_differ = true;
+                "missing from the deployed module".to_string()
+            }
+            Err(e) => format!("could not be read ({e})"),
+        };
+        println!("  {name}: {status}");
+    }
+    if matching_variant.is_none() && !sections_differ {
+        println!("The difference is in the code of the module. Check that the toolchain, its version and the build settings match those used for the deployed module.");
+    }
+
+    bail!("The deployed module of canister '{canister_name}' does not match the local build.")
+}
+
+/// Returns the names and contents of the `icp:public` and `icp:private` metadata sections of a module.
+fn list_metadata_sections(module: &walrus::Module) -> Vec<(String, Vec<u8>)> {
+    list_metadata(module)
+        .iter()
+        .filter_map(|section| {
+            let name = section
+                .strip_prefix("icp:public ")
+                .or_else(|| section.strip_prefix("icp:private "))?;
+            let content = get_metadata(module, name)?;
+            Some((name.to_string(), content.to_vec()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_wasm::metadata::{add_metadata, Kind};
+
+    #[test]
+    fn test_list_metadata_sections() {
+        let mut module = walrus::Module::default();
+        add_metadata(
+            &mut module,
+            Kind::Public,
+            "candid:service",
+            b"service : {}".to_vec(),
+        );
+        add_metadata(&mut module, Kind::Private, "secret", b"42".to_vec());
+        module.customs.add(walrus::RawCustomSection {
+            name: "name".to_string(),
+            data: vec![],
+        });
+
+        assert_eq!(
+            list_metadata_sections(&module),
+            vec![
+                ("candid:service".to_string(), b"service : {}".to_vec()),
+                ("secret".to_string(), b"42".to_vec()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::lib::cycles_ledger_types::index::MAINNET_CYCLES_LEDGER_INDEX_CANISTER_ID;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::operations::cycles_ledger;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::transaction_history::{
+    format_history, parse_cycles_index_transaction, parse_icrc3_block, HistoryOutputFormat,
+    Icrc3Block, TransactionRecord,
+};
+use crate::util::clap::parsers::icrc_subaccount_parser;
+use crate::util::{format_as_trillions, pretty_thousand_separators};
+use anyhow::anyhow;
+use candid::Principal;
+use clap::Parser;
+use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use num_traits::ToPrimitive;
+use slog::warn;
+
+/// Number of transactions or blocks requested at a time.
+const PAGE_SIZE: u64 = 100;
+
+/// Prints the cycles ledger transaction history of an account, newest first.
+///
+/// On the IC mainnet, or with --index-canister-id, the history is read from an index canister.
+/// Otherwise the blocks of the cycles ledger are read and filtered.
+#[derive(Parser)]
+pub struct HistoryOpts {
+    /// Specifies a Principal to get the history of
+    #[arg(long)]
+    owner: Option<Principal>,
+
+    /// Subaccount of the selected identity to get the history of
+    #[arg(long, value_parser = icrc_subaccount_parser)]
+    subaccount: Option<Subaccount>,
+
+    /// Maximum number of transactions to print.
+    #[arg(long, default_value = "100")]
+    limit: u64,
+
+    /// Only prints transactions older than the transaction with this id.
+    /// Use it with the id of the last printed transaction to see the next page.
+    #[arg(long)]
+    before: Option<u64>,
+
+    /// Specifies the format of the output.
+    #[arg(long, value_enum, default_value_t = HistoryOutputFormat::Text)]
+    output: HistoryOutputFormat,
+
+    /// Print amounts as raw values (without downscaling to trillions of cycles).
+    #[arg(long)]
+    precise: bool,
+
+    /// Canister ID of the index canister of the cycles ledger.
+    #[arg(long)]
+    index_canister_id: Option<Principal>,
+}
+
+pub async fn exec(env: &dyn Environment, opts: HistoryOpts) -> DfxResult {
+    fetch_root_key_if_needed(env).await?;
+
+    let agent = env.get_agent();
+    let owner = opts.owner.unwrap_or_else(|| {
+        env.get_selected_identity_principal()
+            .expect("Selected identity not instantiated.")
+    });
+    let account = Account {
+        owner,
+        subaccount: opts.subaccount,
+    };
+    let index_canister_id = opts.index_canister_id.or_else(|| {
+        env.get_network_descriptor()
+            .is_ic
+            .then_some(MAINNET_CYCLES_LEDGER_INDEX_CANISTER_ID)
+    });
+
+    let transactions = if let Some(index_canister_id) = index_canister_id {
+        history_from_index(env, index_canister_id, account, opts.before, opts.limit).await?
+    } else {
+        history_from_blocks(env, account, opts.before, opts.limit).await?
+    };
+
+    println!(
+        "{}",
+        format_history(&transactions, opts.output, |cycles| {
+            if opts.precise {
+                format!("{cycles} cycles")
+            } else {
+                format!(
+                    "{} TC",
+                    pretty_thousand_separators(format_as_trillions(cycles))
+                )
+            }
+        })?
+    );
+
+    Ok(())
+}
+
+async fn history_from_index(
+    env: &dyn Environment,
+    index_canister_id: Principal,
+    account: Account,
+    before: Option<u64>,
+    limit: u64,
+) -> DfxResult<Vec<TransactionRecord>> {
+    let mut transactions: Vec<TransactionRecord> = vec![];
+    let mut start = before;
+    while (transactions.len() as u64) < limit {
+        let max_results = PAGE_SIZE.min(limit - transactions.len() as u64);
+        let page = cycles_ledger::account_transactions(
+            env.get_agent(),
+            index_canister_id,
+            account,
+            start,
+            max_results,
+        )
+        .await?;
+        for transaction in &page {
+            match parse_cycles_index_transaction(transaction)? {
+                Some(record) => transactions.push(record),
+                None => warn!(
+                    env.get_logger(),
+                    "Skipping transaction {} with unsupported kind '{}'.",
+                    transaction.id,
+                    transaction.transaction.kind
+                ),
+            }
+        }
+        match page.last() {
+            Some(last) if page.len() as u64 == max_results => {
+                start = Some(
+                    last.id
+                        .0
+                        .to_u64()
+                        .ok_or_else(|| anyhow!("Invalid transaction id {}.", last.id))?,
+                )
+            }
+            _ => break,
+        }
+    }
+    Ok(transactions)
+}
+
+/// Reads the blocks of the cycles ledger from newest to oldest and keeps the transactions that involve the account.
+async fn history_from_blocks(
+    env: &dyn Environment,
+    account: Account,
+    before: Option<u64>,
+    limit: u64,
+) -> DfxResult<Vec<TransactionRecord>> {
+    let log_length = cycles_ledger::get_blocks(env.get_agent(), 0, 0)
+        .await?
+        .log_length
+        .0
+        .to_u64()
+        .ok_or_else(|| anyhow!("The cycles ledger has too many blocks."))?;
+    let mut end = before.unwrap_or(log_length).min(log_length);
+
+    let mut transactions = vec![];
+    while end > 0 && (transactions.len() as u64) < limit {
+        let start = end.saturating_sub(PAGE_SIZE);
+        let result = cycles_ledger::get_blocks(env.get_agent(), start, end - start).await?;
+        if result.blocks.is_empty() {
+            break;
+        }
+        let mut page = vec![];
+        for block in &result.blocks {
+            let id = block
+                .id
+                .0
+                .to_u64()
+                .ok_or_else(|| anyhow!("Invalid block id {}.", block.id))?;
+            match parse_icrc3_block(id, &block.block)? {
+                Icrc3Block::Transaction(transaction, accounts) => {
+                    if accounts.contains(&account) {
+                        page.push(transaction);
+                    }
+                }
+                Icrc3Block::Unsupported { operation } => warn!(
+                    env.get_logger(),
+                    "Skipping block {id} with unsupported operation '{operation}'."
+                ),
+            }
+        }
+        page.sort_by(|a, b| b.id.cmp(&a.id));
+        transactions.extend(page);
+        end = result
+            .blocks
+            .iter()
+            .filter_map(|block| block.id.0.to_u64())
+            .min()
+            .unwrap_or(start);
+    }
+    transactions.truncate(limit as usize);
+    Ok(transactions)
+}
@@ -1,25 +1,49 @@
+use crate::commands::ledger::top_up::MEMO_TOP_UP_CANISTER;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::lib::ledger_types::Memo;
+use crate::lib::nns_types::account_identifier::Subaccount as NnsSubaccount;
+use crate::lib::nns_types::icpts::TRANSACTION_FEE;
+use crate::lib::operations::canister::get_canister_status;
+use crate::lib::operations::cmc::{notify_top_up, transfer_cmc};
 use crate::lib::operations::cycles_ledger;
+use crate::lib::operations::ledger::xdr_permyriad_per_icp;
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::clap::parsers::{cycle_amount_parser, icrc_subaccount_parser};
+use crate::util::currency_conversion::icp_for_cycles;
+use anyhow::{bail, Context};
 use candid::Principal;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use dfx_core::identity::CallSender;
 use icrc_ledger_types::icrc1::account::Subaccount;
-use slog::warn;
+use num_traits::ToPrimitive;
+use slog::{info, warn};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Send cycles to a canister.
 #[derive(Parser)]
 pub struct TopUpOpts {
     /// Send cycles to this canister.
-    to: String,
+    #[arg(required_unless_present("project"))]
+    to: Option<String>,
 
     /// The number of cycles to send.
-    #[arg(value_parser = cycle_amount_parser)]
-    amount: u128,
+    #[arg(value_parser = cycle_amount_parser, required_unless_present("project"))]
+    amount: Option<u128>,
+
+    /// Top up every canister of the project whose balance is below the `min_balance` of its `cycles` policy in dfx.json.
+    #[arg(long, conflicts_with_all(["to", "amount"]))]
+    project: bool,
+
+    /// Print the top-ups that --project would make without making them.
+    #[arg(long, requires("project"))]
+    dry_run: bool,
 
-    /// Transfer cycles from this subaccount.
+    /// Pay for the top-ups of --project with cycles from the cycles ledger or with ICP.
+    #[arg(long, value_enum, default_value_t = TopUpSource::CyclesLedger, requires("project"))]
+    source: TopUpSource,
+
+    /// Transfer cycles (or ICP, with --source icp) from this subaccount.
     #[arg(long, value_parser = icrc_subaccount_parser)]
     from_subaccount: Option<Subaccount>,
 
@@ -29,11 +53,15 @@ pub struct TopUpOpts {
     created_at_time: Option<u64>,
 }
 
-pub async fn exec(env: &dyn Environment, opts: TopUpOpts) -> DfxResult {
-    let agent = env.get_agent();
-
-    let amount = opts.amount;
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopUpSource {
+    /// Withdraw cycles from the cycles ledger.
+    CyclesLedger,
+    /// Convert ICP into cycles with the cycles minting canister.
+    Icp,
+}
 
+pub async fn exec(env: &dyn Environment, opts: TopUpOpts) -> DfxResult {
     fetch_root_key_if_needed(env).await?;
 
     let created_at_time = opts.created_at_time.unwrap_or(
@@ -43,29 +71,159 @@ pub async fn exec(env: &dyn Environment, opts: TopUpOpts) -> DfxResult {
             .as_nanos() as u64,
     );
 
-    let to = get_canister_id(env, &opts.to)?;
-    let result = cycles_ledger::withdraw(
-        agent,
-        env.get_logger(),
-        to,
-        amount,
-        created_at_time,
-        opts.from_subaccount,
-    )
-    .await;
+    if opts.project {
+        // A retry computes the top-ups from the new balances, so the ledger does not deduplicate it
+        // and there is no --created-at-time to suggest.
+        return top_up_project(env, &opts, created_at_time).await;
+    }
+
+    // clap guarantees both are present without --project
+    let to = get_canister_id(env, opts.to.as_deref().unwrap())?;
+    let amount = opts.amount.unwrap();
+    let result = withdraw(env, to, amount, opts.from_subaccount, created_at_time).await;
     if result.is_err() && opts.created_at_time.is_none() {
         warn!(
             env.get_logger(),
             "If you retry this operation, use --created-at-time {}", created_at_time
         );
     }
-    let block_index = result?;
+    result
+}
+
+async fn withdraw(
+    env: &dyn Environment,
+    to: Principal,
+    amount: u128,
+    from_subaccount: Option<Subaccount>,
+    created_at_time: u64,
+) -> DfxResult {
+    let block_index = cycles_ledger::withdraw(
+        env.get_agent(),
+        env.get_logger(),
+        to,
+        amount,
+        created_at_time,
+        from_subaccount,
+    )
+    .await?;
 
     println!("Transfer sent at block index {block_index}");
 
     Ok(())
 }
 
+/// Tops up the project's canisters that have fallen below the minimum balance of their cycles policy.
+async fn top_up_project(
+    env: &dyn Environment,
+    opts: &TopUpOpts,
+    created_at_time: u64,
+) -> DfxResult {
+    let log = env.get_logger();
+    let config = env.get_config_or_anyhow()?;
+    let config_interface = config.get_config();
+    let network = &env.get_network_descriptor().name;
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let mut top_ups = vec![];
+    for canister_name in config_interface.get_canister_names_with_dependencies(None)? {
+        let Some(policy) = config_interface.get_cycles_policy(&canister_name)? else {
+            continue;
+        };
+        if policy.target_balance() < policy.min_balance {
+            bail!(
+                "The target_balance of canister '{}' is less than its min_balance.",
+                canister_name
+            );
+        }
+        if config_interface.is_remote_canister(&canister_name, network)? {
+            continue;
+        }
+        let Ok(canister_id) = canister_id_store.get(&canister_name) else {
+            warn!(
+                log,
+                "Skipping canister '{}': it has not been created on network '{}'.",
+                canister_name,
+                network
+            );
+            continue;
+        };
+        let status = get_canister_status(env, canister_id, &CallSender::SelectedId)
+            .await
+            .with_context(|| format!("Failed to get the cycles balance of '{canister_name}'."))?;
+        let balance = status.cycles.0.to_u128().unwrap_or(u128::MAX);
+        match policy.top_up_amount(balance) {
+            Some(amount) => top_ups.push((canister_name, canister_id, balance, amount)),
+            None => info!(
+                log,
+                "Canister '{}' has {} cycles, which is not below its minimum of {} cycles.",
+                canister_name,
+                balance,
+                policy.min_balance
+            ),
+        }
+    }
+
+    if top_ups.is_empty() {
+        println!("No canister needs a top-up.");
+        return Ok(());
+    }
+
+    let xdr_permyriad_per_icp = match opts.source {
+        TopUpSource::Icp => Some(xdr_permyriad_per_icp(env.get_agent()).await?),
+        TopUpSource::CyclesLedger => None,
+    };
+    for (canister_name, canister_id, balance, amount) in top_ups {
+        let target = balance + amount;
+        if let Some(rate) = xdr_permyriad_per_icp {
+            let icp = icp_for_cycles(amount, rate)?;
+            if opts.dry_run {
+                println!("Would top up '{canister_name}' with {amount} cycles ({icp} ICP) from {balance} to {target} cycles.");
+                continue;
+            }
+            info!(
+                log,
+                "Topping up '{}' with {} ICP ({} cycles).", canister_name, icp, amount
+            );
+            let height = transfer_cmc(
+                env.get_agent(),
+                log,
+                Memo(MEMO_TOP_UP_CANISTER),
+                icp,
+                TRANSACTION_FEE,
+                opts.from_subaccount.map(NnsSubaccount),
+                canister_id,
+                Some(created_at_time),
+            )
+            .await?;
+            let cycles = notify_top_up(env.get_agent(), canister_id, height)
+                .await
+                .with_context(|| {
+                    format!("Failed to notify the cycles minting canister of the transfer at block height {height}.")
+                })?;
+            println!("Topped up '{canister_name}' with {cycles} cycles.");
+        } else {
+            if opts.dry_run {
+                println!("Would top up '{canister_name}' with {amount} cycles from {balance} to {target} cycles.");
+                continue;
+            }
+            let block_index = cycles_ledger::withdraw(
+                env.get_agent(),
+                log,
+                canister_id,
+                amount,
+                created_at_time,
+                opts.from_subaccount,
+            )
+            .await?;
+            println!(
+                "Topped up '{canister_name}' with {amount} cycles at block index {block_index}."
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn get_canister_id(env: &dyn Environment, s: &str) -> DfxResult<Principal> {
     let principal = Principal::from_text(s).or_else(|_| {
         env.get_canister_id_store()
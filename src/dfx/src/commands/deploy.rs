@@ -3,14 +3,15 @@ use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::network::network_opt::NetworkOpt;
-use crate::lib::operations::canister::deploy_canisters::deploy_canisters;
 use crate::lib::operations::canister::deploy_canisters::DeployMode::{
-    ComputeEvidence, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
+    ComputeEvidence, EstimateCost, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
 };
+use crate::lib::operations::canister::deploy_canisters::{deploy_canisters, watch_canisters};
 use crate::lib::root_key::fetch_root_key_if_needed;
 use crate::util::clap::argument_from_cli::ArgumentFromCliLongOpt;
+use crate::util::clap::build_cache_opt::BuildCacheOpt;
 use crate::util::clap::install_mode::{InstallModeHint, InstallModeOpt};
-use crate::util::clap::parsers::{cycle_amount_parser, icrc_subaccount_parser};
+use crate::util::clap::parsers::{cycle_amount_parser, icrc_subaccount_parser, jobs_parser};
 use crate::util::clap::subnet_selection_opt::SubnetSelectionOpt;
 use crate::util::url::{construct_frontend_url, construct_ui_canister_url};
 use anyhow::{anyhow, bail};
@@ -111,6 +112,51 @@ pub struct DeployOpts {
         conflicts_with("yes")
     )]
     always_assist: bool,
+
+    /// Updates the settings of canisters that already exist to match dfx.json for the current network.
+    /// The differences are displayed and need to be confirmed before they are applied, unless --yes is given.
+    #[arg(
+        long,
+        conflicts_with("by_proposal"),
+        conflicts_with("compute_evidence")
+    )]
+    from_config: bool,
+
+    /// Takes a snapshot of every canister that is upgraded and loads the snapshots back if installing any canister fails.
+    /// Canisters that had no code installed before are uninstalled again.
+    #[arg(
+        long,
+        conflicts_with("by_proposal"),
+        conflicts_with("compute_evidence")
+    )]
+    atomic: bool,
+
+    /// Prints the expected cycles cost of creating and installing the canisters, per canister, instead of deploying them.
+    /// The canisters must be built, e.g. with `dfx build --check`.
+    #[arg(
+        long,
+        conflicts_with("by_proposal"),
+        conflicts_with("compute_evidence"),
+        conflicts_with("atomic")
+    )]
+    estimate: bool,
+
+    /// Builds up to this many canisters at the same time. Canisters are built after the canisters they depend on.
+    #[arg(long, short, default_value = "1", value_parser = jobs_parser)]
+    jobs: usize,
+
+    #[command(flatten)]
+    cache: BuildCacheOpt,
+
+    /// After deploying, keeps watching the sources of the canisters, and redeploys the canisters whose sources change and the canisters that depend on them.
+    /// Changed files in the source directories of asset canisters are synchronized without reinstalling the canisters.
+    #[arg(
+        long,
+        conflicts_with("by_proposal"),
+        conflicts_with("compute_evidence"),
+        conflicts_with("estimate")
+    )]
+    watch: bool,
 }
 
 pub fn exec(env: &dyn Environment, opts: DeployOpts) -> DfxResult {
@@ -128,8 +174,13 @@ pub fn exec(env: &dyn Environment, opts: DeployOpts) -> DfxResult {
     let mut subnet_selection =
         runtime.block_on(opts.subnet_selection.into_subnet_selection_type(&env))?;
     let with_cycles = opts.with_cycles;
+    let build_cache = opts.cache.into_build_cache()?;
 
     let deploy_mode = match (&mode_hint, canister_name) {
+        (_, _) if opts.estimate => EstimateCost,
+        (InstallModeHint::Reinstall, _) if opts.watch => {
+            bail!("The --watch flag cannot be used with --mode=reinstall.");
+        }
         (InstallModeHint::Reinstall, Some(canister_name)) => {
             let network = env.get_network_descriptor();
             if config
@@ -179,15 +230,37 @@ pub fn exec(env: &dyn Environment, opts: DeployOpts) -> DfxResult {
         opts.from_subaccount,
         opts.no_wallet,
         opts.yes,
-        env_file,
+        env_file.clone(),
         opts.no_asset_upgrade,
         &mut subnet_selection,
         opts.always_assist,
+        opts.from_config,
+        opts.atomic,
+        opts.jobs,
+        build_cache.clone(),
     ))?;
 
     if matches!(deploy_mode, NormalDeploy | ForceReinstallSingleCanister(_)) {
         display_urls(&env)?;
     }
+
+    if opts.watch {
+        runtime.block_on(watch_canisters(
+            &env,
+            canister_name,
+            argument_from_cli.as_deref(),
+            argument_type.as_deref(),
+            &mode_hint,
+            opts.upgrade_unchanged,
+            &call_sender,
+            opts.yes,
+            env_file,
+            opts.no_asset_upgrade,
+            opts.always_assist,
+            opts.jobs,
+            build_cache,
+        ))?;
+    }
     Ok(())
 }
 
@@ -5,6 +5,7 @@ use crate::lib::deps::{
 };
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
+use crate::util::candid_json::parse_typed_argument;
 use crate::util::clap::argument_from_cli::ArgumentFromCliLongOpt;
 use crate::util::fuzzy_parse_argument;
 use anyhow::{anyhow, bail, Context};
@@ -114,6 +115,10 @@ fn set_init(
                 let bytes = hex::decode(arg_str)
                     .map_err(|e| anyhow!("Argument is not a valid hex string: {}", e))?;
                 init_json.set_init_arg(canister_id, None, &bytes);
+            } else if let Some(format @ ("json" | "yaml")) = argument_type_from_cli {
+                let args = parse_typed_argument(arg_str, format, &env, &types)?;
+                let bytes = args.to_bytes_with_types(&env, &types)?;
+                init_json.set_init_arg(canister_id, Some(args.to_string()), &bytes);
             } else {
                 let bytes = fuzzy_parse_argument(arg_str, &env, &types)?;
                 init_json.set_init_arg(canister_id, Some(arg_str.to_string()), &bytes);
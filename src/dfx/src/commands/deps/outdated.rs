@@ -0,0 +1,95 @@
+use crate::lib::agent::create_anonymous_agent_environment;
+use crate::lib::deps::{get_canister_prompt, get_pull_canisters_in_config, load_pulled_json};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::network::network_opt::NetworkOpt;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
+use clap::Parser;
+use slog::{info, warn};
+
+/// Check which pulled dependencies have been upgraded since they were pulled.
+/// Compares the module hashes recorded in deps/pulled.json with the current ones.
+/// This command connects to the "ic" mainnet by default.
+/// You can still choose other network by setting `--network`.
+#[derive(Parser)]
+pub struct DepsOutdatedOpts {
+    #[command(flatten)]
+    network: NetworkOpt,
+}
+
+pub async fn exec(env: &dyn Environment, opts: DepsOutdatedOpts) -> DfxResult {
+    let logger = env.get_logger();
+    let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
+    if pull_canisters_in_config.is_empty() {
+        info!(logger, "There are no pull dependencies defined in dfx.json");
+        return Ok(());
+    }
+
+    let project_root = env.get_config_or_anyhow()?.get_project_root().to_path_buf();
+    let pulled_json = load_pulled_json(&project_root)?;
+
+    let network = opts
+        .network
+        .to_network_name()
+        .unwrap_or_else(|| "ic".to_string());
+    let env = create_anonymous_agent_environment(env, Some(network))?;
+    fetch_root_key_if_needed(&env).await?;
+    let agent = env.get_agent();
+
+    let mut outdated = vec![];
+    let mut unknown = vec![];
+    let mut failed = vec![];
+    for (canister_id, pulled_canister) in &pulled_json.canisters {
+        let prompt = get_canister_prompt(canister_id, pulled_canister);
+        let current_hash = match read_state_tree_canister_module_hash(agent, *canister_id).await {
+            Ok(current_hash) => current_hash.map(hex::encode),
+            Err(e) => {
+                warn!(logger, "Failed to read the module hash of {prompt}: {e:#}");
+                failed.push(prompt);
+                continue;
+            }
+        };
+        let Some(current_hash) = current_hash else {
+            outdated.push(format!("{prompt}: no longer has a module installed"));
+            continue;
+        };
+        match &pulled_canister.module_hash {
+            Some(pulled_hash) if *pulled_hash == current_hash => {
+                info!(logger, "{prompt} is up to date.");
+            }
+            Some(pulled_hash) => outdated.push(format!(
+                "{prompt}: module hash changed from {pulled_hash} to {current_hash}"
+            )),
+            // pulled.json written by older versions of dfx only has the expected wasm hash,
+            // which is not necessarily the module hash at pull time.
+            None if pulled_canister.wasm_hash == current_hash => {
+                info!(logger, "{prompt} is up to date.");
+            }
+            None => unknown.push(format!("{prompt}: current module hash is {current_hash}")),
+        }
+    }
+
+    if outdated.is_empty() && unknown.is_empty() && failed.is_empty() {
+        println!("All pulled dependencies are up to date.");
+    }
+    if !outdated.is_empty() {
+        println!(
+            "These pulled dependencies have been upgraded upstream:\n  {}\nRun `dfx deps update <CANISTER>` to pull the new versions.",
+            outdated.join("\n  ")
+        );
+    }
+    if !unknown.is_empty() {
+        println!(
+            "The module hash at pull time is unknown for these pulled dependencies, re-pull them to record it:\n  {}",
+            unknown.join("\n  ")
+        );
+    }
+    if !failed.is_empty() {
+        println!(
+            "Could not check these pulled dependencies:\n  {}",
+            failed.join("\n  ")
+        );
+    }
+    Ok(())
+}
@@ -2,14 +2,19 @@ use crate::lib::agent::create_anonymous_agent_environment;
 use crate::lib::deps::pull::{
     copy_service_candid_to_project, download_all_and_generate_pulled_json, resolve_all_dependencies,
 };
-use crate::lib::deps::{get_pull_canisters_in_config, save_pulled_json};
+use crate::lib::deps::{
+    diff_pulled, get_canister_prompt, get_pull_canisters_in_config, load_pulled_json_if_exists,
+    save_pulled_json, PulledCanister, PulledChange,
+};
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::network::network_opt::NetworkOpt;
 use crate::lib::root_key::fetch_root_key_if_needed;
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+use candid::Principal;
 use clap::Parser;
 use slog::info;
+use std::collections::BTreeSet;
 
 /// Pull canisters upon which the project depends.
 /// This command connects to the "ic" mainnet by default.
@@ -18,9 +23,52 @@ use slog::info;
 pub struct DepsPullOpts {
     #[command(flatten)]
     network: NetworkOpt,
+
+    /// Fail if the pulled canisters are different from the ones recorded in deps/pulled.json,
+    /// instead of updating it.
+    #[arg(long)]
+    locked: bool,
+}
+
+/// Which entries of `pulled.json` a pull may change.
+pub(super) enum Lock {
+    /// Update all entries.
+    Unlocked,
+    /// Fail if any entry would change.
+    Locked,
+    /// Update the entries of these canisters and add new dependencies.
+    /// Fail if any other entry would change.
+    Update(BTreeSet<Principal>),
+}
+
+impl Lock {
+    /// Returns true if the entry of this canister must not change.
+    fn forbids_change(&self, canister_id: &Principal) -> bool {
+        match self {
+            Lock::Unlocked => false,
+            Lock::Locked => true,
+            Lock::Update(updated) => !updated.contains(canister_id),
+        }
+    }
+}
+
+fn unexpected_changes(changes: &[String]) -> anyhow::Error {
+    anyhow!(
+        "The pulled canisters are different from deps/pulled.json:\n  {}\nRun `dfx deps update <CANISTER>` to accept the changes.",
+        changes.join("\n  ")
+    )
 }
 
 pub async fn exec(env: &dyn Environment, opts: DepsPullOpts) -> DfxResult {
+    let lock = if opts.locked {
+        Lock::Locked
+    } else {
+        Lock::Unlocked
+    };
+    pull(env, &opts.network, lock).await
+}
+
+pub(super) async fn pull(env: &dyn Environment, network: &NetworkOpt, lock: Lock) -> DfxResult {
     let logger = env.get_logger();
     let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
     if pull_canisters_in_config.is_empty() {
@@ -28,14 +76,18 @@ pub async fn exec(env: &dyn Environment, opts: DepsPullOpts) -> DfxResult {
         return Ok(());
     }
 
-    let network = opts
-        .network
+    let network = network
         .to_network_name()
         .unwrap_or_else(|| "ic".to_string());
     let env = create_anonymous_agent_environment(env, Some(network))?;
 
     let project_root = env.get_config_or_anyhow()?.get_project_root().to_path_buf();
 
+    let locked_json = load_pulled_json_if_exists(&project_root)?;
+    if matches!(lock, Lock::Locked) && locked_json.is_none() {
+        bail!("Cannot pull with --locked because deps/pulled.json does not exist. Please run `dfx deps pull` first.");
+    }
+
     fetch_root_key_if_needed(&env).await?;
 
     let agent = env.get_agent();
@@ -43,11 +95,59 @@ pub async fn exec(env: &dyn Environment, opts: DepsPullOpts) -> DfxResult {
     let all_dependencies =
         resolve_all_dependencies(agent, logger, &pull_canisters_in_config).await?;
 
+    // fail before the cache of a canister that must not change is touched
+    let check_change = |canister_id: &Principal, pulled: &PulledCanister| -> DfxResult {
+        let Some(locked_json) = &locked_json else {
+            return Ok(());
+        };
+        if !lock.forbids_change(canister_id) {
+            return Ok(());
+        }
+        let Some(locked) = locked_json.canisters.get(canister_id) else {
+            if matches!(lock, Lock::Update(_)) {
+                return Ok(());
+            }
+            return Err(unexpected_changes(&[format!(
+                "{canister_id}: {}",
+                PulledChange::Added
+            )]));
+        };
+        let change = if !pulled.wasm_hash_download.is_empty()
+            && pulled.wasm_hash_download != locked.wasm_hash_download
+        {
+            Some(PulledChange::Wasm {
+                locked: locked.wasm_hash_download.clone(),
+                pulled: pulled.wasm_hash_download.clone(),
+            })
+        } else if !pulled.wasm_hash.is_empty() && pulled.wasm_hash != locked.wasm_hash {
+            Some(PulledChange::Wasm {
+                locked: locked.wasm_hash.clone(),
+                pulled: pulled.wasm_hash.clone(),
+            })
+        } else if pulled.wasm_hash.is_empty()
+            && locked.wasm_url.is_some()
+            && pulled.wasm_url != locked.wasm_url
+        {
+            Some(PulledChange::WasmUrl {
+                locked: locked.wasm_url.clone().unwrap_or_default(),
+                pulled: pulled.wasm_url.clone().unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+        match change {
+            Some(change) => Err(unexpected_changes(&[format!(
+                "{}: {change}",
+                get_canister_prompt(canister_id, locked)
+            )])),
+            None => Ok(()),
+        }
+    };
     let mut pulled_json =
-        download_all_and_generate_pulled_json(agent, logger, &all_dependencies).await?;
+        download_all_and_generate_pulled_json(agent, logger, &all_dependencies, &check_change)
+            .await?;
 
     for (name, canister_id) in &pull_canisters_in_config {
-        copy_service_candid_to_project(&project_root, name, canister_id)?;
         let pulled_canister = pulled_json
             .canisters
             .get_mut(canister_id)
@@ -55,6 +155,37 @@ pub async fn exec(env: &dyn Environment, opts: DepsPullOpts) -> DfxResult {
         pulled_canister.name = Some(name.clone());
     }
 
-    save_pulled_json(&project_root, &pulled_json)?;
+    if let Some(locked_json) = &locked_json {
+        let changes = diff_pulled(locked_json, &pulled_json);
+        let describe = |canister_id: &Principal, change: &PulledChange| {
+            let prompt = match pulled_json.canisters.get(canister_id) {
+                Some(pulled_canister) => get_canister_prompt(canister_id, pulled_canister),
+                None => get_canister_prompt(canister_id, &locked_json.canisters[canister_id]),
+            };
+            format!("{prompt}: {change}")
+        };
+        let unexpected = changes
+            .iter()
+            .filter(|(canister_id, change)| {
+                lock.forbids_change(canister_id)
+                    && !(matches!(lock, Lock::Update(_)) && *change == PulledChange::Added)
+            })
+            .map(|(canister_id, change)| describe(canister_id, change))
+            .collect::<Vec<_>>();
+        if !unexpected.is_empty() {
+            return Err(unexpected_changes(&unexpected));
+        }
+        for (canister_id, change) in &changes {
+            info!(logger, "Updated {}", describe(canister_id, change));
+        }
+    }
+
+    for (name, canister_id) in &pull_canisters_in_config {
+        copy_service_candid_to_project(&project_root, name, canister_id)?;
+    }
+
+    if !matches!(lock, Lock::Locked) {
+        save_pulled_json(&project_root, &pulled_json)?;
+    }
     Ok(())
 }
@@ -0,0 +1,26 @@
+use crate::lib::deps::{format_pull_tree, get_pull_canisters_in_config, load_pulled_json};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use clap::Parser;
+use slog::info;
+
+/// Print the pulled dependencies as a tree, with the hash of each downloaded wasm.
+#[derive(Parser)]
+pub struct DepsTreeOpts {}
+
+pub async fn exec(env: &dyn Environment, _opts: DepsTreeOpts) -> DfxResult {
+    let logger = env.get_logger();
+    let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
+    if pull_canisters_in_config.is_empty() {
+        info!(logger, "There are no pull dependencies defined in dfx.json");
+        return Ok(());
+    }
+
+    let project_root = env.get_config_or_anyhow()?.get_project_root().to_path_buf();
+    let pulled_json = load_pulled_json(&project_root)?;
+    println!(
+        "{}",
+        format_pull_tree(&pulled_json, &pull_canisters_in_config)?
+    );
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use super::pull::{pull, Lock};
+use crate::lib::deps::{
+    get_pull_canister_or_principal, get_pull_canisters_in_config, load_pulled_json,
+};
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::network::network_opt::NetworkOpt;
+use candid::Principal;
+use clap::Parser;
+use std::collections::BTreeSet;
+
+/// Pull the latest version of pull dependencies and record it in deps/pulled.json.
+/// The other dependencies must still match deps/pulled.json.
+#[derive(Parser)]
+pub struct DepsUpdateOpts {
+    /// Names or principals of the pull dependencies to update.
+    /// If none is specified, all pull dependencies are updated.
+    canisters: Vec<String>,
+
+    #[command(flatten)]
+    network: NetworkOpt,
+}
+
+pub async fn exec(env: &dyn Environment, opts: DepsUpdateOpts) -> DfxResult {
+    if opts.canisters.is_empty() {
+        return pull(env, &opts.network, Lock::Unlocked).await;
+    }
+
+    let pull_canisters_in_config = get_pull_canisters_in_config(env)?;
+    let project_root = env.get_config_or_anyhow()?.get_project_root().to_path_buf();
+    let pulled_json = load_pulled_json(&project_root)?;
+    let updated: BTreeSet<Principal> = opts
+        .canisters
+        .iter()
+        .map(|canister| {
+            get_pull_canister_or_principal(canister, &pull_canisters_in_config, &pulled_json)
+        })
+        .collect::<DfxResult<_>>()?;
+
+    pull(env, &opts.network, Lock::Update(updated)).await
+}
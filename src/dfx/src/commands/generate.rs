@@ -57,8 +57,10 @@ pub fn exec(env: &dyn Environment, opts: GenerateOpts) -> DfxResult {
             }
         }
     }
-    let build_config =
-        BuildConfig::from_config(&config)?.with_canisters_to_build(build_before_generate);
+    // Canisters are built with random IDs, so these builds are not cached.
+    let build_config = BuildConfig::from_config(&config)?
+        .with_canisters_to_build(build_before_generate)
+        .with_cache(None);
     let generate_config =
         BuildConfig::from_config(&config)?.with_canisters_to_build(canisters_to_generate);
 
@@ -6,11 +6,12 @@ use clap::Parser;
 use dfx_core::error::identity::CreateNewIdentityError::SwitchBackToIdentityFailed;
 use dfx_core::identity::identity_manager::{
     HardwareIdentityConfiguration, IdentityCreationParameters, IdentityStorageMode,
+    SignerIdentityConfiguration,
 };
 use regex::Regex;
 use slog::{info, warn, Logger};
 use std::str::FromStr;
-use IdentityCreationParameters::{Hardware, Pem};
+use IdentityCreationParameters::{Hardware, Pem, Signer};
 
 /// Creates a new identity.
 #[derive(Parser)]
@@ -34,6 +35,15 @@ pub struct NewIdentityOpts {
     #[arg(long, requires("hsm_pkcs11_lib_path"), value_parser = hsm_key_id_parser)]
     hsm_key_id: Option<String>,
 
+    /// A command that signs on behalf of the identity, e.g. with a cloud KMS or a hardware wallet.
+    /// dfx exchanges JSON messages with it over stdin and stdout to get the public key and to sign.
+    /// Arguments are split like in a shell. A relative program path is made absolute.
+    #[arg(
+        long,
+        conflicts_with_all(["hsm_pkcs11_lib_path", "disable_encryption", "storage_mode"])
+    )]
+    signer_command: Option<String>,
+
     /// DEPRECATED: Please use --storage-mode=plaintext instead
     #[arg(long)]
     disable_encryption: bool,
@@ -67,13 +77,20 @@ pub fn exec(env: &dyn Environment, opts: NewIdentityOpts) -> DfxResult {
 
     let name = opts.new_identity.as_str();
 
-    let creation_parameters = match (opts.hsm_pkcs11_lib_path, opts.hsm_key_id) {
-        (Some(pkcs11_lib_path), Some(key_id)) => Hardware {
+    let creation_parameters = match (
+        opts.hsm_pkcs11_lib_path,
+        opts.hsm_key_id,
+        opts.signer_command,
+    ) {
+        (Some(pkcs11_lib_path), Some(key_id), _) => Hardware {
             hsm: HardwareIdentityConfiguration {
                 pkcs11_lib_path,
                 key_id,
             },
         },
+        (_, _, Some(command)) => Signer {
+            signer: SignerIdentityConfiguration { command },
+        },
         _ => {
             let mode = if opts.disable_encryption {
                 IdentityStorageMode::Plaintext
@@ -0,0 +1,98 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::ledger_types::index::MAINNET_INDEX_CANISTER_ID;
+use crate::lib::nns_types::account_identifier::{AccountIdentifier, Subaccount};
+use crate::lib::nns_types::icpts::ICPTs;
+use crate::lib::operations::ledger;
+use crate::lib::root_key::fetch_root_key_if_needed;
+use crate::lib::transaction_history::{format_history, HistoryOutputFormat, TransactionRecord};
+use anyhow::{anyhow, bail, Context};
+use candid::Principal;
+use clap::Parser;
+use std::str::FromStr;
+
+/// Number of transactions requested from the index canister at a time.
+const PAGE_SIZE: u64 = 100;
+
+/// Prints the transaction history of an account, newest first.
+#[derive(Parser)]
+pub struct HistoryOpts {
+    /// Specifies an AccountIdentifier to get the history of
+    of: Option<String>,
+
+    /// Specifies a principal to get the history of
+    #[arg(long, conflicts_with("of"))]
+    of_principal: Option<Principal>,
+
+    /// Subaccount of the selected identity to get the history of
+    #[arg(long, conflicts_with("of"))]
+    subaccount: Option<Subaccount>,
+
+    /// Maximum number of transactions to print.
+    #[arg(long, default_value = "100")]
+    limit: u64,
+
+    /// Only prints transactions older than the transaction with this id.
+    /// Use it with the id of the last printed transaction to see the next page.
+    #[arg(long)]
+    before: Option<u64>,
+
+    /// Specifies the format of the output.
+    #[arg(long, value_enum, default_value_t = HistoryOutputFormat::Text)]
+    output: HistoryOutputFormat,
+
+    /// Canister ID of the index canister of the ledger.
+    /// Required on networks other than the IC mainnet.
+    #[arg(long)]
+    index_canister_id: Option<Principal>,
+}
+
+pub async fn exec(env: &dyn Environment, opts: HistoryOpts) -> DfxResult {
+    let agent = env.get_agent();
+
+    fetch_root_key_if_needed(env).await?;
+
+    let account = if let Some(of) = opts.of {
+        AccountIdentifier::from_str(&of)
+            .map_err(|e| anyhow!(e))
+            .with_context(|| format!("Failed to parse account identifier from string '{of}'."))?
+    } else {
+        let owner = opts.of_principal.unwrap_or_else(|| {
+            env.get_selected_identity_principal()
+                .expect("Selected identity not instantiated.")
+        });
+        AccountIdentifier::new(owner, opts.subaccount)
+    };
+    let network = env.get_network_descriptor();
+    let index_canister_id = match opts.index_canister_id {
+        Some(index_canister_id) => index_canister_id,
+        None if network.is_ic => MAINNET_INDEX_CANISTER_ID,
+        None => bail!(
+            "The index canister of the ICP ledger on network '{}' is unknown. Specify it with --index-canister-id.",
+            network.name
+        ),
+    };
+
+    let mut transactions: Vec<TransactionRecord> = vec![];
+    let mut start = opts.before;
+    while (transactions.len() as u64) < opts.limit {
+        let max_results = PAGE_SIZE.min(opts.limit - transactions.len() as u64);
+        let page =
+            ledger::account_transactions(agent, index_canister_id, &account, start, max_results)
+                .await?;
+        transactions.extend(page.iter().map(TransactionRecord::from));
+        match page.last() {
+            Some(last) if page.len() as u64 == max_results => start = Some(last.id),
+            _ => break,
+        }
+    }
+
+    println!(
+        "{}",
+        format_history(&transactions, opts.output, |e8s| {
+            ICPTs::from_e8s(e8s as u64).to_string()
+        })?
+    );
+
+    Ok(())
+}
@@ -14,9 +14,10 @@ mod approve;
 mod balance;
 pub mod create_canister;
 mod fabricate_cycles;
+mod history;
 mod notify;
 pub mod show_subnet_types;
-mod top_up;
+pub mod top_up;
 mod transfer;
 mod transfer_from;
 
@@ -39,6 +40,7 @@ enum SubCommand {
     Balance(balance::BalanceOpts),
     CreateCanister(create_canister::CreateCanisterOpts),
     FabricateCycles(fabricate_cycles::FabricateCyclesOpts),
+    History(history::HistoryOpts),
     Notify(notify::NotifyOpts),
     ShowSubnetTypes(show_subnet_types::ShowSubnetTypesOpts),
     TopUp(top_up::TopUpOpts),
@@ -57,6 +59,7 @@ pub fn exec(env: &dyn Environment, opts: LedgerOpts) -> DfxResult {
             SubCommand::Balance(v) => balance::exec(&agent_env, v).await,
             SubCommand::CreateCanister(v) => create_canister::exec(&agent_env, v).await,
             SubCommand::FabricateCycles(v) => fabricate_cycles::exec(&agent_env, v).await,
+            SubCommand::History(v) => history::exec(&agent_env, v).await,
             SubCommand::Notify(v) => notify::exec(&agent_env, v).await,
             SubCommand::ShowSubnetTypes(v) => show_subnet_types::exec(&agent_env, v).await,
             SubCommand::TopUp(v) => top_up::exec(&agent_env, v).await,
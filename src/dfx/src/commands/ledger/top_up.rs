@@ -12,7 +12,7 @@ use anyhow::{bail, Context};
 use candid::Principal;
 use clap::Parser;
 
-const MEMO_TOP_UP_CANISTER: u64 = 1347768404_u64;
+pub const MEMO_TOP_UP_CANISTER: u64 = 1347768404_u64;
 
 /// Top up a canister with cycles minted from ICP
 #[derive(Parser)]
@@ -0,0 +1,32 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::network::network_opt::NetworkOpt;
+use clap::Parser;
+use tokio::runtime::Runtime;
+
+mod time;
+
+/// Controls the local PocketIC instance started with `dfx start`.
+#[derive(Parser)]
+#[command(name = "replica")]
+pub struct ReplicaOpts {
+    #[command(flatten)]
+    network: NetworkOpt,
+
+    #[command(subcommand)]
+    subcmd: SubCommand,
+}
+
+#[derive(Parser)]
+enum SubCommand {
+    Time(time::TimeOpts),
+}
+
+pub fn exec(env: &dyn Environment, opts: ReplicaOpts) -> DfxResult {
+    let runtime = Runtime::new().expect("Unable to create a runtime");
+    runtime.block_on(async {
+        match opts.subcmd {
+            SubCommand::Time(v) => time::exec(env, v, opts.network).await,
+        }
+    })
+}
@@ -0,0 +1,198 @@
+use crate::lib::environment::{create_pocketic, Environment};
+use crate::lib::error::DfxResult;
+use crate::lib::network::network_opt::NetworkOpt;
+use crate::util::clap::parsers::{duration_parser, timestamp_parser};
+use anyhow::{anyhow, bail, Context};
+use clap::{Parser, Subcommand};
+use dfx_core::config::model::replica_config::CachedReplicaConfig;
+use dfx_core::network::provider::{create_network_descriptor, LocalBindDetermination};
+use pocket_ic::common::rest::{AutoProgressConfig, RawTime};
+use reqwest::Client;
+use slog::info;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use url::Url;
+
+/// Gets or changes the time of the local PocketIC instance.
+/// This makes it possible to test timers and other time-dependent logic without waiting in real time.
+#[derive(Parser)]
+pub struct TimeOpts {
+    #[command(subcommand)]
+    subcmd: TimeCommand,
+}
+
+#[derive(Subcommand)]
+enum TimeCommand {
+    /// Displays the current time of the instance in RFC 3339 format.
+    Get {
+        /// Displays the time in nanoseconds since epoch instead.
+        #[arg(long)]
+        nanos: bool,
+    },
+    /// Sets the time of the instance. Time cannot be moved backwards.
+    Set {
+        /// The new time, either in RFC 3339 format (e.g. '2030-01-01T00:00:00Z') or in nanoseconds since epoch.
+        #[arg(value_parser = timestamp_parser)]
+        time: u64,
+    },
+    /// Moves the time of the instance forward.
+    Advance {
+        /// The amount of time to move forward by, as a number followed by a unit (s, m, h, d), e.g. '30d'.
+        #[arg(value_parser = duration_parser)]
+        duration: u64,
+    },
+    /// Stops time from progressing with wall-clock time.
+    /// While time is frozen, the instance only executes messages when time is set or advanced.
+    Freeze,
+    /// Lets time progress with wall-clock time again.
+    Resume,
+}
+
+pub async fn exec(env: &dyn Environment, opts: TimeOpts, network: NetworkOpt) -> DfxResult {
+    let log = env.get_logger();
+    let instance = PocketIcInstance::find(env, network)?;
+    match opts.subcmd {
+        TimeCommand::Get { nanos } => {
+            let time = instance.get_time().await?;
+            if nanos {
+                println!("{time}");
+            } else {
+                println!("{}", format_time(time)?);
+            }
+        }
+        TimeCommand::Set { time } => {
+            let current = instance.get_time().await?;
+            if time < current {
+                bail!(
+                    "Cannot move time backwards from {} to {}.",
+                    format_time(current)?,
+                    format_time(time)?
+                );
+            }
+            instance.set_time(time).await?;
+            info!(log, "Set time to {}.", format_time(time)?);
+        }
+        TimeCommand::Advance { duration } => {
+            let current = instance.get_time().await?;
+            let time = duration
+                .checked_mul(1_000_000_000)
+                .and_then(|nanos| current.checked_add(nanos))
+                .ok_or_else(|| anyhow!("Cannot advance time by {duration} seconds."))?;
+            instance.set_time(time).await?;
+            info!(log, "Advanced time to {}.", format_time(time)?);
+        }
+        TimeCommand::Freeze => {
+            instance.stop_progress().await?;
+            let time = instance.get_time().await?;
+            info!(log, "Froze time at {}.", format_time(time)?);
+        }
+        TimeCommand::Resume => {
+            instance.auto_progress().await?;
+            info!(log, "Time progresses with wall-clock time again.");
+        }
+    }
+    Ok(())
+}
+
+fn format_time(nanos: u64) -> DfxResult<String> {
+    let time = OffsetDateTime::from_unix_timestamp_nanos(nanos.into())?;
+    Ok(time.format(&Rfc3339)?)
+}
+
+/// The PocketIC instance that `dfx start` runs for a local network.
+struct PocketIcInstance {
+    client: Client,
+    server_url: Url,
+    instance_url: Url,
+    artificial_delay: u32,
+}
+
+impl PocketIcInstance {
+    fn find(env: &dyn Environment, network: NetworkOpt) -> DfxResult<Self> {
+        let network_descriptor = create_network_descriptor(
+            env.get_config()?,
+            env.get_networks_config(),
+            network.to_network_name(),
+            None,
+            LocalBindDetermination::AsConfigured,
+        )?;
+        let local = network_descriptor.local_server_descriptor()?;
+        let not_running = || {
+            anyhow!(
+                "PocketIC is not running for network '{}'. Start it with `dfx start`.",
+                network_descriptor.name
+            )
+        };
+        let artificial_delay = match local.effective_config()?.map(|c| c.config) {
+            Some(CachedReplicaConfig::PocketIc { config }) => config.artificial_delay,
+            Some(CachedReplicaConfig::Replica { .. }) => {
+                bail!("The running server is a native replica, not PocketIC.")
+            }
+            None => return Err(not_running()),
+        };
+        let port = local
+            .get_running_pocketic_port(None)?
+            .ok_or_else(not_running)?;
+
+        let mut socket_addr = local.bind_address;
+        socket_addr.set_port(port);
+        let server_url = Url::parse(&format!("http://{socket_addr}"))?;
+        // dfx always creates a single instance with id 0.
+        let instance_url = server_url.join("instances/0/")?;
+        Ok(Self {
+            client: Client::new(),
+            server_url,
+            instance_url,
+            artificial_delay,
+        })
+    }
+
+    async fn get_time(&self) -> DfxResult<u64> {
+        let time = self
+            .client
+            .get(self.instance_url.join("read/get_time")?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<RawTime>()
+            .await
+            .context("Failed to read the time of the PocketIC instance.")?;
+        Ok(time.nanos_since_epoch)
+    }
+
+    /// Sets the time and executes a round, so that canisters observe the new time and their timers run.
+    async fn set_time(&self, nanos_since_epoch: u64) -> DfxResult {
+        self.client
+            .post(self.instance_url.join("update/set_time")?)
+            .json(&RawTime { nanos_since_epoch })
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to set the time of the PocketIC instance.")?;
+        create_pocketic(&self.server_url).tick().await;
+        Ok(())
+    }
+
+    async fn stop_progress(&self) -> DfxResult {
+        self.client
+            .post(self.instance_url.join("stop_progress")?)
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to stop the progress of the PocketIC instance.")?;
+        Ok(())
+    }
+
+    async fn auto_progress(&self) -> DfxResult {
+        self.client
+            .post(self.instance_url.join("auto_progress")?)
+            .json(&AutoProgressConfig {
+                artificial_delay_ms: Some(self.artificial_delay as u64),
+            })
+            .send()
+            .await?
+            .error_for_status()
+            .context("Failed to resume the progress of the PocketIC instance.")?;
+        Ok(())
+    }
+}
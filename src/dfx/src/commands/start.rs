@@ -260,6 +260,18 @@ https://github.com/dfinity/sdk/blob/0.27.0/docs/migration/dfx-0.27.0-migration-g
         .replica
         .subnet_type
         .unwrap_or_default();
+    let subnets = local_server_descriptor
+        .replica
+        .subnets
+        .clone()
+        .unwrap_or_default();
+    for (i, subnet) in subnets.iter().enumerate() {
+        ensure!(
+            subnets[..i].iter().all(|other| other.name != subnet.name),
+            "Subnet '{}' is declared more than once in the replica configuration.",
+            subnet.name
+        );
+    }
     let log_level = local_server_descriptor
         .replica
         .log_level
@@ -273,7 +285,8 @@ https://github.com/dfinity/sdk/blob/0.27.0/docs/migration/dfx-0.27.0-migration-g
 
     let replica_config = {
         let mut replica_config =
-            ReplicaConfig::new(&state_root, subnet_type, log_level, artificial_delay);
+            ReplicaConfig::new(&state_root, subnet_type, log_level, artificial_delay)
+                .with_subnets(subnets);
         if local_server_descriptor.bitcoin.enabled {
             replica_config = replica_config.with_btc_adapter_enabled();
         }
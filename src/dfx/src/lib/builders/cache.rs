@@ -0,0 +1,373 @@
+use crate::config::dfx_version_str;
+use crate::lib::builders::BuildConfig;
+use crate::lib::canister_info::assets::AssetsCanisterInfo;
+use crate::lib::canister_info::custom::CustomCanisterInfo;
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::models::canister::CanisterPool;
+use anyhow::{bail, Context};
+use candid::Principal as CanisterId;
+use cargo_metadata::Metadata;
+use fn_error_context::context;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+/// Directories that hold build artifacts, downloaded dependencies or version control data rather than sources.
+pub(crate) const EXCLUDED_DIRECTORIES: [&str; 4] = [".dfx", ".git", "node_modules", "target"];
+
+/// Where the artifacts of canister builds are cached.
+///
+/// A build is identified by a key that covers everything its artifacts depend on:
+/// the sources of the project and of the Rust packages it uses from outside the project,
+/// the configuration of the canister, the toolchain and the programs that custom builds run,
+/// and the ids and interfaces of the canisters it depends on.
+/// Canisters whose wasm or candid is downloaded from a URL are not cached.
+/// The key of the build whose artifacts are in the output directory of a canister is saved next to them.
+#[derive(Clone, Debug, Default)]
+pub struct BuildCache {
+    /// Holds the artifacts of builds by key, so that other projects and CI runs can reuse them.
+    shared_root: Option<PathBuf>,
+}
+
+impl BuildCache {
+    pub fn new(shared_root: Option<PathBuf>) -> Self {
+        Self { shared_root }
+    }
+
+    /// Restores the artifacts of the build with this key into the output directory of the canister.
+    /// Returns false if the build is not cached.
+    #[context("Failed to restore cached build of canister '{}'.", info.get_name())]
+    pub fn restore(&self, info: &CanisterInfo, key: &str) -> DfxResult<bool> {
+        let key_path = key_path(info);
+        if key_path.exists()
+            && dfx_core::fs::read_to_string(&key_path)? == key
+            && info.get_build_wasm_path().exists()
+        {
+            return Ok(true);
+        }
+        let Some(entry) = self.shared_entry(key).filter(|entry| entry.is_dir()) else {
+            return Ok(false);
+        };
+        copy_dir(&entry, info.get_output_root())?;
+        dfx_core::fs::write(&key_path, key)?;
+        Ok(info.get_build_wasm_path().exists())
+    }
+
+    /// Records that the output directory of the canister holds the artifacts of the build with this key.
+    #[context("Failed to cache build of canister '{}'.", info.get_name())]
+    pub fn store(&self, info: &CanisterInfo, key: &str) -> DfxResult {
+        dfx_core::fs::write(key_path(info), key)?;
+        if let Some(entry) = self.shared_entry(key) {
+            if entry.exists() {
+                return Ok(());
+            }
+            // Copy to a temporary directory first, so that other builds never see a partial entry.
+            let temp = entry.with_extension(format!("tmp-{}", std::process::id()));
+            copy_dir(info.get_output_root(), &temp)?;
+            if dfx_core::fs::rename(&temp, &entry).is_err() {
+                // Another build stored the same artifacts in the meantime.
+                dfx_core::fs::remove_dir_all(&temp)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn shared_entry(&self, key: &str) -> Option<PathBuf> {
+        self.shared_root.as_ref().map(|root| root.join(key))
+    }
+
+    /// Computes the key of the build of a canister.
+    #[context("Failed to compute build cache key of canister '{}'.", info.get_name())]
+    pub fn key(
+        &self,
+        env: &dyn Environment,
+        pool: &CanisterPool,
+        info: &CanisterInfo,
+        dependencies: &[CanisterId],
+        build_config: &BuildConfig,
+    ) -> DfxResult<String> {
+        let config = env.get_config_or_anyhow()?;
+        let json = config.get_json();
+        let project_root = config.get_project_root();
+
+        if info.is_custom() {
+            let custom = info.as_info::<CustomCanisterInfo>()?;
+            if custom.get_input_wasm_url().is_some() || custom.get_input_candid_url().is_some() {
+                bail!("Its wasm or candid is downloaded from a URL, which may serve other content next time.");
+            }
+        }
+
+        let mut key = BuildKey::default();
+        key.add("dfx", dfx_version_str());
+        key.add("network", &build_config.network_name);
+        key.add("profile", format!("{:?}", build_config.profile));
+        key.add("canister", json["canisters"][info.get_name()].to_string());
+        key.add("defaults", json["defaults"].to_string());
+        for canister in pool.get_canister_list() {
+            key.add(canister.get_name(), canister.canister_id().to_text());
+        }
+        // The interfaces of the dependencies were saved here when they were built.
+        for dependency in dependencies {
+            let idl_path = build_config
+                .idl_root
+                .join(dependency.to_text())
+                .with_extension("did");
+            if idl_path.exists() {
+                key.add(&dependency.to_text(), dfx_core::fs::read(&idl_path)?);
+            }
+        }
+        let mut outside_roots = vec![];
+        if info.is_rust() {
+            for tool in ["rustc", "cargo"] {
+                key.add(
+                    tool,
+                    tool_version(Path::new(tool), info.get_workspace_root())?,
+                );
+            }
+            outside_roots = rust_roots_outside(info.get_workspace_root(), project_root)?;
+        } else if info.is_motoko() {
+            // DFX_MOC_PATH may point to another compiler than the one bundled with dfx.
+            let moc = env.get_cache().get_binary_command_path(env, "moc")?;
+            key.add("moc", tool_version(&moc, info.get_workspace_root())?);
+        } else if info.is_custom() {
+            let custom = info.as_info::<CustomCanisterInfo>()?;
+            for task in custom.get_build_tasks() {
+                let program = task_program(task, info.get_workspace_root())?;
+                key.add(&program.to_string_lossy(), dfx_core::fs::read(&program)?);
+            }
+        }
+
+        // Files that dfx or the builds of the canisters write into the project are not sources.
+        let mut excluded = vec![
+            config.get_path().clone(),
+            project_root.join("canister_ids.json"),
+        ];
+        excluded.extend(build_config.env_file.clone());
+        excluded.extend(self.shared_root.clone());
+        for canister in pool.get_canister_list() {
+            let info = canister.get_info();
+            excluded.extend(info.get_declarations_config().output.clone());
+            if info.is_assets() {
+                let assets_info = info.as_info::<AssetsCanisterInfo>()?;
+                excluded.extend(assets_info.get_source_paths());
+            }
+        }
+        let excluded: Vec<PathBuf> = excluded
+            .into_iter()
+            .map(|path| project_root.join(path))
+            .collect();
+        key.add_files(project_root, &excluded)?;
+        for (root, excluded) in outside_roots {
+            key.add(&root.to_string_lossy(), "");
+            key.add_files(&root, &excluded)?;
+        }
+
+        Ok(key.finish())
+    }
+}
+
+/// Accumulates the inputs of a build into its key.
+#[derive(Default)]
+struct BuildKey(Sha256);
+
+impl BuildKey {
+    fn add(&mut self, name: &str, value: impl AsRef<[u8]>) {
+        let value = value.as_ref();
+        for part in [name.as_bytes(), value] {
+            self.0.update((part.len() as u64).to_le_bytes());
+            self.0.update(part);
+        }
+    }
+
+    /// Adds the path and content of the files in the directory, in a stable order.
+    #[context("Failed to hash files in {}.", root.display())]
+    fn add_files(&mut self, root: &Path, excluded: &[PathBuf]) -> DfxResult {
+        let walker = WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                let is_excluded_dir = entry.depth() > 0
+                    && entry.file_type().is_dir()
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| EXCLUDED_DIRECTORIES.contains(&name));
+                !is_excluded_dir && !excluded.iter().any(|path| path == entry.path())
+            });
+        for entry in walker {
+            let entry = entry.context("Failed to read directory entry.")?;
+            if entry.path().is_file() {
+                let relative = entry.path().strip_prefix(root)?;
+                self.add(
+                    &relative.to_string_lossy(),
+                    dfx_core::fs::read(entry.path())?,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> String {
+        hex::encode(self.0.finalize())
+    }
+}
+
+/// Forgets which build the artifacts in the output directory of the canister belong to,
+/// before they are overwritten by another build.
+pub fn invalidate_cached_build(info: &CanisterInfo) -> DfxResult {
+    let key_path = key_path(info);
+    if key_path.exists() {
+        dfx_core::fs::remove_file(&key_path)?;
+    }
+    Ok(())
+}
+
+fn key_path(info: &CanisterInfo) -> PathBuf {
+    info.get_output_root().join("build_key.txt")
+}
+
+#[context("Failed to get the version of {}.", tool.display())]
+fn tool_version(tool: &Path, cwd: &Path) -> DfxResult<String> {
+    let output = Command::new(tool)
+        .arg("--version")
+        .current_dir(cwd)
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the directories of the Rust workspace and of the path dependencies of its packages
+/// that are not in the project, along with the target directory to exclude from them.
+#[context("Failed to find the Rust packages outside of the project.")]
+fn rust_roots_outside(
+    workspace_root: &Path,
+    project_root: &Path,
+) -> DfxResult<Vec<(PathBuf, Vec<PathBuf>)>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version=1", "--locked", "--offline"])
+        .current_dir(workspace_root)
+        .stderr(Stdio::null())
+        .stdout(Stdio::piped())
+        .output()
+        .context("Failed to run `cargo metadata`")?;
+    if !output.status.success() {
+        bail!("`cargo metadata --offline` was unsuccessful. The dependencies may not be downloaded yet.");
+    }
+    let metadata: Metadata = serde_json::from_slice(&output.stdout)
+        .context("Failed to read metadata from `cargo metadata`")?;
+
+    let mut roots = vec![metadata.workspace_root.as_std_path().to_path_buf()];
+    for package in &metadata.packages {
+        // Packages from registries and git repositories are pinned by Cargo.lock.
+        if package.source.is_none() {
+            if let Some(dir) = package.manifest_path.parent() {
+                roots.push(dir.as_std_path().to_path_buf());
+            }
+        }
+    }
+    let project_root =
+        dfx_core::fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+    let mut outside: Vec<PathBuf> = vec![];
+    for root in roots {
+        let root = dfx_core::fs::canonicalize(&root).unwrap_or(root);
+        if !root.starts_with(&project_root) && !outside.iter().any(|r| root.starts_with(r)) {
+            outside.retain(|r| !r.starts_with(&root));
+            outside.push(root);
+        }
+    }
+    let excluded = vec![metadata.target_directory.into_std_path_buf()];
+    Ok(outside
+        .into_iter()
+        .map(|root| (root, excluded.clone()))
+        .collect())
+}
+
+/// Returns the program that a build task runs.
+fn task_program(task: &str, cwd: &Path) -> DfxResult<PathBuf> {
+    let words = shell_words::split(task)
+        .with_context(|| format!("Error interpreting build task `{task}`"))?;
+    let Some(program) = words.first() else {
+        bail!("Build task `{task}` is empty.");
+    };
+    dfx_core::fs::canonicalize(&cwd.join(program))
+        .ok()
+        .filter(|path| path.is_file())
+        .or_else(|| which::which(program).ok())
+        .with_context(|| format!("Cannot find command or file {program}"))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> DfxResult {
+    for entry in WalkDir::new(from) {
+        let entry = entry.context("Failed to read directory entry.")?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            dfx_core::fs::create_dir_all(&target)?;
+        } else {
+            dfx_core::fs::copy(entry.path(), &target)?;
+            dfx_core::fs::set_permissions_readwrite(&target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files_key(root: &Path, excluded: &[PathBuf]) -> String {
+        let mut key = BuildKey::default();
+        key.add_files(root, excluded).unwrap();
+        key.finish()
+    }
+
+    #[test]
+    fn key_changes_with_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.mo"), "actor {}").unwrap();
+        let before = files_key(dir.path(), &[]);
+        assert_eq!(before, files_key(dir.path(), &[]));
+
+        std::fs::write(dir.path().join("src/main.mo"), "actor { }").unwrap();
+        assert_ne!(before, files_key(dir.path(), &[]));
+    }
+
+    #[test]
+    fn key_ignores_excluded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.mo"), "actor {}").unwrap();
+        let before = files_key(dir.path(), &[dir.path().join(".env")]);
+
+        std::fs::create_dir_all(dir.path().join(".dfx/local")).unwrap();
+        std::fs::write(dir.path().join(".dfx/local/main.wasm"), "wasm").unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        std::fs::write(dir.path().join("node_modules/index.js"), "").unwrap();
+        std::fs::write(dir.path().join(".env"), "CANISTER_ID=1").unwrap();
+        assert_eq!(before, files_key(dir.path(), &[dir.path().join(".env")]));
+    }
+
+    #[test]
+    fn finds_programs_of_build_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("build.sh"), "").unwrap();
+        assert_eq!(
+            task_program("./build.sh --release", dir.path()).unwrap(),
+            dfx_core::fs::canonicalize(&dir.path().join("build.sh")).unwrap()
+        );
+        assert!(task_program("sh -c 'echo'", dir.path())
+            .unwrap()
+            .is_absolute());
+        assert!(task_program("no-such-program-for-dfx", dir.path()).is_err());
+    }
+
+    #[test]
+    fn key_distinguishes_names_from_values() {
+        let mut a = BuildKey::default();
+        a.add("ab", "c");
+        let mut b = BuildKey::default();
+        b.add("a", "bc");
+        assert_ne!(a.finish(), b.finish());
+    }
+}
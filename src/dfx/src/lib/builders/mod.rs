@@ -15,6 +15,7 @@ use fn_error_context::context;
 use handlebars::Handlebars;
 use slog::{info, trace, Logger};
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fmt::Write;
@@ -22,14 +23,18 @@ use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 mod assets;
+mod cache;
 mod custom;
 mod motoko;
 mod pull;
+mod python_bindings;
 mod rust;
 
+pub(crate) use cache::EXCLUDED_DIRECTORIES;
+pub use cache::{invalidate_cached_build, BuildCache};
 pub use custom::custom_download;
 
 #[derive(Debug)]
@@ -54,7 +59,7 @@ pub struct BuildOutput {
 }
 
 /// A stateless canister builder. This is meant to not keep any state and be passed everything.
-pub trait CanisterBuilder {
+pub trait CanisterBuilder: Send + Sync {
     /// Returns the dependencies of this canister, if any. This should not be a transitive
     /// list.
     fn get_dependencies(
@@ -204,6 +209,35 @@ pub trait CanisterBuilder {
             trace!(logger, "  {}", &output_mo_path.display());
         }
 
+        // Rust
+        if bindings.contains(&"rs".to_string()) {
+            let output_rs_path = generate_output_dir
+                .join(info.get_name().replace('-', "_"))
+                .with_extension("rs");
+            let mut config = candid_parser::bindings::rust::Config::new();
+            config.set_target(candid_parser::bindings::rust::Target::Agent);
+            let content =
+                ensure_trailing_newline(candid_parser::bindings::rust::compile(&config, &env, &ty));
+            std::fs::write(&output_rs_path, content)
+                .with_context(|| format!("Failed to write to {}.", output_rs_path.display()))?;
+            trace!(logger, "  {}", &output_rs_path.display());
+
+            compile_handlebars_files("rs", info, generate_output_dir)?;
+        }
+
+        // Python
+        if bindings.contains(&"py".to_string()) {
+            let output_py_path = generate_output_dir
+                .join(format!("{}_did", info.get_name().replace('-', "_")))
+                .with_extension("py");
+            let content = ensure_trailing_newline(python_bindings::compile(&env, &ty));
+            std::fs::write(&output_py_path, content)
+                .with_context(|| format!("Failed to write to {}.", output_py_path.display()))?;
+            trace!(logger, "  {}", &output_py_path.display());
+
+            compile_handlebars_files("py", info, generate_output_dir)?;
+        }
+
         // Candid
         if bindings.contains(&"did".to_string()) {
             let output_did_path = generate_output_dir
@@ -308,6 +342,14 @@ export const {canister_name_ident} = canisterId ? createActor(canisterId) : unde
                 &process_string_prefix,
             );
 
+            // Rust and Python bindings read the canister id from the same environment variable at runtime
+            let canister_id_env_var =
+                format!("CANISTER_ID_{}", &canister_name_ident.to_ascii_uppercase());
+            data.insert("canister_id_env_var".to_string(), &canister_id_env_var);
+            if let Some(env_override) = &info.get_declarations_config().env_override {
+                data.insert("canister_id_override".to_string(), env_override);
+            }
+
             let new_file_contents = handlebars.render_template(&file_contents, &data).unwrap();
             let new_path = generate_output_dir.join(pathname.with_extension(""));
             std::fs::write(&new_path, new_file_contents)
@@ -329,6 +371,46 @@ fn ensure_trailing_newline(s: String) -> String {
     }
 }
 
+thread_local! {
+    /// Collects the output of build tools while a canister is built in parallel with others,
+    /// so that it can be printed as a whole instead of interleaving with the output of other builds.
+    static CAPTURED_OUTPUT: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, collecting the output of the build tools it runs on this thread.
+pub(crate) fn capture_build_output<T>(f: impl FnOnce() -> T) -> (T, Vec<u8>) {
+    CAPTURED_OUTPUT.with(|captured| captured.replace(Some(vec![])));
+    let result = f();
+    let output = CAPTURED_OUTPUT.with(|captured| captured.take().unwrap_or_default());
+    (result, output)
+}
+
+/// Runs a build tool whose output is shown to the user.
+/// The output is collected instead while inside [capture_build_output].
+pub(crate) fn run_build_tool(
+    env: &dyn Environment,
+    cmd: &mut std::process::Command,
+) -> std::io::Result<std::process::Output> {
+    if CAPTURED_OUTPUT.with(|captured| captured.borrow().is_none()) {
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+        return with_suspend_all_spinners(env, || cmd.output());
+    }
+    let output = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+    CAPTURED_OUTPUT.with(|captured| {
+        if let Some(captured) = captured.borrow_mut().as_mut() {
+            captured.extend_from_slice(&output.stdout);
+            captured.extend_from_slice(&output.stderr);
+        }
+    });
+    Ok(output)
+}
+
 /// Execute a command and return its output bytes.
 /// If the catch_output is false, the return bytes will always be empty.
 pub fn execute_command(
@@ -344,22 +426,18 @@ pub fn execute_command(
     }
     let mut cmd = direct_or_shell_command(command, cwd)?;
 
-    if !catch_output {
-        cmd.stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-    }
     for (key, value) in vars {
         cmd.env(key.as_ref(), value);
     }
     let output = if catch_output {
         cmd.output()
     } else {
-        with_suspend_all_spinners(env, || cmd.output())
+        run_build_tool(env, &mut cmd)
     }
     .with_context(|| format!("Error executing custom build step {cmd:#?}"))?;
     if output.status.success() {
-        Ok(output.stdout)
+        // Output that was not caught was shown to the user, or collected.
+        Ok(if catch_output { output.stdout } else { vec![] })
     } else {
         Err(DfxError::new(BuildError::CustomToolError(
             output.status.code(),
@@ -454,6 +532,9 @@ pub fn get_and_write_environment_variables<'a>(
 fn write_environment_variables(vars: &[Env<'_>], write_path: &Path) -> DfxResult {
     const START_TAG: &str = "\n# DFX CANISTER ENVIRONMENT VARIABLES";
     const END_TAG: &str = "\n# END DFX CANISTER ENVIRONMENT VARIABLES";
+    // Canisters that are built in parallel must not read and write the file at the same time.
+    static WRITE_LOCK: Mutex<()> = Mutex::new(());
+    let _guard = WRITE_LOCK.lock().unwrap();
     let mut write_string = String::from(START_TAG);
     for (var, val) in vars {
         if let Some(val) = val.to_str() {
@@ -502,6 +583,11 @@ pub struct BuildConfig {
     pub canisters_to_build: Option<Vec<String>>,
     /// If environment variables should be output to a `.env` file, `env_file` is set to its path.
     pub env_file: Option<PathBuf>,
+    /// The number of canisters that may be built at the same time.
+    pub jobs: usize,
+    /// If unchanged canisters should not be built again, `cache` holds where their artifacts are cached.
+    /// Builds are not cached unless requested.
+    pub cache: Option<BuildCache>,
 }
 
 impl BuildConfig {
@@ -519,6 +605,8 @@ impl BuildConfig {
             lsp_root: network_root.join("lsp/"),
             canisters_to_build: None,
             env_file: config.get_output_env_file(None)?,
+            jobs: 1,
+            cache: None,
         })
     }
 
@@ -532,6 +620,17 @@ impl BuildConfig {
     pub fn with_env_file(self, env_file: Option<PathBuf>) -> Self {
         Self { env_file, ..self }
     }
+
+    pub fn with_jobs(self, jobs: usize) -> Self {
+        Self {
+            jobs: jobs.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_cache(self, cache: Option<BuildCache>) -> Self {
+        Self { cache, ..self }
+    }
 }
 
 pub struct BuilderPool {
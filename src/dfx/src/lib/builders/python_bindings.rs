@@ -0,0 +1,265 @@
+//! Generates Python type definitions from a Candid interface.
+//!
+//! Candid values are described the way Python clients usually represent them:
+//! records are dictionaries keyed by field name, variants are dictionaries with a single key,
+//! `opt` values are `Optional` and principals are their textual representation.
+use candid::types::{Field, Label, Type, TypeEnv, TypeInner};
+
+const HEADER: &str = r#"# This file was generated by dfx from the Candid interface of the canister.
+from typing import Any, List, NoReturn, Optional, Protocol, Tuple, TypedDict, Union
+
+# Principals are represented by their textual representation.
+Principal = str
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+pub fn compile(env: &TypeEnv, actor: &Option<Type>) -> String {
+    let mut generator = Generator {
+        env,
+        definitions: vec![],
+    };
+    for (name, ty) in env.0.iter() {
+        generator.define(&python_ident(name), ty);
+    }
+    let service = actor
+        .as_ref()
+        .map(|actor| generator.service(actor))
+        .unwrap_or_default();
+
+    let mut output = HEADER.to_string();
+    for definition in generator.definitions {
+        output.push('\n');
+        output.push_str(&definition);
+        output.push('\n');
+    }
+    if !service.is_empty() {
+        output.push_str("\n\n");
+        output.push_str(&service);
+    }
+    output
+}
+
+struct Generator<'a> {
+    env: &'a TypeEnv,
+    definitions: Vec<String>,
+}
+
+impl Generator<'_> {
+    /// Adds a definition for a type with the given name.
+    fn define(&mut self, name: &str, ty: &Type) {
+        match ty.as_ref() {
+            TypeInner::Record(fields) if !is_tuple(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        let label = label(field);
+                        let ty =
+                            self.type_expr(&format!("{name}_{}", python_ident(&label)), &field.ty);
+                        format!("{label:?}: {ty}")
+                    })
+                    .collect::<Vec<_>>();
+                self.definitions.push(format!(
+                    "{name} = TypedDict({name:?}, {{{}}})",
+                    fields.join(", ")
+                ));
+            }
+            TypeInner::Variant(fields) => {
+                let mut cases = vec![];
+                for field in fields {
+                    let label = label(field);
+                    let case = format!("{name}_{}", python_ident(&label));
+                    let ty = self.type_expr(&case, &field.ty);
+                    self.definitions
+                        .push(format!("{case} = TypedDict({case:?}, {{{label:?}: {ty}}})"));
+                    cases.push(format!("{case:?}"));
+                }
+                let ty = if cases.is_empty() {
+                    "NoReturn".to_string()
+                } else {
+                    format!("Union[{}]", cases.join(", "))
+                };
+                self.definitions.push(format!("{name} = {ty}"));
+            }
+            _ => {
+                let ty = self.type_expr(name, ty);
+                self.definitions.push(format!("{name} = {ty}"));
+            }
+        }
+    }
+
+    /// Returns a type expression for a type, defining anonymous records and variants under the given name.
+    fn type_expr(&mut self, name: &str, ty: &Type) -> String {
+        match ty.as_ref() {
+            TypeInner::Null => "None".to_string(),
+            TypeInner::Bool => "bool".to_string(),
+            TypeInner::Nat
+            | TypeInner::Int
+            | TypeInner::Nat8
+            | TypeInner::Nat16
+            | TypeInner::Nat32
+            | TypeInner::Nat64
+            | TypeInner::Int8
+            | TypeInner::Int16
+            | TypeInner::Int32
+            | TypeInner::Int64 => "int".to_string(),
+            TypeInner::Float32 | TypeInner::Float64 => "float".to_string(),
+            TypeInner::Text => "str".to_string(),
+            TypeInner::Empty => "NoReturn".to_string(),
+            TypeInner::Principal | TypeInner::Service(_) => "Principal".to_string(),
+            TypeInner::Func(_) => "Tuple[Principal, str]".to_string(),
+            TypeInner::Var(id) => format!("{:?}", python_ident(id)),
+            TypeInner::Opt(ty) => format!("Optional[{}]", self.type_expr(name, ty)),
+            TypeInner::Vec(ty) if matches!(ty.as_ref(), TypeInner::Nat8) => "bytes".to_string(),
+            TypeInner::Vec(ty) => format!("List[{}]", self.type_expr(name, ty)),
+            TypeInner::Record(fields) if is_tuple(fields) => {
+                if fields.is_empty() {
+                    return "Tuple[()]".to_string();
+                }
+                let types = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| self.type_expr(&format!("{name}_{i}"), &field.ty))
+                    .collect::<Vec<_>>();
+                format!("Tuple[{}]", types.join(", "))
+            }
+            TypeInner::Record(_) | TypeInner::Variant(_) => {
+                self.define(name, ty);
+                format!("{name:?}")
+            }
+            _ => "Any".to_string(),
+        }
+    }
+
+    /// Returns a `Protocol` class describing the methods of a service.
+    fn service(&mut self, actor: &Type) -> String {
+        let Ok(actor) = self.env.trace_type(actor) else {
+            return String::new();
+        };
+        let service = match actor.as_ref() {
+            TypeInner::Class(_, service) => match self.env.trace_type(service) {
+                Ok(service) => service,
+                Err(_) => return String::new(),
+            },
+            _ => actor,
+        };
+        let TypeInner::Service(methods) = service.as_ref() else {
+            return String::new();
+        };
+
+        let mut output = "class Service(Protocol):\n".to_string();
+        for (method_name, ty) in methods {
+            let Ok(ty) = self.env.trace_type(ty) else {
+                continue;
+            };
+            let TypeInner::Func(func) = ty.as_ref() else {
+                continue;
+            };
+            let ident = python_ident(method_name);
+            let args = func
+                .args
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| {
+                    let ty = self.type_expr(&format!("{ident}_arg{i}"), ty);
+                    format!(", arg{i}: {ty}")
+                })
+                .collect::<String>();
+            let mut rets = func
+                .rets
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| self.type_expr(&format!("{ident}_ret{i}"), ty))
+                .collect::<Vec<_>>();
+            let ret = match rets.len() {
+                0 => "None".to_string(),
+                1 => rets.remove(0),
+                _ => format!("Tuple[{}]", rets.join(", ")),
+            };
+            output.push_str(&format!("    def {ident}(self{args}) -> {ret}: ...\n"));
+        }
+        if methods.is_empty() {
+            output.push_str("    pass\n");
+        }
+        output
+    }
+}
+
+fn is_tuple(fields: &[Field]) -> bool {
+    fields
+        .iter()
+        .enumerate()
+        .all(|(i, field)| matches!(field.id.as_ref(), Label::Unnamed(n) | Label::Id(n) if *n == i as u32))
+}
+
+fn label(field: &Field) -> String {
+    match field.id.as_ref() {
+        Label::Named(name) => name.clone(),
+        Label::Id(n) | Label::Unnamed(n) => n.to_string(),
+    }
+}
+
+/// Turns a Candid name into a valid Python identifier.
+fn python_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid_parser::utils::CandidSource;
+
+    #[test]
+    fn test_compile() {
+        let (env, actor) = CandidSource::Text(
+            r#"
+            type Profile = record { name : text; age : opt nat8; from : principal };
+            type Result = variant { ok : Profile; err : text };
+            service : {
+                get : (text) -> (Result) query;
+                put : (record { text; blob }) -> ();
+            }
+            "#,
+        )
+        .load()
+        .unwrap();
+
+        let bindings = compile(&env, &actor);
+        assert!(bindings.starts_with(HEADER));
+        for line in [
+            r#"Profile = TypedDict("Profile", {"#,
+            r#""age": Optional[int]"#,
+            r#""from": Principal"#,
+            r#""name": str"#,
+            r#"Result_ok = TypedDict("Result_ok", {"ok": "Profile"})"#,
+            r#"Result_err = TypedDict("Result_err", {"err": str})"#,
+            "class Service(Protocol):",
+            r#"    def get(self, arg0: str) -> "Result": ..."#,
+            "    def put(self, arg0: Tuple[str, bytes]) -> None: ...",
+        ] {
+            assert!(bindings.contains(line), "{line} not found in:\n{bindings}");
+        }
+    }
+
+    #[test]
+    fn test_python_ident() {
+        assert_eq!(python_ident("from"), "from_");
+        assert_eq!(python_ident("http-request"), "http_request");
+        assert_eq!(python_ident("1st"), "_1st");
+    }
+}
@@ -1,19 +1,17 @@
 use crate::lib::builders::{
-    BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
+    run_build_tool, BuildConfig, BuildOutput, CanisterBuilder, IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::rust::RustCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::models::canister::CanisterPool;
-use crate::util::with_suspend_all_spinners;
 use anyhow::{anyhow, bail, Context};
 use candid::Principal as CanisterId;
 use fn_error_context::context;
 use slog::{info, o};
 use std::path::PathBuf;
 use std::process::Command;
-use std::process::Stdio;
 
 pub struct RustBuilder {
     logger: slog::Logger,
@@ -65,8 +63,6 @@ impl CanisterBuilder for RustBuilder {
 
         let mut cargo = Command::new("cargo");
         cargo
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
             .current_dir(canister_info.get_workspace_root())
             .arg("build")
             .arg("--target")
@@ -96,9 +92,7 @@ impl CanisterBuilder for RustBuilder {
             package
         );
 
-        let output = with_suspend_all_spinners(env, || {
-            cargo.output().context("Failed to run 'cargo build'. You might need to run `cargo update` (or a similar command like `cargo vendor`) if you have updated `Cargo.toml`, because `dfx build` uses the --locked flag with Cargo.")
-        })?;
+        let output = run_build_tool(env, &mut cargo).context("Failed to run 'cargo build'. You might need to run `cargo update` (or a similar command like `cargo vendor`) if you have updated `Cargo.toml`, because `dfx build` uses the --locked flag with Cargo.")?;
 
         if !output.status.success() {
             bail!("Failed to compile the rust package: {}", package);
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use crate::lib::error::DfxResult;
 use crate::lib::metadata::config::CanisterMetadataConfig;
-
+use crate::util::candid_json::argument_format_from_path;
 use anyhow::{anyhow, bail, Context};
 use candid::Principal as CanisterId;
 use candid::Principal;
@@ -51,6 +51,7 @@ pub struct CanisterInfo {
     dependencies: Vec<String>,
     pre_install: Vec<String>,
     post_install: Vec<String>,
+    watch: Vec<PathBuf>,
     main: Option<PathBuf>,
     shrink: Option<bool>,
     optimize: Option<WasmOptLevel>,
@@ -175,6 +176,11 @@ impl CanisterInfo {
 
         let pre_install = canister_config.pre_install.clone().into_vec();
         let post_install = canister_config.post_install.clone().into_vec();
+        let watch = canister_config
+            .watch
+            .iter()
+            .map(|path| workspace_root.join(path))
+            .collect();
         let metadata = CanisterMetadataConfig::new(&canister_config.metadata, &network_name);
 
         let gzip = canister_config.gzip.unwrap_or(false);
@@ -195,6 +201,7 @@ impl CanisterInfo {
             dependencies,
             pre_install,
             post_install,
+            watch,
             main: canister_config.main.clone(),
             shrink: canister_config.shrink,
             optimize: canister_config.optimize,
@@ -274,6 +281,10 @@ impl CanisterInfo {
         &self.post_install
     }
 
+    pub fn get_watch_paths(&self) -> &[PathBuf] {
+        &self.watch
+    }
+
     pub fn get_args(&self) -> &Option<String> {
         &self.args
     }
@@ -397,24 +408,25 @@ impl CanisterInfo {
         self.gzip
     }
 
-    /// Get the init arg from the dfx.json configuration.
+    /// Get the init arg from the dfx.json configuration, with its argument type.
     ///
-    /// If the `init_arg` field is defined, it will be returned.
-    /// If the `init_arg_file` field is defined, the content of the file will be returned.
+    /// If the `init_arg` field is defined, it will be returned as Candid text.
+    /// If the `init_arg_file` field is defined, the content of the file will be returned,
+    /// as JSON or YAML if the file has a `.json`, `.yaml` or `.yml` extension and as Candid text otherwise.
     /// If both fields are defined, an error will be returned.
     /// If neither field is defined, `None` will be returned.
-    pub fn get_init_arg(&self) -> DfxResult<Option<String>> {
+    pub fn get_init_arg(&self) -> DfxResult<Option<(String, &'static str)>> {
         let init_arg_value = match (&self.init_arg, &self.init_arg_file) {
             (Some(_), Some(_)) => {
                 bail!("At most one of the fields 'init_arg' and 'init_arg_file' should be defined in `dfx.json`.
 Please remove one of them or leave both undefined.");
             }
-            (Some(arg), None) => Some(arg.clone()),
+            (Some(arg), None) => Some((arg.clone(), "idl")),
             (None, Some(arg_file)) => {
                 // The file path is relative to the workspace root.
                 let absolute_path = self.get_workspace_root().join(arg_file);
                 let content = dfx_core::fs::read_to_string(&absolute_path)?;
-                Some(content)
+                Some((content, argument_format_from_path(&absolute_path)))
             }
             (None, None) => None,
         };
@@ -9,6 +9,7 @@ use std::process::{Command, Stdio};
 
 pub struct RustCanisterInfo {
     package: String,
+    package_root: PathBuf,
     output_wasm_path: PathBuf,
 }
 
@@ -17,6 +18,11 @@ impl RustCanisterInfo {
         &self.package
     }
 
+    /// The directory that contains the `Cargo.toml` of the package.
+    pub fn get_package_root(&self) -> &Path {
+        self.package_root.as_path()
+    }
+
     pub fn get_output_wasm_path(&self) -> &Path {
         self.output_wasm_path.as_path()
     }
@@ -94,8 +100,14 @@ impl CanisterInfoFactory for RustCanisterInfo {
             .join(format!("wasm32-unknown-unknown/release/{wasm_name}.wasm"))
             .into();
 
+        let package_root = package_info.manifest_path.parent().map_or_else(
+            || info.get_workspace_root().to_path_buf(),
+            |root| root.as_std_path().to_path_buf(),
+        );
+
         Ok(Self {
             package,
+            package_root,
             output_wasm_path,
         })
     }
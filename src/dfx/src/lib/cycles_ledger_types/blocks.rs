@@ -0,0 +1,47 @@
+// Types of the ICRC-3 `icrc3_get_blocks` endpoint.
+// Copied from https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/ICRC-3.did
+use candid::{CandidType, Int, Nat};
+use serde::Deserialize;
+
+#[derive(CandidType, Clone, Debug)]
+pub struct GetBlocksArgs {
+    pub start: Nat,
+    pub length: Nat,
+}
+
+/// Result of `icrc3_get_blocks`.
+/// The `archived_blocks` field is not decoded, because the cycles ledger does not archive blocks.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetBlocksResult {
+    pub log_length: Nat,
+    pub blocks: Vec<BlockWithId>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BlockWithId {
+    pub id: Nat,
+    pub block: Value,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Blob(serde_bytes::ByteBuf),
+    Text(String),
+    Nat(Nat),
+    Int(Int),
+    Array(Vec<Value>),
+    Map(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the value of a field if this value is a map.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Map(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
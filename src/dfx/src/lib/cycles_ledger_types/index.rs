@@ -0,0 +1,99 @@
+// Types of the ICRC index canister of the cycles ledger.
+// Copied from https://github.com/dfinity/ic/blob/master/rs/ledger_suite/icrc1/index-ng/index-ng.did
+use candid::{CandidType, Nat, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Deserialize;
+
+/// Id of the index canister of the cycles ledger on the IC.
+pub const MAINNET_CYCLES_LEDGER_INDEX_CANISTER_ID: Principal =
+    Principal::from_slice(&[0x00, 0x00, 0x00, 0x00, 0x02, 0x10, 0x00, 0x03, 0x01, 0x01]);
+
+#[derive(CandidType, Clone, Debug)]
+pub struct GetAccountTransactionsArgs {
+    pub account: Account,
+    pub start: Option<Nat>,
+    pub max_results: Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetTransactions {
+    pub balance: Nat,
+    pub transactions: Vec<TransactionWithId>,
+    pub oldest_tx_id: Option<Nat>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetTransactionsErr {
+    pub message: String,
+}
+
+pub type GetTransactionsResult = Result<GetTransactions, GetTransactionsErr>;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionWithId {
+    pub id: Nat,
+    pub transaction: Transaction,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub kind: String,
+    pub mint: Option<Mint>,
+    pub burn: Option<Burn>,
+    pub transfer: Option<Transfer>,
+    pub approve: Option<Approve>,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Mint {
+    pub to: Account,
+    pub amount: Nat,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Burn {
+    pub from: Account,
+    pub amount: Nat,
+    pub spender: Option<Account>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Transfer {
+    pub from: Account,
+    pub to: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub spender: Option<Account>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Approve {
+    pub from: Account,
+    pub spender: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub expected_allowance: Option<Nat>,
+    pub expires_at: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_canister_id() {
+        assert_eq!(
+            MAINNET_CYCLES_LEDGER_INDEX_CANISTER_ID,
+            Principal::from_text("ul4oc-4iaaa-aaaaq-qaabq-cai").unwrap()
+        );
+    }
+}
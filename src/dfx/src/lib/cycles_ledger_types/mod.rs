@@ -1,3 +1,5 @@
+pub mod blocks;
 pub mod create_canister;
 pub mod deposit;
+pub mod index;
 pub mod withdraw;
@@ -10,7 +10,7 @@ use fn_error_context::context;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
 };
 
@@ -44,6 +44,20 @@ pub struct PulledCanister {
     /// The downloaded wasm hash when `dfx deps pull`
     /// It is allowed to be different from `wasm_hash`
     pub wasm_hash_download: String,
+    /// The hash of the service candid extracted from the downloaded wasm
+    /// `dfx deps pull --locked` fails if it changes, just like `wasm_hash_download`
+    /// Empty in pulled.json written by older versions of dfx
+    #[serde(default)]
+    pub candid_hash: String,
+    /// The url from which the wasm was downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub wasm_url: Option<String>,
+    /// The module hash of the canister on chain when `dfx deps pull`
+    /// `dfx deps outdated` compares it with the current module hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub module_hash: Option<String>,
     /// From the dfx metadata of the downloaded wasm module
     pub init_guide: String,
     /// From the dfx metadata of the downloaded wasm module
@@ -78,6 +92,139 @@ impl PulledJson {
     }
 }
 
+/// Print the pull dependencies as a tree, starting from the direct dependencies defined in `dfx.json`.
+///
+/// Each line shows a canister, the hash of its downloaded wasm and the url it was downloaded from.
+/// The url usually tells the version, which the hash alone does not.
+/// A canister that already appeared in the tree is printed again with `(*)`, without its dependencies.
+pub fn format_pull_tree(
+    pulled_json: &PulledJson,
+    pull_canisters_in_config: &BTreeMap<String, Principal>,
+) -> DfxResult<String> {
+    fn add_lines(
+        pulled_json: &PulledJson,
+        canister_id: &Principal,
+        prefix: &str,
+        connector: &str,
+        printed: &mut BTreeSet<Principal>,
+        lines: &mut Vec<String>,
+    ) -> DfxResult {
+        let pulled_canister = pulled_json
+            .canisters
+            .get(canister_id)
+            .ok_or_else(|| anyhow!("Failed to find {canister_id} in pulled.json"))?;
+        let prompt = get_canister_prompt(canister_id, pulled_canister);
+        let mut line = format!(
+            "{prefix}{connector}{prompt} {}",
+            pulled_canister.wasm_hash_download
+        );
+        if let Some(wasm_url) = &pulled_canister.wasm_url {
+            line.push_str(&format!(" from {wasm_url}"));
+        }
+        if !printed.insert(*canister_id) {
+            lines.push(format!("{line} (*)"));
+            return Ok(());
+        }
+        lines.push(line);
+        let child_prefix = match connector {
+            "├── " => format!("{prefix}│   "),
+            "└── " => format!("{prefix}    "),
+            _ => prefix.to_string(),
+        };
+        let count = pulled_canister.dependencies.len();
+        for (i, dependency) in pulled_canister.dependencies.iter().enumerate() {
+            let connector = if i + 1 == count {
+                "└── "
+            } else {
+                "├── "
+            };
+            add_lines(
+                pulled_json,
+                dependency,
+                &child_prefix,
+                connector,
+                printed,
+                lines,
+            )?;
+        }
+        Ok(())
+    }
+
+    let mut lines = vec![];
+    let mut printed = BTreeSet::new();
+    for canister_id in pull_canisters_in_config.values() {
+        add_lines(pulled_json, canister_id, "", "", &mut printed, &mut lines)?;
+    }
+    Ok(lines.join("\n"))
+}
+
+/// How a pulled canister differs from its entry in `pulled.json`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PulledChange {
+    Added,
+    Removed,
+    Wasm { locked: String, pulled: String },
+    WasmUrl { locked: String, pulled: String },
+    Candid { locked: String, pulled: String },
+}
+
+impl std::fmt::Display for PulledChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PulledChange::Added => write!(f, "not in pulled.json"),
+            PulledChange::Removed => write!(f, "no longer a dependency"),
+            PulledChange::Wasm { locked, pulled } => {
+                write!(f, "wasm hash changed from {locked} to {pulled}")
+            }
+            PulledChange::WasmUrl { locked, pulled } => {
+                write!(f, "wasm url changed from {locked} to {pulled}")
+            }
+            PulledChange::Candid { locked, pulled } => {
+                write!(f, "candid hash changed from {locked} to {pulled}")
+            }
+        }
+    }
+}
+
+/// Compare freshly pulled canisters with the ones locked in `pulled.json`.
+///
+/// The candid hash is not compared if `pulled.json` doesn't have one.
+pub fn diff_pulled(locked: &PulledJson, pulled: &PulledJson) -> Vec<(Principal, PulledChange)> {
+    let mut changes = vec![];
+    for (canister_id, locked_canister) in &locked.canisters {
+        let Some(pulled_canister) = pulled.canisters.get(canister_id) else {
+            changes.push((*canister_id, PulledChange::Removed));
+            continue;
+        };
+        if locked_canister.wasm_hash_download != pulled_canister.wasm_hash_download {
+            changes.push((
+                *canister_id,
+                PulledChange::Wasm {
+                    locked: locked_canister.wasm_hash_download.clone(),
+                    pulled: pulled_canister.wasm_hash_download.clone(),
+                },
+            ));
+        }
+        if !locked_canister.candid_hash.is_empty()
+            && locked_canister.candid_hash != pulled_canister.candid_hash
+        {
+            changes.push((
+                *canister_id,
+                PulledChange::Candid {
+                    locked: locked_canister.candid_hash.clone(),
+                    pulled: pulled_canister.candid_hash.clone(),
+                },
+            ));
+        }
+    }
+    for canister_id in pulled.canisters.keys() {
+        if !locked.canisters.contains_key(canister_id) {
+            changes.push((*canister_id, PulledChange::Added));
+        }
+    }
+    changes
+}
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct InitJson {
     canisters: BTreeMap<Principal, InitItem>,
@@ -151,6 +298,7 @@ pub fn get_pull_canisters_in_config(
 ///   - whether the wasm modules in pulled cache are consistent with `pulled.json`
 ///     - This can happen when the user manually modifies the wasm file in the cache
 ///     - Or the same canister was pulled in different projects and the downloaded wasm is different
+///   - whether the service candid files in pulled cache are consistent with `pulled.json`
 pub fn validate_pulled(
     pulled_json: &PulledJson,
     pull_canisters_in_config: &BTreeMap<String, Principal>,
@@ -190,6 +338,23 @@ pub fn validate_pulled(
 The pulled cache may be modified manually or the same canister was pulled in different projects."
             );
         }
+
+        if !pulled_canister.candid_hash.is_empty() {
+            let candid_path = get_pulled_service_candid_path(canister_id)?;
+            let bytes = dfx_core::fs::read(&candid_path)?;
+            let hash_cache = hex::encode(Sha256::digest(bytes));
+            if hash_cache != pulled_canister.candid_hash {
+                bail!(
+                    "The candid of {canister_id} in pulled cache has different hash than in pulled.json:
+    The pulled cache is at {candid_path:?}. Its hash is:
+        {hash_cache}
+    The hash (candid_hash) in pulled.json is:
+        {}
+The pulled cache may be modified manually or the same canister was pulled in different projects.",
+                    pulled_canister.candid_hash
+                );
+            }
+        }
     }
 
     Ok(())
@@ -225,6 +390,15 @@ pub fn load_pulled_json(project_root: &Path) -> DfxResult<PulledJson> {
     Ok(pulled_json)
 }
 
+/// Load `pulled.json` in `deps/` if it exists.
+pub fn load_pulled_json_if_exists(project_root: &Path) -> DfxResult<Option<PulledJson>> {
+    if get_pulled_json_path(project_root).exists() {
+        Ok(Some(load_pulled_json(project_root)?))
+    } else {
+        Ok(None)
+    }
+}
+
 /// Save `pulled.json` in `deps/`.
 #[context("Failed to save pulled.json")]
 pub fn save_pulled_json(project_root: &Path, pulled_json: &PulledJson) -> DfxResult {
@@ -320,3 +494,111 @@ pub fn get_canister_prompt(canister_id: &Principal, pulled_canister: &PulledCani
         None => canister_id.to_text(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pulled_canister(wasm_hash_download: &str, candid_hash: &str) -> PulledCanister {
+        PulledCanister {
+            wasm_hash_download: wasm_hash_download.to_string(),
+            candid_hash: candid_hash.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_pulled_reports_changes() {
+        let a = Principal::from_text("aaaaa-aa").unwrap();
+        let b = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let c = Principal::from_text("mxzaz-hqaaa-aaaar-qaada-cai").unwrap();
+        let locked = PulledJson {
+            canisters: BTreeMap::from([
+                (a, pulled_canister("01", "02")),
+                (b, pulled_canister("03", "")),
+            ]),
+        };
+
+        assert!(diff_pulled(&locked, &locked).is_empty());
+
+        // An empty candid hash in pulled.json is not compared.
+        let pulled = PulledJson {
+            canisters: BTreeMap::from([
+                (a, pulled_canister("01", "04")),
+                (c, pulled_canister("05", "06")),
+            ]),
+        };
+        assert_eq!(
+            diff_pulled(&locked, &pulled),
+            vec![
+                (
+                    a,
+                    PulledChange::Candid {
+                        locked: "02".to_string(),
+                        pulled: "04".to_string()
+                    }
+                ),
+                (b, PulledChange::Removed),
+                (c, PulledChange::Added),
+            ]
+        );
+
+        let pulled = PulledJson {
+            canisters: BTreeMap::from([
+                (a, pulled_canister("07", "02")),
+                (b, pulled_canister("03", "08")),
+            ]),
+        };
+        assert_eq!(
+            diff_pulled(&locked, &pulled),
+            vec![(
+                a,
+                PulledChange::Wasm {
+                    locked: "01".to_string(),
+                    pulled: "07".to_string()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn format_pull_tree_marks_repeated_canisters() {
+        let a = Principal::from_text("aaaaa-aa").unwrap();
+        let b = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let c = Principal::from_text("mxzaz-hqaaa-aaaar-qaada-cai").unwrap();
+        let pulled_json = PulledJson {
+            canisters: BTreeMap::from([
+                (a, pulled_canister("01", "")),
+                (
+                    b,
+                    PulledCanister {
+                        name: Some("dep_b".to_string()),
+                        dependencies: vec![a, c],
+                        ..pulled_canister("02", "")
+                    },
+                ),
+                (
+                    c,
+                    PulledCanister {
+                        name: Some("dep_c".to_string()),
+                        dependencies: vec![a],
+                        wasm_url: Some("https://example.com/c-1.2.0.wasm".to_string()),
+                        ..pulled_canister("03", "")
+                    },
+                ),
+            ]),
+        };
+        let pull_canisters_in_config =
+            BTreeMap::from([("dep_b".to_string(), b), ("dep_c".to_string(), c)]);
+        assert_eq!(
+            format_pull_tree(&pulled_json, &pull_canisters_in_config).unwrap(),
+            format!(
+                "{b} (dep_b) 02
+├── {a} 01
+└── {c} (dep_c) 03 from https://example.com/c-1.2.0.wasm
+    └── {a} 01 (*)
+{c} (dep_c) 03 from https://example.com/c-1.2.0.wasm (*)"
+            )
+        );
+    }
+}
@@ -5,7 +5,7 @@ use dfx_core::fs::{composite::ensure_parent_dir_exists, read, read_to_string, wr
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
-use super::{super::PulledCanister, write_to_tempfile_then_rename};
+use super::{super::PulledCanister, write_to_tempfile_then_rename, CheckChange};
 use crate::lib::deps::{
     get_pulled_canister_dir, get_pulled_service_candid_path, get_pulled_wasm_path,
 };
@@ -130,14 +130,20 @@ pub(super) fn facade_dependencies(canister_id: &Principal) -> Option<Vec<Princip
         .map(|facade| facade.dependencies.clone())
 }
 
-pub(super) async fn facade_download(canister_id: &Principal) -> DfxResult<Option<PulledCanister>> {
+pub(super) async fn facade_download(
+    canister_id: &Principal,
+    check_change: &CheckChange<'_>,
+) -> DfxResult<Option<PulledCanister>> {
     if let Some(facade) = FACADE.get(canister_id) {
         let mut pulled_canister = PulledCanister {
             dependencies: facade.dependencies.clone(),
             init_guide: facade.init_guide.clone(),
             gzip: facade.wasm_url.ends_with(".gz"),
+            wasm_url: Some(facade.wasm_url.clone()),
             ..Default::default()
         };
+        // the wasm url contains the IC revision, so it identifies the wasm
+        check_change(canister_id, &pulled_canister)?;
         let ic_rev_path = get_cache_root()?
             .join("pulled")
             .join(".facade")
@@ -70,16 +70,34 @@ async fn get_dependencies(
     Ok(dependencies)
 }
 
+/// Checks whether a pull may change a canister, before its files in the cache are replaced.
+///
+/// It is called with the fields of the pulled canister known so far: `wasm_url` and `wasm_hash`
+/// before the download, and also `wasm_hash_download` after it.
+pub type CheckChange<'a> = dyn Fn(&Principal, &PulledCanister) -> DfxResult + 'a;
+
 pub async fn download_all_and_generate_pulled_json(
     agent: &Agent,
     logger: &Logger,
     all_dependencies: &[Principal],
+    check_change: &CheckChange<'_>,
 ) -> DfxResult<PulledJson> {
     let mut any_download_fail = false;
     let mut pulled_json = PulledJson::default();
     for canister_id in all_dependencies {
-        match download_and_generate_pulled_canister(agent, logger, *canister_id).await {
-            Ok(pulled_canister) => {
+        match download_and_generate_pulled_canister(agent, logger, *canister_id, check_change).await
+        {
+            Ok(mut pulled_canister) => {
+                let service_candid_path = get_pulled_service_candid_path(canister_id)?;
+                let candid_hash = Sha256::digest(dfx_core::fs::read(&service_candid_path)?);
+                pulled_canister.candid_hash = hex::encode(candid_hash);
+                // Only informational, so a canister whose module hash can't be read is still pulled.
+                pulled_canister.module_hash =
+                    read_state_tree_canister_module_hash(agent, *canister_id)
+                        .await
+                        .ok()
+                        .flatten()
+                        .map(hex::encode);
                 pulled_json.canisters.insert(*canister_id, pulled_canister);
             }
             Err(e) => {
@@ -100,9 +118,10 @@ async fn download_and_generate_pulled_canister(
     agent: &Agent,
     logger: &Logger,
     canister_id: Principal,
+    check_change: &CheckChange<'_>,
 ) -> DfxResult<PulledCanister> {
     info!(logger, "Pulling canister {canister_id}...");
-    if let Some(pulled_canister) = facade_download(&canister_id).await? {
+    if let Some(pulled_canister) = facade_download(&canister_id, check_change).await? {
         return Ok(pulled_canister);
     }
 
@@ -110,9 +129,11 @@ async fn download_and_generate_pulled_canister(
 
     let dfx_metadata = fetch_dfx_metadata(agent, &canister_id).await?;
     let pullable = dfx_metadata.get_pullable()?;
+    pulled_canister.wasm_url = Some(pullable.wasm_url.clone());
 
     let hash_on_chain = get_hash_on_chain(agent, logger, canister_id, pullable).await?;
     pulled_canister.wasm_hash = hex::encode(&hash_on_chain);
+    check_change(&canister_id, &pulled_canister)?;
 
     // skip download if cache hit
     let mut cache_hit = false;
@@ -133,13 +154,6 @@ async fn download_and_generate_pulled_canister(
     }
 
     if !cache_hit {
-        // delete files from previous pull
-        let pulled_canister_dir = get_pulled_canister_dir(&canister_id)?;
-        if pulled_canister_dir.exists() {
-            dfx_core::fs::remove_dir_all(&pulled_canister_dir)?;
-        }
-        dfx_core::fs::create_dir_all(&pulled_canister_dir)?;
-
         // lookup `wasm_url` in dfx metadata
         let wasm_url = reqwest::Url::parse(&pullable.wasm_url)?;
 
@@ -149,6 +163,14 @@ async fn download_and_generate_pulled_canister(
         // hash check
         let hash_download = Sha256::digest(&content);
         pulled_canister.wasm_hash_download = hex::encode(hash_download);
+        check_change(&canister_id, &pulled_canister)?;
+
+        // delete files from previous pull
+        let pulled_canister_dir = get_pulled_canister_dir(&canister_id)?;
+        if pulled_canister_dir.exists() {
+            dfx_core::fs::remove_dir_all(&pulled_canister_dir)?;
+        }
+        dfx_core::fs::create_dir_all(&pulled_canister_dir)?;
 
         let gzip = decompress_bytes(&content).is_ok();
         pulled_canister.gzip = gzip;
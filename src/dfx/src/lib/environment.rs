@@ -23,13 +23,13 @@ use pocket_ic::nonblocking::PocketIc;
 use semver::Version;
 use slog::{Logger, Record};
 use std::borrow::Cow;
-use std::cell::RefCell;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use url::Url;
 
-pub trait Environment {
+/// Environments are `Sync` because canisters that are built in parallel share them.
+pub trait Environment: Sync {
     fn get_cache(&self) -> VersionCache;
     fn get_config(&self) -> Result<Option<Arc<Config>>, LoadDfxConfigError>;
     fn get_networks_config(&self) -> Arc<NetworksConfig>;
@@ -96,7 +96,7 @@ pub enum ProjectConfig {
 }
 
 pub struct EnvironmentImpl {
-    project_config: RefCell<ProjectConfig>,
+    project_config: RwLock<ProjectConfig>,
     shared_networks_config: Arc<NetworksConfig>,
     tool_config: Arc<Mutex<ToolConfig>>,
 
@@ -123,7 +123,7 @@ impl EnvironmentImpl {
 
         Ok(EnvironmentImpl {
             cache: VersionCache::with_version(&version),
-            project_config: RefCell::new(ProjectConfig::NotLoaded),
+            project_config: RwLock::new(ProjectConfig::NotLoaded),
             shared_networks_config: Arc::new(shared_networks_config),
             tool_config: Arc::new(Mutex::new(tool_config)),
             version: version.clone(),
@@ -186,7 +186,7 @@ impl EnvironmentImpl {
             }
             ProjectConfig::Loaded(Arc::new(config))
         });
-        self.project_config.replace(project_config);
+        *self.project_config.write().unwrap() = project_config;
         Ok(())
     }
 }
@@ -197,11 +197,15 @@ impl Environment for EnvironmentImpl {
     }
 
     fn get_config(&self) -> Result<Option<Arc<Config>>, LoadDfxConfigError> {
-        if matches!(*self.project_config.borrow(), ProjectConfig::NotLoaded) {
+        if matches!(
+            *self.project_config.read().unwrap(),
+            ProjectConfig::NotLoaded
+        ) {
             self.load_config()?;
         }
 
-        let config = if let ProjectConfig::Loaded(ref config) = *self.project_config.borrow() {
+        let config = if let ProjectConfig::Loaded(ref config) = *self.project_config.read().unwrap()
+        {
             Some(Arc::clone(config))
         } else {
             None
@@ -3,13 +3,13 @@ use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use anyhow::{anyhow, Context, Error};
 use byte_unit::Byte;
-use candid::Principal;
+use candid::{Nat, Principal};
 use dfx_core::config::model::dfinity::ConfigInterface;
 use fn_error_context::context;
 use ic_utils::interfaces::management_canister::{
     attributes::{ComputeAllocation, FreezingThreshold, MemoryAllocation, ReservedCyclesLimit},
     builders::WasmMemoryLimit,
-    LogVisibility, StatusCallResult,
+    DefiniteCanisterSettings, LogVisibility, StatusCallResult,
 };
 use num_traits::ToPrimitive;
 use std::convert::TryFrom;
@@ -130,11 +130,12 @@ pub fn get_compute_allocation(
     compute_allocation: Option<u64>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<ComputeAllocation>> {
     let compute_allocation = match (compute_allocation, config_interface, canister_name) {
         (Some(compute_allocation), _, _) => Some(compute_allocation),
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_compute_allocation(canister_name)? as _
+            config_interface.get_compute_allocation(canister_name, network)? as _
         }
         _ => None,
     };
@@ -150,11 +151,12 @@ pub fn get_memory_allocation(
     memory_allocation: Option<Byte>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<MemoryAllocation>> {
     let memory_allocation = match (memory_allocation, config_interface, canister_name) {
         (Some(memory_allocation), _, _) => Some(memory_allocation),
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_memory_allocation(canister_name)?
+            config_interface.get_memory_allocation(canister_name, network)?
         }
         _ => None,
     };
@@ -173,11 +175,12 @@ pub fn get_freezing_threshold(
     freezing_threshold: Option<u64>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<FreezingThreshold>> {
     let freezing_threshold = match (freezing_threshold, config_interface, canister_name) {
         (Some(freezing_threshold), _, _) => Some(freezing_threshold),
         (None, Some(config_interface), Some(canister_name)) => config_interface
-            .get_freezing_threshold(canister_name)?
+            .get_freezing_threshold(canister_name, network)?
             .map(|dur| dur.as_secs()),
         _ => None,
     };
@@ -194,11 +197,12 @@ pub fn get_reserved_cycles_limit(
     reserved_cycles_limit: Option<u128>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<ReservedCyclesLimit>> {
     let reserved_cycles_limit = match (reserved_cycles_limit, config_interface, canister_name) {
         (Some(reserved_cycles_limit), _, _) => Some(reserved_cycles_limit),
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_reserved_cycles_limit(canister_name)?
+            config_interface.get_reserved_cycles_limit(canister_name, network)?
         }
         _ => None,
     };
@@ -214,11 +218,12 @@ pub fn get_wasm_memory_limit(
     wasm_memory_limit: Option<Byte>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<WasmMemoryLimit>> {
     let wasm_memory_limit = match (wasm_memory_limit, config_interface, canister_name) {
         (Some(memory_limit), _, _) => Some(memory_limit),
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_wasm_memory_limit(canister_name)?
+            config_interface.get_wasm_memory_limit(canister_name, network)?
         }
         _ => None,
     };
@@ -235,11 +240,12 @@ pub fn get_wasm_memory_threshold(
     wasm_memory_threshold: Option<Byte>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<WasmMemoryLimit>> {
     let wasm_memory_threshold = match (wasm_memory_threshold, config_interface, canister_name) {
         (Some(memory_threshold), _, _) => Some(memory_threshold),
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_wasm_memory_threshold(canister_name)?
+            config_interface.get_wasm_memory_threshold(canister_name, network)?
         }
         _ => None,
     };
@@ -259,15 +265,247 @@ pub fn get_log_visibility(
     current_settings: Option<&StatusCallResult>,
     config_interface: Option<&ConfigInterface>,
     canister_name: Option<&str>,
+    network: &str,
 ) -> DfxResult<Option<LogVisibility>> {
     let log_visibility = match (log_visibility, config_interface, canister_name) {
         (Some(log_visibility), _, _) => {
             Some(log_visibility.to_log_visibility(env, current_settings)?)
         }
         (None, Some(config_interface), Some(canister_name)) => {
-            config_interface.get_log_visibility(canister_name)?
+            config_interface.get_log_visibility(canister_name, network)?
         }
         _ => None,
     };
     Ok(log_visibility)
 }
+
+/// Returns the settings that dfx.json configures for a canister on a network.
+pub fn get_canister_settings_from_config(
+    env: &dyn Environment,
+    config_interface: &ConfigInterface,
+    canister_name: &str,
+    network: &str,
+) -> DfxResult<CanisterSettings> {
+    let config_interface = Some(config_interface);
+    let canister_name = Some(canister_name);
+    Ok(CanisterSettings {
+        controllers: None,
+        compute_allocation: get_compute_allocation(None, config_interface, canister_name, network)?,
+        memory_allocation: get_memory_allocation(None, config_interface, canister_name, network)?,
+        freezing_threshold: get_freezing_threshold(None, config_interface, canister_name, network)?,
+        reserved_cycles_limit: get_reserved_cycles_limit(
+            None,
+            config_interface,
+            canister_name,
+            network,
+        )?,
+        wasm_memory_limit: get_wasm_memory_limit(None, config_interface, canister_name, network)?,
+        wasm_memory_threshold: get_wasm_memory_threshold(
+            None,
+            config_interface,
+            canister_name,
+            network,
+        )?,
+        log_visibility: get_log_visibility(
+            env,
+            None,
+            None,
+            config_interface,
+            canister_name,
+            network,
+        )?,
+    })
+}
+
+/// A setting whose value in dfx.json differs from its value on the canister.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SettingChange {
+    pub name: &'static str,
+    pub current: String,
+    pub configured: String,
+}
+
+/// Compares the settings configured in dfx.json with the current settings of a canister.
+///
+/// Returns the settings to update, which only contain the configured settings that differ,
+/// and a description of each of those changes.
+pub fn diff_canister_settings(
+    configured: &CanisterSettings,
+    current: &DefiniteCanisterSettings,
+) -> (CanisterSettings, Vec<SettingChange>) {
+    let mut changes = vec![];
+    let update = CanisterSettings {
+        controllers: None,
+        compute_allocation: diff_setting(
+            "compute_allocation",
+            configured
+                .compute_allocation
+                .map(|x| (x, Nat::from(u8::from(x)))),
+            Some(&current.compute_allocation),
+            &mut changes,
+        ),
+        memory_allocation: diff_setting(
+            "memory_allocation",
+            configured
+                .memory_allocation
+                .map(|x| (x, Nat::from(u64::from(x)))),
+            Some(&current.memory_allocation),
+            &mut changes,
+        ),
+        freezing_threshold: diff_setting(
+            "freezing_threshold",
+            configured
+                .freezing_threshold
+                .map(|x| (x, Nat::from(u64::from(x)))),
+            Some(&current.freezing_threshold),
+            &mut changes,
+        ),
+        reserved_cycles_limit: diff_setting(
+            "reserved_cycles_limit",
+            configured
+                .reserved_cycles_limit
+                .map(|x| (x, Nat::from(u128::from(x)))),
+            current.reserved_cycles_limit.as_ref(),
+            &mut changes,
+        ),
+        wasm_memory_limit: diff_setting(
+            "wasm_memory_limit",
+            configured
+                .wasm_memory_limit
+                .map(|x| (x, Nat::from(u64::from(x)))),
+            current.wasm_memory_limit.as_ref(),
+            &mut changes,
+        ),
+        wasm_memory_threshold: diff_setting(
+            "wasm_memory_threshold",
+            configured
+                .wasm_memory_threshold
+                .map(|x| (x, Nat::from(u64::from(x)))),
+            current.wasm_memory_threshold.as_ref(),
+            &mut changes,
+        ),
+        log_visibility: configured.log_visibility.as_ref().and_then(|configured| {
+            let configured_text = format_log_visibility(configured);
+            let current_text = format_log_visibility(&current.log_visibility);
+            if configured_text == current_text {
+                return None;
+            }
+            changes.push(SettingChange {
+                name: "log_visibility",
+                current: current_text,
+                configured: configured_text,
+            });
+            Some(configured.clone())
+        }),
+    };
+    (update, changes)
+}
+
+fn diff_setting<T>(
+    name: &'static str,
+    configured: Option<(T, Nat)>,
+    current: Option<&Nat>,
+    changes: &mut Vec<SettingChange>,
+) -> Option<T> {
+    let (value, configured) = configured?;
+    if current == Some(&configured) {
+        return None;
+    }
+    changes.push(SettingChange {
+        name,
+        current: current.map_or_else(|| "not set".to_string(), Nat::to_string),
+        configured: configured.to_string(),
+    });
+    Some(value)
+}
+
+/// Formats log visibility so that equal visibilities are formatted the same,
+/// regardless of the order of allowed viewers.
+fn format_log_visibility(log_visibility: &LogVisibility) -> String {
+    match log_visibility {
+        LogVisibility::Controllers => "controllers".to_string(),
+        LogVisibility::Public => "public".to_string(),
+        LogVisibility::AllowedViewers(viewers) => {
+            let mut viewers: Vec<_> = viewers.iter().map(Principal::to_text).collect();
+            viewers.sort();
+            format!("allowed viewers [{}]", viewers.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current_settings() -> DefiniteCanisterSettings {
+        DefiniteCanisterSettings {
+            controllers: vec![],
+            compute_allocation: Nat::from(0u8),
+            memory_allocation: Nat::from(0u8),
+            freezing_threshold: Nat::from(2_592_000u64),
+            reserved_cycles_limit: Some(Nat::from(5_000_000_000_000u64)),
+            wasm_memory_limit: None,
+            wasm_memory_threshold: Some(Nat::from(0u8)),
+            log_visibility: LogVisibility::AllowedViewers(vec![
+                Principal::anonymous(),
+                Principal::management_canister(),
+            ]),
+        }
+    }
+
+    #[test]
+    fn diff_canister_settings_only_updates_changed_settings() {
+        let configured = CanisterSettings {
+            controllers: None,
+            compute_allocation: Some(ComputeAllocation::try_from(0u8).unwrap()),
+            memory_allocation: None,
+            freezing_threshold: Some(FreezingThreshold::try_from(7_776_000u64).unwrap()),
+            reserved_cycles_limit: None,
+            wasm_memory_limit: Some(WasmMemoryLimit::try_from(1_000_000u64).unwrap()),
+            wasm_memory_threshold: None,
+            // Same viewers in a different order.
+            log_visibility: Some(LogVisibility::AllowedViewers(vec![
+                Principal::management_canister(),
+                Principal::anonymous(),
+            ])),
+        };
+
+        let (update, changes) = diff_canister_settings(&configured, &current_settings());
+        assert_eq!(
+            changes,
+            vec![
+                SettingChange {
+                    name: "freezing_threshold",
+                    current: "2_592_000".to_string(),
+                    configured: "7_776_000".to_string(),
+                },
+                SettingChange {
+                    name: "wasm_memory_limit",
+                    current: "not set".to_string(),
+                    configured: "1_000_000".to_string(),
+                },
+            ]
+        );
+        assert!(update.compute_allocation.is_none());
+        assert_eq!(update.freezing_threshold.map(u64::from), Some(7_776_000));
+        assert_eq!(update.wasm_memory_limit.map(u64::from), Some(1_000_000));
+        assert!(update.log_visibility.is_none());
+    }
+
+    #[test]
+    fn diff_canister_settings_of_matching_settings_is_empty() {
+        let configured = CanisterSettings {
+            freezing_threshold: Some(FreezingThreshold::try_from(2_592_000u64).unwrap()),
+            log_visibility: Some(LogVisibility::AllowedViewers(vec![
+                Principal::anonymous(),
+                Principal::management_canister(),
+            ])),
+            ..Default::default()
+        };
+
+        let (update, changes) = diff_canister_settings(&configured, &current_settings());
+        assert!(changes.is_empty());
+        assert!(update.freezing_threshold.is_none());
+        assert!(update.log_visibility.is_none());
+    }
+}
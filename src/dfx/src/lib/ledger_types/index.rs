@@ -0,0 +1,104 @@
+// Types of the ICP index canister.
+// Copied from https://github.com/dfinity/ic/blob/master/rs/ledger_suite/icp/index/index.did
+use candid::{CandidType, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::Deserialize;
+
+/// Id of the ICP index canister on the IC.
+pub const MAINNET_INDEX_CANISTER_ID: Principal =
+    Principal::from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0b, 0x01, 0x01]);
+
+#[derive(CandidType, Clone, Debug)]
+pub struct GetAccountTransactionsArgs {
+    pub account: Account,
+    pub start: Option<u64>,
+    pub max_results: u64,
+}
+
+#[derive(CandidType, Clone, Debug)]
+pub struct GetAccountIdentifierTransactionsArgs {
+    pub account_identifier: String,
+    pub start: Option<u64>,
+    pub max_results: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetAccountIdentifierTransactionsResponse {
+    pub balance: u64,
+    pub transactions: Vec<TransactionWithId>,
+    pub oldest_tx_id: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetAccountIdentifierTransactionsError {
+    pub message: String,
+}
+
+pub type GetAccountIdentifierTransactionsResult =
+    Result<GetAccountIdentifierTransactionsResponse, GetAccountIdentifierTransactionsError>;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionWithId {
+    pub id: u64,
+    pub transaction: Transaction,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub memo: u64,
+    pub icrc1_memo: Option<Vec<u8>>,
+    pub operation: Operation,
+    pub created_at_time: Option<TimeStamp>,
+    pub timestamp: Option<TimeStamp>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub struct TimeStamp {
+    pub timestamp_nanos: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub struct Tokens {
+    pub e8s: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum Operation {
+    Approve {
+        fee: Tokens,
+        from: String,
+        allowance: Tokens,
+        expected_allowance: Option<Tokens>,
+        expires_at: Option<TimeStamp>,
+        spender: String,
+    },
+    Burn {
+        from: String,
+        amount: Tokens,
+        spender: Option<String>,
+    },
+    Mint {
+        to: String,
+        amount: Tokens,
+    },
+    Transfer {
+        to: String,
+        fee: Tokens,
+        from: String,
+        amount: Tokens,
+        spender: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_canister_id() {
+        assert_eq!(
+            MAINNET_INDEX_CANISTER_ID,
+            Principal::from_text("qhbym-qaaaa-aaaaa-aaafq-cai").unwrap()
+        );
+    }
+}
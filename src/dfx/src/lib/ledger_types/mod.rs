@@ -15,6 +15,8 @@ use std::fmt;
 
 use super::cycles_ledger_types::create_canister::SubnetSelection;
 
+pub mod index;
+
 /// Id of the ledger canister on the IC.
 pub const MAINNET_LEDGER_CANISTER_ID: Principal =
     Principal::from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x01, 0x01]);
@@ -36,5 +36,6 @@ pub mod sign;
 pub mod state_tree;
 pub mod subnet;
 pub mod telemetry;
+pub mod transaction_history;
 pub mod warning;
 pub mod wasm;
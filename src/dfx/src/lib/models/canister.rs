@@ -1,6 +1,7 @@
 use crate::lib::builders::{
-    custom_download, BuildConfig, BuildOutput, BuilderPool, CanisterBuilder, IdlBuildOutput,
-    WasmBuildOutput,
+    capture_build_output, custom_download, get_and_write_environment_variables,
+    invalidate_cached_build, BuildCache, BuildConfig, BuildOutput, BuilderPool, CanisterBuilder,
+    IdlBuildOutput, WasmBuildOutput,
 };
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
@@ -21,16 +22,16 @@ use ic_wasm::metadata::{add_metadata, remove_metadata, Kind};
 use ic_wasm::optimize::OptLevel;
 use itertools::Itertools;
 use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::Direction;
 use rand::{thread_rng, RngCore};
 use slog::{error, info, trace, warn, Logger};
-use std::cell::RefCell;
 use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 
 /// Represents a canister from a DFX project. It can be a virtual Canister.
 /// Multiple canister instances can have the same info, but would be differentiated
@@ -40,10 +41,8 @@ use std::sync::Arc;
 pub struct Canister {
     info: CanisterInfo,
     builder: Arc<dyn CanisterBuilder>,
-    output: RefCell<Option<BuildOutput>>,
+    output: OnceLock<BuildOutput>,
 }
-unsafe impl Send for Canister {}
-unsafe impl Sync for Canister {}
 
 impl Canister {
     /// Create a new canister.
@@ -52,7 +51,7 @@ impl Canister {
         Self {
             info,
             builder,
-            output: RefCell::new(None),
+            output: OnceLock::new(),
         }
     }
 
@@ -72,10 +71,23 @@ impl Canister {
         build_config: &BuildConfig,
     ) -> DfxResult<&BuildOutput> {
         let output = self.builder.build(env, pool, &self.info, build_config)?;
+        self.set_build_output(output)
+    }
 
-        // Ignore the old output, and return a reference.
-        let _ = self.output.replace(Some(output));
-        Ok(self.get_build_output().unwrap())
+    /// Uses the artifacts of a previous build, which were restored from the build cache.
+    fn use_cached_build(&self, idl_path: PathBuf) -> DfxResult<&BuildOutput> {
+        self.set_build_output(BuildOutput {
+            wasm: WasmBuildOutput::File(self.info.get_build_wasm_path()),
+            idl: IdlBuildOutput::File(idl_path),
+        })
+    }
+
+    /// A canister is built at most once, so its output never changes once it is known.
+    fn set_build_output(&self, output: BuildOutput) -> DfxResult<&BuildOutput> {
+        if self.output.set(output).is_err() {
+            bail!("Canister '{}' was already built.", self.get_name());
+        }
+        Ok(self.output.get().unwrap())
     }
 
     pub fn postbuild(
@@ -111,7 +123,7 @@ impl Canister {
     /// Get the build output of a build process. If the output isn't known at this time,
     /// will return [None].
     pub fn get_build_output(&self) -> Option<&BuildOutput> {
-        unsafe { (*self.output.as_ptr()).as_ref() }
+        self.output.get()
     }
 
     #[context("Failed while trying to generate type declarations for '{}'.", self.info.get_name())]
@@ -754,6 +766,225 @@ impl CanisterPool {
         Ok(())
     }
 
+    /// Runs the prebuild, build and postbuild steps of a canister, unless its artifacts
+    /// can be restored from the build cache.
+    fn build_canister<'a>(
+        &self,
+        env: &dyn Environment,
+        log: &Logger,
+        build_config: &BuildConfig,
+        canister: &'a Canister,
+    ) -> Result<&'a BuildOutput, BuildError> {
+        let mut cache_key = None;
+        if let Some(cache) = build_config
+            .cache
+            .as_ref()
+            .filter(|_| !canister.info.is_assets())
+        {
+            match self.restore_cached_build(env, build_config, cache, canister) {
+                Ok((_, Some(output))) => {
+                    info!(log, "Building canister '{}' (cached).", canister.get_name());
+                    return Ok(output);
+                }
+                Ok((key, None)) => cache_key = Some(key),
+                Err(e) => warn!(
+                    log,
+                    "Not using the build cache for canister '{}': {:#}",
+                    canister.get_name(),
+                    e
+                ),
+            }
+        }
+        info!(log, "Building canister '{}'.", canister.get_name());
+
+        let canister_id = canister.canister_id();
+        invalidate_cached_build(&canister.info)
+            .and_then(|_| self.step_prebuild(env, build_config, canister))
+            .map_err(|e| {
+                BuildError::PreBuildStepFailed(
+                    canister_id,
+                    canister.get_name().to_string(),
+                    Box::new(e),
+                )
+            })
+            .and_then(|_| {
+                self.step_build(env, build_config, canister).map_err(|e| {
+                    BuildError::BuildStepFailed(
+                        canister_id,
+                        canister.get_name().to_string(),
+                        Box::new(e),
+                    )
+                })
+            })
+            .and_then(|o| {
+                self.step_postbuild(env, build_config, canister, o)
+                    .map_err(|e| {
+                        BuildError::PostBuildStepFailed(
+                            canister_id,
+                            canister.get_name().to_string(),
+                            Box::new(e),
+                        )
+                    })
+                    .map(|_| o)
+            })
+            .inspect(|_| {
+                if let (Some(cache), Some(key)) = (&build_config.cache, &cache_key) {
+                    if let Err(e) = cache.store(&canister.info, key) {
+                        warn!(log, "{:#}", e);
+                    }
+                }
+            })
+    }
+
+    /// Restores the artifacts of a canister from the build cache, if the inputs of its build did not change.
+    /// Returns the key of its build, and its build output if it was restored.
+    fn restore_cached_build<'a>(
+        &self,
+        env: &dyn Environment,
+        build_config: &BuildConfig,
+        cache: &BuildCache,
+        canister: &'a Canister,
+    ) -> DfxResult<(String, Option<&'a BuildOutput>)> {
+        let info = &canister.info;
+        let dependencies = canister
+            .builder
+            .get_dependencies(env, self, info)
+            .unwrap_or_default();
+        let key = cache.key(env, self, info, &dependencies, build_config)?;
+        if !cache.restore(info, &key)? {
+            return Ok((key, None));
+        }
+
+        // Save what the skipped steps save outside of the output directory of the canister.
+        let service_idl_path = info.get_service_idl_path();
+        for root in [&build_config.idl_root, &build_config.lsp_root] {
+            let target = root
+                .join(canister.canister_id().to_text())
+                .with_extension("did");
+            dfx_core::fs::composite::ensure_parent_dir_exists(&target)?;
+            dfx_core::fs::copy(&service_idl_path, &target)?;
+            dfx_core::fs::set_permissions_readwrite(&target)?;
+        }
+        get_and_write_environment_variables(
+            info,
+            &build_config.network_name,
+            self,
+            &dependencies,
+            build_config.env_file.as_deref(),
+        )?;
+
+        let idl_path = if info.get_output_idl_path().exists() {
+            info.get_output_idl_path().to_path_buf()
+        } else {
+            service_idl_path
+        };
+        Ok((key, Some(canister.use_cached_build(idl_path)?)))
+    }
+
+    /// Builds canisters on up to `build_config.jobs` threads. A canister starts building once all of its
+    /// dependencies are built. After a build fails, no other build starts, and the running ones are finished.
+    /// The output of the build tools of each canister is logged at once when the canister is built.
+    fn build_in_parallel<'a>(
+        &'a self,
+        env: &dyn Environment,
+        log: &Logger,
+        build_config: &BuildConfig,
+        graph: &DiGraph<CanisterId, ()>,
+        canisters_to_build: &[&'a Canister],
+    ) -> Vec<Result<&'a BuildOutput, BuildError>> {
+        struct BuildQueue<'a> {
+            /// The number of dependencies each waiting canister still needs to be built.
+            waiting: BTreeMap<NodeIndex, usize>,
+            ready: Vec<NodeIndex>,
+            running: usize,
+            failed: bool,
+            results: Vec<Result<&'a BuildOutput, BuildError>>,
+        }
+
+        let to_build = |node: NodeIndex| -> Option<&'a Canister> {
+            let canister_id = graph.node_weight(node)?;
+            canisters_to_build
+                .iter()
+                .find(|c| c.canister_id() == *canister_id)
+                .copied()
+        };
+        let mut waiting = BTreeMap::new();
+        let mut ready = vec![];
+        for node in graph
+            .node_indices()
+            .filter(|node| to_build(*node).is_some())
+        {
+            let dependencies = graph
+                .neighbors_directed(node, Direction::Outgoing)
+                .filter(|dependency| to_build(*dependency).is_some())
+                .count();
+            if dependencies == 0 {
+                ready.push(node);
+            } else {
+                waiting.insert(node, dependencies);
+            }
+        }
+        let queue = Mutex::new(BuildQueue {
+            waiting,
+            ready,
+            running: 0,
+            failed: false,
+            results: vec![],
+        });
+        let changed = Condvar::new();
+
+        let worker = || loop {
+            let node = {
+                let mut queue = queue.lock().unwrap();
+                loop {
+                    if queue.failed || (queue.ready.is_empty() && queue.running == 0) {
+                        return;
+                    }
+                    if let Some(node) = queue.ready.pop() {
+                        queue.running += 1;
+                        break node;
+                    }
+                    queue = changed.wait(queue).unwrap();
+                }
+            };
+            let canister = to_build(node).unwrap();
+            let (result, output) =
+                capture_build_output(|| self.build_canister(env, log, build_config, canister));
+            if !output.is_empty() {
+                info!(
+                    log,
+                    "Output of building canister '{}':\n{}",
+                    canister.get_name(),
+                    String::from_utf8_lossy(&output).trim_end()
+                );
+            }
+
+            let mut queue = queue.lock().unwrap();
+            queue.running -= 1;
+            if result.is_ok() {
+                for dependent in graph.neighbors_directed(node, Direction::Incoming) {
+                    if let Some(dependencies) = queue.waiting.get_mut(&dependent) {
+                        *dependencies -= 1;
+                        if *dependencies == 0 {
+                            queue.waiting.remove(&dependent);
+                            queue.ready.push(dependent);
+                        }
+                    }
+                }
+            } else {
+                queue.failed = true;
+            }
+            queue.results.push(result);
+            changed.notify_all();
+        };
+        std::thread::scope(|scope| {
+            for _ in 0..build_config.jobs.min(canisters_to_build.len()) {
+                scope.spawn(worker);
+            }
+        });
+        queue.into_inner().unwrap().results
+    }
+
     /// Build all canisters, returning a vector of results of each builds.
     #[context("Failed while trying to build all canisters in the canister pool.")]
     pub fn build(
@@ -784,51 +1015,25 @@ impl CanisterPool {
             .collect();
 
         let canisters_to_build = self.canisters_to_build(build_config);
-        let mut result = Vec::new();
-        for canister_id in &order {
-            if let Some(canister) = self.get_canister(canister_id) {
-                if canisters_to_build
-                    .iter()
-                    .map(|c| c.get_name())
-                    .contains(&canister.get_name())
-                {
-                    info!(log, "Building canister '{}'.", canister.get_name());
-                } else {
-                    trace!(log, "Not building canister '{}'.", canister.get_name());
-                    continue;
+        let result = if build_config.jobs > 1 {
+            self.build_in_parallel(env, log, build_config, &graph, &canisters_to_build)
+        } else {
+            let mut result = Vec::new();
+            for canister_id in &order {
+                if let Some(canister) = self.get_canister(canister_id) {
+                    if !canisters_to_build
+                        .iter()
+                        .map(|c| c.get_name())
+                        .contains(&canister.get_name())
+                    {
+                        trace!(log, "Not building canister '{}'.", canister.get_name());
+                        continue;
+                    }
+                    result.push(self.build_canister(env, log, build_config, canister));
                 }
-                result.push(
-                    self.step_prebuild(env, build_config, canister)
-                        .map_err(|e| {
-                            BuildError::PreBuildStepFailed(
-                                *canister_id,
-                                canister.get_name().to_string(),
-                                Box::new(e),
-                            )
-                        })
-                        .and_then(|_| {
-                            self.step_build(env, build_config, canister).map_err(|e| {
-                                BuildError::BuildStepFailed(
-                                    *canister_id,
-                                    canister.get_name().to_string(),
-                                    Box::new(e),
-                                )
-                            })
-                        })
-                        .and_then(|o| {
-                            self.step_postbuild(env, build_config, canister, o)
-                                .map_err(|e| {
-                                    BuildError::PostBuildStepFailed(
-                                        *canister_id,
-                                        canister.get_name().to_string(),
-                                        Box::new(e),
-                                    )
-                                })
-                                .map(|_| o)
-                        }),
-                );
             }
-        }
+            result
+        };
 
         self.step_postbuild_all(build_config, &order)
             .map_err(|e| DfxError::new(BuildError::PostBuildAllStepFailed(Box::new(e))))?;
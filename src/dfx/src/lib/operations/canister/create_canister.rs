@@ -1,5 +1,5 @@
 use crate::lib::cycles_ledger_types::create_canister::{
-    CmcCreateCanisterArgs, CmcCreateCanisterError,
+    CmcCreateCanisterArgs, CmcCreateCanisterError, SubnetSelection,
 };
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
@@ -8,6 +8,7 @@ use crate::lib::identity::wallet::{get_or_create_wallet_canister, GetOrCreateWal
 use crate::lib::ledger_types::MAINNET_CYCLE_MINTER_CANISTER_ID;
 use crate::lib::operations::canister::motoko_playground::reserve_canister_with_playground;
 use crate::lib::operations::cycles_ledger::create_with_cycles_ledger;
+use crate::lib::subnet::get_local_subnet;
 use crate::lib::telemetry::{CyclesHost, Telemetry};
 use crate::util::clap::subnet_selection_opt::SubnetSelectionType;
 use anyhow::{anyhow, bail, Context};
@@ -121,6 +122,36 @@ The command line value will be used.",
         specified_id = None;
     }
 
+    // The subnet in dfx.json applies unless a subnet was selected on the command line.
+    // On a local network, it must be one of the declared subnets, which are targeted through their canister ranges.
+    // Elsewhere, only subnet principals can be selected.
+    let mut canister_subnet_selection;
+    let mut local_subnet = None;
+    let subnet_selection = match config_interface.get_subnet(canister_name)? {
+        Some(subnet) if subnet_selection.get_user_choice().is_none() => {
+            if env
+                .get_network_descriptor()
+                .local_server_descriptor()
+                .is_ok()
+            {
+                local_subnet = Some(get_local_subnet(env, &subnet)?);
+                subnet_selection
+            } else if let Ok(subnet) = Principal::from_text(&subnet) {
+                canister_subnet_selection = SubnetSelectionType::Explicit {
+                    user_choice: SubnetSelection::Subnet { subnet },
+                };
+                &mut canister_subnet_selection
+            } else {
+                warn!(
+                    log,
+                    "Ignoring subnet '{subnet}' of canister '{canister_name}', because named subnets only exist on local networks."
+                );
+                subnet_selection
+            }
+        }
+        _ => subnet_selection,
+    };
+
     // Replace call_sender with wallet canister unless:
     // 1. specified_id is in effect OR
     // 2. --no-wallet is set explicitly OR
@@ -153,32 +184,62 @@ The command line value will be used.",
 
     let spinner = env.new_spinner(format!("Creating canister {canister_name}...").into());
     let agent = env.get_agent();
-    let cid = match call_sender {
-        CallSender::SelectedId => {
-            let auto_wallet_disabled = std::env::var("DFX_DISABLE_AUTO_WALLET").is_ok();
-            let ic_network = env.get_network_descriptor().is_ic;
-            if ic_network || auto_wallet_disabled {
-                create_with_cycles_ledger(
-                    env,
-                    agent,
-                    canister_name,
-                    with_cycles,
-                    from_subaccount,
-                    settings,
-                    created_at_time,
-                    subnet_selection,
-                )
-                .await
-            } else {
-                create_with_management_canister(env, agent, with_cycles, specified_id, settings)
+    let cid = if let Some(local_subnet) = local_subnet {
+        // Keep the wallet in control, as if it had created the canister.
+        let settings = match call_sender {
+            CallSender::Wallet(wallet_id) if settings.controllers.is_none() => {
+                let identity = agent
+                    .get_principal()
+                    .map_err(|err| anyhow!("Failed to get selected identity principal: {err}"))?;
+                DfxCanisterSettings {
+                    controllers: Some(vec![wallet_id, identity]),
+                    ..settings
+                }
+            }
+            _ => settings,
+        };
+        create_with_management_canister(
+            agent,
+            with_cycles,
+            specified_id,
+            settings,
+            local_subnet.effective_canister_id,
+        )
+        .await
+    } else {
+        match call_sender {
+            CallSender::SelectedId => {
+                let auto_wallet_disabled = std::env::var("DFX_DISABLE_AUTO_WALLET").is_ok();
+                let ic_network = env.get_network_descriptor().is_ic;
+                if ic_network || auto_wallet_disabled {
+                    create_with_cycles_ledger(
+                        env,
+                        agent,
+                        canister_name,
+                        with_cycles,
+                        from_subaccount,
+                        settings,
+                        created_at_time,
+                        subnet_selection,
+                    )
                     .await
+                } else {
+                    create_with_management_canister(
+                        agent,
+                        with_cycles,
+                        specified_id,
+                        settings,
+                        env.get_effective_canister_id(),
+                    )
+                    .await
+                }
+            }
+            CallSender::Impersonate(_) => {
+                unreachable!("Impersonating sender when creating canisters is not supported.")
+            }
+            CallSender::Wallet(wallet_id) => {
+                create_with_wallet(agent, &wallet_id, with_cycles, settings, subnet_selection).await
             }
-        }
-        CallSender::Impersonate(_) => {
-            unreachable!("Impersonating sender when creating canisters is not supported.")
-        }
-        CallSender::Wallet(wallet_id) => {
-            create_with_wallet(agent, &wallet_id, with_cycles, settings, subnet_selection).await
         }
     }?;
     spinner.finish_and_clear();
@@ -196,17 +257,17 @@ The command line value will be used.",
 }
 
 async fn create_with_management_canister(
-    env: &dyn Environment,
     agent: &Agent,
     with_cycles: Option<u128>,
     specified_id: Option<Principal>,
     settings: DfxCanisterSettings,
+    effective_canister_id: Principal,
 ) -> DfxResult<Principal> {
     let mgr = ManagementCanister::create(agent);
     let mut builder = mgr
         .create_canister()
         .as_provisional_create_with_amount(with_cycles)
-        .with_effective_canister_id(env.get_effective_canister_id());
+        .with_effective_canister_id(effective_canister_id);
     if let Some(sid) = specified_id {
         builder = builder.as_provisional_create_with_specified_id(sid);
     }
@@ -1,18 +1,25 @@
-use crate::lib::builders::BuildConfig;
+use crate::lib::builders::{BuildCache, BuildConfig};
 use crate::lib::canister_info::assets::AssetsCanisterInfo;
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
 use crate::lib::ic_attributes::CanisterSettings;
-use crate::lib::installers::assets::prepare_assets_for_proposal;
+use crate::lib::installers::assets::{post_install_store_assets, prepare_assets_for_proposal};
 use crate::lib::models::canister::CanisterPool;
 use crate::lib::operations::canister::deploy_canisters::DeployMode::{
-    ComputeEvidence, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
+    ComputeEvidence, EstimateCost, ForceReinstallSingleCanister, NormalDeploy, PrepareForProposal,
 };
+use crate::lib::operations::canister::estimate_cost::{estimate_deploy_cost, print_cost_estimates};
 use crate::lib::operations::canister::motoko_playground::reserve_canister_with_playground;
+use crate::lib::operations::canister::watch::{
+    canisters_affected_by, FileWatcher, WatchedCanister,
+};
 use crate::lib::operations::canister::{
-    all_project_canisters_with_ids, create_canister, install_canister::install_canister,
+    all_project_canisters_with_ids, create_canister, delete_canister_snapshot, get_canister_status,
+    install_canister::{finish_install, install_canister, InstallSteps},
+    load_canister_snapshot, start_canister, stop_canister, take_canister_snapshot, uninstall_code,
 };
+use crate::lib::state_tree::canister_info::read_state_tree_canister_module_hash;
 use crate::util::clap::install_mode::InstallModeHint;
 use crate::util::clap::subnet_selection_opt::SubnetSelectionType;
 use anyhow::{anyhow, bail, Context};
@@ -25,9 +32,11 @@ use ic_utils::interfaces::management_canister::attributes::{
     ComputeAllocation, FreezingThreshold, MemoryAllocation, ReservedCyclesLimit,
 };
 use ic_utils::interfaces::management_canister::builders::WasmMemoryLimit;
+use ic_utils::interfaces::management_canister::CanisterStatus;
 use icrc_ledger_types::icrc1::account::Subaccount;
-use slog::info;
+use slog::{error, info, warn};
 use std::convert::TryFrom;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -36,6 +45,7 @@ pub enum DeployMode {
     ForceReinstallSingleCanister(String),
     PrepareForProposal(String),
     ComputeEvidence(String),
+    EstimateCost,
 }
 
 #[context("Failed while trying to deploy canisters.")]
@@ -59,6 +69,10 @@ pub async fn deploy_canisters(
     no_asset_upgrade: bool,
     subnet_selection: &mut SubnetSelectionType,
     always_assist: bool,
+    reconcile_settings: bool,
+    atomic: bool,
+    jobs: usize,
+    build_cache: Option<BuildCache>,
 ) -> DfxResult {
     let log = env.get_logger();
 
@@ -87,7 +101,7 @@ pub async fn deploy_canisters(
             // don't force-reinstall the dependencies too.
             vec![String::from(canister_name)]
         }
-        NormalDeploy => canisters_to_deploy
+        NormalDeploy | EstimateCost => canisters_to_deploy
             .clone()
             .into_iter()
             .filter(|canister_name| {
@@ -105,6 +119,17 @@ pub async fn deploy_canisters(
         .filter(|canister_name| !pull_canisters_in_config.contains_key(canister_name))
         .collect();
 
+    if matches!(deploy_mode, EstimateCost) {
+        let subnet = if env.get_network_descriptor().is_ic {
+            subnet_selection.resolve(env).await?
+        } else {
+            None
+        };
+        let estimates = estimate_deploy_cost(env, &canisters_to_install, subnet.as_ref()).await?;
+        print_cost_estimates(env, &estimates);
+        return Ok(());
+    }
+
     if some_canister.is_some() {
         info!(log, "Deploying: {}", canisters_to_install.join(" "));
     } else {
@@ -132,6 +157,21 @@ pub async fn deploy_canisters(
         info!(env.get_logger(), "All canisters have already been created.");
     }
 
+    if reconcile_settings {
+        let canister_id_store = env.get_canister_id_store()?;
+        for canister_name in &canisters_to_install {
+            crate::lib::operations::canister::reconcile_settings(
+                env,
+                config.get_config(),
+                canister_name,
+                canister_id_store.get(canister_name)?,
+                call_sender,
+                skip_consent,
+            )
+            .await?;
+        }
+    }
+
     let canisters_to_load = all_project_canisters_with_ids(env, &config);
 
     let pool = build_canisters(
@@ -140,27 +180,36 @@ pub async fn deploy_canisters(
         &canisters_to_build,
         &config,
         env_file.clone(),
+        jobs,
+        build_cache,
     )
     .await?;
 
     match deploy_mode {
         NormalDeploy | ForceReinstallSingleCanister(_) => {
-            install_canisters(
-                env,
-                &canisters_to_install,
-                &config,
-                argument,
-                argument_type,
-                mode_hint,
-                upgrade_unchanged,
-                call_sender,
-                pool,
-                skip_consent,
-                env_file.as_deref(),
-                no_asset_upgrade,
-                always_assist,
-            )
-            .await?;
+            let install = |order| {
+                install_canisters(
+                    env,
+                    &canisters_to_install,
+                    &config,
+                    argument,
+                    argument_type,
+                    mode_hint,
+                    upgrade_unchanged,
+                    call_sender,
+                    &pool,
+                    skip_consent,
+                    env_file.as_deref(),
+                    no_asset_upgrade,
+                    always_assist,
+                    order,
+                )
+            };
+            if atomic {
+                install_atomically(env, &canisters_to_install, call_sender, install).await?;
+            } else {
+                install(InstallOrder::OneByOne).await?;
+            }
             info!(log, "Deployed canisters.");
         }
         PrepareForProposal(canister_name) => {
@@ -170,11 +219,121 @@ pub async fn deploy_canisters(
         ComputeEvidence(canister_name) => {
             compute_evidence(env, initial_canister_id_store, &config, canister_name).await?
         }
+        EstimateCost => unreachable!("cost estimates return before creating canisters"),
     }
 
     Ok(())
 }
 
+/// Redeploys deployed canisters whenever their sources change, until interrupted.
+/// Canisters that depend on a changed canister are redeployed too.
+/// When only the assets of an asset canister change, all its assets are synchronized without reinstalling it.
+/// Synchronizing compares every file with the canister, and only uploads the ones whose content changed.
+#[context("Failed while watching canisters for changes.")]
+#[allow(clippy::too_many_arguments)]
+pub async fn watch_canisters(
+    env: &dyn Environment,
+    some_canister: Option<&str>,
+    argument: Option<&str>,
+    argument_type: Option<&str>,
+    mode_hint: &InstallModeHint,
+    upgrade_unchanged: bool,
+    call_sender: &CallSender,
+    skip_consent: bool,
+    env_file: Option<PathBuf>,
+    no_asset_upgrade: bool,
+    always_assist: bool,
+    jobs: usize,
+    build_cache: Option<BuildCache>,
+) -> DfxResult {
+    let log = env.get_logger();
+    let config = env.get_config_or_anyhow()?;
+    let network = env.get_network_descriptor();
+    let pull_canisters_in_config = config.get_config().get_pull_canisters()?;
+    let canisters_to_watch: Vec<String> = canister_with_dependencies(&config, some_canister)?
+        .into_iter()
+        .filter(|canister_name| {
+            !config
+                .get_config()
+                .is_remote_canister(canister_name, &network.name)
+                .unwrap_or(false)
+                && !pull_canisters_in_config.contains_key(canister_name)
+        })
+        .collect();
+    let canisters_to_load = all_project_canisters_with_ids(env, &config);
+
+    let canister_id_store = env.get_canister_id_store()?;
+    let mut watched = vec![];
+    for canister_name in &canisters_to_watch {
+        let canister_id = canister_id_store.get(canister_name)?;
+        let canister_info = CanisterInfo::load(&config, canister_name, Some(canister_id))?;
+        watched.push(WatchedCanister::new(&canister_info)?);
+    }
+    let mut watcher = FileWatcher::new(
+        watched
+            .iter()
+            .flat_map(|canister| canister.paths.clone())
+            .collect(),
+    );
+    info!(log, "Watching for changes. Press Ctrl-C to stop.");
+    loop {
+        let changed = watcher.changes().await;
+        let (to_redeploy, to_sync) = canisters_affected_by(&watched, &changed);
+        if to_redeploy.is_empty() && to_sync.is_empty() {
+            continue;
+        }
+        let redeployed: DfxResult = async {
+            if !to_redeploy.is_empty() {
+                info!(log, "Redeploying: {}", to_redeploy.join(" "));
+                let pool = build_canisters(
+                    env,
+                    &canisters_to_load,
+                    &to_redeploy,
+                    &config,
+                    env_file.clone(),
+                    jobs,
+                    build_cache.clone(),
+                )
+                .await?;
+                install_canisters(
+                    env,
+                    &to_redeploy,
+                    &config,
+                    argument,
+                    argument_type,
+                    mode_hint,
+                    upgrade_unchanged,
+                    call_sender,
+                    &pool,
+                    skip_consent,
+                    env_file.as_deref(),
+                    no_asset_upgrade,
+                    always_assist,
+                    InstallOrder::OneByOne,
+                )
+                .await?;
+            }
+            for canister_name in &to_sync {
+                info!(
+                    log,
+                    "Synchronizing all assets of canister '{canister_name}'. Only changed content is uploaded."
+                );
+                let canister_id = canister_id_store.get(canister_name)?;
+                let canister_info = CanisterInfo::load(&config, canister_name, Some(canister_id))?;
+                post_install_store_assets(env, &canister_info, env.get_agent()).await?;
+            }
+            Ok(())
+        }
+        .await;
+        match redeployed {
+            Ok(()) => info!(log, "Deployed changes. Watching for changes."),
+            Err(e) => error!(log, "{e:#}\nWatching for changes."),
+        }
+        // Builds and installations may have written to watched files.
+        watcher.reset();
+    }
+}
+
 #[context("Failed to collect canisters and their dependencies.")]
 fn canister_with_dependencies(
     config: &Config,
@@ -217,17 +376,18 @@ async fn register_canisters(
         spinner.finish_and_clear();
     } else {
         let spinner = env.new_spinner("Creating canisters...".into());
+        let network = env.get_network_descriptor();
         for canister_name in &canisters_to_create {
             let config_interface = config.get_config();
             let compute_allocation = config_interface
-                .get_compute_allocation(canister_name)?
+                .get_compute_allocation(canister_name, &network.name)?
                 .map(|arg| {
                     ComputeAllocation::try_from(arg)
                         .context("Compute Allocation must be a percentage.")
                 })
                 .transpose()?;
             let memory_allocation = config_interface
-                .get_memory_allocation(canister_name)?
+                .get_memory_allocation(canister_name, &network.name)?
                 .map(|arg| {
                     u64::try_from(arg.get_bytes())
                         .map_err(|e| anyhow!(e))
@@ -237,20 +397,19 @@ async fn register_canisters(
                         )
                 })
                 .transpose()?;
-            let freezing_threshold =
-                config_interface
-                    .get_freezing_threshold(canister_name)?
-                    .map(|arg| {
-                        FreezingThreshold::try_from(arg.as_secs())
-                            .expect("Freezing threshold must be between 0 and 2^64-1, inclusively.")
-                    });
+            let freezing_threshold = config_interface
+                .get_freezing_threshold(canister_name, &network.name)?
+                .map(|arg| {
+                    FreezingThreshold::try_from(arg.as_secs())
+                        .expect("Freezing threshold must be between 0 and 2^64-1, inclusively.")
+                });
             let reserved_cycles_limit = config_interface
-                .get_reserved_cycles_limit(canister_name)?
+                .get_reserved_cycles_limit(canister_name, &network.name)?
                 .map(|arg| {
                     ReservedCyclesLimit::try_from(arg)
                         .expect("Reserved cycles limit must be between 0 and 2^128-1, inclusively.")
                 });
-            let wasm_memory_limit = config_interface.get_wasm_memory_limit(canister_name)?.map(
+            let wasm_memory_limit = config_interface.get_wasm_memory_limit(canister_name, &network.name)?.map(
                 |arg| {
                     u64::try_from(arg.get_bytes())
                         .map_err(|e| anyhow!(e))
@@ -260,7 +419,8 @@ async fn register_canisters(
                         )
                 },
             ).transpose()?;
-            let log_visibility = config_interface.get_log_visibility(canister_name)?;
+            let log_visibility =
+                config_interface.get_log_visibility(canister_name, &network.name)?;
 
             let controllers = None;
             create_canister(
@@ -298,6 +458,8 @@ async fn build_canisters(
     canisters_to_build: &[String],
     config: &Config,
     env_file: Option<PathBuf>,
+    jobs: usize,
+    build_cache: Option<BuildCache>,
 ) -> DfxResult<CanisterPool> {
     let spinner = env.new_spinner("Building canisters...".into());
     let build_mode_check = false;
@@ -305,7 +467,9 @@ async fn build_canisters(
 
     let build_config = BuildConfig::from_config(config)?
         .with_canisters_to_build(canisters_to_build.into())
-        .with_env_file(env_file);
+        .with_env_file(env_file)
+        .with_jobs(jobs)
+        .with_cache(build_cache);
     canister_pool
         .build_or_fail(env, env.get_logger(), &build_config)
         .await?;
@@ -313,6 +477,15 @@ async fn build_canisters(
     Ok(canister_pool)
 }
 
+/// The order in which [install_canisters] performs the installation steps.
+enum InstallOrder {
+    /// Install each canister completely before moving on to the next one.
+    OneByOne,
+    /// Install the code of all canisters first, which works while they are stopped.
+    /// Then start the given canisters, and only then upload assets and run post-install tasks.
+    CodeFirst { start: Vec<Principal> },
+}
+
 #[context("Failed while trying to install all canisters.")]
 async fn install_canisters(
     env: &dyn Environment,
@@ -323,16 +496,22 @@ async fn install_canisters(
     mode_hint: &InstallModeHint,
     upgrade_unchanged: bool,
     call_sender: &CallSender,
-    pool: CanisterPool,
+    pool: &CanisterPool,
     skip_consent: bool,
     env_file: Option<&Path>,
     no_asset_upgrade: bool,
     always_assist: bool,
+    order: InstallOrder,
 ) -> DfxResult {
     let spinner = env.new_spinner("Installing canisters...".into());
 
     let canister_id_store = env.get_canister_id_store()?;
+    let steps = match order {
+        InstallOrder::OneByOne => InstallSteps::All,
+        InstallOrder::CodeFirst { .. } => InstallSteps::CodeOnly,
+    };
 
+    let mut canister_infos = vec![];
     for canister_name in canister_names {
         let canister_id = canister_id_store.get(canister_name)?;
         let canister_info = CanisterInfo::load(config, canister_name, Some(canister_id))?;
@@ -348,18 +527,181 @@ async fn install_canisters(
             mode_hint,
             call_sender,
             upgrade_unchanged,
-            Some(&pool),
+            Some(pool),
             skip_consent,
             env_file,
             no_asset_upgrade,
             always_assist,
+            steps,
         )
         .await?;
+        canister_infos.push((canister_id, canister_info));
+    }
+
+    if let InstallOrder::CodeFirst { start } = order {
+        for canister_id in start {
+            start_canister(env, canister_id, call_sender).await?;
+        }
+        for (canister_id, canister_info) in &canister_infos {
+            finish_install(
+                env,
+                canister_id_store,
+                *canister_id,
+                canister_info,
+                call_sender,
+                Some(pool),
+                env_file,
+            )
+            .await?;
+        }
     }
     spinner.finish_and_clear();
     Ok(())
 }
 
+/// Runs `install`, restoring the previous state of the canisters if it fails.
+///
+/// Canisters that already have code installed are stopped and snapshotted first, so that
+/// all snapshots reflect the same point in time. They stay stopped while `install` installs
+/// the code of all canisters, and only the ones that were running before are started again.
+/// If `install` fails, the snapshots are loaded back and canisters that had no code before
+/// are uninstalled. The snapshots taken here are deleted afterwards.
+#[context("Failed to deploy canisters atomically.")]
+async fn install_atomically<F: Future<Output = DfxResult>>(
+    env: &dyn Environment,
+    canister_names: &[String],
+    call_sender: &CallSender,
+    install: impl FnOnce(InstallOrder) -> F,
+) -> DfxResult {
+    let log = env.get_logger();
+    let agent = env.get_agent();
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let mut upgraded = vec![];
+    let mut fresh = vec![];
+    let mut running = vec![];
+    for canister_name in canister_names {
+        let canister_id = canister_id_store.get(canister_name)?;
+        if read_state_tree_canister_module_hash(agent, canister_id)
+            .await?
+            .is_some()
+        {
+            upgraded.push((canister_name.as_str(), canister_id));
+            let status = get_canister_status(env, canister_id, call_sender).await?;
+            if status.status == CanisterStatus::Running {
+                running.push((canister_name.as_str(), canister_id));
+            }
+        } else {
+            fresh.push((canister_name.as_str(), canister_id));
+        }
+    }
+
+    let mut snapshots = vec![];
+    let prepared: DfxResult = async {
+        for (canister_name, canister_id) in &upgraded {
+            info!(log, "Stopping canister '{canister_name}'.");
+            stop_canister(env, *canister_id, call_sender).await?;
+        }
+        for (canister_name, canister_id) in &upgraded {
+            info!(log, "Taking a snapshot of canister '{canister_name}'.");
+            let snapshot = take_canister_snapshot(env, *canister_id, None, call_sender).await?;
+            snapshots.push((*canister_name, *canister_id, snapshot.id));
+        }
+        Ok(())
+    }
+    .await;
+
+    let result = match prepared {
+        Err(e) => Err(e.context(
+            "Failed to take snapshots before deploying. No canister was changed. \
+            If a canister has reached its snapshot limit, delete one of its snapshots with `dfx canister snapshot delete`.",
+        )),
+        Ok(()) => match install(InstallOrder::CodeFirst {
+            start: running.iter().map(|(_, canister_id)| *canister_id).collect(),
+        })
+        .await
+        {
+            Ok(()) => Ok(()),
+            Err(install_error) => {
+                warn!(log, "Deployment failed, rolling back: {install_error:#}");
+                let mut rolled_back = vec![];
+                let mut uninstalled = vec![];
+                let mut failed = vec![];
+                for (canister_name, canister_id, snapshot_id) in &snapshots {
+                    // The canister may have been started again before the failure.
+                    let restored = async {
+                        stop_canister(env, *canister_id, call_sender).await?;
+                        load_canister_snapshot(env, *canister_id, snapshot_id, call_sender).await
+                    }
+                    .await;
+                    match restored {
+                        Ok(()) => rolled_back.push(*canister_name),
+                        Err(e) => {
+                            warn!(log, "Failed to roll back canister '{canister_name}': {e:#}");
+                            failed.push(*canister_name);
+                        }
+                    }
+                }
+                for (canister_name, canister_id) in &fresh {
+                    let removed = async {
+                        if read_state_tree_canister_module_hash(agent, *canister_id)
+                            .await?
+                            .is_none()
+                        {
+                            return Ok(false);
+                        }
+                        uninstall_code(env, *canister_id, call_sender).await?;
+                        Ok(true)
+                    }
+                    .await;
+                    match removed {
+                        Ok(true) => uninstalled.push(*canister_name),
+                        Ok(false) => (),
+                        Err(e) => {
+                            warn!(log, "Failed to uninstall canister '{canister_name}': {e:#}");
+                            failed.push(*canister_name);
+                        }
+                    }
+                }
+
+                if !rolled_back.is_empty() {
+                    info!(log, "Rolled back to their snapshots: {}", rolled_back.join(", "));
+                }
+                if !uninstalled.is_empty() {
+                    info!(log, "Uninstalled new code from: {}", uninstalled.join(", "));
+                }
+                if failed.is_empty() {
+                    Err(install_error.context("Deployment failed and was rolled back."))
+                } else {
+                    Err(install_error.context(format!(
+                        "Deployment failed and could not be rolled back for: {}",
+                        failed.join(", ")
+                    )))
+                }
+            }
+        },
+    };
+
+    // Starting a canister that is already running has no effect.
+    for (canister_name, canister_id) in &running {
+        if let Err(e) = start_canister(env, *canister_id, call_sender).await {
+            warn!(log, "Failed to start canister '{canister_name}': {e:#}");
+        }
+    }
+    for (canister_name, canister_id, snapshot_id) in &snapshots {
+        if let Err(e) = delete_canister_snapshot(env, *canister_id, snapshot_id, call_sender).await
+        {
+            warn!(
+                log,
+                "Failed to delete snapshot {} of canister '{canister_name}': {e:#}",
+                hex::encode(snapshot_id)
+            );
+        }
+    }
+
+    result
+}
+
 #[context("Failed to prepare assets for commit.")]
 async fn prepare_assets_for_commit(
     env: &dyn Environment,
@@ -0,0 +1,279 @@
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::cycles_ledger_types::create_canister::SubnetSelection;
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::ic_attributes::{get_memory_allocation, get_reserved_cycles_limit};
+use crate::lib::sign::signer::{CHUNK_CUTOFF, CHUNK_SIZE};
+use crate::lib::subnet::{get_subnet_for_canister, get_subnet_size};
+use crate::util::format_as_trillions;
+use anyhow::bail;
+use candid::Principal;
+use fn_error_context::context;
+use slog::info;
+
+/// The fees below are those of a 13-node application subnet. Fees of other subnets scale linearly with their size.
+const REFERENCE_SUBNET_SIZE: u64 = 13;
+const CANISTER_CREATION_FEE: u128 = 500_000_000_000;
+const UPDATE_MESSAGE_EXECUTION_FEE: u128 = 5_000_000;
+const TEN_UPDATE_INSTRUCTIONS_EXECUTION_FEE: u128 = 4;
+const INGRESS_MESSAGE_RECEPTION_FEE: u128 = 1_200_000;
+const INGRESS_BYTE_RECEPTION_FEE: u128 = 2_000;
+const GIB_STORAGE_PER_SECOND_FEE: u128 = 127_000;
+
+/// `install_code` fails beyond this many instructions, which bounds what an installation can cost.
+const MAX_INSTALL_CODE_INSTRUCTIONS: u128 = 300_000_000_000;
+/// Cycles are reserved for at most 10 years of storage of newly allocated memory.
+const MAX_RESERVATION_SECONDS: u128 = 10 * 365 * 24 * 60 * 60;
+/// The default `reserved_cycles_limit` of a canister.
+const DEFAULT_RESERVED_CYCLES_LIMIT: u128 = 5_000_000_000_000;
+const STORAGE_ESTIMATE_SECONDS: u128 = 30 * 24 * 60 * 60;
+const GIB: u128 = 1 << 30;
+
+/// The expected cycles cost of deploying a canister.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CostEstimate {
+    pub canister_name: String,
+    pub subnet_size: u64,
+    pub creation: u128,
+    /// Upper bound: the installation is charged for the instructions it actually executes.
+    pub install: u128,
+    pub chunk_uploads: u128,
+    /// Storage of the module, or of the memory allocation, for 30 days.
+    pub storage: u128,
+    /// Upper bound: cycles are only reserved while the subnet's memory usage is above 450 GiB.
+    pub reserved: u128,
+}
+
+impl CostEstimate {
+    pub fn new(
+        canister_name: &str,
+        subnet_size: u64,
+        create: bool,
+        wasm_size: u128,
+        memory_allocation: Option<u128>,
+        reserved_cycles_limit: Option<u128>,
+    ) -> Self {
+        let scale = |fee: u128| fee * subnet_size as u128 / REFERENCE_SUBNET_SIZE as u128;
+        let update_call = |bytes: u128, instructions: u128| {
+            scale(
+                INGRESS_MESSAGE_RECEPTION_FEE
+                    + INGRESS_BYTE_RECEPTION_FEE * bytes
+                    + UPDATE_MESSAGE_EXECUTION_FEE
+                    + TEN_UPDATE_INSTRUCTIONS_EXECUTION_FEE * instructions / 10,
+            )
+        };
+        let storage =
+            |bytes: u128, seconds: u128| scale(GIB_STORAGE_PER_SECOND_FEE * bytes * seconds / GIB);
+
+        let (install, chunk_uploads) = if wasm_size < CHUNK_CUTOFF as u128 {
+            (update_call(wasm_size, MAX_INSTALL_CODE_INSTRUCTIONS), 0)
+        } else {
+            let chunk_size = CHUNK_SIZE as u128;
+            let chunks = wasm_size.div_ceil(chunk_size);
+            // upload_chunk for each chunk, and clear_chunk_store afterwards
+            let uploads = (0..chunks)
+                .map(|i| update_call(chunk_size.min(wasm_size - i * chunk_size), 0))
+                .sum::<u128>()
+                + update_call(0, 0);
+            // install_chunked_code only carries the hashes of the chunks
+            (
+                update_call(32 * (chunks + 1), MAX_INSTALL_CODE_INSTRUCTIONS),
+                uploads,
+            )
+        };
+        let stored_bytes = memory_allocation.unwrap_or(wasm_size);
+        Self {
+            canister_name: canister_name.to_string(),
+            subnet_size,
+            creation: if create {
+                scale(CANISTER_CREATION_FEE)
+            } else {
+                0
+            },
+            install,
+            chunk_uploads,
+            storage: storage(stored_bytes, STORAGE_ESTIMATE_SECONDS),
+            reserved: storage(stored_bytes, MAX_RESERVATION_SECONDS)
+                .min(reserved_cycles_limit.unwrap_or(DEFAULT_RESERVED_CYCLES_LIMIT)),
+        }
+    }
+
+    pub fn total(&self) -> u128 {
+        self.creation + self.install + self.chunk_uploads + self.storage + self.reserved
+    }
+}
+
+/// Estimates the cycles cost of deploying the given canisters, from their built modules.
+/// Canisters without an id are expected to be created.
+#[context("Failed to estimate the cycles cost of deploying canisters.")]
+pub(crate) async fn estimate_deploy_cost(
+    env: &dyn Environment,
+    canister_names: &[String],
+    subnet_selection: Option<&SubnetSelection>,
+) -> DfxResult<Vec<CostEstimate>> {
+    let config = env.get_config_or_anyhow()?;
+    let config_interface = config.get_config();
+    let network = env.get_network_descriptor();
+    let canister_id_store = env.get_canister_id_store()?;
+
+    let new_canister_subnet_size = match subnet_selection {
+        Some(SubnetSelection::Subnet { subnet }) if network.is_ic => {
+            get_subnet_size(env.get_agent(), *subnet).await?
+        }
+        _ => REFERENCE_SUBNET_SIZE,
+    };
+
+    let mut estimates = vec![];
+    for canister_name in canister_names {
+        let canister_id = canister_id_store.find(canister_name);
+        let canister_info = CanisterInfo::load(&config, canister_name, canister_id)?;
+        let wasm_path = canister_info.get_build_wasm_path();
+        if !wasm_path.exists() {
+            bail!(
+                "Canister '{0}' has not been built. Run `dfx build --network {1} --check {0}` first.",
+                canister_name,
+                network.name
+            );
+        }
+        let wasm_size = dfx_core::fs::metadata(&wasm_path)?.len() as u128;
+        let subnet_size = match canister_id {
+            Some(canister_id) => get_canister_subnet_size(env, canister_id).await?,
+            None => new_canister_subnet_size,
+        };
+        let memory_allocation = get_memory_allocation(
+            None,
+            Some(config_interface),
+            Some(canister_name),
+            &network.name,
+        )?
+        .map(|allocation| u64::from(allocation) as u128);
+        let reserved_cycles_limit = get_reserved_cycles_limit(
+            None,
+            Some(config_interface),
+            Some(canister_name),
+            &network.name,
+        )?
+        .map(u128::from);
+        estimates.push(CostEstimate::new(
+            canister_name,
+            subnet_size,
+            canister_id.is_none(),
+            wasm_size,
+            memory_allocation.filter(|allocation| *allocation > 0),
+            reserved_cycles_limit,
+        ));
+    }
+    Ok(estimates)
+}
+
+async fn get_canister_subnet_size(env: &dyn Environment, canister_id: Principal) -> DfxResult<u64> {
+    if !env.get_network_descriptor().is_ic {
+        return Ok(REFERENCE_SUBNET_SIZE);
+    }
+    let subnet = get_subnet_for_canister(env.get_agent(), canister_id).await?;
+    get_subnet_size(env.get_agent(), subnet).await
+}
+
+/// Prints the breakdown of each estimate, and the total of all of them.
+pub(crate) fn print_cost_estimates(env: &dyn Environment, estimates: &[CostEstimate]) {
+    let log = env.get_logger();
+    let cycles = |amount: u128| format!("{} TC", format_as_trillions(amount));
+    for estimate in estimates {
+        info!(
+            log,
+            "{} (subnet of {} nodes):", estimate.canister_name, estimate.subnet_size
+        );
+        if estimate.creation > 0 {
+            info!(
+                log,
+                "  Canister creation:          {}",
+                cycles(estimate.creation)
+            );
+        }
+        info!(
+            log,
+            "  Installation (at most):     {}",
+            cycles(estimate.install)
+        );
+        if estimate.chunk_uploads > 0 {
+            info!(
+                log,
+                "  Chunk uploads:              {}",
+                cycles(estimate.chunk_uploads)
+            );
+        }
+        info!(
+            log,
+            "  Storage for 30 days:        {}",
+            cycles(estimate.storage)
+        );
+        info!(
+            log,
+            "  Reserved cycles (at most):  {}",
+            cycles(estimate.reserved)
+        );
+        info!(
+            log,
+            "  Total:                      {}",
+            cycles(estimate.total())
+        );
+    }
+    let total: u128 = estimates.iter().map(CostEstimate::total).sum();
+    info!(log, "Estimated total: {} ({} cycles)", cycles(total), total);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_module_is_installed_in_one_call() {
+        let estimate = CostEstimate::new("small", 13, true, 1_000_000, None, None);
+        assert_eq!(estimate.creation, 500_000_000_000);
+        assert_eq!(
+            estimate.install,
+            1_200_000 + 2_000 * 1_000_000 + 5_000_000 + 120_000_000_000
+        );
+        assert_eq!(estimate.chunk_uploads, 0);
+        assert_eq!(
+            estimate.storage,
+            127_000 * 1_000_000 * STORAGE_ESTIMATE_SECONDS / GIB
+        );
+    }
+
+    #[test]
+    fn large_module_is_uploaded_in_chunks() {
+        let wasm_size = 2 * CHUNK_SIZE as u128 + 10;
+        let estimate = CostEstimate::new("large", 13, false, wasm_size, None, None);
+        assert_eq!(estimate.creation, 0);
+        // three upload_chunk calls and clear_chunk_store
+        assert_eq!(
+            estimate.chunk_uploads,
+            4 * (1_200_000 + 5_000_000) + 2_000 * wasm_size
+        );
+        assert_eq!(
+            estimate.install,
+            1_200_000 + 2_000 * 32 * 4 + 5_000_000 + 120_000_000_000
+        );
+    }
+
+    #[test]
+    fn fees_scale_with_subnet_size() {
+        let small = CostEstimate::new("a", 13, true, 1_000, None, None);
+        let large = CostEstimate::new("a", 34, true, 1_000, None, None);
+        assert_eq!(large.creation, 500_000_000_000 * 34 / 13);
+        assert!(large.total() > small.total());
+    }
+
+    #[test]
+    fn reserved_cycles_are_capped_by_limit() {
+        let allocation = 1 << 20;
+        let estimate = CostEstimate::new("a", 13, true, 1_000, Some(allocation), Some(1_000));
+        assert_eq!(estimate.reserved, 1_000);
+        let estimate = CostEstimate::new("a", 13, true, 1_000, Some(allocation), None);
+        assert_eq!(
+            estimate.reserved,
+            127_000 * allocation * MAX_RESERVATION_SECONDS / GIB
+        );
+    }
+}
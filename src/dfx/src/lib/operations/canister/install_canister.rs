@@ -39,6 +39,16 @@ use std::process::{Command, Stdio};
 
 use super::motoko_playground::playground_install_code;
 
+/// The steps of an installation that [install_canister] performs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstallSteps {
+    /// Install the code, upload assets and run the post-install tasks.
+    All,
+    /// Only install the code, which also works while the canister is stopped.
+    /// The caller runs [finish_install] once the canister is running.
+    CodeOnly,
+}
+
 #[context("Failed to install wasm module to canister '{}'.", canister_info.get_name())]
 pub async fn install_canister(
     env: &dyn Environment,
@@ -56,6 +66,7 @@ pub async fn install_canister(
     env_file: Option<&Path>,
     no_asset_upgrade: bool,
     always_assist: bool,
+    steps: InstallSteps,
 ) -> DfxResult {
     let log = env.get_logger();
     let agent = env.get_agent();
@@ -168,12 +179,12 @@ pub async fn install_canister(
         // The argument and argument_type from the CLI take precedence over the dfx.json configuration.
         let argument_from_json = canister_info.get_init_arg()?;
         let (argument, argument_type) = match (argument_from_cli, &argument_from_json) {
-            (Some(a_cli), Some(a_json)) => {
+            (Some(a_cli), Some((a_json, json_type))) => {
                 // We want to warn the user when the argument from CLI and json are different.
                 // There are two cases to consider:
-                // 1. The argument from CLI is in raw format, while the argument from json is always in Candid format.
-                // 2. Both arguments are in Candid format, but they are different.
-                if argument_type_from_cli == Some("raw") || a_cli != a_json {
+                // 1. The arguments are in different formats, e.g. raw from CLI and Candid from json.
+                // 2. Both arguments are in the same format, but they are different.
+                if argument_type_from_cli.unwrap_or("idl") != *json_type || a_cli != a_json {
                     warn!(
                         log,
                         "Canister '{0}' has init_arg/init_arg_file in dfx.json: {1},
@@ -187,7 +198,7 @@ The command line value will be used.",
                 (argument_from_cli, argument_type_from_cli)
             }
             (Some(_), None) => (argument_from_cli, argument_type_from_cli),
-            (None, Some(a_json)) => (Some(a_json.as_str()), Some("idl")), // `init_arg` in dfx.json is always in Candid format
+            (None, Some((a_json, json_type))) => (Some(a_json.as_str()), Some(*json_type)),
             (None, None) => (None, None),
         };
         let install_args = blob_from_arguments(
@@ -245,55 +256,17 @@ The command line value will be used.",
         .await?;
     }
 
-    if canister_info.is_assets() {
-        if let Some(canister_timeout) = canister_id_store.get_timestamp(canister_info.get_name()) {
-            // playground installed the code, so playground has to authorize call_sender to upload files
-            let uploader_principal = env
-                .get_selected_identity_principal()
-                .context("Failed to figure out selected identity's principal.")?;
-            authorize_asset_uploader(
-                env,
-                canister_info.get_canister_id()?,
-                canister_timeout,
-                &uploader_principal,
-            )
-            .await?;
-        }
-        if let CallSender::Wallet(wallet_id) = call_sender {
-            let wallet = build_wallet_canister(*wallet_id, agent).await?;
-            let identity_name = env.get_selected_identity().expect("No selected identity.");
-            info!(
-                log,
-                "Authorizing our identity ({}) to the asset canister...", identity_name
-            );
-            let self_id = env
-                .get_selected_identity_principal()
-                .expect("Selected identity not instantiated.");
-            // Before storing assets, make sure the DFX principal is in there first.
-            wallet
-                .call::<(), _>(
-                    canister_id,
-                    "authorize",
-                    Argument::from_candid((self_id,)),
-                    0,
-                )
-                .await
-                .context("Failed to authorize your principal with the canister. You can still control the canister by using your wallet with the --wallet flag.")?;
-        };
-
-        debug!(log, "Uploading assets to asset canister...");
-        post_install_store_assets(env, canister_info, agent).await?;
-    }
-    if !canister_info.get_post_install().is_empty() {
-        let config = env.get_config()?;
-        run_customized_install_tasks(
+    if steps == InstallSteps::All {
+        finish_install(
             env,
+            canister_id_store,
+            canister_id,
             canister_info,
-            false,
-            network,
+            call_sender,
             pool,
-            env_file.or_else(|| config.as_ref()?.get_config().output_env_file.as_deref()),
-        )?;
+            env_file,
+        )
+        .await?;
     }
     spinner.finish_and_clear();
     info!(
@@ -405,6 +378,74 @@ async fn wait_for_module_hash(
     Ok(())
 }
 
+/// Uploads the assets of an asset canister and runs the post-install tasks of a canister
+/// whose code was installed with [InstallSteps::CodeOnly]. The canister must be running.
+#[context("Failed to finish installing canister '{}'.", canister_info.get_name())]
+pub async fn finish_install(
+    env: &dyn Environment,
+    canister_id_store: &CanisterIdStore,
+    canister_id: Principal,
+    canister_info: &CanisterInfo,
+    call_sender: &CallSender,
+    pool: Option<&CanisterPool>,
+    env_file: Option<&Path>,
+) -> DfxResult {
+    let log = env.get_logger();
+    let agent = env.get_agent();
+    let network = env.get_network_descriptor();
+    if canister_info.is_assets() {
+        if let Some(canister_timeout) = canister_id_store.get_timestamp(canister_info.get_name()) {
+            // playground installed the code, so playground has to authorize call_sender to upload files
+            let uploader_principal = env
+                .get_selected_identity_principal()
+                .context("Failed to figure out selected identity's principal.")?;
+            authorize_asset_uploader(
+                env,
+                canister_info.get_canister_id()?,
+                canister_timeout,
+                &uploader_principal,
+            )
+            .await?;
+        }
+        if let CallSender::Wallet(wallet_id) = call_sender {
+            let wallet = build_wallet_canister(*wallet_id, agent).await?;
+            let identity_name = env.get_selected_identity().expect("No selected identity.");
+            info!(
+                log,
+                "Authorizing our identity ({}) to the asset canister...", identity_name
+            );
+            let self_id = env
+                .get_selected_identity_principal()
+                .expect("Selected identity not instantiated.");
+            // Before storing assets, make sure the DFX principal is in there first.
+            wallet
+                .call::<(), _>(
+                    canister_id,
+                    "authorize",
+                    Argument::from_candid((self_id,)),
+                    0,
+                )
+                .await
+                .context("Failed to authorize your principal with the canister. You can still control the canister by using your wallet with the --wallet flag.")?;
+        };
+
+        debug!(log, "Uploading assets to asset canister...");
+        post_install_store_assets(env, canister_info, agent).await?;
+    }
+    if !canister_info.get_post_install().is_empty() {
+        let config = env.get_config()?;
+        run_customized_install_tasks(
+            env,
+            canister_info,
+            false,
+            network,
+            pool,
+            env_file.or_else(|| config.as_ref()?.get_config().output_env_file.as_deref()),
+        )?;
+    }
+    Ok(())
+}
+
 enum StableCompatibility {
     Okay,
     Warning(String),
@@ -1,13 +1,22 @@
 pub(crate) mod create_canister;
 pub(crate) mod deploy_canisters;
+pub(crate) mod estimate_cost;
 pub(crate) mod install_canister;
 pub mod motoko_playground;
+mod reconcile_settings;
 mod skip_remote_canister;
+pub mod snapshot_data;
+pub(crate) mod watch;
 
 pub use create_canister::create_canister;
 use ic_utils::interfaces::management_canister::Snapshot;
 pub use install_canister::install_wallet;
+pub use reconcile_settings::reconcile_settings;
 pub use skip_remote_canister::skip_remote_canister;
+use snapshot_data::{
+    ExportedGlobal, GlobalTimer, OnLowWasmMemoryHookStatus, SnapshotMetadata, SnapshotReadKind,
+    SnapshotUploadKind,
+};
 
 use crate::lib::canister_info::CanisterInfo;
 use crate::lib::environment::Environment;
@@ -578,3 +587,156 @@ pub async fn delete_canister_snapshot(
     .await?;
     Ok(())
 }
+
+#[context(
+    "Failed to read metadata of snapshot {} in canister {canister_id}",
+    hex::encode(snapshot_id)
+)]
+pub async fn read_canister_snapshot_metadata(
+    env: &dyn Environment,
+    canister_id: Principal,
+    snapshot_id: &[u8],
+    call_sender: &CallSender,
+) -> DfxResult<SnapshotMetadata> {
+    #[derive(CandidType)]
+    struct In<'a> {
+        canister_id: Principal,
+        snapshot_id: &'a [u8],
+    }
+    let (metadata,) = do_management_call(
+        env,
+        canister_id,
+        "read_canister_snapshot_metadata",
+        &In {
+            canister_id,
+            snapshot_id,
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(metadata)
+}
+
+#[context(
+    "Failed to read data of snapshot {} in canister {canister_id}",
+    hex::encode(snapshot_id)
+)]
+pub async fn read_canister_snapshot_data(
+    env: &dyn Environment,
+    canister_id: Principal,
+    snapshot_id: &[u8],
+    kind: &SnapshotReadKind,
+    call_sender: &CallSender,
+) -> DfxResult<Vec<u8>> {
+    #[derive(CandidType)]
+    struct In<'a> {
+        canister_id: Principal,
+        snapshot_id: &'a [u8],
+        kind: &'a SnapshotReadKind,
+    }
+    #[derive(CandidType, serde::Deserialize)]
+    struct Out {
+        #[serde(with = "serde_bytes")]
+        chunk: Vec<u8>,
+    }
+    let (out,): (Out,) = do_management_call(
+        env,
+        canister_id,
+        "read_canister_snapshot_data",
+        &In {
+            canister_id,
+            snapshot_id,
+            kind,
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(out.chunk)
+}
+
+/// Creates a new snapshot (or replaces an existing one) from uploaded metadata.
+/// Its contents must then be uploaded with `upload_canister_snapshot_data`.
+#[context("Failed to upload snapshot metadata to canister {canister_id}")]
+pub async fn upload_canister_snapshot_metadata(
+    env: &dyn Environment,
+    canister_id: Principal,
+    replace_snapshot: Option<&[u8]>,
+    metadata: &SnapshotMetadata,
+    call_sender: &CallSender,
+) -> DfxResult<Vec<u8>> {
+    #[derive(CandidType)]
+    struct In<'a> {
+        canister_id: Principal,
+        replace_snapshot: Option<&'a [u8]>,
+        wasm_module_size: u64,
+        exported_globals: &'a [ExportedGlobal],
+        wasm_memory_size: u64,
+        stable_memory_size: u64,
+        certified_data: &'a [u8],
+        global_timer: Option<&'a GlobalTimer>,
+        on_low_wasm_memory_hook_status: Option<&'a OnLowWasmMemoryHookStatus>,
+    }
+    #[derive(CandidType, serde::Deserialize)]
+    struct Out {
+        #[serde(with = "serde_bytes")]
+        snapshot_id: Vec<u8>,
+    }
+    let (out,): (Out,) = do_management_call(
+        env,
+        canister_id,
+        "upload_canister_snapshot_metadata",
+        &In {
+            canister_id,
+            replace_snapshot,
+            wasm_module_size: metadata.wasm_module_size,
+            exported_globals: &metadata.exported_globals,
+            wasm_memory_size: metadata.wasm_memory_size,
+            stable_memory_size: metadata.stable_memory_size,
+            certified_data: &metadata.certified_data,
+            global_timer: metadata.global_timer.as_ref(),
+            on_low_wasm_memory_hook_status: metadata.on_low_wasm_memory_hook_status.as_ref(),
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(out.snapshot_id)
+}
+
+#[context(
+    "Failed to upload data of snapshot {} to canister {canister_id}",
+    hex::encode(snapshot_id)
+)]
+pub async fn upload_canister_snapshot_data(
+    env: &dyn Environment,
+    canister_id: Principal,
+    snapshot_id: &[u8],
+    kind: &SnapshotUploadKind,
+    chunk: &[u8],
+    call_sender: &CallSender,
+) -> DfxResult {
+    #[derive(CandidType)]
+    struct In<'a> {
+        canister_id: Principal,
+        snapshot_id: &'a [u8],
+        kind: &'a SnapshotUploadKind,
+        chunk: &'a [u8],
+    }
+    do_management_call::<_, ()>(
+        env,
+        canister_id,
+        "upload_canister_snapshot_data",
+        &In {
+            canister_id,
+            snapshot_id,
+            kind,
+            chunk,
+        },
+        call_sender,
+        0,
+    )
+    .await?;
+    Ok(())
+}
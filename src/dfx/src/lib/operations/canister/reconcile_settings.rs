@@ -0,0 +1,68 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::ic_attributes::{diff_canister_settings, get_canister_settings_from_config};
+use crate::lib::operations::canister::{get_canister_status, update_settings};
+use crate::util::ask_for_consent;
+use candid::Principal;
+use dfx_core::config::model::dfinity::ConfigInterface;
+use dfx_core::identity::CallSender;
+use fn_error_context::context;
+use slog::info;
+
+/// Updates the settings of a canister that differ from the settings in dfx.json.
+///
+/// The differences are displayed first, and unless `skip_consent` is set,
+/// the user needs to confirm them before they are applied.
+#[context(
+    "Failed to reconcile settings of canister '{}' with dfx.json.",
+    canister_name
+)]
+pub async fn reconcile_settings(
+    env: &dyn Environment,
+    config_interface: &ConfigInterface,
+    canister_name: &str,
+    canister_id: Principal,
+    call_sender: &CallSender,
+    skip_consent: bool,
+) -> DfxResult {
+    let log = env.get_logger();
+    let network = env.get_network_descriptor();
+    let configured =
+        get_canister_settings_from_config(env, config_interface, canister_name, &network.name)?;
+    let current = get_canister_status(env, canister_id, call_sender).await?;
+
+    let (update, changes) = diff_canister_settings(&configured, &current.settings);
+    if changes.is_empty() {
+        info!(
+            log,
+            "Settings of canister '{canister_name}' already match dfx.json."
+        );
+        return Ok(());
+    }
+
+    let diff = changes
+        .iter()
+        .map(|change| {
+            format!(
+                "  {}: {} -> {}",
+                change.name, change.current, change.configured
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    info!(
+        log,
+        "Settings of canister '{canister_name}' that differ from dfx.json on network '{}':\n{diff}",
+        network.name
+    );
+    if !skip_consent {
+        ask_for_consent(
+            env,
+            &format!("This will update the settings of canister '{canister_name}'."),
+        )?;
+    }
+
+    update_settings(env, canister_id, update, call_sender).await?;
+    info!(log, "Updated settings of canister '{canister_name}'.");
+    Ok(())
+}
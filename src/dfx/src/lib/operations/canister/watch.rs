@@ -0,0 +1,240 @@
+use crate::lib::builders::EXCLUDED_DIRECTORIES;
+use crate::lib::canister_info::assets::AssetsCanisterInfo;
+use crate::lib::canister_info::custom::CustomCanisterInfo;
+use crate::lib::canister_info::motoko::MotokoCanisterInfo;
+use crate::lib::canister_info::rust::RustCanisterInfo;
+use crate::lib::canister_info::CanisterInfo;
+use crate::lib::error::DfxResult;
+use fn_error_context::context;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A canister, and the files whose changes redeploy it.
+#[derive(Debug)]
+pub(crate) struct WatchedCanister {
+    pub name: String,
+    pub dependencies: Vec<String>,
+    pub paths: Vec<PathBuf>,
+    /// Changes to the sources of an asset canister only need its assets to be synchronized.
+    pub is_assets: bool,
+}
+
+impl WatchedCanister {
+    #[context("Failed to determine the sources of canister '{}'.", info.get_name())]
+    pub fn new(info: &CanisterInfo) -> DfxResult<Self> {
+        let mut paths = vec![];
+        if info.is_motoko() {
+            let motoko_info = info.as_info::<MotokoCanisterInfo>()?;
+            paths.extend(motoko_info.get_main_path().parent().map(Path::to_path_buf));
+        } else if info.is_rust() {
+            let rust_info = info.as_info::<RustCanisterInfo>()?;
+            paths.push(rust_info.get_package_root().to_path_buf());
+            paths.push(info.get_output_idl_path().to_path_buf());
+        } else if info.is_custom() {
+            let custom_info = info.as_info::<CustomCanisterInfo>()?;
+            if custom_info.get_input_wasm_url().is_none() {
+                paths.push(custom_info.get_output_wasm_path().to_path_buf());
+            }
+            if custom_info.get_input_candid_url().is_none() {
+                paths.push(info.get_output_idl_path().to_path_buf());
+            }
+        } else if info.is_assets() {
+            let assets_info = info.as_info::<AssetsCanisterInfo>()?;
+            paths.extend(assets_info.get_source_paths());
+        }
+        paths.extend_from_slice(info.get_watch_paths());
+        Ok(Self {
+            name: info.get_name().to_string(),
+            dependencies: info.get_dependencies().to_vec(),
+            paths,
+            is_assets: info.is_assets(),
+        })
+    }
+
+    fn is_affected_by(&self, changed: &[PathBuf]) -> bool {
+        changed
+            .iter()
+            .any(|file| self.paths.iter().any(|path| file.starts_with(path)))
+    }
+}
+
+/// Returns the canisters to rebuild and reinstall because their sources changed or because they
+/// depend on such a canister, and the asset canisters whose assets only need to be synchronized.
+pub(crate) fn canisters_affected_by(
+    canisters: &[WatchedCanister],
+    changed: &[PathBuf],
+) -> (Vec<String>, Vec<String>) {
+    let mut redeploy: BTreeSet<&str> = canisters
+        .iter()
+        .filter(|canister| !canister.is_assets && canister.is_affected_by(changed))
+        .map(|canister| canister.name.as_str())
+        .collect();
+    loop {
+        let dependents: Vec<&str> = canisters
+            .iter()
+            .filter(|canister| !redeploy.contains(canister.name.as_str()))
+            .filter(|canister| {
+                canister
+                    .dependencies
+                    .iter()
+                    .any(|dependency| redeploy.contains(dependency.as_str()))
+            })
+            .map(|canister| canister.name.as_str())
+            .collect();
+        if dependents.is_empty() {
+            break;
+        }
+        redeploy.extend(dependents);
+    }
+    let sync = canisters
+        .iter()
+        .filter(|canister| canister.is_assets && canister.is_affected_by(changed))
+        .filter(|canister| !redeploy.contains(canister.name.as_str()))
+        .map(|canister| canister.name.clone())
+        .collect();
+    let redeploy = canisters
+        .iter()
+        .filter(|canister| redeploy.contains(canister.name.as_str()))
+        .map(|canister| canister.name.clone())
+        .collect();
+    (redeploy, sync)
+}
+
+type FileState = (Option<SystemTime>, u64);
+
+/// Detects changes to files by comparing their modification time and size at regular intervals.
+pub(crate) struct FileWatcher {
+    paths: Vec<PathBuf>,
+    files: BTreeMap<PathBuf, FileState>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        let files = snapshot(&paths);
+        Self { paths, files }
+    }
+
+    /// Waits until files were added, modified or removed, and then stopped changing.
+    /// Returns the paths of these files.
+    pub async fn changes(&mut self) -> Vec<PathBuf> {
+        let mut changed = BTreeSet::new();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let files = snapshot(&self.paths);
+            let new_changes = changed_files(&self.files, &files);
+            self.files = files;
+            if new_changes.is_empty() && !changed.is_empty() {
+                return changed.into_iter().collect();
+            }
+            changed.extend(new_changes);
+        }
+    }
+
+    /// Ignores the changes made since the files were last compared, such as files written by builds.
+    pub fn reset(&mut self) {
+        self.files = snapshot(&self.paths);
+    }
+}
+
+fn snapshot(paths: &[PathBuf]) -> BTreeMap<PathBuf, FileState> {
+    paths
+        .iter()
+        .flat_map(|path| {
+            WalkDir::new(path).into_iter().filter_entry(|entry| {
+                entry.depth() == 0
+                    || !entry.file_type().is_dir()
+                    || !entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| EXCLUDED_DIRECTORIES.contains(&name))
+            })
+        })
+        // Files may be removed while the directories are traversed.
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let state = (metadata.modified().ok(), metadata.len());
+            Some((entry.into_path(), state))
+        })
+        .collect()
+}
+
+fn changed_files(
+    before: &BTreeMap<PathBuf, FileState>,
+    after: &BTreeMap<PathBuf, FileState>,
+) -> Vec<PathBuf> {
+    let removed = before.keys().filter(|path| !after.contains_key(*path));
+    let added_or_modified = after
+        .iter()
+        .filter(|(path, state)| before.get(*path) != Some(*state))
+        .map(|(path, _)| path);
+    removed.chain(added_or_modified).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canister(name: &str, dependencies: &[&str], path: &str, is_assets: bool) -> WatchedCanister {
+        WatchedCanister {
+            name: name.to_string(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            paths: vec![PathBuf::from(path)],
+            is_assets,
+        }
+    }
+
+    #[test]
+    fn dependents_of_changed_canisters_are_redeployed() {
+        let canisters = [
+            canister("backend", &[], "src/backend", false),
+            canister("frontend", &["backend"], "dist", true),
+            canister("indexer", &["backend"], "src/indexer", false),
+            canister("other", &[], "src/other", false),
+        ];
+        let (redeploy, sync) =
+            canisters_affected_by(&canisters, &[PathBuf::from("src/backend/main.mo")]);
+        assert_eq!(redeploy, ["backend", "frontend", "indexer"]);
+        assert!(sync.is_empty());
+    }
+
+    #[test]
+    fn changed_assets_are_only_synchronized() {
+        let canisters = [
+            canister("backend", &[], "src/backend", false),
+            canister("frontend", &["backend"], "dist", true),
+        ];
+        let (redeploy, sync) =
+            canisters_affected_by(&canisters, &[PathBuf::from("dist/index.html")]);
+        assert!(redeploy.is_empty());
+        assert_eq!(sync, ["frontend"]);
+    }
+
+    #[test]
+    fn detects_added_modified_and_removed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.mo"), "a").unwrap();
+        std::fs::write(root.join("b.mo"), "b").unwrap();
+        std::fs::create_dir(root.join("node_modules")).unwrap();
+        let before = snapshot(&[root.to_path_buf()]);
+
+        std::fs::write(root.join("a.mo"), "changed").unwrap();
+        std::fs::remove_file(root.join("b.mo")).unwrap();
+        std::fs::write(root.join("c.mo"), "c").unwrap();
+        std::fs::write(root.join("node_modules/d.js"), "d").unwrap();
+        let after = snapshot(&[root.to_path_buf()]);
+
+        let mut changed = changed_files(&before, &after);
+        changed.sort();
+        assert_eq!(
+            changed,
+            [root.join("a.mo"), root.join("b.mo"), root.join("c.mo")]
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::lib::cycles_ledger_types;
+use crate::lib::cycles_ledger_types::blocks::{GetBlocksArgs, GetBlocksResult};
 use crate::lib::cycles_ledger_types::create_canister::{
     CmcCreateCanisterArgs, CreateCanisterArgs, CreateCanisterError, CreateCanisterSuccess,
 };
 use crate::lib::cycles_ledger_types::deposit::DepositArg;
+use crate::lib::cycles_ledger_types::index::{
+    GetAccountTransactionsArgs, GetTransactionsResult, TransactionWithId,
+};
 use crate::lib::cycles_ledger_types::withdraw::WithdrawError;
 use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
@@ -39,6 +43,8 @@ use slog::{info, Logger};
 const WITHDRAW_METHOD: &str = "withdraw";
 const CREATE_CANISTER_METHOD: &str = "create_canister";
 const CYCLES_LEDGER_DEPOSIT_METHOD: &str = "deposit";
+const GET_ACCOUNT_TRANSACTIONS_METHOD: &str = "get_account_transactions";
+const ICRC3_GET_BLOCKS_METHOD: &str = "icrc3_get_blocks";
 const CYCLES_LEDGER_CANISTER_ID: Principal =
     Principal::from_slice(&[0x00, 0x00, 0x00, 0x00, 0x02, 0x10, 0x00, 0x02, 0x01, 0x01]);
 
@@ -403,6 +409,86 @@ pub async fn wallet_deposit_to_cycles_ledger(
         .context("Failed deposit call.")
 }
 
+/// Fetches up to `max_results` transactions of an account from an index canister of the cycles ledger, newest first.
+/// With `start`, only transactions older than the transaction with that id are returned.
+#[context(
+    "Failed to get the transactions of account {} from the index canister.",
+    account
+)]
+pub async fn account_transactions(
+    agent: &Agent,
+    index_canister_id: Principal,
+    account: Account,
+    start: Option<u64>,
+    max_results: u64,
+) -> DfxResult<Vec<TransactionWithId>> {
+    let canister = Canister::builder()
+        .with_agent(agent)
+        .with_canister_id(index_canister_id)
+        .build()?;
+
+    let retry_policy = ExponentialBackoff::default();
+
+    let result: GetTransactionsResult = retry(retry_policy, || async {
+        let arg = GetAccountTransactionsArgs {
+            account,
+            start: start.map(Nat::from),
+            max_results: Nat::from(max_results),
+        };
+        let result = canister
+            .query(GET_ACCOUNT_TRANSACTIONS_METHOD)
+            .with_arg(arg)
+            .build()
+            .call()
+            .await;
+        match result {
+            Ok((result,)) => Ok(result),
+            Err(agent_err) if retryable(&agent_err) => {
+                Err(backoff::Error::transient(anyhow!(agent_err)))
+            }
+            Err(agent_err) => Err(backoff::Error::permanent(anyhow!(agent_err))),
+        }
+    })
+    .await?;
+
+    match result {
+        Ok(response) => Ok(response.transactions),
+        Err(err) => bail!(err.message),
+    }
+}
+
+/// Fetches up to `length` blocks of the cycles ledger, starting at block `start`.
+#[context("Failed to get blocks from the cycles ledger.")]
+pub async fn get_blocks(agent: &Agent, start: u64, length: u64) -> DfxResult<GetBlocksResult> {
+    let canister = Canister::builder()
+        .with_agent(agent)
+        .with_canister_id(CYCLES_LEDGER_CANISTER_ID)
+        .build()?;
+
+    let retry_policy = ExponentialBackoff::default();
+
+    retry(retry_policy, || async {
+        let arg = vec![GetBlocksArgs {
+            start: Nat::from(start),
+            length: Nat::from(length),
+        }];
+        let result = canister
+            .query(ICRC3_GET_BLOCKS_METHOD)
+            .with_arg(arg)
+            .build()
+            .call()
+            .await;
+        match result {
+            Ok((result,)) => Ok(result),
+            Err(agent_err) if retryable(&agent_err) => {
+                Err(backoff::Error::transient(anyhow!(agent_err)))
+            }
+            Err(agent_err) => Err(backoff::Error::permanent(anyhow!(agent_err))),
+        }
+    })
+    .await
+}
+
 #[test]
 fn ledger_canister_id_text_representation() {
     assert_eq!(
@@ -1,4 +1,7 @@
 use crate::lib::diagnosis::DiagnosedError;
+use crate::lib::ledger_types::index::{
+    GetAccountIdentifierTransactionsArgs, GetAccountIdentifierTransactionsResult, TransactionWithId,
+};
 use crate::lib::ledger_types::{AccountIdBlob, BlockHeight, Memo, TransferError};
 use crate::lib::nns_types::account_identifier::Subaccount;
 use crate::lib::operations::{
@@ -38,6 +41,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 const ACCOUNT_BALANCE_METHOD: &str = "account_balance";
 const TRANSFER_METHOD: &str = "transfer";
+const GET_ACCOUNT_IDENTIFIER_TRANSACTIONS_METHOD: &str = "get_account_identifier_transactions";
 
 pub async fn balance(
     agent: &Agent,
@@ -415,6 +419,54 @@ pub async fn icrc2_allowance(
     .await
 }
 
+/// Fetches up to `max_results` transactions of an account from the ICP index canister, newest first.
+/// With `start`, only transactions older than the transaction with that id are returned.
+#[context(
+    "Failed to get the transactions of account {} from the index canister.",
+    account
+)]
+pub async fn account_transactions(
+    agent: &Agent,
+    index_canister_id: Principal,
+    account: &AccountIdentifier,
+    start: Option<u64>,
+    max_results: u64,
+) -> DfxResult<Vec<TransactionWithId>> {
+    let canister = Canister::builder()
+        .with_agent(agent)
+        .with_canister_id(index_canister_id)
+        .build()?;
+
+    let retry_policy = ExponentialBackoff::default();
+
+    let result: GetAccountIdentifierTransactionsResult = retry(retry_policy, || async {
+        let arg = GetAccountIdentifierTransactionsArgs {
+            account_identifier: account.to_hex(),
+            start,
+            max_results,
+        };
+        let result = canister
+            .query(GET_ACCOUNT_IDENTIFIER_TRANSACTIONS_METHOD)
+            .with_arg(arg)
+            .build()
+            .call()
+            .await;
+        match result {
+            Ok((result,)) => Ok(result),
+            Err(agent_err) if retryable(&agent_err) => {
+                Err(backoff::Error::transient(anyhow!(agent_err)))
+            }
+            Err(agent_err) => Err(backoff::Error::permanent(anyhow!(agent_err))),
+        }
+    })
+    .await?;
+
+    match result {
+        Ok(response) => Ok(response.transactions),
+        Err(err) => bail!(err.message),
+    }
+}
+
 fn diagnose_insufficient_funds_error(
     agent: &Agent,
     subaccount: Option<Subaccount>,
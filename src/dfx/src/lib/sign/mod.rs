@@ -1 +1,2 @@
 pub mod signed_message;
+pub mod signer;
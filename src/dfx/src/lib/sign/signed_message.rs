@@ -25,6 +25,23 @@ pub(crate) struct SignedMessageV1 {
     pub request_id: Option<String>, // only useful for update call
     pub content: String,            // hex::encode the Vec<u8>
     pub signed_request_status: Option<String>, // hex::encode the Vec<u8>, only accompany update call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_canister_id: Option<String>, // only differs from canister_id for management canister calls
+}
+
+/// Signed messages that must be sent in order, each one after the previous one completed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct SignedMessageBatchV1 {
+    version: usize,
+    pub messages: Vec<SignedMessageV1>,
+}
+
+/// The content of a message file: a single message, or a batch of messages.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum SignedMessageFile {
+    Batch(SignedMessageBatchV1),
+    Single(SignedMessageV1),
 }
 
 impl SignedMessageV1 {
@@ -52,9 +69,26 @@ impl SignedMessageV1 {
             request_id: None,
             content: String::new(),
             signed_request_status: None,
+            effective_canister_id: None,
         }
     }
 
+    pub fn with_effective_canister_id(mut self, effective_canister_id: Principal) -> Self {
+        if effective_canister_id.to_string() != self.canister_id {
+            self.effective_canister_id = Some(effective_canister_id.to_string());
+        }
+        self
+    }
+
+    /// The canister whose subnet receives the message.
+    pub fn get_effective_canister_id(&self) -> DfxResult<Principal> {
+        let id = self
+            .effective_canister_id
+            .as_deref()
+            .unwrap_or(&self.canister_id);
+        Principal::from_text(id).with_context(|| format!("Failed to parse canister id {id:?}."))
+    }
+
     pub fn with_call_type(mut self, request_type: String) -> Self {
         self.call_type = request_type;
         self
@@ -181,6 +215,37 @@ impl SignedMessageV1 {
     }
 }
 
+impl SignedMessageBatchV1 {
+    pub fn new(messages: Vec<SignedMessageV1>) -> Self {
+        Self {
+            version: 1,
+            messages,
+        }
+    }
+
+    #[context("Failed to validate signed messages.")]
+    pub fn validate(&self) -> DfxResult {
+        if self.version != 1 {
+            bail!("Invalid message batch: version must be 1");
+        }
+        if self.messages.is_empty() {
+            bail!("Invalid message batch: no messages");
+        }
+        for (i, message) in self.messages.iter().enumerate() {
+            message
+                .validate()
+                .with_context(|| format!("Invalid message {}.", i + 1))?;
+            if message.call_type != "update" {
+                bail!(
+                    "Invalid message batch: message {} is not an update call",
+                    i + 1
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 mod date_time_utc {
     time::serde::format_description!(date_time, PrimitiveDateTime, "[year repr:full padding:zero]-[month repr:numerical padding:zero]-[day padding:zero] [hour repr:24 padding:zero]:[minute padding:zero]:[second padding:zero] UTC");
 
@@ -0,0 +1,256 @@
+use crate::lib::environment::Environment;
+use crate::lib::error::DfxResult;
+use crate::lib::ic_attributes::CanisterSettings as DfxCanisterSettings;
+use crate::lib::sign::signed_message::{SignedMessageBatchV1, SignedMessageV1};
+use anyhow::{anyhow, bail, Context};
+use candid::{CandidType, Encode, Principal};
+use dfx_core::json::save_json_file;
+use ic_agent::Agent;
+use ic_utils::interfaces::management_canister::builders::{
+    CanisterInstall, CanisterSettings, InstallMode,
+};
+use ic_utils::interfaces::management_canister::{ChunkHash, MgmtMethod};
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+use std::path::Path;
+use std::time::SystemTime;
+use time::OffsetDateTime;
+
+/// Modules and arguments at least this large are uploaded in chunks, like `ManagementCanister::install` does.
+pub(crate) const CHUNK_CUTOFF: usize = (1.85 * 1024. * 1024.) as usize;
+pub(crate) const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Signs messages with the selected identity, without contacting the network,
+/// so that `dfx canister send` can send them later.
+pub(crate) struct MessageSigner<'a> {
+    agent: &'a Agent,
+    network: String,
+    is_ic: bool,
+    sender: Principal,
+    creation: OffsetDateTime,
+    expiration: OffsetDateTime,
+    expiration_system_time: SystemTime,
+}
+
+impl<'a> MessageSigner<'a> {
+    /// Creates a signer for messages that expire after `expire_after`, e.g. `5m`.
+    pub fn new(env: &'a dyn Environment, expire_after: &str) -> DfxResult<Self> {
+        let network_descriptor = env.get_network_descriptor();
+        let network = network_descriptor
+            .providers
+            .first()
+            .expect("Cannot get network provider (url).")
+            .to_string();
+        let sender = env
+            .get_selected_identity_principal()
+            .expect("Selected identity not instantiated.");
+
+        let timeout = humantime::parse_duration(expire_after).map_err(|_| {
+            anyhow!("Cannot parse expire_after as a duration (e.g. `1h`, `1h 30m`)")
+        })?;
+        let expiration_system_time = SystemTime::now()
+            .checked_add(timeout)
+            .ok_or_else(|| anyhow!("Time wrapped around."))?;
+        let creation = OffsetDateTime::now_utc();
+        let expiration = creation
+            .checked_add(timeout.try_into()?)
+            .ok_or_else(|| anyhow!("Expiration datetime overflow."))?;
+
+        Ok(Self {
+            agent: env.get_agent(),
+            network,
+            is_ic: network_descriptor.is_ic,
+            sender,
+            creation,
+            expiration,
+            expiration_system_time,
+        })
+    }
+
+    pub fn agent(&self) -> &Agent {
+        self.agent
+    }
+
+    pub fn expiration_system_time(&self) -> SystemTime {
+        self.expiration_system_time
+    }
+
+    /// A message without call type and content, which the caller fills in.
+    pub fn message_template(
+        &self,
+        canister_id: Principal,
+        method_name: &str,
+        arg: Vec<u8>,
+    ) -> SignedMessageV1 {
+        SignedMessageV1::new(
+            self.creation,
+            self.expiration,
+            self.network.clone(),
+            self.is_ic,
+            self.sender,
+            canister_id,
+            method_name.to_string(),
+            arg,
+        )
+    }
+
+    /// Signs an update call, and the request_status call to read its result.
+    pub fn sign_update(
+        &self,
+        canister_id: Principal,
+        effective_canister_id: Principal,
+        method_name: &str,
+        arg: Vec<u8>,
+    ) -> DfxResult<SignedMessageV1> {
+        let signed_update = self
+            .agent
+            .update(&canister_id, method_name)
+            .with_effective_canister_id(effective_canister_id)
+            .with_arg(arg.clone())
+            .expire_at(self.expiration_system_time)
+            .sign()?;
+        let request_id = signed_update.request_id;
+        let signed_request_status = self
+            .agent
+            .sign_request_status(effective_canister_id, request_id)?;
+        Ok(self
+            .message_template(canister_id, method_name, arg)
+            .with_call_type("update".to_string())
+            .with_effective_canister_id(effective_canister_id)
+            .with_request_id(request_id)
+            .with_content(hex::encode(&signed_update.signed_update))
+            .with_signed_request_status(hex::encode(signed_request_status.signed_request_status)))
+    }
+
+    /// Signs a management canister call about `canister_id`.
+    fn sign_management_call(
+        &self,
+        canister_id: Principal,
+        method: MgmtMethod,
+        arg: impl CandidType,
+    ) -> DfxResult<SignedMessageV1> {
+        let arg = Encode!(&arg).context("Failed to encode the call argument.")?;
+        self.sign_update(
+            Principal::management_canister(),
+            canister_id,
+            method.as_ref(),
+            arg,
+        )
+    }
+
+    /// Signs the messages that install a module: a single `install_code` call for a small module,
+    /// or `upload_chunk` calls, `install_chunked_code` and `clear_chunk_store` for a large one.
+    pub fn sign_install(
+        &self,
+        canister_id: Principal,
+        wasm_module: &[u8],
+        arg: &[u8],
+        mode: InstallMode,
+    ) -> DfxResult<Vec<SignedMessageV1>> {
+        if wasm_module.len() + arg.len() < CHUNK_CUTOFF {
+            let install = CanisterInstall {
+                mode,
+                canister_id,
+                wasm_module: wasm_module.to_vec(),
+                arg: arg.to_vec(),
+            };
+            return Ok(vec![self.sign_management_call(
+                canister_id,
+                MgmtMethod::InstallCode,
+                install,
+            )?]);
+        }
+
+        #[derive(CandidType)]
+        struct UploadChunk<'a> {
+            canister_id: Principal,
+            chunk: &'a [u8],
+        }
+        #[derive(CandidType)]
+        struct InstallChunkedCode {
+            mode: InstallMode,
+            target_canister: Principal,
+            store_canister: Option<Principal>,
+            chunk_hashes_list: Vec<ChunkHash>,
+            wasm_module_hash: Vec<u8>,
+            arg: Vec<u8>,
+            sender_canister_version: Option<u64>,
+        }
+        #[derive(CandidType)]
+        struct ClearChunkStore {
+            canister_id: Principal,
+        }
+
+        let mut messages = vec![];
+        let mut chunk_hashes_list = vec![];
+        for chunk in wasm_module.chunks(CHUNK_SIZE) {
+            messages.push(self.sign_management_call(
+                canister_id,
+                MgmtMethod::UploadChunk,
+                UploadChunk { canister_id, chunk },
+            )?);
+            chunk_hashes_list.push(ChunkHash {
+                hash: Sha256::digest(chunk).to_vec(),
+            });
+        }
+        messages.push(self.sign_management_call(
+            canister_id,
+            MgmtMethod::InstallChunkedCode,
+            InstallChunkedCode {
+                mode,
+                target_canister: canister_id,
+                store_canister: None,
+                chunk_hashes_list,
+                wasm_module_hash: Sha256::digest(wasm_module).to_vec(),
+                arg: arg.to_vec(),
+                sender_canister_version: None,
+            },
+        )?);
+        messages.push(self.sign_management_call(
+            canister_id,
+            MgmtMethod::ClearChunkStore,
+            ClearChunkStore { canister_id },
+        )?);
+        Ok(messages)
+    }
+
+    /// Signs an `update_settings` call.
+    pub fn sign_update_settings(
+        &self,
+        canister_id: Principal,
+        settings: DfxCanisterSettings,
+    ) -> DfxResult<SignedMessageV1> {
+        #[derive(CandidType)]
+        struct In {
+            canister_id: Principal,
+            settings: CanisterSettings,
+        }
+        self.sign_management_call(
+            canister_id,
+            MgmtMethod::UpdateSettings,
+            In {
+                canister_id,
+                settings: settings.into(),
+            },
+        )
+    }
+}
+
+/// Saves signed messages to a file for `dfx canister send`, as a batch if there are several.
+pub(crate) fn save_signed_messages(
+    file_name: &Path,
+    mut messages: Vec<SignedMessageV1>,
+) -> DfxResult {
+    if file_name.exists() {
+        bail!(
+            "[{}] already exists, please specify a different output file name.",
+            file_name.display(),
+        );
+    }
+    if messages.len() == 1 {
+        save_json_file(file_name, &messages.remove(0))?;
+    } else {
+        save_json_file(file_name, &SignedMessageBatchV1::new(messages))?;
+    }
+    Ok(())
+}
@@ -1,8 +1,11 @@
+use crate::lib::environment::Environment;
 use crate::lib::error::DfxResult;
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
 use backoff::future::retry;
 use backoff::ExponentialBackoff;
 use candid::{CandidType, Deserialize, Principal};
+use dfx_core::config::model::replica_config::LocalSubnet;
+use ic_agent::hash_tree::Label;
 use ic_agent::{Agent, AgentError};
 use ic_utils::call::SyncCall;
 use ic_utils::Canister;
@@ -65,6 +68,57 @@ pub async fn get_subnet_for_canister(
     .await
 }
 
+/// Counts the nodes of a subnet, which scale the fees its canisters pay.
+pub async fn get_subnet_size(agent: &Agent, subnet_id: Principal) -> DfxResult<u64> {
+    let paths = vec![vec![
+        "subnet".into(),
+        Label::from_bytes(subnet_id.as_slice()),
+        "node".into(),
+    ]];
+    let cert = agent
+        .read_subnet_state_raw(paths, subnet_id)
+        .await
+        .with_context(|| format!("Failed to read the nodes of subnet {subnet_id}."))?;
+    let nodes = cert
+        .tree
+        .list_paths()
+        .into_iter()
+        .filter(|path| {
+            path.len() == 5 && path[2].as_bytes() == b"node" && path[4].as_bytes() == b"public_key"
+        })
+        .count();
+    if nodes == 0 {
+        bail!("Subnet {subnet_id} has no nodes in its state tree.");
+    }
+    Ok(nodes as u64)
+}
+
+/// Looks up a subnet declared in `networks.<network>.replica.subnets` on the running local network,
+/// either by its name or by its principal.
+pub fn get_local_subnet(env: &dyn Environment, subnet: &str) -> DfxResult<LocalSubnet> {
+    let network = env.get_network_descriptor();
+    let local_server_descriptor = network.local_server_descriptor()?;
+    let config = local_server_descriptor.effective_config()?;
+    if let Ok(subnet_id) = Principal::from_text(subnet) {
+        return config
+            .and_then(|config| config.find_subnet(subnet_id))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Subnet {subnet_id} is not one of the subnets declared in the replica configuration of network '{}'. Only declared subnets can be selected on a local network.",
+                    network.name
+                )
+            });
+    }
+    config
+        .and_then(|config| config.get_subnet(subnet))
+        .ok_or_else(|| {
+            anyhow!(
+                "Subnet '{subnet}' is not declared in the replica configuration of network '{}', or the network was started before it was declared. Restart it with `dfx start --clean`.",
+                network.name
+            )
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,530 @@
+//! Transaction histories of the ICP ledger and the cycles ledger, as printed by `dfx ledger history` and `dfx cycles history`.
+use crate::lib::cycles_ledger_types;
+use crate::lib::cycles_ledger_types::blocks::Value;
+use crate::lib::error::DfxResult;
+use crate::lib::ledger_types;
+use anyhow::{anyhow, bail};
+use candid::{Nat, Principal};
+use clap::ValueEnum;
+use icrc_ledger_types::icrc1::account::Account;
+use num_traits::ToPrimitive;
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryOutputFormat {
+    /// A table with one transaction per line.
+    Text,
+    /// A JSON array of transactions.
+    Json,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+}
+
+impl TransactionKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TransactionKind::Mint => "mint",
+            TransactionKind::Burn => "burn",
+            TransactionKind::Transfer => "transfer",
+            TransactionKind::Approve => "approve",
+        }
+    }
+}
+
+/// A transaction as printed by the history commands.
+/// For approvals, `amount` is the approved allowance.
+///
+/// Timestamps, amounts and fees are printed as JSON strings, because JSON numbers lose precision past 2^53.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransactionRecord {
+    pub id: u64,
+    pub kind: TransactionKind,
+    #[serde(serialize_with = "serialize_option_as_string")]
+    pub timestamp_nanos: Option<u64>,
+    pub timestamp: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub spender: Option<String>,
+    #[serde(serialize_with = "serialize_as_string")]
+    pub amount: u128,
+    #[serde(serialize_with = "serialize_option_as_string")]
+    pub fee: Option<u128>,
+}
+
+fn serialize_as_string<S: Serializer>(
+    value: &impl Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn serialize_option_as_string<S: Serializer>(
+    value: &Option<impl Display>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl TransactionRecord {
+    fn new(id: u64, kind: TransactionKind, timestamp_nanos: Option<u64>, amount: u128) -> Self {
+        Self {
+            id,
+            kind,
+            timestamp_nanos,
+            timestamp: timestamp_nanos.and_then(format_timestamp),
+            from: None,
+            to: None,
+            spender: None,
+            amount,
+            fee: None,
+        }
+    }
+}
+
+impl From<&ledger_types::index::TransactionWithId> for TransactionRecord {
+    fn from(tx: &ledger_types::index::TransactionWithId) -> Self {
+        use ledger_types::index::Operation;
+        let timestamp = tx
+            .transaction
+            .timestamp
+            .or(tx.transaction.created_at_time)
+            .map(|t| t.timestamp_nanos);
+        match &tx.transaction.operation {
+            Operation::Mint { to, amount } => TransactionRecord {
+                to: Some(to.clone()),
+                ..Self::new(tx.id, TransactionKind::Mint, timestamp, amount.e8s.into())
+            },
+            Operation::Burn {
+                from,
+                amount,
+                spender,
+            } => TransactionRecord {
+                from: Some(from.clone()),
+                spender: spender.clone(),
+                ..Self::new(tx.id, TransactionKind::Burn, timestamp, amount.e8s.into())
+            },
+            Operation::Transfer {
+                to,
+                fee,
+                from,
+                amount,
+                spender,
+            } => TransactionRecord {
+                from: Some(from.clone()),
+                to: Some(to.clone()),
+                spender: spender.clone(),
+                fee: Some(fee.e8s.into()),
+                ..Self::new(
+                    tx.id,
+                    TransactionKind::Transfer,
+                    timestamp,
+                    amount.e8s.into(),
+                )
+            },
+            Operation::Approve {
+                fee,
+                from,
+                allowance,
+                spender,
+                ..
+            } => TransactionRecord {
+                from: Some(from.clone()),
+                spender: Some(spender.clone()),
+                fee: Some(fee.e8s.into()),
+                ..Self::new(
+                    tx.id,
+                    TransactionKind::Approve,
+                    timestamp,
+                    allowance.e8s.into(),
+                )
+            },
+        }
+    }
+}
+
+/// Decodes a transaction returned by the index canister of the cycles ledger.
+///
+/// Returns `None` for a transaction of a kind that the history commands do not show.
+pub fn parse_cycles_index_transaction(
+    tx: &cycles_ledger_types::index::TransactionWithId,
+) -> DfxResult<Option<TransactionRecord>> {
+    let id = nat_to_u64(&tx.id)?;
+    let timestamp = Some(tx.transaction.timestamp);
+    let transaction = &tx.transaction;
+    let record = if let Some(mint) = &transaction.mint {
+        TransactionRecord {
+            to: Some(mint.to.to_string()),
+            ..TransactionRecord::new(
+                id,
+                TransactionKind::Mint,
+                timestamp,
+                nat_to_u128(&mint.amount)?,
+            )
+        }
+    } else if let Some(burn) = &transaction.burn {
+        TransactionRecord {
+            from: Some(burn.from.to_string()),
+            spender: burn.spender.as_ref().map(|spender| spender.to_string()),
+            ..TransactionRecord::new(
+                id,
+                TransactionKind::Burn,
+                timestamp,
+                nat_to_u128(&burn.amount)?,
+            )
+        }
+    } else if let Some(transfer) = &transaction.transfer {
+        TransactionRecord {
+            from: Some(transfer.from.to_string()),
+            to: Some(transfer.to.to_string()),
+            spender: transfer.spender.as_ref().map(|spender| spender.to_string()),
+            fee: transfer.fee.as_ref().map(nat_to_u128).transpose()?,
+            ..TransactionRecord::new(
+                id,
+                TransactionKind::Transfer,
+                timestamp,
+                nat_to_u128(&transfer.amount)?,
+            )
+        }
+    } else if let Some(approve) = &transaction.approve {
+        TransactionRecord {
+            from: Some(approve.from.to_string()),
+            spender: Some(approve.spender.to_string()),
+            fee: approve.fee.as_ref().map(nat_to_u128).transpose()?,
+            ..TransactionRecord::new(
+                id,
+                TransactionKind::Approve,
+                timestamp,
+                nat_to_u128(&approve.amount)?,
+            )
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(record))
+}
+
+/// A decoded block of an ICRC-3 ledger.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Icrc3Block {
+    /// The transaction of the block and the accounts it involves.
+    Transaction(TransactionRecord, Vec<Account>),
+    /// A block with an operation that the history commands do not show.
+    Unsupported { operation: String },
+}
+
+/// Decodes a block of an ICRC-3 ledger.
+pub fn parse_icrc3_block(id: u64, block: &Value) -> DfxResult<Icrc3Block> {
+    let tx = block.field("tx");
+    let kind = match (tx.and_then(|tx| tx.field("op")), block.field("btype")) {
+        (Some(Value::Text(op)), _) | (None, Some(Value::Text(op))) => op.as_str(),
+        _ => bail!("Block {id} has no operation."),
+    };
+    let kind = match kind {
+        "mint" | "1mint" => TransactionKind::Mint,
+        "burn" | "1burn" => TransactionKind::Burn,
+        "xfer" | "1xfer" | "2xfer" => TransactionKind::Transfer,
+        "approve" | "2approve" => TransactionKind::Approve,
+        operation => {
+            return Ok(Icrc3Block::Unsupported {
+                operation: operation.to_string(),
+            })
+        }
+    };
+    let tx = tx.ok_or_else(|| anyhow!("Block {id} has no transaction."))?;
+    let timestamp = block.field("ts").map(value_to_u64).transpose()?;
+    let amount = tx
+        .field("amt")
+        .map(value_to_u128)
+        .transpose()?
+        .ok_or_else(|| anyhow!("Block {id} has no amount."))?;
+    let fee = tx
+        .field("fee")
+        .or_else(|| block.field("fee"))
+        .map(value_to_u128)
+        .transpose()?;
+    let from = tx.field("from").map(value_to_account).transpose()?;
+    let to = tx.field("to").map(value_to_account).transpose()?;
+    let spender = tx.field("spender").map(value_to_account).transpose()?;
+
+    let record = TransactionRecord {
+        from: from.map(|account| account.to_string()),
+        to: to.map(|account| account.to_string()),
+        spender: spender.map(|account| account.to_string()),
+        fee,
+        ..TransactionRecord::new(id, kind, timestamp, amount)
+    };
+    let accounts = [from, to, spender].into_iter().flatten().collect();
+    Ok(Icrc3Block::Transaction(record, accounts))
+}
+
+/// Formats transactions in the requested format, using `format_amount` for amounts and fees in text output.
+pub fn format_history(
+    transactions: &[TransactionRecord],
+    output: HistoryOutputFormat,
+    format_amount: impl Fn(u128) -> String,
+) -> DfxResult<String> {
+    if output == HistoryOutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(transactions)?);
+    }
+    if transactions.is_empty() {
+        return Ok("No transactions found.".to_string());
+    }
+
+    let header = ["ID", "TIME", "TYPE", "FROM", "TO", "AMOUNT", "FEE"].map(String::from);
+    let rows = transactions
+        .iter()
+        .map(|tx| {
+            let optional = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+            // Transfers by a spender are shown with the spender next to the source account.
+            let from = match (&tx.kind, &tx.from, &tx.spender) {
+                (TransactionKind::Transfer | TransactionKind::Burn, Some(from), Some(spender)) => {
+                    format!("{from} (by {spender})")
+                }
+                _ => optional(&tx.from),
+            };
+            let to = match tx.kind {
+                TransactionKind::Approve => optional(&tx.spender),
+                _ => optional(&tx.to),
+            };
+            [
+                tx.id.to_string(),
+                optional(&tx.timestamp),
+                tx.kind.as_str().to_string(),
+                from,
+                to,
+                format_amount(tx.amount),
+                tx.fee.map_or_else(|| "-".to_string(), &format_amount),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = header.clone().map(|column| column.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let lines = std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn format_timestamp(timestamp_nanos: u64) -> Option<String> {
+    OffsetDateTime::from_unix_timestamp_nanos(timestamp_nanos.into())
+        .ok()?
+        .format(&Rfc3339)
+        .ok()
+}
+
+fn nat_to_u64(nat: &Nat) -> DfxResult<u64> {
+    nat.0
+        .to_u64()
+        .ok_or_else(|| anyhow!("{nat} does not fit into 64 bits."))
+}
+
+fn nat_to_u128(nat: &Nat) -> DfxResult<u128> {
+    nat.0
+        .to_u128()
+        .ok_or_else(|| anyhow!("{nat} does not fit into 128 bits."))
+}
+
+fn value_to_u64(value: &Value) -> DfxResult<u64> {
+    match value {
+        Value::Nat(nat) => nat_to_u64(nat),
+        _ => bail!("Expected a natural number in block, found {value:?}."),
+    }
+}
+
+fn value_to_u128(value: &Value) -> DfxResult<u128> {
+    match value {
+        Value::Nat(nat) => nat_to_u128(nat),
+        _ => bail!("Expected a natural number in block, found {value:?}."),
+    }
+}
+
+/// Accounts are encoded as an array of the owner and an optional subaccount.
+fn value_to_account(value: &Value) -> DfxResult<Account> {
+    let Value::Array(parts) = value else {
+        bail!("Expected an account in block, found {value:?}.");
+    };
+    match parts.as_slice() {
+        [Value::Blob(owner)] => Ok(Account {
+            owner: Principal::try_from_slice(owner)?,
+            subaccount: None,
+        }),
+        [Value::Blob(owner), Value::Blob(subaccount)] => Ok(Account {
+            owner: Principal::try_from_slice(owner)?,
+            subaccount: Some(
+                subaccount
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid subaccount in block."))?,
+            ),
+        }),
+        _ => bail!("Expected an account in block, found {value:?}."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_bytes::ByteBuf;
+
+    fn account_value(owner: Principal) -> Value {
+        Value::Array(vec![Value::Blob(ByteBuf::from(owner.as_slice().to_vec()))])
+    }
+
+    #[test]
+    fn test_parse_icrc3_block() {
+        let alice = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+        let bob = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+        let block = Value::Map(vec![
+            ("fee".to_string(), Value::Nat(Nat::from(100_000_000u64))),
+            (
+                "ts".to_string(),
+                Value::Nat(Nat::from(1_620_328_630_000_000_001u64)),
+            ),
+            (
+                "tx".to_string(),
+                Value::Map(vec![
+                    ("op".to_string(), Value::Text("xfer".to_string())),
+                    ("from".to_string(), account_value(alice)),
+                    ("to".to_string(), account_value(bob)),
+                    ("amt".to_string(), Value::Nat(Nat::from(5_000_000_000u64))),
+                ]),
+            ),
+        ]);
+
+        let Icrc3Block::Transaction(record, accounts) = parse_icrc3_block(7, &block).unwrap()
+        else {
+            panic!("expected a transaction");
+        };
+        assert_eq!(
+            record,
+            TransactionRecord {
+                id: 7,
+                kind: TransactionKind::Transfer,
+                timestamp_nanos: Some(1_620_328_630_000_000_001),
+                timestamp: Some("2021-05-06T19:17:10.000000001Z".to_string()),
+                from: Some(alice.to_string()),
+                to: Some(bob.to_string()),
+                spender: None,
+                amount: 5_000_000_000,
+                fee: Some(100_000_000),
+            }
+        );
+        assert_eq!(accounts, vec![Account::from(alice), Account::from(bob)]);
+
+        let fee_collector = Value::Map(vec![
+            ("btype".to_string(), Value::Text("107feecol".to_string())),
+            (
+                "ts".to_string(),
+                Value::Nat(Nat::from(1_620_328_630_000_000_001u64)),
+            ),
+        ]);
+        assert_eq!(
+            parse_icrc3_block(8, &fee_collector).unwrap(),
+            Icrc3Block::Unsupported {
+                operation: "107feecol".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_unsupported_index_transactions_are_skipped() {
+        let transaction = cycles_ledger_types::index::TransactionWithId {
+            id: Nat::from(5u64),
+            transaction: cycles_ledger_types::index::Transaction {
+                kind: "fee_collector".to_string(),
+                mint: None,
+                burn: None,
+                transfer: None,
+                approve: None,
+                timestamp: 1_620_328_630_000_000_001,
+            },
+        };
+        assert_eq!(parse_cycles_index_transaction(&transaction).unwrap(), None);
+    }
+
+    #[test]
+    fn test_large_numbers_are_json_strings() {
+        let record = TransactionRecord {
+            fee: Some(10_000_000_000),
+            ..TransactionRecord::new(
+                3,
+                TransactionKind::Mint,
+                Some(1_620_328_630_000_000_001),
+                u128::MAX,
+            )
+        };
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["id"], serde_json::json!(3));
+        assert_eq!(json["timestamp_nanos"], "1620328630000000001");
+        assert_eq!(json["amount"], u128::MAX.to_string());
+        assert_eq!(json["fee"], "10000000000");
+        assert!(
+            serde_json::to_value(TransactionRecord::new(4, TransactionKind::Mint, None, 1))
+                .unwrap()["timestamp_nanos"]
+                .is_null()
+        );
+    }
+
+    #[test]
+    fn test_format_history() {
+        let transactions = vec![
+            TransactionRecord {
+                to: Some("bob".to_string()),
+                ..TransactionRecord::new(2, TransactionKind::Mint, None, 300)
+            },
+            TransactionRecord {
+                from: Some("alice".to_string()),
+                to: Some("bob".to_string()),
+                fee: Some(1),
+                ..TransactionRecord::new(
+                    1,
+                    TransactionKind::Transfer,
+                    Some(1_620_328_630_000_000_000),
+                    20,
+                )
+            },
+        ];
+        assert_eq!(
+            format_history(&transactions, HistoryOutputFormat::Text, |amount| amount
+                .to_string())
+            .unwrap(),
+            [
+                "ID  TIME                  TYPE      FROM   TO   AMOUNT  FEE",
+                "2   -                     mint      -      bob  300     -",
+                "1   2021-05-06T19:17:10Z  transfer  alice  bob  20      1",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            format_history(&[], HistoryOutputFormat::Text, |amount| amount.to_string()).unwrap(),
+            "No transactions found."
+        );
+    }
+}
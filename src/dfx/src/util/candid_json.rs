@@ -0,0 +1,368 @@
+//! Conversion of JSON and YAML arguments into Candid values, guided by the Candid types of a method.
+//!
+//! A method with one argument takes the document itself as its argument.
+//! A method with several arguments takes an array with one element per argument.
+//!
+//! Numbers that do not fit in a JSON number, such as large `nat` values, can be written as strings.
+//! An `opt` value is either `null` or the value itself; `[]` and `[value]` are accepted as well.
+//! A variant is an object with a single key, or the name of the case if the case has type `null`.
+use crate::lib::error::DfxResult;
+use anyhow::{anyhow, bail, Context};
+use candid::types::value::{IDLField, IDLValue, VariantValue};
+use candid::types::{Field, Label, Type, TypeEnv, TypeInner};
+use candid::{IDLArgs, Int, Nat, Principal};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The argument format of a file, which is `json` or `yaml` for files with those extensions, and `idl` otherwise.
+pub fn argument_format_from_path(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => "json",
+        Some("yaml" | "yml") => "yaml",
+        _ => "idl",
+    }
+}
+
+/// Parses a JSON or YAML document into arguments of the given types.
+pub fn parse_typed_argument(
+    document: &str,
+    format: &str,
+    env: &TypeEnv,
+    types: &[Type],
+) -> DfxResult<IDLArgs> {
+    let value: Value = match format {
+        "json" => serde_json::from_str(document).context("Invalid JSON argument.")?,
+        "yaml" => serde_yaml_ng::from_str(document).context("Invalid YAML argument.")?,
+        _ => bail!("Unsupported argument format '{format}'."),
+    };
+    let args = match (types, &value) {
+        ([ty], _) => vec![to_idl_value(&value, env, ty, "$")?],
+        ([], Value::Null) => vec![],
+        (types, Value::Array(values)) => {
+            if values.len() > types.len() {
+                bail!(
+                    "$: expected at most {} arguments, found {}",
+                    types.len(),
+                    values.len()
+                );
+            }
+            types
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| {
+                    let path = format!("$[{i}]");
+                    match values.get(i) {
+                        Some(value) => to_idl_value(value, env, ty, &path),
+                        None => omitted_value(env, ty)?
+                            .ok_or_else(|| anyhow!("{path}: missing argument of type {ty}")),
+                    }
+                })
+                .collect::<DfxResult<_>>()?
+        }
+        (types, value) => bail!(
+            "$: expected an array of {} arguments, found {}",
+            types.len(),
+            describe(value)
+        ),
+    };
+    Ok(IDLArgs { args })
+}
+
+/// The value of an argument or record field that may be left out.
+fn omitted_value(env: &TypeEnv, ty: &Type) -> DfxResult<Option<IDLValue>> {
+    Ok(match env.trace_type(ty)?.as_ref() {
+        TypeInner::Opt(_) => Some(IDLValue::None),
+        TypeInner::Null => Some(IDLValue::Null),
+        TypeInner::Reserved => Some(IDLValue::Reserved),
+        _ => None,
+    })
+}
+
+fn to_idl_value(value: &Value, env: &TypeEnv, ty: &Type, path: &str) -> DfxResult<IDLValue> {
+    let traced = env.trace_type(ty)?;
+    let mismatch = || anyhow!("{path}: expected {ty}, found {}", describe(value));
+    Ok(match (traced.as_ref(), value) {
+        (TypeInner::Reserved, _) => IDLValue::Reserved,
+        (TypeInner::Null, Value::Null) => IDLValue::Null,
+        (TypeInner::Bool, Value::Bool(b)) => IDLValue::Bool(*b),
+        (TypeInner::Text, Value::String(s)) => IDLValue::Text(s.clone()),
+        (TypeInner::Nat, _) => IDLValue::Nat(parse_integer::<Nat>(value, ty, path)?),
+        (TypeInner::Int, _) => IDLValue::Int(parse_integer::<Int>(value, ty, path)?),
+        (TypeInner::Nat8, _) => IDLValue::Nat8(parse_integer(value, ty, path)?),
+        (TypeInner::Nat16, _) => IDLValue::Nat16(parse_integer(value, ty, path)?),
+        (TypeInner::Nat32, _) => IDLValue::Nat32(parse_integer(value, ty, path)?),
+        (TypeInner::Nat64, _) => IDLValue::Nat64(parse_integer(value, ty, path)?),
+        (TypeInner::Int8, _) => IDLValue::Int8(parse_integer(value, ty, path)?),
+        (TypeInner::Int16, _) => IDLValue::Int16(parse_integer(value, ty, path)?),
+        (TypeInner::Int32, _) => IDLValue::Int32(parse_integer(value, ty, path)?),
+        (TypeInner::Int64, _) => IDLValue::Int64(parse_integer(value, ty, path)?),
+        (TypeInner::Float32, Value::Number(n)) => {
+            IDLValue::Float32(n.as_f64().ok_or_else(mismatch)? as f32)
+        }
+        (TypeInner::Float64, Value::Number(n)) => {
+            IDLValue::Float64(n.as_f64().ok_or_else(mismatch)?)
+        }
+        (TypeInner::Principal, Value::String(s)) => IDLValue::Principal(
+            Principal::from_text(s).with_context(|| format!("{path}: invalid principal '{s}'"))?,
+        ),
+        (TypeInner::Service(_), Value::String(s)) => IDLValue::Service(
+            Principal::from_text(s).with_context(|| format!("{path}: invalid principal '{s}'"))?,
+        ),
+        (TypeInner::Opt(_), Value::Null) => IDLValue::None,
+        (TypeInner::Opt(inner), _) => {
+            let inner_is_sequence = matches!(
+                env.trace_type(inner)?.as_ref(),
+                TypeInner::Vec(_) | TypeInner::Opt(_)
+            );
+            match value {
+                // The `[]` or `[value]` form, as printed by `--output json`.
+                Value::Array(values) if values.len() <= 1 && !inner_is_sequence => {
+                    match values.first() {
+                        Some(value) => IDLValue::Opt(Box::new(to_idl_value(
+                            value,
+                            env,
+                            inner,
+                            &format!("{path}[0]"),
+                        )?)),
+                        None => IDLValue::None,
+                    }
+                }
+                _ => IDLValue::Opt(Box::new(to_idl_value(value, env, inner, path)?)),
+            }
+        }
+        (TypeInner::Vec(inner), Value::Array(values)) => IDLValue::Vec(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| to_idl_value(value, env, inner, &format!("{path}[{i}]")))
+                .collect::<DfxResult<_>>()?,
+        ),
+        (TypeInner::Record(fields), Value::Array(values)) if is_tuple(fields) => {
+            if values.len() != fields.len() {
+                bail!(
+                    "{path}: expected a tuple of {} values, found {}",
+                    fields.len(),
+                    values.len()
+                );
+            }
+            IDLValue::Record(
+                fields
+                    .iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(i, (Field { id, ty }, value))| {
+                        Ok(IDLField {
+                            id: id.as_ref().clone(),
+                            val: to_idl_value(value, env, ty, &format!("{path}[{i}]"))?,
+                        })
+                    })
+                    .collect::<DfxResult<_>>()?,
+            )
+        }
+        (TypeInner::Record(fields), Value::Object(object)) => {
+            if let Some(key) = object
+                .keys()
+                .find(|key| !fields.iter().any(|field| label_matches(&field.id, key)))
+            {
+                bail!(
+                    "{path}: unknown field '{key}', expected one of: {}",
+                    field_names(fields)
+                );
+            }
+            let mut record = vec![];
+            for Field { id, ty } in fields.iter() {
+                let field_path = format!("{path}.{id}");
+                let val = match object.iter().find(|(key, _)| label_matches(id, key)) {
+                    Some((_, value)) => to_idl_value(value, env, ty, &field_path)?,
+                    None => omitted_value(env, ty)?
+                        .ok_or_else(|| anyhow!("{field_path}: missing field of type {ty}"))?,
+                };
+                record.push(IDLField {
+                    id: id.as_ref().clone(),
+                    val,
+                });
+            }
+            IDLValue::Record(record)
+        }
+        (TypeInner::Variant(fields), Value::Object(object)) if object.len() == 1 => {
+            let (key, value) = object.iter().next().unwrap();
+            let (index, Field { id, ty }) = find_case(fields, key, path)?;
+            let val = to_idl_value(value, env, ty, &format!("{path}.{id}"))?;
+            variant(id, val, index)
+        }
+        (TypeInner::Variant(fields), Value::String(key)) => {
+            let (index, Field { id, ty }) = find_case(fields, key, path)?;
+            if !matches!(env.trace_type(ty)?.as_ref(), TypeInner::Null) {
+                bail!("{path}: case '{key}' has a value of type {ty}, write it as {{\"{key}\": <value>}}");
+            }
+            variant(id, IDLValue::Null, index)
+        }
+        _ => return Err(mismatch()),
+    })
+}
+
+fn parse_integer<T: FromStr>(value: &Value, ty: &Type, path: &str) -> DfxResult<T> {
+    let text = match value {
+        Value::Number(n) if n.is_u64() || n.is_i64() => n.to_string(),
+        Value::String(s) => s.replace('_', ""),
+        _ => bail!("{path}: expected {ty}, found {}", describe(value)),
+    };
+    text.parse()
+        .map_err(|_| anyhow!("{path}: '{text}' is not a valid {ty}"))
+}
+
+fn is_tuple(fields: &[Field]) -> bool {
+    fields
+        .iter()
+        .enumerate()
+        .all(|(i, field)| matches!(field.id.as_ref(), Label::Unnamed(id) if *id as usize == i))
+}
+
+fn label_matches(label: &Label, key: &str) -> bool {
+    match label {
+        Label::Named(name) => name == key,
+        Label::Id(id) | Label::Unnamed(id) => key.parse::<u32>() == Ok(*id),
+    }
+}
+
+fn field_names(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| field.id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn find_case<'a>(fields: &'a [Field], key: &str, path: &str) -> DfxResult<(usize, &'a Field)> {
+    fields
+        .iter()
+        .enumerate()
+        .find(|(_, field)| label_matches(&field.id, key))
+        .ok_or_else(|| {
+            anyhow!(
+                "{path}: unknown variant case '{key}', expected one of: {}",
+                field_names(fields)
+            )
+        })
+}
+
+fn variant(id: &Label, val: IDLValue, index: usize) -> IDLValue {
+    IDLValue::Variant(VariantValue(
+        Box::new(IDLField {
+            id: id.clone(),
+            val,
+        }),
+        index as u64,
+    ))
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => format!("the number {n}"),
+        Value::String(s) => format!("the string {s:?}"),
+        Value::Array(_) => "an array".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid_parser::utils::CandidSource;
+
+    const DID: &str = r#"
+type Account = record { owner : principal; subaccount : opt blob };
+type Mode = variant { Init : record { decimals : nat8; minting_account : Account; balances : vec record { Account; nat } }; Upgrade };
+service : {
+  init : (Mode) -> ();
+  pair : (text, opt nat64) -> ();
+}
+"#;
+
+    fn args(method: &str, document: &str, format: &str) -> DfxResult<String> {
+        let (env, service) = CandidSource::Text(DID).load().unwrap();
+        let func = env.get_method(&service.unwrap(), method).unwrap().clone();
+        let args = parse_typed_argument(document, format, &env, &func.args)?;
+        args.to_bytes_with_types(&env, &func.args)?;
+        Ok(args.to_string())
+    }
+
+    #[test]
+    fn converts_json_and_yaml() {
+        let json = r#"{"Init": {
+            "decimals": 8,
+            "minting_account": {"owner": "aaaaa-aa"},
+            "balances": [[{"owner": "2vxsx-fae", "subaccount": [1, 2]}, "100_000_000_000_000_000_000"]]
+        }}"#;
+        let yaml = r#"
+Init:
+  decimals: 8
+  minting_account:
+    owner: aaaaa-aa
+  balances:
+    - - owner: 2vxsx-fae
+        subaccount: [1, 2]
+      - "100_000_000_000_000_000_000"
+"#;
+        let json = args("init", json, "json").unwrap();
+        assert_eq!(json, args("init", yaml, "yaml").unwrap());
+        assert!(json.contains("100_000_000_000_000_000_000 : nat"));
+
+        assert_eq!(
+            args("init", r#""Upgrade""#, "json").unwrap(),
+            "(variant { Upgrade })"
+        );
+        assert_eq!(args("pair", r#"["a"]"#, "json").unwrap(), r#"("a", null)"#);
+    }
+
+    #[test]
+    fn reports_the_path_of_mismatches() {
+        let error = |document: &str| args("init", document, "json").unwrap_err().to_string();
+        assert_eq!(
+            error(
+                r#"{"Init": {"decimals": 256, "minting_account": {"owner": "aaaaa-aa"}, "balances": []}}"#
+            ),
+            "$.Init.decimals: '256' is not a valid nat8"
+        );
+        assert_eq!(
+            error(r#"{"Init": {"decimals": 8, "minting_account": {"owner": 1}, "balances": []}}"#),
+            "$.Init.minting_account.owner: expected principal, found the number 1"
+        );
+        assert_eq!(
+            error(
+                r#"{"Init": {"decimals": 8, "minting_account": {"owner": "aaaaa-aa"}, "balances": [[{"owner": "aaaaa-aa"}]]}}"#
+            ),
+            "$.Init.balances[0]: expected a tuple of 2 values, found 1"
+        );
+        assert_eq!(
+            error(r#"{"Init": {"decimals": 8, "minting_account": {"owner": "aaaaa-aa"}}}"#),
+            "$.Init.balances: missing field of type vec record { Account; nat }"
+        );
+        assert_eq!(
+            error(
+                r#"{"Init": {"decimals": 8, "minting_account": {"owner": "aaaaa-aa", "extra": 1}, "balances": []}}"#
+            ),
+            "$.Init.minting_account: unknown field 'extra', expected one of: owner, subaccount"
+        );
+        assert_eq!(
+            error(r#"{"Reinit": null}"#),
+            "$: unknown variant case 'Reinit', expected one of: Upgrade, Init"
+        );
+        assert_eq!(
+            args("pair", r#""a""#, "json").unwrap_err().to_string(),
+            "$: expected an array of 2 arguments, found the string \"a\""
+        );
+    }
+
+    #[test]
+    fn argument_formats_from_paths() {
+        assert_eq!(argument_format_from_path(Path::new("init.json")), "json");
+        assert_eq!(argument_format_from_path(Path::new("a/init.yml")), "yaml");
+        assert_eq!(argument_format_from_path(Path::new("init.yaml")), "yaml");
+        assert_eq!(argument_format_from_path(Path::new("init.did")), "idl");
+        assert_eq!(argument_format_from_path(Path::new("init")), "idl");
+    }
+}
@@ -16,6 +16,8 @@
 //!   - In [ArgumentFromCliLongOpt], it is a "long" option, it must be set with `--argument <ARGUMENT>` or `--argument=<ARGUMENT>`.
 //!   - In [ArgumentFromCliPositionalOpt], it is a "positional" option, e.g. `dfx canister call <CANISTER_NAME> <METHOD_NAME> [ARGUMENT]`
 //!
+//! Both variants have an `--argument-format` option, which takes precedence over the argument type.
+//!
 //! Beyond that, the name of the field for the argument type is also different:
 //!   - In [ArgumentFromCliLongOpt], it is [argument_type](ArgumentFromCliLongOpt::argument_type).
 //!   - In [ArgumentFromCliPositionalOpt], it is [type](ArgumentFromCliPositionalOpt::type).
@@ -41,6 +43,10 @@ pub struct ArgumentFromCliLongOpt {
     #[arg(long, requires("argument"), value_parser = ["idl", "raw"])]
     argument_type: Option<String>,
 
+    /// Specifies the format of the argument. JSON and YAML arguments are converted using the Candid types of the method.
+    #[arg(long, conflicts_with("argument_type"), value_parser = ["idl", "json", "yaml"])]
+    argument_format: Option<String>,
+
     /// Specifies the file from which to read the argument to pass to the method.
     #[arg(long, value_parser = file_or_stdin_parser, conflicts_with("argument"))]
     argument_file: Option<PathBuf>,
@@ -48,7 +54,13 @@ pub struct ArgumentFromCliLongOpt {
 
 impl ArgumentFromCliLongOpt {
     pub fn get_argument_and_type(&self) -> DfxResult<(Option<String>, Option<String>)> {
-        get_argument_from_cli(&self.argument, &self.argument_type, &self.argument_file)
+        get_argument_from_cli(
+            &self.argument,
+            self.argument_format
+                .as_ref()
+                .or(self.argument_type.as_ref()),
+            &self.argument_file,
+        )
     }
 }
 
@@ -66,6 +78,10 @@ pub struct ArgumentFromCliPositionalOpt {
     #[arg(long, requires("argument"), value_parser = ["idl", "raw"])]
     r#type: Option<String>,
 
+    /// Specifies the format of the argument. JSON and YAML arguments are converted using the Candid types of the method.
+    #[arg(long, conflicts_with("type"), value_parser = ["idl", "json", "yaml"])]
+    argument_format: Option<String>,
+
     /// Specifies the file from which to read the argument to pass to the method.
     #[arg(long, value_parser = file_or_stdin_parser, conflicts_with("argument"))]
     argument_file: Option<PathBuf>,
@@ -73,13 +89,17 @@ pub struct ArgumentFromCliPositionalOpt {
 
 impl ArgumentFromCliPositionalOpt {
     pub fn get_argument_and_type(&self) -> DfxResult<(Option<String>, Option<String>)> {
-        get_argument_from_cli(&self.argument, &self.r#type, &self.argument_file)
+        get_argument_from_cli(
+            &self.argument,
+            self.argument_format.as_ref().or(self.r#type.as_ref()),
+            &self.argument_file,
+        )
     }
 }
 
 fn get_argument_from_cli(
     argument: &Option<String>,
-    argument_type: &Option<String>,
+    argument_type: Option<&String>,
     argument_file: &Option<PathBuf>,
 ) -> DfxResult<(Option<String>, Option<String>)> {
     let arguments_from_file = argument_file
@@ -88,5 +108,5 @@ fn get_argument_from_cli(
         .transpose()?;
     let arguments = argument.clone();
     let argument_from_cli = arguments_from_file.or(arguments);
-    Ok((argument_from_cli, argument_type.clone()))
+    Ok((argument_from_cli, argument_type.cloned()))
 }
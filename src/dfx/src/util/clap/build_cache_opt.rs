@@ -0,0 +1,40 @@
+use crate::lib::builders::BuildCache;
+use crate::lib::error::DfxResult;
+use anyhow::Context;
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Clone, Debug, Default)]
+pub struct BuildCacheOpt {
+    /// Skips building canisters whose sources, configuration and toolchain did not change since they were last built.
+    #[arg(long, conflicts_with = "no_cache")]
+    cache: bool,
+
+    /// Builds canisters even if a build cache directory is set.
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Reuses the artifacts of builds cached in this directory, and caches new builds in it.
+    /// The directory can be shared by projects and CI runs. Implies --cache.
+    #[arg(long, env = "DFX_BUILD_CACHE_DIR")]
+    build_cache_dir: Option<PathBuf>,
+}
+
+impl BuildCacheOpt {
+    pub fn into_build_cache(self) -> DfxResult<Option<BuildCache>> {
+        if self.no_cache || !(self.cache || self.build_cache_dir.is_some()) {
+            return Ok(None);
+        }
+        let shared_root = match self.build_cache_dir {
+            Some(dir) => {
+                dfx_core::fs::create_dir_all(&dir)?;
+                Some(
+                    dfx_core::fs::canonicalize(&dir)
+                        .context("Failed to resolve the build cache directory.")?,
+                )
+            }
+            None => None,
+        };
+        Ok(Some(BuildCache::new(shared_root)))
+    }
+}
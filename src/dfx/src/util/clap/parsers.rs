@@ -109,6 +109,13 @@ pub fn compute_allocation_parser(compute_allocation: &str) -> Result<u64, String
     Err("Must be a percent between 0 and 100".to_string())
 }
 
+pub fn jobs_parser(jobs: &str) -> Result<usize, String> {
+    match jobs.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err("Must be a number of at least 1".to_string()),
+    }
+}
+
 pub fn memory_allocation_parser(memory_allocation: &str) -> Result<Byte, String> {
     // This limit should track MAX_MEMORY_ALLOCATION
     // at https://gitlab.com/dfinity-lab/core/ic/-/blob/master/rs/types/types/src/lib.rs#L492
@@ -24,3 +24,31 @@ pub async fn as_cycles_with_current_exchange_rate(icpts: &ICPTs) -> DfxResult<u1
     let cycles = xdr * Decimal::from(CYCLES_PER_XDR);
     Ok(u128::try_from(cycles)?)
 }
+
+/// Returns the amount of ICP that the cycles minting canister converts into at least `cycles` cycles.
+pub fn icp_for_cycles(cycles: u128, xdr_permyriad_per_icp: u64) -> DfxResult<ICPTs> {
+    // One e8 is worth `xdr_permyriad_per_icp` cycles: 1e-8 ICP * (xdr_permyriad_per_icp / 1e4) XDR/ICP * 1e12 cycles/XDR.
+    let e8s = cycles.div_ceil(u128::from(xdr_permyriad_per_icp.max(1)));
+    let e8s = u64::try_from(e8s).context("The amount of ICP is too large.")?;
+    Ok(ICPTs::from_e8s(e8s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icp_for_cycles_rounds_up() {
+        // 1 ICP = 4 XDR = 4T cycles
+        let rate = 40_000;
+        assert_eq!(
+            ICPTs::from_e8s(100_000_000),
+            icp_for_cycles(4_000_000_000_000, rate).unwrap()
+        );
+        assert_eq!(
+            ICPTs::from_e8s(100_000_001),
+            icp_for_cycles(4_000_000_000_001, rate).unwrap()
+        );
+        assert_eq!(ICPTs::from_e8s(0), icp_for_cycles(0, rate).unwrap());
+    }
+}
@@ -27,6 +27,7 @@ use std::path::Path;
 use std::time::Duration;
 
 pub mod assets;
+pub mod candid_json;
 pub mod clap;
 pub mod command;
 pub mod currency_conversion;
@@ -273,6 +274,16 @@ pub fn blob_from_arguments(
             .map_err(|e| error_invalid_data!("Unable to serialize Candid values: {}", e))?;
             Ok(typed_args)
         }
+        format @ ("json" | "yaml") => {
+            let Some((env, func)) = method_type else {
+                bail!("Cannot convert a {format} argument without the Candid type of the method.");
+            };
+            let arguments = arguments
+                .ok_or_else(|| error_invalid_data!("Expected arguments but found none."))?;
+            let args = candid_json::parse_typed_argument(arguments, format, env, &func.args)?;
+            args.to_bytes_with_types(env, &func.args)
+                .map_err(|e| error_invalid_data!("Unable to serialize Candid values: {}", e))
+        }
         v => Err(error_unknown!("Invalid type: {}", v)),
     }
 }